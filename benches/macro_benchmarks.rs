@@ -81,8 +81,19 @@
 //! bench binary still compiles and runs — it just records no measurements.
 
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
-use noet_core::{beliefbase::BeliefBase, codec::DocumentCompiler, event::BeliefEvent};
-use std::path::{Path, PathBuf};
+use noet_core::{
+    beliefbase::{BeliefBase, BeliefGraph},
+    codec::DocumentCompiler,
+    db::{db_init, DbConnection, Transaction},
+    error::BuildonomyError,
+    event::BeliefEvent,
+    properties::{BeliefKind, WeightKind, WeightSet},
+    query::{BeliefSource, Expression, NeighborsExpression, Query, StatePred},
+};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
 use tokio::sync::mpsc::unbounded_channel;
 
 // ---------------------------------------------------------------------------
@@ -258,6 +269,7 @@ async fn run_parse_with_html(corpus_root: &Path, html_out: &Path) -> usize {
         None,  // no live-reload script
         false, // no CDN
         base_url,
+        None, // default jobs
     )
     .expect("DocumentCompiler::with_html_output failed");
 
@@ -429,6 +441,111 @@ fn bench_graph_queries(c: &mut Criterion) {
     group.finish();
 }
 
+// ---------------------------------------------------------------------------
+// Benchmark: traversal queries against the SQLite cache
+// ---------------------------------------------------------------------------
+//
+// Compares `DbConnection`'s recursive-CTE `eval_query` with the trait's default
+// hop-by-hop loop, which issues an `eval_unbalanced` per traversal hop and an
+// `eval_trace` per balance level.
+
+/// Forwards only the primitive queries to the database, so `eval_query` and
+/// `balance` fall back to the `BeliefSource` default implementations.
+struct RoundTripSource<'a>(&'a DbConnection);
+
+impl BeliefSource for RoundTripSource<'_> {
+    async fn eval_unbalanced(&self, expr: &Expression) -> Result<BeliefGraph, BuildonomyError> {
+        self.0.eval_unbalanced(expr).await
+    }
+
+    async fn eval_trace(
+        &self,
+        expr: &Expression,
+        weight_filter: WeightSet,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        self.0.eval_trace(expr, weight_filter).await
+    }
+}
+
+fn bench_db_traversal(c: &mut Criterion) {
+    let Some(corpus) = require_mdn_corpus() else {
+        eprintln!("  [macro_benchmarks] skipping db_traversal");
+        return;
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let db_dir = tempfile::tempdir().expect("could not create db dir");
+
+    let (db, root) = rt.block_on(async {
+        let mut c =
+            DocumentCompiler::simple(corpus.clone()).expect("DocumentCompiler::simple failed");
+        c.parse_all(BeliefBase::default(), false)
+            .await
+            .expect("pre-compile failed");
+        let bb = c.cache();
+
+        let db = DbConnection(
+            db_init(db_dir.path().join("belief_cache.db"))
+                .await
+                .expect("db_init failed"),
+        );
+        let parsed: BTreeSet<_> = bb.states().keys().copied().collect();
+        let mut transaction = Transaction::default();
+        for event in BeliefBase::compute_diff(&BeliefBase::empty(), bb, &parsed)
+            .expect("compute_diff failed")
+        {
+            transaction.add_event(&event).ok();
+        }
+        transaction.execute(&db.0).await.expect("db write failed");
+
+        // The corpus root is the network no other node is a Section child of.
+        let root = bb
+            .states()
+            .values()
+            .filter(|node| node.kind.contains(BeliefKind::Network))
+            .find(|node| {
+                bb.bid_to_index(&node.bid).is_some_and(|idx| {
+                    bb.relations()
+                        .as_graph()
+                        .edges_directed(idx, petgraph::Direction::Outgoing)
+                        .all(|edge| edge.weight().get(&WeightKind::Section).is_none())
+                })
+            })
+            .map(|node| node.bid)
+            .expect("corpus has no root network");
+        (db, root)
+    });
+
+    let mut group = c.benchmark_group("db_traversal");
+    for hops in [1u8, 3] {
+        let query = Query {
+            seed: Expression::StateIn(StatePred::Bid(vec![root])),
+            traverse: Some(NeighborsExpression {
+                filter: Some(WeightSet::from(WeightKind::Section)),
+                upstream: hops,
+                downstream: 0,
+            }),
+        };
+        let node_count = rt
+            .block_on(db.eval_query(&query, false))
+            .expect("query failed")
+            .states
+            .len();
+        eprintln!("  [macro_benchmarks] db_traversal — {hops} hops reach {node_count} nodes");
+
+        group.bench_function(format!("recursive_cte_{hops}_hops"), |b| {
+            b.to_async(&rt).iter(|| db.eval_query(&query, false));
+        });
+        let round_trip = RoundTripSource(&db);
+        group.bench_function(format!("round_trip_{hops}_hops"), |b| {
+            b.to_async(&rt)
+                .iter(|| round_trip.eval_query(&query, false));
+        });
+    }
+
+    group.finish();
+}
+
 // ---------------------------------------------------------------------------
 // Criterion configuration and entry point
 // ---------------------------------------------------------------------------
@@ -444,7 +561,8 @@ criterion_group! {
     targets =
         bench_parse_throughput,
         bench_cache_warmup,
-        bench_graph_queries
+        bench_graph_queries,
+        bench_db_traversal
 }
 
 criterion_main!(macro_benches);
@@ -18,7 +18,10 @@ use crate::{
 #[cfg(not(target_arch = "wasm32"))]
 use crate::query::BeliefSource;
 
-use crate::query::{Expression, RelationPred, SetOp, StatePred};
+use crate::query::{
+    glob_regex, order_nodes, reading_positions, Expression, OrderBy, OrderKey, RelationPred, SetOp,
+    StatePred,
+};
 #[cfg(not(target_arch = "wasm32"))]
 use parking_lot::{ArcRwLockReadGuard, RawRwLock, RwLock};
 
@@ -38,7 +41,7 @@ use std::{
     },
 };
 
-use super::{context::BeliefContext, graph::MergeOp, BeliefGraph, BidGraph};
+use super::{context::BeliefContext, graph::MergeOp, BeliefGraph, BidGraph, ReadingOrder};
 
 // Conditional type alias for thread-safe shared locks
 // WASM uses Rc<RefCell<T>> (single-threaded)
@@ -336,6 +339,15 @@ impl BeliefBase {
         &self.states
     }
 
+    /// Replace the payload of `bid` outright. A [`BeliefEvent::NodeUpdate`] doesn't register
+    /// fields added to the payload of a known node, so callers holding the authoritative payload
+    /// (e.g. after rewriting a source file) set it here.
+    pub(crate) fn replace_payload(&mut self, bid: &Bid, payload: toml::Table) {
+        if let Some(node) = self.states.get_mut(bid) {
+            node.payload = payload;
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn paths(&self) -> ArcRwLockReadGuard<RawRwLock, PathMapMap> {
         self.index_sync(false);
@@ -818,6 +830,12 @@ impl BeliefBase {
         {
             content_len -= 1;
         }
+        if self
+            .states
+            .contains_key(&crate::properties::tag_namespace())
+        {
+            content_len -= 1;
+        }
         content_len == 0
     }
 
@@ -1896,6 +1914,26 @@ impl BeliefBase {
                         .filter_map(|bid| self.states().get(bid).map(|node| (*bid, node.clone()))),
                 )
             }
+            StatePred::NetPathGlob(net, pattern) => {
+                let Ok(re) = glob_regex(pattern) else {
+                    return BTreeMap::new();
+                };
+                let paths_guard = self.paths();
+                let path_bid_tuples = paths_guard
+                    .get_map(net)
+                    .map(|pm| {
+                        pm.recursive_map(&paths_guard, &mut std::collections::BTreeSet::new())
+                    })
+                    .unwrap_or_default();
+                BTreeMap::from_iter(
+                    path_bid_tuples
+                        .iter()
+                        .filter(|(path, ..)| re.is_match(path))
+                        .filter_map(|(_path, bid, _order)| {
+                            self.states().get(bid).map(|node| (*bid, node.clone()))
+                        }),
+                )
+            }
             StatePred::NetPathIn(net) => {
                 let paths_guard = self.paths();
                 let path_bid_tuples = paths_guard
@@ -2033,6 +2071,54 @@ impl BeliefBase {
         }
     }
 
+    /// `bids` sorted by `order_by`, then by BID. A node's path order is the smallest among the
+    /// paths that lead to it.
+    pub fn ordered_bids(&self, bids: &[Bid], order_by: &[OrderBy]) -> Vec<Bid> {
+        let states = self.states();
+        let mut path_orders = BTreeMap::<Bid, Vec<u16>>::new();
+        if order_by.iter().any(|order| order.key == OrderKey::Path) {
+            for (_path, bid, order) in self.paths().all_paths().into_values().flatten() {
+                match path_orders.entry(bid) {
+                    BTreeEntry::Vacant(entry) => {
+                        entry.insert(order);
+                    }
+                    BTreeEntry::Occupied(mut entry) => {
+                        if order < *entry.get() {
+                            entry.insert(order);
+                        }
+                    }
+                }
+            }
+        }
+        let reading = match order_by
+            .iter()
+            .any(|order| order.key == OrderKey::Prerequisites)
+        {
+            true => reading_positions(&self.relations(), bids),
+            false => BTreeMap::new(),
+        };
+        order_nodes(
+            bids.iter().filter_map(|bid| states.get(bid)),
+            order_by,
+            &path_orders,
+            &reading,
+        )
+    }
+
+    /// The `seeds` and everything they depend on through Epistemic relations, prerequisites
+    /// first. See [`BidGraph::reading_order`].
+    pub fn reading_order(&self, seeds: &[Bid]) -> ReadingOrder {
+        self.relations()
+            .reading_order(seeds, &WeightSet::from(WeightKind::Epistemic))
+    }
+
+    /// The cycles of Epistemic relations: groups of nodes that each depend, through the others,
+    /// on themselves.
+    pub fn epistemic_cycles(&self) -> Vec<Vec<Bid>> {
+        self.relations()
+            .cycles(&WeightSet::from(WeightKind::Epistemic))
+    }
+
     pub fn evaluate_expression(&self, expr: &Expression) -> BeliefGraph {
         self.index_sync(false);
         match expr {
@@ -2230,6 +2316,56 @@ impl BeliefSource for BeliefBase {
         // Clone and consume the entire BeliefBase to get complete BeliefGraph
         Ok(self.clone().consume())
     }
+
+    async fn order_bids(
+        &self,
+        bids: &[Bid],
+        order_by: &[OrderBy],
+    ) -> Result<Vec<Bid>, BuildonomyError> {
+        Ok(self.ordered_bids(bids, order_by))
+    }
+
+    async fn eval_shortest_path(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        let relations = self.relations();
+        let paths = Vec::from_iter(relations.shortest_path(from, to, &weights));
+        Ok(BeliefGraph::from_paths(
+            &paths,
+            self.states(),
+            &relations,
+            &weights,
+        ))
+    }
+
+    async fn eval_simple_paths(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+        max_hops: u8,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        let relations = self.relations();
+        let paths = relations.simple_paths(from, to, &weights, max_hops as usize);
+        Ok(BeliefGraph::from_paths(
+            &paths,
+            self.states(),
+            &relations,
+            &weights,
+        ))
+    }
+
+    async fn eval_reachable(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> Result<bool, BuildonomyError> {
+        Ok(self.relations().is_reachable(from, to, &weights))
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -2274,4 +2410,54 @@ impl BeliefSource for &BeliefBase {
         // Clone and consume the entire BeliefBase to get complete BeliefGraph
         Ok((*self).clone().consume())
     }
+
+    async fn order_bids(
+        &self,
+        bids: &[Bid],
+        order_by: &[OrderBy],
+    ) -> Result<Vec<Bid>, BuildonomyError> {
+        Ok(self.ordered_bids(bids, order_by))
+    }
+
+    async fn eval_shortest_path(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        let relations = self.relations();
+        let paths = Vec::from_iter(relations.shortest_path(from, to, &weights));
+        Ok(BeliefGraph::from_paths(
+            &paths,
+            self.states(),
+            &relations,
+            &weights,
+        ))
+    }
+
+    async fn eval_simple_paths(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+        max_hops: u8,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        let relations = self.relations();
+        let paths = relations.simple_paths(from, to, &weights, max_hops as usize);
+        Ok(BeliefGraph::from_paths(
+            &paths,
+            self.states(),
+            &relations,
+            &weights,
+        ))
+    }
+
+    async fn eval_reachable(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> Result<bool, BuildonomyError> {
+        Ok(self.relations().is_reachable(from, to, &weights))
+    }
 }
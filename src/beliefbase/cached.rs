@@ -78,7 +78,7 @@ use crate::{
     beliefbase::BeliefGraph,
     event::BeliefEvent,
     properties::{Bid, WeightSet},
-    query::{BeliefSource, Expression, Query},
+    query::{BeliefSource, Expression, OrderBy, Query},
     BuildonomyError,
 };
 
@@ -325,6 +325,43 @@ impl<B: BeliefSource + Clone + Send> BeliefSource for CachedBeliefSource<B> {
     ) -> impl std::future::Future<Output = Result<BeliefGraph, BuildonomyError>> + Send {
         self.inner_source.export_beliefgraph()
     }
+
+    fn order_bids(
+        &self,
+        bids: &[Bid],
+        order_by: &[OrderBy],
+    ) -> impl std::future::Future<Output = Result<Vec<Bid>, BuildonomyError>> + Send {
+        self.inner_source.order_bids(bids, order_by)
+    }
+
+    fn eval_shortest_path(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> impl std::future::Future<Output = Result<BeliefGraph, BuildonomyError>> + Send {
+        self.inner_source.eval_shortest_path(from, to, weights)
+    }
+
+    fn eval_simple_paths(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+        max_hops: u8,
+    ) -> impl std::future::Future<Output = Result<BeliefGraph, BuildonomyError>> + Send {
+        self.inner_source
+            .eval_simple_paths(from, to, weights, max_hops)
+    }
+
+    fn eval_reachable(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> impl std::future::Future<Output = Result<bool, BuildonomyError>> + Send {
+        self.inner_source.eval_reachable(from, to, weights)
+    }
 }
 
 // ---------------------------------------------------------------------------
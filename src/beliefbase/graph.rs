@@ -11,7 +11,7 @@ use crate::{
     properties::{
         BeliefKind, BeliefNode, BeliefRefRelation, Bid, WeightKind, WeightSet, WEIGHT_SORT_KEY,
     },
-    query::{Expression, RelationPred, ResultsPage, StatePred, DEFAULT_LIMIT},
+    query::{Expression, PaginatedQuery, RelationPred, ResultsPage, StatePred, DEFAULT_LIMIT},
 };
 
 // ---------------------------------------------------------------------------
@@ -54,6 +54,8 @@ impl MergeOp {
     }
 }
 use petgraph::{
+    algo::{all_simple_paths, astar, has_path_connecting, tarjan_scc},
+    graph::NodeIndex,
     graphmap::GraphMap,
     visit::{depth_first_search, Control, DfsEvent},
     Directed, Direction, IntoWeightedEdge,
@@ -211,6 +213,183 @@ impl BidGraph {
         }
         subtree_nodes
     }
+
+    /// The shortest chain of relations of the `weights` kinds leading from `from` to `to`,
+    /// following each relation from source to sink, as the BIDs along it.
+    pub fn shortest_path(&self, from: Bid, to: Bid, weights: &WeightSet) -> Option<Vec<Bid>> {
+        if from == to {
+            return Some(vec![from]);
+        }
+        let filtered = self.filter(&RelationPred::Kind(weights.clone()), false);
+        let graph = filtered.as_graph();
+        let (start, goal) = (node_index(graph, from)?, node_index(graph, to)?);
+        let (_, path) = astar(graph, start, |idx| idx == goal, |_| 1usize, |_| 0)?;
+        Some(path.into_iter().map(|idx| graph[idx]).collect())
+    }
+
+    /// Every chain of at most `max_hops` relations of the `weights` kinds leading from `from` to
+    /// `to` without visiting a node twice, shortest first.
+    pub fn simple_paths(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: &WeightSet,
+        max_hops: usize,
+    ) -> Vec<Vec<Bid>> {
+        if from == to {
+            return vec![vec![from]];
+        }
+        let filtered = self.filter(&RelationPred::Kind(weights.clone()), false);
+        let graph = filtered.as_graph();
+        let (Some(start), Some(goal), Some(max_intermediate)) = (
+            node_index(graph, from),
+            node_index(graph, to),
+            max_hops.checked_sub(1),
+        ) else {
+            return vec![];
+        };
+        let mut paths =
+            all_simple_paths::<Vec<_>, _>(graph, start, goal, 0, Some(max_intermediate))
+                .map(|path| path.into_iter().map(|idx| graph[idx]).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+        paths.sort_by(|lhs, rhs| lhs.len().cmp(&rhs.len()).then_with(|| lhs.cmp(rhs)));
+        paths
+    }
+
+    /// Whether a chain of relations of the `weights` kinds leads from `from` to `to`.
+    pub fn is_reachable(&self, from: Bid, to: Bid, weights: &WeightSet) -> bool {
+        if from == to {
+            return true;
+        }
+        let filtered = self.filter(&RelationPred::Kind(weights.clone()), false);
+        let graph = filtered.as_graph();
+        match (node_index(graph, from), node_index(graph, to)) {
+            (Some(start), Some(goal)) => has_path_connecting(graph, start, goal, None),
+            _ => false,
+        }
+    }
+
+    /// The strongly connected components of the relations of the `weights` kinds that form a
+    /// cycle: two or more nodes that each lead to the others, or a node related to itself. Each
+    /// cycle lists its members in BID order.
+    pub fn cycles(&self, weights: &WeightSet) -> Vec<Vec<Bid>> {
+        let filtered = self.filter(&RelationPred::Kind(weights.clone()), false);
+        let graph = filtered.as_graph();
+        cyclic_components(graph, &tarjan_scc(graph))
+    }
+
+    /// The `seeds` and every node upstream of them over relations of the `weights` kinds, each
+    /// after the sources of its relations. Over Epistemic relations, that puts prerequisites
+    /// first. The members of a cycle have no such order; they are kept together in BID order and
+    /// reported in [`ReadingOrder::cycles`]. Nodes that are otherwise unordered go in BID order.
+    pub fn reading_order(&self, seeds: &[Bid], weights: &WeightSet) -> ReadingOrder {
+        let filtered = self.filter(&RelationPred::Kind(weights.clone()), false);
+        let graph = filtered.as_graph();
+        let graph_indices = graph
+            .node_indices()
+            .map(|idx| (graph[idx], idx))
+            .collect::<BTreeMap<_, _>>();
+
+        // The subgraph upstream of the seeds
+        let mut upstream = petgraph::Graph::<Bid, ()>::new();
+        let mut indices = BTreeMap::<Bid, NodeIndex>::new();
+        let mut stack = seeds.to_vec();
+        while let Some(bid) = stack.pop() {
+            if indices.contains_key(&bid) {
+                continue;
+            }
+            indices.insert(bid, upstream.add_node(bid));
+            if let Some(idx) = graph_indices.get(&bid) {
+                stack.extend(
+                    graph
+                        .neighbors_directed(*idx, Direction::Incoming)
+                        .map(|source| graph[source]),
+                );
+            }
+        }
+        for edge in graph.raw_edges() {
+            let source = indices.get(&graph[edge.source()]);
+            let sink = indices.get(&graph[edge.target()]);
+            if let (Some(source), Some(sink)) = (source, sink) {
+                upstream.update_edge(*source, *sink, ());
+            }
+        }
+
+        // Order the cycle-free graph of its components, each component by its smallest BID.
+        let components = tarjan_scc(&upstream);
+        let mut component_of = vec![0usize; upstream.node_count()];
+        for (component, members) in components.iter().enumerate() {
+            for idx in members {
+                component_of[idx.index()] = component;
+            }
+        }
+        let members = components
+            .iter()
+            .map(|members| {
+                let mut bids = members.iter().map(|idx| upstream[*idx]).collect::<Vec<_>>();
+                bids.sort();
+                bids
+            })
+            .collect::<Vec<_>>();
+        let mut dependencies = vec![0usize; components.len()];
+        let mut dependents = vec![BTreeSet::new(); components.len()];
+        for edge in upstream.raw_edges() {
+            let source = component_of[edge.source().index()];
+            let sink = component_of[edge.target().index()];
+            if source != sink && dependents[source].insert(sink) {
+                dependencies[sink] += 1;
+            }
+        }
+        let mut ready = (0..components.len())
+            .filter(|component| dependencies[*component] == 0)
+            .map(|component| (members[component][0], component))
+            .collect::<BTreeSet<_>>();
+        let mut order = Vec::with_capacity(upstream.node_count());
+        while let Some((_, component)) = ready.pop_first() {
+            order.extend(members[component].iter().copied());
+            for dependent in dependents[component].iter() {
+                dependencies[*dependent] -= 1;
+                if dependencies[*dependent] == 0 {
+                    ready.insert((members[*dependent][0], *dependent));
+                }
+            }
+        }
+        ReadingOrder {
+            order,
+            cycles: cyclic_components(&upstream, &components),
+        }
+    }
+}
+
+fn node_index<N: PartialEq, E>(graph: &petgraph::Graph<N, E>, node: N) -> Option<NodeIndex> {
+    graph.node_indices().find(|idx| graph[*idx] == node)
+}
+
+/// The `components` of `graph` that form a cycle, each in BID order, sorted.
+fn cyclic_components<E>(
+    graph: &petgraph::Graph<Bid, E>,
+    components: &[Vec<NodeIndex>],
+) -> Vec<Vec<Bid>> {
+    let mut cycles = components
+        .iter()
+        .filter(|members| members.len() > 1 || graph.contains_edge(members[0], members[0]))
+        .map(|members| {
+            let mut bids = members.iter().map(|idx| graph[*idx]).collect::<Vec<_>>();
+            bids.sort();
+            bids
+        })
+        .collect::<Vec<_>>();
+    cycles.sort();
+    cycles
+}
+
+/// An order to read nodes in, from [`BidGraph::reading_order`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadingOrder {
+    /// The nodes, each after the nodes it depends on
+    pub order: Vec<Bid>,
+    /// The groups of nodes that depend on each other, which `order` can't put one before another
+    pub cycles: Vec<Vec<Bid>>,
 }
 
 impl From<BidRefGraph<'_>> for BidGraph {
@@ -787,30 +966,71 @@ impl BeliefGraph {
         }
     }
 
-    pub fn paginate(
-        &self,
-        limit: Option<usize>,
-        offset: Option<usize>,
-    ) -> ResultsPage<BeliefGraph> {
-        let count = self.states.len();
-        let start = offset.unwrap_or(0);
-        let mut page_limit = limit.unwrap_or(DEFAULT_LIMIT);
-        if page_limit > (count - start) {
-            page_limit = count;
+    /// The nodes along `paths` and the relations of the `weights` kinds joining them. Only the
+    /// paths' relations are included, so every node is marked Trace.
+    pub fn from_paths(
+        paths: &[Vec<Bid>],
+        states: &BTreeMap<Bid, BeliefNode>,
+        relations: &BidGraph,
+        weights: &WeightSet,
+    ) -> BeliefGraph {
+        let steps = paths
+            .iter()
+            .flat_map(|path| path.windows(2).map(|step| (step[0], step[1])))
+            .collect::<BTreeSet<_>>();
+        let graph = relations.as_graph();
+        let edges = graph.raw_edges().iter().filter_map(|edge| {
+            let (source, sink) = (graph[edge.source()], graph[edge.target()]);
+            let path_weights = edge.weight.intersection(weights);
+            (steps.contains(&(source, sink)) && !path_weights.is_empty()).then_some((
+                source,
+                sink,
+                path_weights,
+            ))
+        });
+        let states = paths
+            .iter()
+            .flatten()
+            .filter_map(|bid| states.get(bid))
+            .map(|node| {
+                let mut node = node.clone();
+                node.kind.insert(BeliefKind::Trace);
+                (node.bid, node)
+            })
+            .collect();
+        BeliefGraph {
+            states,
+            relations: BidGraph::from_edges(edges),
         }
-        let results = match count > page_limit || start > 0 {
+    }
+
+    /// The page of this graph `pq` asks for, taking nodes in `order`, followed by any nodes missing
+    /// from it in BID order. Relations are kept where both ends are on the page. The nodes'
+    /// payloads are narrowed to `pq.projection`'s keys when one is given.
+    pub fn paginate(&self, pq: &PaginatedQuery, order: &[Bid]) -> ResultsPage<BeliefGraph> {
+        let count = self.states.len();
+        let start = pq.offset.unwrap_or(0);
+        let page_limit = pq.limit.unwrap_or(DEFAULT_LIMIT);
+        let ordered = order
+            .iter()
+            .filter(|bid| self.states.contains_key(bid))
+            .copied()
+            .collect::<BTreeSet<_>>();
+        let page_order = order
+            .iter()
+            .filter(|bid| self.states.contains_key(bid))
+            .chain(self.states.keys().filter(|bid| !ordered.contains(bid)))
+            .skip(start)
+            .take(page_limit)
+            .copied()
+            .collect::<Vec<_>>();
+        let mut results = match count > page_limit || start > 0 {
             true => {
-                let states = BTreeMap::from_iter(self.states.iter().enumerate().filter_map(
-                    |(idx, (bid, node))| {
-                        if idx < start {
-                            None
-                        } else if idx < (start + page_limit) {
-                            Some((*bid, node.clone()))
-                        } else {
-                            None
-                        }
-                    },
-                ));
+                let states = BTreeMap::from_iter(
+                    page_order
+                        .iter()
+                        .map(|bid| (*bid, self.states[bid].clone())),
+                );
                 let relations = BidGraph::from_edges(
                     self.relations
                         .as_graph()
@@ -829,11 +1049,6 @@ impl BeliefGraph {
                             )
                         }),
                 );
-
-                // log::debug!(
-                //     "[paginate] self relation count: {}, self state count: {}, paginate state count {}, paginate relation count {}",
-                //     self.states().len(), self.relations().node_count(), states.len(), relations.node_count()
-                // );
                 BeliefGraph { states, relations }
             }
             false => BeliefGraph {
@@ -841,10 +1056,17 @@ impl BeliefGraph {
                 relations: self.relations.clone(),
             },
         };
+        if let Some(keys) = &pq.projection {
+            for node in results.states.values_mut() {
+                node.payload
+                    .retain(|key, _| keys.iter().any(|projected| projected == key));
+            }
+        }
         ResultsPage {
             count,
             start,
             results,
+            order: page_order,
         }
     }
 }
@@ -33,6 +33,6 @@ pub use base::BeliefBase;
 #[cfg(not(target_arch = "wasm32"))]
 pub use cached::CachedBeliefSource;
 pub use context::{BeliefContext, ExtendedRelation};
-pub use graph::{BeliefGraph, BidGraph, BidRefGraph, BidSubGraph};
+pub use graph::{BeliefGraph, BidGraph, BidRefGraph, BidSubGraph, ReadingOrder};
 #[cfg(not(target_arch = "wasm32"))]
 pub use sink::BeliefSink;
@@ -4,8 +4,17 @@
 //!
 //! ## Commands
 //!
-//! - `parse <path>`: One-shot parsing with diagnostics
-//! - `watch <path>`: Continuous file watching and parsing
+//! - `parse <path>`: One-shot parsing with diagnostics, including cycles of Epistemic relations
+//! - `query <expr> [path]`: Print the nodes matching a query expression, from a fresh parse or
+//!   the `watch` service's cache (`--cache`), optionally as a learning path: with their
+//!   prerequisites, in reading order (`--prerequisites`)
+//! - `path <from> <to> [path]`: Print the shortest chain of relations between two nodes, or every
+//!   chain up to a length (`--all`)
+//! - `migrate <path>`: Upgrade documents to the current version of their schema
+//! - `mv <from> <to>`: Move a document or directory, rewriting the links into and out of it
+//! - `watch <path>`: Continuous file watching and parsing, optionally answering JSON-RPC requests
+//!   (`--listen`)
+//! - `call <addr> <op>`: Execute an op on a listening `watch` service
 //!
 //! ## Write-Back Support
 //!
@@ -31,13 +40,29 @@
 use clap::{Parser, Subcommand};
 #[cfg(feature = "service")]
 mod dev_server;
-use noet_core::codec::{compiler::DocumentCompiler, diagnostic::ParseDiagnostic};
+use noet_core::beliefbase::BeliefBase;
+use noet_core::codec::{
+    compiler::DocumentCompiler,
+    diagnostic::ParseDiagnostic,
+    external::register_external_codecs,
+    migration::{apply_migrations, plan_migrations},
+};
+#[cfg(feature = "service")]
+use noet_core::commands::OpExecutor;
 #[cfg(feature = "service")]
 use noet_core::event::Event;
+use noet_core::nodekey::NodeKey;
+#[cfg(feature = "service")]
+use noet_core::paths::os_path_to_string;
+use noet_core::properties::{BeliefNode, Bid};
+use noet_core::query::{Expression, SetOp};
+#[cfg(feature = "service")]
+use noet_core::rpc::{RpcAddr, RpcClient};
 #[cfg(feature = "service")]
 use noet_core::watch::WatchService;
+use std::collections::BTreeSet;
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(feature = "service")]
 use std::sync::mpsc::channel;
 #[cfg(feature = "service")]
@@ -54,6 +79,17 @@ enum ColorChoice {
     Never,
 }
 
+#[derive(clap::ValueEnum, Clone, Default)]
+enum OutputFormat {
+    /// One line per node: BREF, kinds, title and path
+    #[default]
+    Table,
+    /// A JSON array of nodes
+    Json,
+    /// A TOML array of `[[nodes]]` tables
+    Toml,
+}
+
 #[derive(Parser)]
 #[command(name = "noet")]
 #[command(author, version, about = "A tool for parsing and watching markdown documents", long_about = None)]
@@ -64,6 +100,11 @@ struct Cli {
     #[arg(long, default_value = "auto", global = true)]
     color: ColorChoice,
 
+    /// External codec configuration file (TOML `[[codec]]` entries) registering out-of-tree
+    /// formats before parsing. See `noet_core::codec::external`.
+    #[arg(long, global = true)]
+    codecs: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -135,6 +176,107 @@ enum Commands {
         jobs: Option<usize>,
     },
 
+    /// Print the nodes matching a query expression, e.g.
+    /// 'kind:Document & schema:task & payload.status~/open/ | net:docs/path:guides/*'.
+    /// See `Expression::from_str` in `noet_core::query` for the syntax.
+    Query {
+        /// The query expression
+        expr: String,
+
+        /// Path to the document or directory to query (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Read the graph from a `watch` service's cache database (its `belief_cache.db`) instead
+        /// of parsing the documents under `path`
+        #[cfg(feature = "service")]
+        #[arg(long)]
+        cache: Option<PathBuf>,
+
+        /// Also include nodes up to this many relations upstream of the matches
+        #[arg(long, default_value_t = 0)]
+        up: u8,
+
+        /// Also include nodes up to this many relations downstream of the matches
+        #[arg(long, default_value_t = 0)]
+        down: u8,
+
+        /// Also include everything the nodes depend on through Epistemic relations, and print
+        /// them in reading order: each node after its prerequisites
+        #[arg(long)]
+        prerequisites: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Print the shortest chain of relations leading from one node to another, e.g.
+    /// `noet path docs/design.md id:storage-spec --kind epistemic`. Nodes are named by any
+    /// `NodeKey` form: a BID or BREF, `id:...`, `path:...` or a bare path.
+    Path {
+        /// The node the paths start from
+        from: String,
+
+        /// The node the paths lead to
+        to: String,
+
+        /// Path to the document or directory to search (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Read the graph from a `watch` service's cache database (its `belief_cache.db`) instead
+        /// of parsing the documents under `path`
+        #[cfg(feature = "service")]
+        #[arg(long)]
+        cache: Option<PathBuf>,
+
+        /// Only follow relations of this kind (repeatable; default: every kind)
+        #[arg(long)]
+        kind: Vec<String>,
+
+        /// Print every path without repeated nodes, up to `--max-hops` relations long
+        #[arg(long)]
+        all: bool,
+
+        /// The most relations a path printed by `--all` may follow
+        #[arg(long, default_value_t = 5)]
+        max_hops: u8,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Upgrade documents to the current version of their schema and write them back
+    Migrate {
+        /// Path to the directory to migrate
+        path: PathBuf,
+
+        /// List the files that would change without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Move a document or directory, rewriting the relative links that the move would break.
+    /// Moving it back reverses both.
+    #[cfg(feature = "service")]
+    Mv {
+        /// File or directory to move
+        from: PathBuf,
+
+        /// Destination path
+        to: PathBuf,
+
+        /// Root directory of the network (default: current directory)
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        /// List the moves and link rewrites without changing any file
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Watch a directory for changes and continuously parse
     #[cfg(feature = "service")]
     Watch {
@@ -174,6 +316,21 @@ enum Commands {
         /// Port for dev server (default: 9037)
         #[arg(long, default_value = "9037")]
         port: u16,
+
+        /// Answer JSON-RPC requests on a Unix socket path or a local TCP address
+        /// (e.g., 127.0.0.1:9038)
+        #[arg(long)]
+        listen: Option<RpcAddr>,
+    },
+
+    /// Execute an op on a `watch --listen` service and print the result as JSON
+    #[cfg(feature = "service")]
+    Call {
+        /// Address the service listens on
+        addr: RpcAddr,
+
+        /// The op as JSON, e.g. '"LoadNetworks"' or '{"GetStates": {"query": ...}}'
+        op: String,
     },
 }
 
@@ -226,6 +383,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let cli = Cli::parse();
     let color_choice = cli.color.clone();
+    if let Some(codecs) = cli.codecs.as_ref() {
+        register_external_codecs(codecs)?;
+    }
 
     match cli.command {
         Commands::Init {
@@ -339,7 +499,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .enable_all()
                 .build()?;
             runtime.block_on(async {
-                use noet_core::beliefbase::BeliefBase;
                 use noet_core::event::BeliefEvent;
                 use tokio::sync::mpsc::unbounded_channel;
 
@@ -395,11 +554,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 // Finalize HTML generation with synchronized BeliefBase
                 // Note: finalize() was already called during parse_all (with empty global_bb)
                 // Now call finalize_html with synchronized final_bb for remaining tasks
-                let finalize_diagnostics = if html_output.is_some() {
+                let mut finalize_diagnostics = if html_output.is_some() {
                     compiler.finalize_html(&final_bb).await?
                 } else {
                     Vec::new()
                 };
+                // Nodes that depend on each other have no reading order.
+                for cycle in final_bb.epistemic_cycles() {
+                    finalize_diagnostics
+                        .push(ParseDiagnostic::warning(cycle_warning(&final_bb, &cycle)));
+                }
 
                 // Collect and report diagnostics
                 let colors = DiagColors::new(&color_choice);
@@ -538,6 +702,290 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(())
         }
 
+        Commands::Query {
+            expr,
+            path,
+            #[cfg(feature = "service")]
+            cache,
+            up,
+            down,
+            prerequisites,
+            format,
+        } => {
+            use noet_core::properties::WeightKind;
+            use noet_core::query::{BeliefSource, NeighborsExpression, Query};
+
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let (bb, nodes, cycles) = runtime.block_on(async {
+                #[cfg(feature = "service")]
+                let bb = if let Some(db_path) = cache {
+                    read_cache(db_path).await?
+                } else {
+                    parse_graph(&path).await?
+                };
+                #[cfg(not(feature = "service"))]
+                let bb = parse_graph(&path).await?;
+
+                let seed = Expression::from_str_with_cache(&expr, &bb)?;
+                let mut bids = matching_bids(&bb, &seed);
+                if up > 0 || down > 0 {
+                    let query = Query {
+                        seed,
+                        traverse: Some(NeighborsExpression {
+                            filter: None,
+                            upstream: up,
+                            downstream: down,
+                        }),
+                    };
+                    let graph = bb.eval_query(&query, false).await?;
+                    bids.extend(
+                        graph
+                            .states
+                            .values()
+                            .filter(|node| node.kind.is_complete())
+                            .map(|node| node.bid),
+                    );
+                }
+                let (bids, cycles) = match prerequisites {
+                    true => {
+                        // A document's links are made from its sections, so they seed its
+                        // learning path too.
+                        let seeds = {
+                            let relations = bb.relations();
+                            bids.iter()
+                                .flat_map(|bid| {
+                                    let mut tree =
+                                        relations.source_subgraph(*bid, WeightKind::Section);
+                                    tree.insert(*bid);
+                                    tree
+                                })
+                                .collect::<BTreeSet<_>>()
+                        };
+                        let reading = bb.reading_order(&Vec::from_iter(seeds));
+                        (reading.order, reading.cycles)
+                    }
+                    false => (Vec::from_iter(bids), vec![]),
+                };
+                let nodes: Vec<BeliefNode> = bids
+                    .iter()
+                    .filter_map(|bid| bb.states().get(bid).cloned())
+                    .collect();
+                Ok::<_, Box<dyn std::error::Error>>((bb, nodes, cycles))
+            })?;
+
+            let colors = DiagColors::new(&color_choice);
+            for cycle in cycles.iter() {
+                let label = format!("{}warning{}", colors.warning, colors.reset);
+                eprintln!("{label}: {}", cycle_warning(&bb, cycle));
+            }
+            match format {
+                OutputFormat::Table => {
+                    for node in nodes.iter() {
+                        println!("{}", table_row(&bb, node));
+                    }
+                }
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&nodes)?),
+                OutputFormat::Toml => {
+                    let mut results = toml::Table::new();
+                    results.insert("nodes".to_string(), toml::Value::try_from(&nodes)?);
+                    print!("{}", toml::to_string(&results)?);
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Path {
+            from,
+            to,
+            path,
+            #[cfg(feature = "service")]
+            cache,
+            kind,
+            all,
+            max_hops,
+            format,
+        } => {
+            use noet_core::properties::{WeightKind, WeightSet};
+
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let (bb, paths) = runtime.block_on(async {
+                #[cfg(feature = "service")]
+                let bb = if let Some(db_path) = cache {
+                    read_cache(db_path).await?
+                } else {
+                    parse_graph(&path).await?
+                };
+                #[cfg(not(feature = "service"))]
+                let bb = parse_graph(&path).await?;
+
+                // Parsing the networks' schemas, or opening the cache, registers their custom
+                // relation kinds.
+                let weights = match kind.is_empty() {
+                    true => WeightSet::full(),
+                    false => kind.iter().try_fold(WeightSet::empty(), |weights, kind| {
+                        WeightKind::try_from(kind.as_str())
+                            .map(|kind| weights.union(&WeightSet::from(kind)))
+                    })?,
+                };
+                let from_bid = find_node(&bb, &from)?.bid;
+                let to_bid = find_node(&bb, &to)?.bid;
+                // The whole graph is in memory, so the paths are read straight off its relations.
+                let relations = bb.relations();
+                let paths = if all {
+                    relations.simple_paths(from_bid, to_bid, &weights, max_hops as usize)
+                } else {
+                    Vec::from_iter(relations.shortest_path(from_bid, to_bid, &weights))
+                };
+                if paths.is_empty() {
+                    return Err(format!("No path leads from '{from}' to '{to}'").into());
+                }
+                Ok::<_, Box<dyn std::error::Error>>((bb, paths))
+            })?;
+
+            let paths: Vec<Vec<BeliefNode>> = paths
+                .iter()
+                .map(|path| {
+                    path.iter()
+                        .filter_map(|bid| bb.states().get(bid).cloned())
+                        .collect()
+                })
+                .collect();
+            match format {
+                OutputFormat::Table => {
+                    for (idx, path) in paths.iter().enumerate() {
+                        if idx > 0 {
+                            println!();
+                        }
+                        for node in path {
+                            println!("{}", table_row(&bb, node));
+                        }
+                    }
+                }
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&paths)?),
+                OutputFormat::Toml => {
+                    let paths = paths
+                        .iter()
+                        .map(|nodes| {
+                            let mut path = toml::Table::new();
+                            path.insert("nodes".to_string(), toml::Value::try_from(nodes)?);
+                            Ok(toml::Value::Table(path))
+                        })
+                        .collect::<Result<Vec<_>, toml::ser::Error>>()?;
+                    let mut results = toml::Table::new();
+                    results.insert("paths".to_string(), toml::Value::Array(paths));
+                    print!("{}", toml::to_string(&results)?);
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Migrate { path, dry_run } => {
+            let pending = plan_migrations(&path)?;
+            for migration in &pending {
+                println!(
+                    "{}: {} v{} -> v{} ({})",
+                    migration.path.display(),
+                    migration.schema,
+                    migration.from_version,
+                    migration.to_version,
+                    migration.changed_fields.join(", ")
+                );
+            }
+            let count = pending.len();
+            let files = if count == 1 { "file" } else { "files" };
+            if dry_run || pending.is_empty() {
+                println!("{count} {files} would be migrated");
+                return Ok(());
+            }
+
+            // Migrations run as documents are parsed; only the migrated documents are written.
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let parse_results = runtime.block_on(apply_migrations(&path, &pending))?;
+
+            let colors = DiagColors::new(&color_choice);
+            let mut error_count = 0usize;
+            for result in parse_results
+                .iter()
+                .filter(|result| pending.iter().any(|m| m.path == result.path))
+            {
+                for diagnostic in result.diagnostics.iter().filter(|d| d.is_parse_error()) {
+                    let label = format!("{}error{}", colors.error, colors.reset);
+                    eprintln!("{}: {label}: {diagnostic}", result.path.display());
+                    error_count += 1;
+                }
+            }
+            println!("{} {files} migrated", count);
+            if error_count > 0 {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+
+        #[cfg(feature = "service")]
+        Commands::Mv {
+            from,
+            to,
+            root,
+            dry_run,
+        } => {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            runtime.block_on(async {
+                let mut executor = OpExecutor::new(&root)?;
+                let cwd = std::env::current_dir()?.canonicalize()?;
+                let repo_root = executor.compiler().builder().repo_root().to_path_buf();
+                let relative = |path: &PathBuf| {
+                    cwd.join(path)
+                        .strip_prefix(&repo_root)
+                        .map(os_path_to_string)
+                        .map_err(|_| {
+                            format!("{} is outside of {}", path.display(), repo_root.display())
+                        })
+                };
+                let plan = executor
+                    .plan_move(&relative(&from)?, &relative(&to)?)
+                    .await?;
+                for (from, to) in plan.files.iter() {
+                    println!("{from} -> {to}");
+                }
+                for link in plan.links.iter() {
+                    println!("  {}: {} -> {}", link.path, link.from, link.to);
+                }
+                let files = if plan.files.len() == 1 {
+                    "file"
+                } else {
+                    "files"
+                };
+                let links = if plan.links.len() == 1 {
+                    "link"
+                } else {
+                    "links"
+                };
+                if dry_run {
+                    println!(
+                        "{} {files} would be moved, {} {links} rewritten",
+                        plan.files.len(),
+                        plan.links.len()
+                    );
+                    return Ok::<(), Box<dyn std::error::Error>>(());
+                }
+                executor.apply_move(&plan).await?;
+                println!(
+                    "{} {files} moved, {} {links} rewritten",
+                    plan.files.len(),
+                    plan.links.len()
+                );
+                Ok(())
+            })
+        }
+
         #[cfg(feature = "service")]
         Commands::Watch {
             path,
@@ -549,6 +997,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             base_url,
             serve,
             port,
+            listen,
         } => {
             // Read base_url from environment if not provided via CLI
             let base_url = base_url.or_else(|| std::env::var("NOET_BASE_URL").ok());
@@ -583,6 +1032,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     if serve {
                         println!("Dev server: enabled on port {}", port);
                     }
+                    if let Some(ref addr) = listen {
+                        println!("JSON-RPC: listening on {addr}");
+                    }
                 }
 
                 // Determine root directory for service
@@ -720,8 +1172,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 };
 
                 // Keep running until Ctrl-C
-                while running.load(std::sync::atomic::Ordering::SeqCst) {
-                    std::thread::sleep(Duration::from_millis(100));
+                if let Some(ref addr) = listen {
+                    let running = running.clone();
+                    service.serve_rpc(addr, async move {
+                        while running.load(std::sync::atomic::Ordering::SeqCst) {
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                        }
+                    })?;
+                } else {
+                    while running.load(std::sync::atomic::Ordering::SeqCst) {
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
                 }
 
                 // Cleanup
@@ -756,5 +1217,155 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 Ok(())
             }
         }
+
+        #[cfg(feature = "service")]
+        Commands::Call { addr, op } => {
+            let op = serde_json::from_str(&op)?;
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let result = runtime.block_on(async {
+                let mut client = RpcClient::connect(&addr).await?;
+                client.execute(op).await
+            })?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
+            Ok(())
+        }
+    }
+}
+
+/// The nodes `expr` selects. Evaluating an expression also returns the nodes related to its
+/// matches, so set operations are applied to the matches of each predicate instead.
+fn matching_bids(bb: &BeliefBase, expr: &Expression) -> BTreeSet<Bid> {
+    match expr {
+        Expression::Dyad(lhs, op, rhs) => {
+            let (lhs, rhs) = (matching_bids(bb, lhs), matching_bids(bb, rhs));
+            match op {
+                SetOp::Union => &lhs | &rhs,
+                SetOp::Intersection => &lhs & &rhs,
+                SetOp::Difference => &lhs - &rhs,
+                SetOp::SymmetricDifference => &lhs ^ &rhs,
+            }
+        }
+        // Relation predicates select the nodes at either end of the matching relations.
+        Expression::RelationIn(_) | Expression::RelationNotIn(_) => {
+            bb.evaluate_expression(expr).states.into_keys().collect()
+        }
+        _ => bb
+            .evaluate_expression(expr)
+            .states
+            .into_values()
+            .filter(|node| node.kind.is_complete())
+            .map(|node| node.bid)
+            .collect(),
+    }
+}
+
+/// A node's path within the first network that holds it.
+fn node_path(bb: &BeliefBase, bid: &Bid) -> Option<String> {
+    let paths = bb.paths();
+    paths
+        .nets()
+        .iter()
+        .find_map(|net| paths.net_path(&net.bref(), bid))
+        .map(|(_, path)| path)
+}
+
+/// A node's line in table output: its BREF, kinds, title and path within its network.
+fn table_row(bb: &BeliefBase, node: &BeliefNode) -> String {
+    format!(
+        "{}\t{}\t{}\t{}",
+        node.bid.bref(),
+        node.kind,
+        node.display_title(),
+        node_path(bb, &node.bid).unwrap_or_default()
+    )
+}
+
+/// The warning for a cycle of Epistemic relations, naming its nodes by title and path.
+fn cycle_warning(bb: &BeliefBase, cycle: &[Bid]) -> String {
+    let states = bb.states();
+    let members = cycle
+        .iter()
+        .map(|bid| {
+            let title = states
+                .get(bid)
+                .map(|node| node.display_title())
+                .unwrap_or_else(|| bid.bref().to_string());
+            match node_path(bb, bid) {
+                Some(path) => format!("'{title}' ({path})"),
+                None => format!("'{title}'"),
+            }
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "circular Epistemic dependency, no reading order puts one first: {}",
+        members.join(", ")
+    )
+}
+
+/// The node a `NodeKey` string names. Keys without an explicit network are looked up in each
+/// network in turn.
+fn find_node(bb: &BeliefBase, key: &str) -> Result<BeliefNode, Box<dyn std::error::Error>> {
+    let node_key = NodeKey::from_str_with_cache(key, bb)?;
+    let candidates = match &node_key {
+        NodeKey::Id { net, id } if net.is_default() => bb
+            .paths()
+            .nets()
+            .iter()
+            .map(|net| NodeKey::Id {
+                net: net.bref(),
+                id: id.clone(),
+            })
+            .collect(),
+        NodeKey::Path { net, path } if net.is_default() => bb
+            .paths()
+            .nets()
+            .iter()
+            .map(|net| NodeKey::Path {
+                net: net.bref(),
+                path: path.clone(),
+            })
+            .collect(),
+        _ => vec![node_key],
+    };
+    candidates
+        .iter()
+        .find_map(|key| bb.get(key))
+        .ok_or_else(|| format!("No node matches '{key}'").into())
+}
+
+/// The graph of the documents under `path`, parsed without writing back to them.
+async fn parse_graph(path: &Path) -> Result<BeliefBase, noet_core::BuildonomyError> {
+    use noet_core::event::BeliefEvent;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    let (tx, mut rx) = unbounded_channel::<BeliefEvent>();
+    let mut compiler = DocumentCompiler::new(path, Some(tx), None, false)?;
+    let cache = compiler.builder().doc_bb().clone();
+    compiler.parse_all(cache, false).await?;
+    compiler.builder_mut().close_tx();
+    let mut bb = BeliefBase::empty();
+    while let Some(event) = rx.recv().await {
+        let _ = bb.process_event(&event);
+    }
+    Ok(bb)
+}
+
+/// The graph a `watch` service keeps in its cache database.
+#[cfg(feature = "service")]
+async fn read_cache(db_path: PathBuf) -> Result<BeliefBase, Box<dyn std::error::Error>> {
+    use noet_core::db::{db_init, DbConnection};
+    use noet_core::query::{BeliefSource, Query, StatePred};
+
+    if !db_path.exists() {
+        return Err(format!("No cache at {}", db_path.display()).into());
     }
+    let db = DbConnection(db_init(db_path).await?);
+    // Not every predicate translates to SQL, so queries run against the whole graph in memory.
+    let all = Query {
+        seed: Expression::StateIn(StatePred::Any),
+        traverse: None,
+    };
+    Ok(BeliefBase::from(db.eval_query(&all, false).await?))
 }
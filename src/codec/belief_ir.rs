@@ -1,13 +1,13 @@
 use crate::{
     beliefbase::BeliefContext,
-    codec::schema_registry::{migrate_schema, EdgeDirection, SCHEMAS},
+    codec::schema_registry::{EdgeDirection, SCHEMAS},
     error::BuildonomyError,
     nodekey::NodeKey,
     paths::to_anchor,
     properties::{BeliefKindSet, BeliefNode, Bref, Weight, WeightKind},
 };
 
-use std::{mem::replace, ops::Deref, str::FromStr};
+use std::{mem::replace, ops::Deref, path::Path, str::FromStr};
 use toml::{to_string, Table as TomlTable};
 use toml_edit::{value, DocumentMut};
 
@@ -123,7 +123,7 @@ fn parse_yaml_to_document(yaml_str: &str) -> Result<DocumentMut, BuildonomyError
 }
 
 /// Convert JSON value to TOML string
-fn json_to_toml_string(json: &serde_json::Value) -> Result<String, BuildonomyError> {
+pub(crate) fn json_to_toml_string(json: &serde_json::Value) -> Result<String, BuildonomyError> {
     // Convert JSON to TOML via toml::Value
     let toml_value = json_value_to_toml_value(json)?;
     toml::to_string(&toml_value)
@@ -263,6 +263,9 @@ pub struct IntermediateRelation {
     /// `None` for relations derived from serialized data (TOML schema fields).
     /// Convert to `(line, col)` at display time via [`crate::codec::byte_offset_to_location`].
     pub location: Option<usize>,
+    /// The schema graph field this relation was read from, if any. Used to check the
+    /// field's target, cardinality and cycle constraints once the relation is resolved.
+    pub schema_field: Option<String>,
 }
 
 impl IntermediateRelation {
@@ -272,6 +275,7 @@ impl IntermediateRelation {
             kind,
             weight,
             location: None,
+            schema_field: None,
         }
     }
 
@@ -279,6 +283,11 @@ impl IntermediateRelation {
         self.location = Some(byte_offset);
         self
     }
+
+    pub fn with_schema_field(mut self, field_name: impl Into<String>) -> Self {
+        self.schema_field = Some(field_name.into());
+        self
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -418,7 +427,9 @@ impl IRNode {
         &mut self,
         ctx: &BeliefContext<'_>,
     ) -> Result<Option<BeliefNode>, BuildonomyError> {
-        let mut changed = self.merge(&mut IRNode::try_from(ctx.node)?);
+        let mut from_ctx = IRNode::try_from(ctx.node)?;
+        from_ctx.strip_schema_defaults(&self.document);
+        let mut changed = self.merge(&mut from_ctx);
         // Only update path from context for section nodes (heading > 2)
         // Document nodes already have correct path from IRNode::new()
         // Section nodes need path from PathMap because they don't have independent file paths
@@ -436,6 +447,31 @@ impl IRNode {
         }
     }
 
+    /// Drop the schema defaults that `source` does not set. They are materialised into the node
+    /// payload, but the source only holds what its author wrote.
+    fn strip_schema_defaults(&mut self, source: &DocumentMut) {
+        let Some(schema_def) = self
+            .document
+            .get("schema")
+            .and_then(|schema| schema.as_str())
+            .and_then(|schema| SCHEMAS.get_for(schema, Path::new(&self.path)))
+        else {
+            return;
+        };
+        for field in schema_def.fields.iter() {
+            let Some(default) = field.default.as_ref() else {
+                continue;
+            };
+            let is_default = self
+                .document
+                .get(&field.field_name)
+                .is_some_and(|item| toml_edit_to_toml_value(item).as_ref() == Some(default));
+            if is_default && source.get(&field.field_name).is_none() {
+                self.document.remove(&field.field_name);
+            }
+        }
+    }
+
     /// Updates the schema-defined fields in the TOML document based on BeliefContext relationships.
     /// This syncs the document's parent_connections array with the actual graph state.
     fn update_schema(&mut self, _ctx: &BeliefContext<'_>) -> Result<bool, BuildonomyError> {
@@ -459,14 +495,28 @@ impl IRNode {
         if self.document.get("schema").is_none() {
             self.document.insert("schema", value(schema_name.clone()));
         }
+        let schema_def = match SCHEMAS.get_for(&schema_name, Path::new(&self.path)) {
+            Some(def) => def,
+            None => return Ok(()), // Schema not found in registry
+        };
+
         // Apply schema migrations before traversal
         // Convert document to toml::Value for migration, then back to toml_edit
         let toml_string = self.document.to_string();
-        let mut toml_value: toml::Value = toml::from_str(&toml_string).map_err(|e| {
+        let mut toml_value: TomlTable = toml::from_str(&toml_string).map_err(|e| {
             BuildonomyError::Codec(format!("Failed to convert to toml::Value: {e}"))
         })?;
 
-        if migrate_schema(&schema_name, &mut toml_value) {
+        let migrated_from = schema_def
+            .migrate(&mut toml_value)
+            .map_err(|e| BuildonomyError::Codec(format!("Schema '{schema_name}' {e}")))?;
+        if let Some(from_version) = migrated_from {
+            tracing::debug!(
+                "[traverse_schema] Migrated {:?} from schema '{schema_name}' version \
+                 {from_version} to {}",
+                self.path,
+                schema_def.version
+            );
             // Migration occurred - convert back and update document
             let migrated_string = toml::to_string(&toml_value).map_err(|e| {
                 BuildonomyError::Codec(format!("Failed to serialize migrated TOML: {e}"))
@@ -481,14 +531,9 @@ impl IRNode {
             self.content = self.document.to_string();
         }
 
-        let schema_def = match SCHEMAS.get(&schema_name) {
-            Some(def) => def,
-            None => return Ok(()), // Schema not found in registry
-        };
-
         // Traverse each graph field defined in the schema using toml::Value (simpler than toml_edit)
         for graph_field in schema_def.graph_fields.iter() {
-            let field_value = match toml_value.get(graph_field.field_name) {
+            let field_value = match toml_value.get(&graph_field.field_name) {
                 Some(v) => v,
                 None => {
                     if graph_field.required {
@@ -523,9 +568,8 @@ impl IRNode {
                         // Build payload from specified fields
                         let mut payload_table = TomlTable::new();
                         for payload_field in graph_field.payload_fields.iter() {
-                            if let Some(payload_value) = table.get(*payload_field) {
-                                payload_table
-                                    .insert(payload_field.to_string(), payload_value.clone());
+                            if let Some(payload_value) = table.get(payload_field) {
+                                payload_table.insert(payload_field.clone(), payload_value.clone());
                             }
                         }
 
@@ -549,21 +593,11 @@ impl IRNode {
                         continue; // Unknown item type
                     };
                     // Add to appropriate edge list based on direction enum
+                    let relation = IntermediateRelation::new(node_key, weight_kind, payload)
+                        .with_schema_field(&graph_field.field_name);
                     match graph_field.direction {
-                        EdgeDirection::Downstream => {
-                            self.downstream.push(IntermediateRelation::new(
-                                node_key,
-                                weight_kind,
-                                payload,
-                            ));
-                        }
-                        EdgeDirection::Upstream => {
-                            self.upstream.push(IntermediateRelation::new(
-                                node_key,
-                                weight_kind,
-                                payload,
-                            ));
-                        }
+                        EdgeDirection::Downstream => self.downstream.push(relation),
+                        EdgeDirection::Upstream => self.upstream.push(relation),
                     }
                 }
             } else if let Some(id_str) = field_value.as_str() {
@@ -572,18 +606,11 @@ impl IRNode {
                     net: Bref::default(),
                     id: id_str.to_string(),
                 });
+                let relation = IntermediateRelation::new(node_key, weight_kind, None)
+                    .with_schema_field(&graph_field.field_name);
                 match graph_field.direction {
-                    EdgeDirection::Downstream => {
-                        self.downstream.push(IntermediateRelation::new(
-                            node_key,
-                            weight_kind,
-                            None,
-                        ));
-                    }
-                    EdgeDirection::Upstream => {
-                        self.upstream
-                            .push(IntermediateRelation::new(node_key, weight_kind, None));
-                    }
+                    EdgeDirection::Downstream => self.downstream.push(relation),
+                    EdgeDirection::Upstream => self.upstream.push(relation),
                 }
             }
         }
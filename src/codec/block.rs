@@ -0,0 +1,421 @@
+//! Obsidian/Logseq-style block references for [`MdCodec`](super::md::MdCodec).
+//!
+//! A block id is a `^block-id` marker (letters, digits and `-`) at the end of a paragraph or list
+//! item, separated from the text by whitespace:
+//!
+//! ```markdown
+//! The build is deterministic. ^deterministic
+//!
+//! - first item
+//! - second item ^second
+//! ```
+//!
+//! Tables, lists, quotes and code blocks can't end in a marker, so, as in Obsidian, a paragraph
+//! holding only a marker names the block right before it.
+//!
+//! Each block becomes a section node below the heading it appears under, with the block id as its
+//! anchor (`doc.md#deterministic`) and the block's plain text as its `text` payload, so links,
+//! search and the HTML output can all target a single paragraph. Obsidian's `doc.md#^block-id`
+//! link form resolves to the same node, and links to blocks are written back in that form.
+//!
+//! The marker is the source of truth and is never rewritten; [`Renderer`] strips it from the HTML
+//! output and sets the block's anchor as the id of the rendered element.
+
+use pulldown_cmark::{CowStr, Event as MdEvent, Tag as MdTag, TagEnd as MdTagEnd};
+use std::collections::{HashMap, HashSet};
+
+use crate::paths::to_anchor;
+
+/// The payload key marking a node as a block.
+pub const BLOCK_KEY: &str = "block";
+
+/// Maximum length, in characters, of the title derived from a block's text.
+const TITLE_LEN: usize = 60;
+
+/// Where a block's marker is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Marker {
+    /// The marker ends the run of text events `first..=last`; the first `keep` bytes of their
+    /// text precede it.
+    Trailing {
+        first: usize,
+        last: usize,
+        keep: usize,
+    },
+    /// The marker is a paragraph of its own spanning these event indices, after the block.
+    Paragraph { start: usize, end: usize },
+}
+
+/// A block found in a stream of events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// The normalized block id, without the `^`.
+    pub id: String,
+    /// The block's plain text, without the marker.
+    pub text: String,
+    /// Index of the block's start tag.
+    pub start: usize,
+    /// Index of the block's end tag.
+    pub end: usize,
+    pub marker: Marker,
+}
+
+impl Block {
+    /// A title for the block node: its text, shortened at a word boundary if needed.
+    pub fn title(&self) -> String {
+        if self.text.chars().count() <= TITLE_LEN {
+            return self.text.clone();
+        }
+        let cut = self
+            .text
+            .char_indices()
+            .nth(TITLE_LEN)
+            .map(|(idx, _)| idx)
+            .unwrap_or(self.text.len());
+        let head = &self.text[..cut];
+        let head = head.rsplit_once(' ').map(|(head, _)| head).unwrap_or(head);
+        format!("{}…", head.trim_end())
+    }
+}
+
+/// The block id ending `text`, and the length of the text before the marker (and the whitespace
+/// separating them).
+pub fn parse_marker(text: &str) -> Option<(usize, String)> {
+    let trimmed = text.trim_end();
+    let caret = trimmed.rfind('^')?;
+    let raw_id = &trimmed[caret + 1..];
+    if raw_id.is_empty() || !raw_id.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        return None;
+    }
+    let before = &trimmed[..caret];
+    if !before.is_empty() && !before.ends_with(char::is_whitespace) {
+        return None;
+    }
+    let id = to_anchor(raw_id);
+    (!id.is_empty()).then_some((before.trim_end().len(), id))
+}
+
+/// The text ending the container spanning `start..=end`: the index of the first of the text events
+/// it's made of, and their text. pulldown-cmark splits text at characters that may start inline
+/// markup, like the `^` of a marker.
+fn trailing_text(events: &[MdEvent<'_>], start: usize, end: usize) -> Option<(usize, String)> {
+    let first = (start + 1..end)
+        .rev()
+        .take_while(|idx| matches!(events[*idx], MdEvent::Text(_)))
+        .last()?;
+    let text = events[first..end]
+        .iter()
+        .filter_map(|event| match event {
+            MdEvent::Text(text) => Some(text.as_ref()),
+            _ => None,
+        })
+        .collect();
+    Some((first, text))
+}
+
+/// The marker ending the container spanning `start..=end`, if it is separated from the preceding
+/// content: the first text event of the marker's run, the length of the text to keep and the id.
+fn trailing_marker(
+    events: &[MdEvent<'_>],
+    start: usize,
+    end: usize,
+) -> Option<(usize, usize, String)> {
+    let (first, text) = trailing_text(events, start, end)?;
+    let (keep, id) = parse_marker(&text)?;
+    if keep == 0 && !text.starts_with(char::is_whitespace) && first > start + 1 {
+        // Only a line break may separate the marker from inline markup before it.
+        if !matches!(events[first - 1], MdEvent::SoftBreak | MdEvent::HardBreak) {
+            return None;
+        }
+    }
+    Some((first, keep, id))
+}
+
+/// The plain text of `events`, with whitespace collapsed.
+fn plain_text<'a>(events: impl Iterator<Item = &'a MdEvent<'a>>) -> String {
+    let mut text = String::new();
+    for event in events {
+        match event {
+            MdEvent::Text(t) | MdEvent::Code(t) | MdEvent::InlineMath(t) => text.push_str(t),
+            MdEvent::SoftBreak | MdEvent::HardBreak | MdEvent::End(_) => text.push(' '),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The blocks in `events`, in the order their markers appear.
+pub fn find_blocks(events: &[MdEvent<'_>]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut open = Vec::new();
+    // Start index of each closed container, by end index.
+    let mut closed = HashMap::new();
+    for (idx, event) in events.iter().enumerate() {
+        match event {
+            MdEvent::Start(_) => open.push(idx),
+            MdEvent::End(tag_end) => {
+                let Some(start) = open.pop() else {
+                    continue;
+                };
+                closed.insert(idx, start);
+                if !matches!(tag_end, MdTagEnd::Paragraph | MdTagEnd::Item) {
+                    continue;
+                }
+                let Some((first, keep, id)) = trailing_marker(events, start, idx) else {
+                    continue;
+                };
+                if keep == 0 && first == start + 1 && matches!(tag_end, MdTagEnd::Paragraph) {
+                    // A marker paragraph names the preceding block, if it has no marker of its
+                    // own.
+                    let previous = start.checked_sub(1).filter(|prev| {
+                        matches!(
+                            events[*prev],
+                            MdEvent::End(
+                                MdTagEnd::Table
+                                    | MdTagEnd::List(_)
+                                    | MdTagEnd::BlockQuote(_)
+                                    | MdTagEnd::CodeBlock
+                            )
+                        )
+                    });
+                    if let Some(prev_end) = previous {
+                        let prev_start = closed[&prev_end];
+                        blocks.push(Block {
+                            id,
+                            text: plain_text(events[prev_start..=prev_end].iter()),
+                            start: prev_start,
+                            end: prev_end,
+                            marker: Marker::Paragraph { start, end: idx },
+                        });
+                    }
+                    continue;
+                }
+                let (_, text) = trailing_text(events, start, idx)
+                    .expect("trailing_marker only matches containers ending in text");
+                let kept = MdEvent::Text(CowStr::from(&text[..keep]));
+                blocks.push(Block {
+                    id,
+                    text: plain_text(events[start..first].iter().chain([&kept])),
+                    start,
+                    end: idx,
+                    marker: Marker::Trailing {
+                        first,
+                        last: idx - 1,
+                        keep,
+                    },
+                });
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Remove the markers of `blocks` from `events`, returning for each remaining event its index in
+/// `events`.
+fn strip_markers(events: &[MdEvent<'static>], blocks: &[&Block]) -> Vec<(usize, MdEvent<'static>)> {
+    let mut trailing = HashMap::new();
+    let mut skipped = HashSet::new();
+    for block in blocks {
+        match block.marker {
+            Marker::Trailing { first, last, keep } => {
+                trailing.insert(first, (last, keep));
+                skipped.extend(first + 1..=last);
+            }
+            Marker::Paragraph { start, end } => skipped.extend(start..=end),
+        }
+    }
+    events
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !skipped.contains(idx))
+        .map(|(idx, event)| match trailing.get(&idx) {
+            // The run of text holding the marker becomes a single event without it.
+            Some((last, keep)) => {
+                let text = events[idx..=*last]
+                    .iter()
+                    .filter_map(|event| match event {
+                        MdEvent::Text(text) => Some(text.as_ref()),
+                        _ => None,
+                    })
+                    .collect::<String>();
+                (idx, MdEvent::Text(CowStr::from(text[..*keep].to_string())))
+            }
+            None => (idx, event.clone()),
+        })
+        .collect()
+}
+
+/// The events of the block `id` in `events`, without its marker, for embedding.
+pub fn block_events(events: &[MdEvent<'static>], id: &str) -> Option<Vec<MdEvent<'static>>> {
+    let blocks = find_blocks(events);
+    let block = blocks.iter().find(|block| block.id == id)?;
+    Some(
+        strip_markers(&events[..=block.end], &[block])
+            .into_iter()
+            .filter(|(idx, _)| *idx >= block.start)
+            .map(|(_, event)| event)
+            .collect(),
+    )
+}
+
+fn html(fragment: String) -> MdEvent<'static> {
+    MdEvent::Html(CowStr::from(fragment))
+}
+
+/// Strips block markers from rendered output and sets block anchors as element ids.
+pub struct Renderer<'a> {
+    ids: &'a HashSet<String>,
+}
+
+impl<'a> Renderer<'a> {
+    /// `ids` are the blocks that became nodes; other markers (e.g. ones whose anchor collides
+    /// with a heading) are left as written.
+    pub fn new(ids: &'a HashSet<String>) -> Self {
+        Renderer { ids }
+    }
+
+    pub fn render(&self, events: Vec<MdEvent<'static>>) -> Vec<MdEvent<'static>> {
+        if self.ids.is_empty() {
+            return events;
+        }
+        let blocks = find_blocks(&events);
+        let blocks = blocks
+            .iter()
+            .filter(|block| self.ids.contains(&block.id))
+            .collect::<Vec<_>>();
+        let starts = blocks
+            .iter()
+            .map(|block| (block.start, *block))
+            .collect::<HashMap<_, _>>();
+        let wrapped_ends = blocks
+            .iter()
+            .filter(|block| matches!(block.marker, Marker::Paragraph { .. }))
+            .map(|block| block.end)
+            .collect::<HashSet<_>>();
+
+        let mut out = Vec::with_capacity(events.len());
+        for (idx, event) in strip_markers(&events, &blocks) {
+            match (starts.get(&idx), &event) {
+                (Some(block), MdEvent::Start(MdTag::Paragraph)) => {
+                    out.push(html(format!("<p id=\"{}\">", block.id)));
+                }
+                (Some(block), MdEvent::Start(MdTag::Item)) => {
+                    out.push(html(format!("<li id=\"{}\">", block.id)));
+                }
+                (Some(block), _) => {
+                    out.push(html(format!("<div id=\"{}\">\n", block.id)));
+                    out.push(event);
+                }
+                (None, _) => {
+                    let wrapped = wrapped_ends.contains(&idx);
+                    out.push(event);
+                    if wrapped {
+                        out.push(html("</div>\n".to_string()));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::md::buildonomy_md_options;
+    use pulldown_cmark::Parser as MdParser;
+
+    fn events(markdown: &str) -> Vec<MdEvent<'static>> {
+        MdParser::new_ext(markdown, buildonomy_md_options())
+            .map(|event| event.into_static())
+            .collect()
+    }
+
+    fn render(markdown: &str, ids: &[&str]) -> String {
+        let ids = ids.iter().map(|id| id.to_string()).collect();
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(
+            &mut html,
+            Renderer::new(&ids).render(events(markdown)).into_iter(),
+        );
+        html
+    }
+
+    #[test]
+    fn test_parse_marker() {
+        assert_eq!(
+            parse_marker("Some text ^Block-1 "),
+            Some((9, "block-1".to_string()))
+        );
+        assert_eq!(parse_marker("^only"), Some((0, "only".to_string())));
+        assert_eq!(parse_marker("2^10"), None);
+        assert_eq!(parse_marker("a ^not_an_id"), None);
+        assert_eq!(parse_marker("a ^"), None);
+    }
+
+    #[test]
+    fn test_find_blocks() {
+        let md = "Intro paragraph.\n\nA claim with `code`. ^claim\n\n\
+                  - first\n- second ^item\n\n\
+                  | a | b |\n|---|---|\n| 1 | 2 |\n\n^table\n\n\
+                  Not a block^x, nor is ^this one.\n";
+        let blocks = find_blocks(&events(md));
+        let summary = blocks
+            .iter()
+            .map(|block| (block.id.as_str(), block.text.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("claim", "A claim with code."),
+                ("item", "second"),
+                ("table", "a b 1 2"),
+            ]
+        );
+        assert!(matches!(blocks[2].marker, Marker::Paragraph { .. }));
+    }
+
+    #[test]
+    fn test_block_title() {
+        let block = Block {
+            id: "long".to_string(),
+            text: "word ".repeat(20).trim_end().to_string(),
+            start: 0,
+            end: 0,
+            marker: Marker::Trailing {
+                first: 0,
+                last: 0,
+                keep: 0,
+            },
+        };
+        assert_eq!(block.title(), format!("{}…", "word ".repeat(12).trim_end()));
+    }
+
+    #[test]
+    fn test_render() {
+        let html = render("A claim. ^claim\n\nOther ^other\n", &["claim"]);
+        assert!(html.contains("<p id=\"claim\">A claim.</p>"), "{html}");
+        assert!(html.contains("Other ^other"), "{html}");
+
+        let html = render(
+            "- one ^one\n- two\n\n```\ncode\n```\n\n^code\n",
+            &["one", "code"],
+        );
+        assert!(html.contains("<li id=\"one\">one</li>"), "{html}");
+        assert!(html.contains("<div id=\"code\">\n<pre>"), "{html}");
+        assert!(!html.contains("^code"), "{html}");
+    }
+
+    #[test]
+    fn test_block_events() {
+        let events = events("Intro.\n\nThe claim. ^claim\n\nOutro.\n");
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(
+            &mut html,
+            block_events(&events, "claim").unwrap().into_iter(),
+        );
+        assert_eq!(html, "<p>The claim.</p>\n");
+        assert!(block_events(&events, "missing").is_none());
+    }
+}
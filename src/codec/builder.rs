@@ -13,21 +13,23 @@ use tokio::sync::mpsc::UnboundedSender;
 use toml::value::Table as TomlTable;
 
 use crate::{
-    beliefbase::{BeliefBase, BeliefGraph},
+    beliefbase::{BeliefBase, BeliefGraph, ExtendedRelation},
     codec::{
         belief_ir::IRNode,
-        diagnostic::ParseDiagnostic,
+        diagnostic::{byte_offset_to_location, ParseDiagnostic},
         network::{detect_network_file, NETWORK_NAME},
         proto_index::ProtoIndex,
-        DocCodec, CODECS,
+        schema_registry::{FieldViolation, RelationViolation},
+        tag, DocCodec, CODECS, SCHEMAS,
     },
     error::BuildonomyError,
     event::{BeliefEvent, EventOrigin},
     nodekey::NodeKey,
-    paths::{as_anchor, os_path_to_string, path::string_to_os_path, AnchorPath},
+    paths::{as_anchor, os_path_to_string, path::string_to_os_path, to_anchor, AnchorPath},
     properties::{
-        buildonomy_namespace, content_namespaces, href_namespace, BeliefKind, BeliefKindSet,
-        BeliefNode, Bid, Bref, Weight, WeightKind, WEIGHT_DOC_PATHS, WEIGHT_SORT_KEY,
+        buildonomy_namespace, content_namespaces, href_namespace, tag_namespace, BeliefKind,
+        BeliefKindSet, BeliefNode, Bid, Bref, Weight, WeightKind, WEIGHT_DOC_PATHS,
+        WEIGHT_SORT_KEY,
     },
     query::{BeliefSource, Expression, Query},
 };
@@ -361,6 +363,7 @@ impl GraphBuilder {
                 } else {
                     None
                 };
+                diagnostics.extend(schema_diagnostics(proto, &content)?);
                 let (bid, (source, _nodekeys, unique_oldkeys)) = self
                     .push(
                         proto,
@@ -428,7 +431,9 @@ impl GraphBuilder {
             }
 
             tracing::debug!("Phase 2: Balance and process relations");
-            let mut generated_href_nodes = Vec::new();
+            let mut generated_nodes = Vec::new();
+            let mut title_index = None;
+            let mut schema_relations = Vec::new();
             for (proto, bid) in codec.nodes().iter().zip(parsed_bids.iter()) {
                 // Process upstream_relations (sink-owned, default)
                 for (index, relation) in proto.upstream.iter().enumerate() {
@@ -442,21 +447,30 @@ impl GraphBuilder {
                             global_bb.clone(),
                             &mut relation_event_queue,
                             &mut missing_structure,
+                            &mut generated_nodes,
                         )
                         .await?;
 
                     match result {
                         GetOrCreateResult::Resolved(node, source) => {
+                            diagnostics.extend(self.check_ambiguous_reference(
+                                relation,
+                                &node,
+                                &content,
+                                &mut title_index,
+                            ));
+                            schema_relations
+                                .extend(SchemaRelation::new(proto, *bid, relation, node.bid));
                             if source.is_from_cache() {
                                 inject_context = true;
                             } else if matches!(source, NodeSource::Generated) {
-                                generated_href_nodes.push(node.bid);
+                                generated_nodes.push(node.bid);
                                 if let Some(const_namespace) = content_namespaces()
                                     .iter()
                                     .find(|ns| node.bid.parent_bref() == ns.bref())
                                 {
-                                    if !generated_href_nodes.contains(const_namespace) {
-                                        generated_href_nodes.push(*const_namespace);
+                                    if !generated_nodes.contains(const_namespace) {
+                                        generated_nodes.push(*const_namespace);
                                     }
                                 }
                             }
@@ -480,15 +494,24 @@ impl GraphBuilder {
                             global_bb.clone(),
                             &mut relation_event_queue,
                             &mut missing_structure,
+                            &mut generated_nodes,
                         )
                         .await?;
 
                     match result {
                         GetOrCreateResult::Resolved(node, source) => {
+                            diagnostics.extend(self.check_ambiguous_reference(
+                                relation,
+                                &node,
+                                &content,
+                                &mut title_index,
+                            ));
+                            schema_relations
+                                .extend(SchemaRelation::new(proto, *bid, relation, node.bid));
                             if source == NodeSource::GlobalCache {
                                 inject_context = true;
                             } else if matches!(source, NodeSource::Generated) {
-                                generated_href_nodes.push(node.bid);
+                                generated_nodes.push(node.bid);
                             }
                         }
                         GetOrCreateResult::Unresolved(unresolved) => {
@@ -498,8 +521,8 @@ impl GraphBuilder {
                     }
                 }
             }
-            if !generated_href_nodes.is_empty() {
-                parsed_bids.append(&mut generated_href_nodes);
+            if !generated_nodes.is_empty() {
+                parsed_bids.append(&mut generated_nodes);
             }
 
             // Perform this after going through all the proto relations so we don't destroy our
@@ -592,7 +615,10 @@ impl GraphBuilder {
                 // Inject proto text into our self set here, because inject context is where the
                 // markdown parser generates section-specific text fields regardless of whether
                 // it changes the markdown itself due to the injected context.
-                if let Some(updated_node) = codec.inject_context(proto, &ctx, &mut diagnostics)? {
+                if let Some(mut updated_node) =
+                    codec.inject_context(proto, &ctx, &mut diagnostics)?
+                {
+                    SCHEMAS.apply_defaults(&mut updated_node, Path::new(&proto.path));
                     if old_node != updated_node.toml() {
                         tracing::trace!(
                             "[inject_context] node changed: bid={} path={:?}\n  old={:?}\n  new={:?}",
@@ -616,7 +642,8 @@ impl GraphBuilder {
             // Phase 4b: Finalize codec (cross-node cleanup, emit events for modified nodes)
             tracing::debug!("Phase 4b: codec finalization");
             let finalized_nodes = codec.finalize(&mut diagnostics)?;
-            for (_proto, updated_node) in finalized_nodes {
+            for (proto, mut updated_node) in finalized_nodes {
+                SCHEMAS.apply_defaults(&mut updated_node, Path::new(&proto.path));
                 let old_toml = self
                     .doc_bb
                     .states()
@@ -634,6 +661,8 @@ impl GraphBuilder {
                 }
             }
 
+            diagnostics.extend(self.check_schema_relations(&schema_relations, &content));
+
             if is_changed || has_new_bids {
                 tracing::debug!("Generating source");
                 let maybe_new_content = codec.generate_source();
@@ -1171,6 +1200,8 @@ impl GraphBuilder {
         // Can't use self.doc_bb.paths() to generate keys here, because we can't assume that self.doc_bb
         // is balanced until we're out of phase 1 of parse_content.
         let mut parsed_node = BeliefNode::try_from(proto)?;
+        // Schema defaults are part of the payload, but are never written back to the source.
+        SCHEMAS.apply_defaults(&mut parsed_node, Path::new(&proto.path));
 
         // Generate keys based on node type
         let mut keys = self.speculative_path_key(proto)?;
@@ -1471,6 +1502,7 @@ impl GraphBuilder {
         global_bb: B,
         update_queue: &mut Vec<BeliefEvent>,
         missing_structure: &mut BeliefGraph,
+        generated_nodes: &mut Vec<Bid>,
     ) -> Result<GetOrCreateResult, BuildonomyError> {
         let other_key = &relation.key;
         let kind = &relation.kind;
@@ -1511,7 +1543,7 @@ impl GraphBuilder {
         let other_key_regularized =
             other_key.regularize_unchecked(self.repo(), &owner_rel_path, &repo_root_str);
 
-        let other_keys = vec![other_key_regularized.clone()];
+        let mut other_keys = vec![other_key_regularized.clone()];
         let mut weight = maybe_weight.clone().unwrap_or_default();
         weight.set(WEIGHT_SORT_KEY, index as u16)?;
         let owner = match direction {
@@ -1520,9 +1552,22 @@ impl GraphBuilder {
         };
         weight.set(crate::properties::WEIGHT_OWNED_BY, owner).ok();
         // Translate relative paths into absolute paths and resolve the "other" node
-        let cache_fetch_result = self
+        let mut cache_fetch_result = self
             .cache_fetch(&other_keys, global_bb.clone(), true, missing_structure)
             .await?;
+        // An id that matches no node id may still name a document by its title or one of its
+        // `aliases` (e.g. an Obsidian `[[Note]]` wikilink). Those are only consulted here, for
+        // relation targets, so they can never hijack a node's identity.
+        if let (GetOrCreateResult::Unresolved(_), NodeKey::Id { net, id }) =
+            (&cache_fetch_result, &other_key_regularized)
+        {
+            if let Some(bid) = self.bid_from_title_or_alias(net, id) {
+                other_keys = vec![NodeKey::Bid { bid }];
+                cache_fetch_result = self
+                    .cache_fetch(&other_keys, global_bb.clone(), true, missing_structure)
+                    .await?;
+            }
+        }
         let (other_node, other_node_source) = match cache_fetch_result {
             GetOrCreateResult::Resolved(mut other_node, other_node_source) => {
                 // Mark these nodes as traces -- we're not guaranteeing that we have all their
@@ -1576,6 +1621,23 @@ impl GraphBuilder {
                         EventOrigin::Remote,
                     ));
                     (href_node, NodeSource::Generated)
+                } else if let Some(tag) = match &other_key_regularized {
+                    NodeKey::Path { net, path } if *net == tag_namespace().bref() => {
+                        Some(path.clone())
+                    }
+                    _ => None,
+                } {
+                    // First use of this #tag.
+                    let tag_node = self
+                        .generate_tag_node(
+                            &tag,
+                            global_bb.clone(),
+                            update_queue,
+                            missing_structure,
+                            generated_nodes,
+                        )
+                        .await?;
+                    (tag_node, NodeSource::Generated)
                 } else {
                     let mut unresolved = unresolved_initial.clone();
                     unresolved.direction = direction;
@@ -1753,6 +1815,99 @@ impl GraphBuilder {
         Ok(GetOrCreateResult::Resolved(other_node, other_node_source))
     }
 
+    /// Generate the node for a `#tag` referenced for the first time, along with any of its
+    /// ancestor tags (`status` for `status/draft`) that don't exist yet.
+    ///
+    /// Each tag is a section of the tag network under its full tag path, and is related to its
+    /// parent tag by an Epistemic edge (parent → child) owned by the child. Generated ancestors
+    /// (and the tag network itself, the first time it is installed) are pushed onto
+    /// `generated_nodes` so they are published as parsed content; the returned tag is left for
+    /// the caller to record.
+    async fn generate_tag_node<B: BeliefSource + Clone>(
+        &mut self,
+        tag: &str,
+        global_bb: B,
+        update_queue: &mut Vec<BeliefEvent>,
+        missing_structure: &mut BeliefGraph,
+        generated_nodes: &mut Vec<Bid>,
+    ) -> Result<BeliefNode, BuildonomyError> {
+        if !self.doc_bb.states().contains_key(&tag_namespace()) {
+            let tag_net_node = BeliefNode::tag_network();
+            update_queue.push(BeliefEvent::NodeUpdate(
+                tag_net_node.keys(Some(buildonomy_namespace()), None, &self.doc_bb),
+                tag_net_node.toml(),
+                EventOrigin::Remote,
+            ));
+        }
+        if !generated_nodes.contains(&tag_namespace()) {
+            generated_nodes.push(tag_namespace());
+        }
+
+        let tag_node = BeliefNode::tag(tag);
+        update_queue.push(BeliefEvent::NodeUpdate(
+            tag_node.keys(Some(tag_namespace()), None, &self.doc_bb),
+            tag_node.toml(),
+            EventOrigin::Remote,
+        ));
+        let mut child = tag_node.clone();
+        loop {
+            let mut tag_weight = Weight::default();
+            tag_weight.set(WEIGHT_DOC_PATHS, vec![child.title.clone()])?;
+            update_queue.push(BeliefEvent::RelationChange(
+                child.bid,
+                tag_namespace(),
+                WeightKind::Section,
+                Some(tag_weight),
+                EventOrigin::Remote,
+            ));
+
+            let Some(parent_tag) = tag::parent(&child.title).map(str::to_string) else {
+                break;
+            };
+            let parent_keys = vec![tag::to_nodekey(&parent_tag)];
+            let (parent, parent_exists) = match self
+                .cache_fetch(&parent_keys, global_bb.clone(), true, missing_structure)
+                .await?
+            {
+                GetOrCreateResult::Resolved(mut parent, parent_source) => {
+                    if parent_source != NodeSource::SourceFile {
+                        parent.kind.insert(BeliefKind::Trace);
+                        update_queue.push(BeliefEvent::NodeUpdate(
+                            parent_keys,
+                            parent.toml(),
+                            EventOrigin::Remote,
+                        ));
+                    }
+                    (parent, true)
+                }
+                GetOrCreateResult::Unresolved(_) => {
+                    let parent = BeliefNode::tag(&parent_tag);
+                    update_queue.push(BeliefEvent::NodeUpdate(
+                        parent.keys(Some(tag_namespace()), None, &self.doc_bb),
+                        parent.toml(),
+                        EventOrigin::Remote,
+                    ));
+                    generated_nodes.push(parent.bid);
+                    (parent, false)
+                }
+            };
+            let mut hierarchy_weight = Weight::default();
+            hierarchy_weight.set(crate::properties::WEIGHT_OWNED_BY, "sink")?;
+            update_queue.push(BeliefEvent::RelationChange(
+                parent.bid,
+                child.bid,
+                WeightKind::Epistemic,
+                Some(hierarchy_weight),
+                EventOrigin::Remote,
+            ));
+            if parent_exists {
+                break;
+            }
+            child = parent;
+        }
+        Ok(tag_node)
+    }
+
     /// Fast-path for `initialize_stack`: if `abs_path` is already present in `session_bb`,
     /// reconstruct `self.stack` from the balanced graph that `cache_fetch` returns and skip
     /// the O(siblings) ancestor push() loop and peer-enumeration fan-out entirely.
@@ -2114,6 +2269,205 @@ impl GraphBuilder {
         Ok(Some((initial, doc_sort_key)))
     }
 
+    /// Warn when an id reference could name several documents by id, title or alias. The
+    /// document index is built on first use and reused for the rest of the parse.
+    fn check_ambiguous_reference(
+        &self,
+        relation: &IntermediateRelation,
+        resolved: &BeliefNode,
+        content: &str,
+        title_index: &mut Option<BTreeMap<String, BTreeSet<Bid>>>,
+    ) -> Option<ParseDiagnostic> {
+        let NodeKey::Id { id, .. } = &relation.key else {
+            return None;
+        };
+        let candidates = title_index
+            .get_or_insert_with(|| self.document_title_index())
+            .get(id)
+            .filter(|candidates| candidates.len() > 1)?;
+        let diagnostic = ParseDiagnostic::warning(format!(
+            "ambiguous link '{id}' — {} documents match by id, title or alias: [{}]. \
+            Linked to {}.",
+            candidates.len(),
+            candidates
+                .iter()
+                .map(|bid| bid.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            resolved.bid
+        ));
+        Some(match relation.location {
+            Some(offset) => {
+                let (line, col) = crate::codec::byte_offset_to_location(content, offset);
+                diagnostic.with_location(line, col)
+            }
+            None => diagnostic,
+        })
+    }
+
+    /// Check the resolved relations read from schema graph fields against the fields' target
+    /// schema, cardinality and cycle constraints.
+    fn check_schema_relations(
+        &mut self,
+        relations: &[SchemaRelation],
+        content: &str,
+    ) -> Vec<ParseDiagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut counts: BTreeMap<(Bid, &str), BTreeSet<Bid>> = BTreeMap::new();
+        for relation in relations.iter() {
+            let Some(graph_field) = SCHEMAS
+                .get_for(&relation.schema, Path::new(&relation.path))
+                .and_then(|schema_def| {
+                    schema_def
+                        .graph_fields
+                        .iter()
+                        .find(|field| field.field_name == relation.field_name)
+                        .cloned()
+                })
+            else {
+                continue;
+            };
+            // Resolve which way the edge points so cycles are followed in the same direction.
+            let repo = self.repo();
+            let Some((target, edge)) =
+                self.doc_bb
+                    .get_context(&repo, &relation.owner)
+                    .and_then(|ctx| {
+                        let find_other = |relations: Vec<ExtendedRelation>| {
+                            relations
+                                .into_iter()
+                                .find(|other| {
+                                    other.other.bid == relation.target
+                                        && other.weight.get(&graph_field.weight_kind).is_some()
+                                })
+                                .map(|other| other.other.clone())
+                        };
+                        find_other(ctx.sinks())
+                            .map(|target| (target, (relation.owner, relation.target)))
+                            .or_else(|| {
+                                find_other(ctx.sources())
+                                    .map(|target| (target, (relation.target, relation.owner)))
+                            })
+                    })
+            else {
+                continue;
+            };
+            let mut violations = Vec::new();
+            if !graph_field.target_schemas.is_empty()
+                && !target
+                    .schema
+                    .as_ref()
+                    .is_some_and(|schema| graph_field.target_schemas.contains(schema))
+            {
+                violations.push(RelationViolation::WrongTarget {
+                    field_name: graph_field.field_name.clone(),
+                    target: target.display_title(),
+                    found: target.schema.clone(),
+                    allowed: graph_field.target_schemas.clone(),
+                });
+            }
+            if let Some(max) = graph_field.max_count {
+                let targets = counts
+                    .entry((relation.owner, relation.field_name.as_str()))
+                    .or_default();
+                targets.insert(relation.target);
+                if targets.len() == max + 1 {
+                    violations.push(RelationViolation::TooMany {
+                        field_name: graph_field.field_name.clone(),
+                        max,
+                        found: relations
+                            .iter()
+                            .filter(|other| {
+                                other.owner == relation.owner
+                                    && other.field_name == relation.field_name
+                            })
+                            .map(|other| other.target)
+                            .collect::<BTreeSet<_>>()
+                            .len(),
+                    });
+                }
+            }
+            if graph_field.acyclic && self.reaches(edge.1, edge.0, graph_field.weight_kind) {
+                violations.push(RelationViolation::Cycle {
+                    field_name: graph_field.field_name.clone(),
+                    target: target.display_title(),
+                });
+            }
+            for violation in violations {
+                let diagnostic = ParseDiagnostic::parse_error(
+                    format!("Schema '{}': {violation}", relation.schema),
+                    1,
+                );
+                let location = match relation.location {
+                    Some(offset) => Some(byte_offset_to_location(content, offset)),
+                    None => field_location(content, &relation.field_name),
+                };
+                diagnostics.push(match location {
+                    Some((line, column)) => diagnostic.with_location(line, column),
+                    None => diagnostic,
+                });
+            }
+        }
+        diagnostics
+    }
+
+    /// Whether `to` can be reached from `from` along edges carrying `kind`, across both the
+    /// document and session belief bases.
+    fn reaches(&self, from: Bid, to: Bid, kind: WeightKind) -> bool {
+        let mut adjacency: BTreeMap<Bid, BTreeSet<Bid>> = BTreeMap::new();
+        for bb in [&self.doc_bb, &self.session_bb] {
+            let relations = bb.relations();
+            let graph = relations.as_graph();
+            for edge in graph.raw_edges() {
+                if edge.weight.get(&kind).is_some() {
+                    adjacency
+                        .entry(graph[edge.source()])
+                        .or_default()
+                        .insert(graph[edge.target()]);
+                }
+            }
+        }
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![from];
+        while let Some(bid) = stack.pop() {
+            if bid == to {
+                return true;
+            }
+            if visited.insert(bid) {
+                stack.extend(adjacency.get(&bid).into_iter().flatten().copied());
+            }
+        }
+        false
+    }
+
+    /// Map every id, anchored title and alias of the known documents to the documents using it.
+    fn document_title_index(&self) -> BTreeMap<String, BTreeSet<Bid>> {
+        let mut index: BTreeMap<String, BTreeSet<Bid>> = BTreeMap::new();
+        for bb in [&self.doc_bb, &self.session_bb] {
+            for node in bb.states().values().filter(|node| node.kind.is_document()) {
+                let names = [node.id(), to_anchor(&node.title)]
+                    .into_iter()
+                    .chain(node.aliases());
+                for name in names.filter(|name| !name.is_empty()) {
+                    index.entry(name).or_default().insert(node.bid);
+                }
+            }
+        }
+        index
+    }
+
+    /// Find a document whose anchored title or alias equals `id`, preferring the document being
+    /// parsed over the rest of the session.
+    fn bid_from_title_or_alias(&self, net: &Bref, id: &str) -> Option<Bid> {
+        [&self.doc_bb, &self.session_bb].into_iter().find_map(|bb| {
+            let paths = bb.paths();
+            paths
+                .net_get_from_title(net, id)
+                .or_else(|| paths.net_get_from_alias(net, id))
+                .map(|(_net, bid)| bid)
+        })
+    }
+
     async fn cache_fetch<B: BeliefSource + Clone>(
         &mut self,
         keys: &[NodeKey],
@@ -2247,6 +2601,102 @@ impl GraphBuilder {
     }
 }
 
+/// A resolved relation read from a schema graph field, kept until the document is finalized so
+/// it can be checked against the field's constraints.
+struct SchemaRelation {
+    owner: Bid,
+    path: String,
+    schema: String,
+    field_name: String,
+    target: Bid,
+    location: Option<usize>,
+}
+
+impl SchemaRelation {
+    fn new(
+        proto: &IRNode,
+        owner: Bid,
+        relation: &IntermediateRelation,
+        target: Bid,
+    ) -> Option<Self> {
+        let field_name = relation.schema_field.clone()?;
+        let schema = proto.document.get("schema")?.as_str()?.to_string();
+        (target != owner).then(|| SchemaRelation {
+            owner,
+            path: proto.path.clone(),
+            schema,
+            field_name,
+            target,
+            location: relation.location,
+        })
+    }
+}
+
+/// Check a proto node's payload against the typed fields of its schema, locating each violation
+/// at the line declaring the field (or, for a missing field, the schema).
+fn schema_diagnostics(
+    proto: &IRNode,
+    content: &str,
+) -> Result<Vec<ParseDiagnostic>, BuildonomyError> {
+    let Some(schema_def) = proto
+        .document
+        .get("schema")
+        .and_then(|schema| schema.as_str())
+        .and_then(|schema| SCHEMAS.get_for(schema, Path::new(&proto.path)))
+    else {
+        return Ok(vec![]);
+    };
+    if schema_def.fields.is_empty() {
+        return Ok(vec![]);
+    }
+    let node = BeliefNode::try_from(proto)?;
+    let schema = node.schema.clone().unwrap_or_default();
+    Ok(schema_def
+        .validate(&node.payload)
+        .into_iter()
+        .map(|violation| {
+            let message = format!("Schema '{schema}': {violation}");
+            let diagnostic = if violation.is_error() {
+                ParseDiagnostic::parse_error(message, 1)
+            } else {
+                ParseDiagnostic::warning(message)
+            };
+            let key = match violation {
+                FieldViolation::Missing { .. } => "schema",
+                _ => violation.field_name(),
+            };
+            match field_location(content, key) {
+                Some((line, column)) => diagnostic.with_location(line, column),
+                None => diagnostic,
+            }
+        })
+        .collect())
+}
+
+/// The position of the first line assigning `key` in TOML (`key =`), YAML (`key:`) or JSON
+/// (`"key":`) frontmatter.
+fn field_location(content: &str, key: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let rest = trimmed
+            .strip_prefix(key)
+            .or_else(|| {
+                trimmed
+                    .strip_prefix('"')
+                    .and_then(|quoted| quoted.strip_prefix(key))
+                    .and_then(|quoted| quoted.strip_prefix('"'))
+            })
+            .map(|rest| rest.trim_start());
+        if rest.is_some_and(|rest| rest.starts_with('=') || rest.starts_with(':')) {
+            let column_offset = line.len() - trimmed.len();
+            return Some(byte_offset_to_location(content, offset + column_offset));
+        }
+        offset += line.len();
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
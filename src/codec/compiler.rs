@@ -4,9 +4,9 @@ use crate::{
         assets::get_stylesheet_urls,
         belief_ir::IRNode,
         builder::{GraphBuilder, ParseContentWithCodec},
-        network::{detect_network_file, NetworkCodec, NETWORK_NAME},
+        network::{detect_network_file, iter_net_docs, NetworkCodec, NETWORK_NAME},
         proto_index::ProtoIndex,
-        DocCodec, ParseDiagnostic, UnresolvedReference, CODECS,
+        DocCodec, ParseDiagnostic, UnresolvedReference, CODECS, SCHEMAS,
     },
     error::BuildonomyError,
     event::BeliefEvent,
@@ -151,6 +151,9 @@ pub struct DocumentCompiler {
     reparse_stable: bool,
     /// Network files that need HTML generation deferred until all documents are parsed
     deferred_html: HashSet<PathBuf>,
+    /// Schema files that failed to load, by network directory. Reported as parse errors by
+    /// `parse_all` until the network's schemas are reloaded.
+    schema_errors: BTreeMap<PathBuf, Vec<(PathBuf, String)>>,
 }
 
 /// Result of parsing a single document
@@ -213,6 +216,7 @@ impl DocumentCompiler {
         // Build the ProtoIndex with a single WalkDir pass from repo_root.
         // Falls back to an empty index on error (e.g. entry_path is not yet a full repo)
         // so construction never fails due to a missing network file at startup.
+        let schema_errors = Self::load_schemas(builder.repo_root());
         let proto_index = ProtoIndex::build(builder.repo_root()).unwrap_or_else(|e| {
             tracing::warn!(
                 "[DocumentCompiler] ProtoIndex::build failed for {:?}: {e} — using empty index",
@@ -253,6 +257,7 @@ impl DocumentCompiler {
             last_round_updates: HashSet::new(),
             reparse_stable: false,
             deferred_html: HashSet::new(),
+            schema_errors,
         })
     }
 
@@ -281,6 +286,7 @@ impl DocumentCompiler {
         let entry_path = Self::normalize_queue_path(entry_point.as_ref().canonicalize()?);
 
         let builder = GraphBuilder::new(&entry_path, None)?;
+        let schema_errors = Self::load_schemas(builder.repo_root());
         let proto_index = ProtoIndex::build(builder.repo_root()).unwrap_or_else(|e| {
             tracing::warn!(
                 "[DocumentCompiler::simple] ProtoIndex::build failed for {:?}: {e} — using empty index",
@@ -314,6 +320,7 @@ impl DocumentCompiler {
             last_round_updates: HashSet::new(),
             reparse_stable: false,
             deferred_html: HashSet::new(),
+            schema_errors,
         })
     }
 
@@ -1087,6 +1094,21 @@ impl DocumentCompiler {
             }
         }
 
+        // Schema files are not documents, but a schema that fails to load is still an error in
+        // the network's sources.
+        for (path, message) in self.schema_errors.values().flatten() {
+            latest
+                .entry(path.clone())
+                .or_insert_with(|| ParseResult {
+                    path: path.clone(),
+                    rewritten_content: None,
+                    dependent_paths: vec![],
+                    diagnostics: vec![],
+                })
+                .diagnostics
+                .push(ParseDiagnostic::parse_error(message.clone(), 1));
+        }
+
         // All passes complete. Exactly one result per path exists in `latest`.
         // Any remaining UnresolvedReference is a permanent author error — promote to Warning.
         let mut results: Vec<ParseResult> = latest.into_values().collect();
@@ -1322,6 +1344,45 @@ impl DocumentCompiler {
         string_to_os_path(&os_path_to_string(&resolved))
     }
 
+    /// Load the schema files of every network under `repo_root` into [`SCHEMAS`], returning the
+    /// files that failed to load by network directory.
+    fn load_schemas(repo_root: &Path) -> BTreeMap<PathBuf, Vec<(PathBuf, String)>> {
+        ProtoIndex::discover_network_dirs(repo_root)
+            .into_iter()
+            .map(|network_dir| {
+                let errors = Self::schema_load_errors(SCHEMAS.load_network(&network_dir));
+                (network_dir, errors)
+            })
+            .filter(|(_, errors)| !errors.is_empty())
+            .collect()
+    }
+
+    fn schema_load_errors(errors: Vec<(PathBuf, BuildonomyError)>) -> Vec<(PathBuf, String)> {
+        errors
+            .into_iter()
+            .map(|(path, e)| {
+                tracing::warn!("[DocumentCompiler] Failed to load schema file {path:?}: {e}");
+                (path, format!("Failed to load schema: {e}"))
+            })
+            .collect()
+    }
+
+    /// Reload the schema files of the network rooted at `network_dir` (e.g., from file watcher)
+    /// and enqueue the network's documents, which are validated against the reloaded schemas.
+    pub fn reload_schemas(&mut self, network_dir: impl AsRef<Path>) {
+        let network_dir = Self::normalize_queue_path(network_dir.as_ref().to_path_buf());
+        let errors = Self::schema_load_errors(SCHEMAS.load_network(&network_dir));
+        if errors.is_empty() {
+            self.schema_errors.remove(&network_dir);
+        } else {
+            self.schema_errors.insert(network_dir.clone(), errors);
+        }
+        for path in iter_net_docs(&network_dir) {
+            self.reset_processed(&path);
+            self.enqueue(path);
+        }
+    }
+
     /// Add a path to the queue (e.g., from file watcher)
     ///
     /// This method checks if the path is already in either queue to avoid duplicates.
@@ -2068,7 +2129,7 @@ impl DocumentCompiler {
         let json_path = html_dir.join("beliefbase.json");
 
         // Serialize to JSON
-        let json_string = serde_json::to_string_pretty(&graph)
+        let json_string = serde_json::to_string_pretty(&crate::shard::GraphExport::new(&graph))
             .map_err(|e| BuildonomyError::Serialization(e.to_string()))?;
 
         let file_size_bytes = json_string.len();
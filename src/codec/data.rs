@@ -0,0 +1,1454 @@
+//! Codecs for standalone structured data files (`.toml`, `.json`, `.yaml`/`.yml`).
+//!
+//! Unlike Markdown, where metadata lives in a frontmatter block above prose, a data file *is* its
+//! metadata. The top-level table/object of each file becomes a Document node: its keys become the
+//! node's `bid`/`title`/`schema`/`id` and payload, and any
+//! [`SchemaRegistry`](super::schema_registry::SchemaRegistry) graph fields become relations via
+//! [`IRNode::traverse_schema`].
+//!
+//! Top-level arrays of tables (TOML `[[tasks]]`, a JSON/YAML list of objects) become child nodes of
+//! the document, addressable as `file.toml#<id>` exactly like Markdown sections. Arrays named by a
+//! schema graph field (e.g. `parent_connections`) are edges, not children, and are left alone.
+//!
+//! All three formats share one pipeline, [`DataCodec`], parameterised by a [`DataFormat`]:
+//!
+//! - [`TomlCodec`] edits the source through `toml_edit`, so injecting a BID touches only the `bid`
+//!   key and leaves the author's comments, key order and whitespace alone.
+//! - [`JsonCodec`] and [`YamlCodec`] hold the source as an order-preserving
+//!   [`serde_yaml::Value`]. JSON write-back keeps key order and indentation width but
+//!   re-serializes the file. YAML write-back edits the `bid` lines into the text, keeping comments
+//!   and layout; a YAML file with comments that can't be edited that way (flow-style mappings,
+//!   several documents) is not rewritten and gets a warning instead. Files are only touched when a
+//!   BID actually has to be written.
+//!
+//! All three are common formats for files that aren't documents at all (`Cargo.toml`,
+//! `package.json`, CI workflows), so the codecs only [claim](DocCodec::claims) a file whose
+//! top-level table already carries noet metadata: a `bid`, `title` or `schema` key. Other files are
+//! left out of the network and never rewritten.
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event as MdEvent, HeadingLevel, Tag as MdTag, TagEnd};
+use std::{collections::HashSet, fmt::Debug, fs, marker::PhantomData, path::Path, result::Result};
+use titlecase::titlecase;
+use toml_edit::{value, DocumentMut, InlineTable, Item, Table};
+
+use crate::{
+    beliefbase::BeliefContext,
+    codec::{
+        belief_ir::{IRNode, MetadataFormat},
+        diagnostic::ParseDiagnostic,
+        schema_registry::SCHEMAS,
+        DocCodec,
+    },
+    error::BuildonomyError,
+    paths::{os_path_to_string, AnchorPath},
+    properties::{BeliefKind, BeliefNode},
+};
+
+/// Codec for standalone `.toml` documents.
+///
+/// ```toml
+/// bid = "0192f0e4-5d1c-7a3e-8b4f-1234567890ab"  # injected on first parse
+/// title = "Ship the release"
+/// schema = "intention_lattice.intention"
+///
+/// [[parent_connections]]
+/// parent_id = "quarterly-goals"
+/// notes = "Blocks the Q3 milestone"
+///
+/// [[tasks]]
+/// title = "Tag the release"
+/// ```
+///
+/// If the file has no `title`, one is derived from the filestem (`ship_release.toml` →
+/// "Ship Release"), matching [`MdCodec`](super::md::MdCodec). Derived titles are not written back
+/// to the source.
+pub type TomlCodec = DataCodec<TomlFormat>;
+
+/// Codec for standalone `.json` documents. The top-level value must be an object.
+pub type JsonCodec = DataCodec<JsonFormat>;
+
+/// Codec for standalone `.yaml`/`.yml` documents. The top-level value must be a mapping.
+pub type YamlCodec = DataCodec<YamlFormat>;
+
+/// In-memory source tree of a data file.
+#[derive(Debug, Clone)]
+pub enum DataSource {
+    /// Format-preserving TOML document.
+    Toml(DocumentMut),
+    /// Order-preserving JSON/YAML tree. The root is always a mapping.
+    Tree(serde_yaml::Value),
+}
+
+/// A structured data format that can be read into and written out of a [`DataSource`].
+pub trait DataFormat: Debug + Default + Clone + Send + Sync + 'static {
+    /// File extensions handled by this format, without the leading dot.
+    const EXTENSIONS: &'static [&'static str];
+    /// Language tag used for the fenced code block in generated HTML.
+    const LANGUAGE: &'static str;
+
+    fn parse_source(content: &str) -> Result<DataSource, BuildonomyError>;
+
+    /// Serialize `source` back to text. `original` is the text the source was parsed from (or an
+    /// empty string for fragments), for formats that can mirror its layout.
+    fn render_source(source: &DataSource, original: &str) -> Result<String, BuildonomyError>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct TomlFormat;
+
+#[derive(Debug, Default, Clone)]
+pub struct JsonFormat;
+
+#[derive(Debug, Default, Clone)]
+pub struct YamlFormat;
+
+impl DataFormat for TomlFormat {
+    const EXTENSIONS: &'static [&'static str] = &["toml"];
+    const LANGUAGE: &'static str = "toml";
+
+    fn parse_source(content: &str) -> Result<DataSource, BuildonomyError> {
+        content
+            .parse::<DocumentMut>()
+            .map(DataSource::Toml)
+            .map_err(|e| BuildonomyError::Codec(format!("Failed to parse TOML: {e}")))
+    }
+
+    fn render_source(source: &DataSource, _original: &str) -> Result<String, BuildonomyError> {
+        match source {
+            DataSource::Toml(doc) => Ok(doc.to_string()),
+            DataSource::Tree(_) => Err(BuildonomyError::Codec(
+                "TomlFormat cannot render a JSON/YAML source tree".to_string(),
+            )),
+        }
+    }
+}
+
+impl DataFormat for JsonFormat {
+    const EXTENSIONS: &'static [&'static str] = &["json"];
+    const LANGUAGE: &'static str = "json";
+
+    fn parse_source(content: &str) -> Result<DataSource, BuildonomyError> {
+        if content.trim().is_empty() {
+            return Ok(DataSource::Tree(serde_yaml::Value::Mapping(
+                serde_yaml::Mapping::new(),
+            )));
+        }
+        // Deserialize straight into serde_yaml's Mapping, which keeps insertion order without
+        // needing serde_json's crate-wide `preserve_order` feature.
+        let tree = serde_json::from_str::<serde_yaml::Value>(content)
+            .map_err(|e| BuildonomyError::Codec(format!("Failed to parse JSON: {e}")))?;
+        DataSource::tree(tree, "JSON")
+    }
+
+    fn render_source(source: &DataSource, original: &str) -> Result<String, BuildonomyError> {
+        let DataSource::Tree(tree) = source else {
+            return Err(BuildonomyError::Codec(
+                "JsonFormat cannot render a TOML source document".to_string(),
+            ));
+        };
+        let indent = detect_indent(original);
+        let mut buf = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buf, formatter);
+        serde::Serialize::serialize(tree, &mut serializer)
+            .map_err(|e| BuildonomyError::Codec(format!("Failed to serialize JSON: {e}")))?;
+        let mut rendered = String::from_utf8(buf)
+            .map_err(|e| BuildonomyError::Codec(format!("Serialized JSON is not UTF-8: {e}")))?;
+        if original.is_empty() || original.ends_with('\n') {
+            rendered.push('\n');
+        }
+        Ok(rendered)
+    }
+}
+
+impl DataFormat for YamlFormat {
+    const EXTENSIONS: &'static [&'static str] = &["yaml", "yml"];
+    const LANGUAGE: &'static str = "yaml";
+
+    fn parse_source(content: &str) -> Result<DataSource, BuildonomyError> {
+        let tree = serde_yaml::from_str::<serde_yaml::Value>(content)
+            .map_err(|e| BuildonomyError::Codec(format!("Failed to parse YAML: {e}")))?;
+        DataSource::tree(tree, "YAML")
+    }
+
+    fn render_source(source: &DataSource, original: &str) -> Result<String, BuildonomyError> {
+        let DataSource::Tree(tree) = source else {
+            return Err(BuildonomyError::Codec(
+                "YamlFormat cannot render a TOML source document".to_string(),
+            ));
+        };
+        if original.trim().is_empty() {
+            return serde_yaml::to_string(tree)
+                .map_err(|e| BuildonomyError::Codec(format!("Failed to serialize YAML: {e}")));
+        }
+        // serde_yaml drops comments, so write the BIDs into the text itself and only trust the
+        // edit if it reads back as the tree.
+        if let Some(edited) = write_yaml_bids(original, tree).filter(|edited| {
+            serde_yaml::from_str::<serde_yaml::Value>(edited)
+                .ok()
+                .as_ref()
+                == Some(tree)
+        }) {
+            return Ok(edited);
+        }
+        if original.lines().any(|line| line.contains('#')) {
+            return Err(BuildonomyError::Codec(
+                "Cannot write BIDs into this YAML file without dropping its comments. Add the \
+                'bid' keys by hand or use block-style mappings."
+                    .to_string(),
+            ));
+        }
+        serde_yaml::to_string(tree)
+            .map_err(|e| BuildonomyError::Codec(format!("Failed to serialize YAML: {e}")))
+    }
+}
+
+/// Edit the `bid` keys of block-style YAML `original` to match `tree`, replacing existing `bid`
+/// values in place and appending new `bid` lines to the end of their mapping, so comments and
+/// layout survive. Only the root mapping and the entries of its top-level sequences carry BIDs.
+/// Returns `None` when the text doesn't have the expected layout.
+fn write_yaml_bids(original: &str, tree: &serde_yaml::Value) -> Option<String> {
+    let before = serde_yaml::from_str::<serde_yaml::Value>(original).ok()?;
+    let bid_of = |value: Option<&serde_yaml::Value>| {
+        value
+            .and_then(|v| v.get("bid"))
+            .and_then(|bid| bid.as_str())
+            .map(|bid| bid.to_string())
+    };
+    let mut lines = original
+        .split_inclusive('\n')
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+    // (line, replace it) edits, applied bottom-up so earlier line numbers stay valid.
+    let mut edits = Vec::<(usize, bool, String)>::new();
+
+    for (key, value) in tree.as_mapping()? {
+        let (Some(field), Some(entries)) = (key.as_str(), value.as_sequence()) else {
+            continue;
+        };
+        let before_entries = before.get(field).and_then(|v| v.as_sequence());
+        let changed = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let bid = bid_of(Some(entry))?;
+                let old = bid_of(before_entries.and_then(|seq| seq.get(index)));
+                (old.as_deref() != Some(bid.as_str())).then_some((index, bid))
+            })
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            continue;
+        }
+        let items = yaml_sequence_items(&lines, field)?;
+        for (index, bid) in changed {
+            let &(start, end) = items.get(index)?;
+            edits.push(yaml_bid_edit(&lines, start, end, &bid)?);
+        }
+    }
+
+    if let Some(bid) = bid_of(Some(tree)).filter(|bid| bid_of(Some(&before)).as_ref() != Some(bid))
+    {
+        match lines.iter().position(|line| line.starts_with("bid:")) {
+            Some(line) => edits.push((line, true, yaml_bid_line(&lines[line], 0, &bid))),
+            None => {
+                if lines.last().is_some_and(|line| !line.ends_with('\n')) {
+                    lines.last_mut()?.push('\n');
+                }
+                edits.push((lines.len(), false, format!("bid: {bid}\n")));
+            }
+        }
+    }
+
+    edits.sort_by_key(|(line, _, _)| std::cmp::Reverse(*line));
+    for (line, replace, text) in edits {
+        if replace {
+            lines[line] = text;
+        } else {
+            lines.insert(line, text);
+        }
+    }
+    Some(lines.concat())
+}
+
+/// The indentation of `line`, or `None` for blank and comment-only lines.
+fn yaml_indent(line: &str) -> Option<usize> {
+    let content = line.trim_start_matches(' ');
+    (!content.trim().is_empty() && !content.starts_with('#')).then(|| line.len() - content.len())
+}
+
+/// The `[start, end)` line ranges of the entries of the block sequence under the top-level key
+/// `field`.
+fn yaml_sequence_items(lines: &[String], field: &str) -> Option<Vec<(usize, usize)>> {
+    let key_line = lines.iter().position(|line| {
+        line.strip_prefix(field)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| rest.trim().is_empty() || rest.trim_start().starts_with('#'))
+    })?;
+    let mut starts = Vec::new();
+    let mut seq_indent = None;
+    let mut stop = lines.len();
+    for (line, text) in lines.iter().enumerate().skip(key_line + 1) {
+        let Some(indent) = yaml_indent(text) else {
+            continue;
+        };
+        let is_item = text[indent..].starts_with("- ") || text[indent..].trim_end() == "-";
+        match seq_indent {
+            None if is_item => seq_indent = Some(indent),
+            Some(seq) if is_item && indent == seq => {}
+            Some(seq) if indent > seq => continue,
+            _ => {
+                stop = line;
+                break;
+            }
+        }
+        starts.push(line);
+    }
+    // An entry ends at its last content line, leaving trailing comments with what follows.
+    let ends = starts.iter().skip(1).copied().chain([stop]);
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| {
+            let last = (start..end)
+                .rev()
+                .find(|line| yaml_indent(&lines[*line]).is_some())?;
+            Some((start, last + 1))
+        })
+        .collect()
+}
+
+/// The edit writing `bid` into the sequence entry spanning lines `[start, end)`.
+fn yaml_bid_edit(
+    lines: &[String],
+    start: usize,
+    end: usize,
+    bid: &str,
+) -> Option<(usize, bool, String)> {
+    let dash = yaml_indent(&lines[start])?;
+    let rest = lines[start][dash + 1..].trim_start_matches(' ');
+    let indent = match rest.trim().is_empty() {
+        true => lines[start + 1..end]
+            .iter()
+            .find_map(|line| yaml_indent(line))?,
+        false => lines[start].len() - rest.len(),
+    };
+    if rest.starts_with("bid:") {
+        let line = &lines[start];
+        return Some((
+            start,
+            true,
+            format!(
+                "{}{}",
+                &line[..indent],
+                &yaml_bid_line(&line[indent..], 0, bid)
+            ),
+        ));
+    }
+    let existing = (start + 1..end).find(|line| {
+        yaml_indent(&lines[*line]) == Some(indent) && lines[*line][indent..].starts_with("bid:")
+    });
+    Some(match existing {
+        Some(line) => (line, true, yaml_bid_line(&lines[line], indent, bid)),
+        None => (end, false, format!("{}bid: {bid}\n", " ".repeat(indent))),
+    })
+}
+
+/// `line`, a `bid:` key at `indent`, with its value replaced by `bid`, keeping any trailing comment.
+fn yaml_bid_line(line: &str, indent: usize, bid: &str) -> String {
+    let value = &line[indent + "bid:".len()..];
+    let comment = value
+        .find(" #")
+        .map(|at| value[value[..at].trim_end().len()..].trim_end())
+        .unwrap_or_default();
+    format!("{}bid: {bid}{comment}\n", " ".repeat(indent))
+}
+
+/// Return the indentation unit of the first indented line in `original` (two spaces if none).
+fn detect_indent(original: &str) -> String {
+    original
+        .lines()
+        .map(|line| {
+            line.chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect::<String>()
+        })
+        .find(|indent| !indent.is_empty())
+        .unwrap_or_else(|| "  ".to_string())
+}
+
+/// Where a node's table lives within its [`DataSource`].
+#[derive(Debug, Clone, PartialEq)]
+enum NodeLocation {
+    /// The top-level table (the Document node).
+    Root,
+    /// Entry `index` of the top-level array of tables named `field`.
+    Item { field: String, index: usize },
+}
+
+impl DataSource {
+    fn tree(tree: serde_yaml::Value, format_name: &str) -> Result<DataSource, BuildonomyError> {
+        match tree {
+            serde_yaml::Value::Mapping(_) => Ok(DataSource::Tree(tree)),
+            serde_yaml::Value::Null => Ok(DataSource::Tree(serde_yaml::Value::Mapping(
+                serde_yaml::Mapping::new(),
+            ))),
+            _ => Err(BuildonomyError::Codec(format!(
+                "Expected a top-level {format_name} object, found {tree:?}"
+            ))),
+        }
+    }
+
+    /// Whether the top-level table has any of the keys that mark a noet document.
+    fn has_metadata(&self) -> bool {
+        ["bid", "title", "schema"].iter().any(|key| match self {
+            DataSource::Toml(doc) => doc.contains_key(key),
+            DataSource::Tree(tree) => tree.get(key).is_some(),
+        })
+    }
+
+    fn schema(&self) -> Option<String> {
+        match self {
+            DataSource::Toml(doc) => doc.get("schema").and_then(|item| item.as_str()),
+            DataSource::Tree(tree) => tree.get("schema").and_then(|v| v.as_str()),
+        }
+        .map(|schema| schema.to_string())
+    }
+
+    /// Names of top-level fields holding a non-empty array of tables, in source order, skipping
+    /// `exclude`.
+    fn child_fields(&self, exclude: &[&str]) -> Vec<String> {
+        match self {
+            DataSource::Toml(doc) => doc
+                .iter()
+                .filter(|(key, item)| {
+                    !exclude.contains(key)
+                        && (item.as_array_of_tables().is_some_and(|aot| !aot.is_empty())
+                            || item.as_array().is_some_and(|array| {
+                                !array.is_empty()
+                                    && array.iter().all(|v| v.as_inline_table().is_some())
+                            }))
+                })
+                .map(|(key, _)| key.to_string())
+                .collect(),
+            DataSource::Tree(tree) => tree
+                .as_mapping()
+                .into_iter()
+                .flat_map(|mapping| mapping.iter())
+                .filter_map(|(key, v)| {
+                    let key = key.as_str()?;
+                    let is_table_array = v.as_sequence().is_some_and(|seq| {
+                        !seq.is_empty() && seq.iter().all(|entry| entry.is_mapping())
+                    });
+                    (is_table_array && !exclude.contains(&key)).then(|| key.to_string())
+                })
+                .collect(),
+        }
+    }
+
+    /// A copy of the top-level table with the `children` fields removed.
+    fn root(&self, children: &[String]) -> DataSource {
+        let mut root = self.clone();
+        for field in children {
+            match &mut root {
+                DataSource::Toml(doc) => {
+                    doc.remove(field);
+                }
+                DataSource::Tree(tree) => {
+                    if let Some(mapping) = tree.as_mapping_mut() {
+                        mapping.remove(field.as_str());
+                    }
+                }
+            }
+        }
+        root
+    }
+
+    /// Each entry of the array of tables named `field`, as a standalone source.
+    fn entries(&self, field: &str) -> Vec<DataSource> {
+        match self {
+            DataSource::Toml(doc) => match doc.get(field) {
+                Some(Item::ArrayOfTables(aot)) => aot
+                    .iter()
+                    .map(|table| DataSource::Toml(DocumentMut::from(table.clone())))
+                    .collect(),
+                Some(Item::Value(toml_edit::Value::Array(array))) => array
+                    .iter()
+                    .filter_map(|v| v.as_inline_table())
+                    .map(|table| DataSource::Toml(DocumentMut::from(table.clone().into_table())))
+                    .collect(),
+                _ => Vec::new(),
+            },
+            DataSource::Tree(tree) => tree
+                .get(field)
+                .and_then(|v| v.as_sequence())
+                .into_iter()
+                .flatten()
+                .map(|entry| DataSource::Tree(entry.clone()))
+                .collect(),
+        }
+    }
+
+    /// Convert this (sub)tree into an [`IRNode`] through the shared metadata pipeline.
+    pub(crate) fn to_ir(&self) -> Result<IRNode, BuildonomyError> {
+        match self {
+            DataSource::Toml(doc) => {
+                IRNode::from_str_with_format(&doc.to_string(), MetadataFormat::Toml)
+            }
+            DataSource::Tree(tree) => {
+                let json = serde_json::to_string(tree).map_err(|e| {
+                    BuildonomyError::Codec(format!("Cannot represent data as metadata: {e}"))
+                })?;
+                IRNode::from_str_with_format(&json, MetadataFormat::Json)
+            }
+        }
+    }
+
+    /// Write `bid` into the table at `location`. Returns true if the source changed.
+    fn set_bid(&mut self, location: &NodeLocation, bid: &str) -> bool {
+        match (self, location) {
+            (DataSource::Toml(doc), NodeLocation::Root) => set_table_bid(doc.as_table_mut(), bid),
+            (DataSource::Toml(doc), NodeLocation::Item { field, index }) => {
+                match doc.get_mut(field) {
+                    Some(Item::ArrayOfTables(aot)) => aot
+                        .get_mut(*index)
+                        .is_some_and(|table| set_table_bid(table, bid)),
+                    Some(Item::Value(toml_edit::Value::Array(array))) => array
+                        .get_mut(*index)
+                        .and_then(|v| v.as_inline_table_mut())
+                        .is_some_and(|table| set_inline_table_bid(table, bid)),
+                    _ => false,
+                }
+            }
+            (DataSource::Tree(tree), NodeLocation::Root) => set_mapping_bid(tree, bid),
+            (DataSource::Tree(tree), NodeLocation::Item { field, index }) => tree
+                .get_mut(field.as_str())
+                .and_then(|v| v.get_mut(*index))
+                .is_some_and(|entry| set_mapping_bid(entry, bid)),
+        }
+    }
+}
+
+fn set_table_bid(table: &mut Table, bid: &str) -> bool {
+    match table.get_mut("bid").and_then(|item| item.as_value_mut()) {
+        Some(existing) => replace_bid_value(existing, bid),
+        None => {
+            table.insert("bid", value(bid));
+            true
+        }
+    }
+}
+
+fn set_inline_table_bid(table: &mut InlineTable, bid: &str) -> bool {
+    match table.get_mut("bid") {
+        Some(existing) => replace_bid_value(existing, bid),
+        None => {
+            table.insert("bid", bid.into());
+            true
+        }
+    }
+}
+
+fn replace_bid_value(existing: &mut toml_edit::Value, bid: &str) -> bool {
+    if existing.as_str() == Some(bid) {
+        return false;
+    }
+    // Keep any trailing comment or alignment attached to the old value.
+    let decor = existing.decor().clone();
+    *existing = bid.into();
+    *existing.decor_mut() = decor;
+    true
+}
+
+fn set_mapping_bid(tree: &mut serde_yaml::Value, bid: &str) -> bool {
+    let Some(mapping) = tree.as_mapping_mut() else {
+        return false;
+    };
+    if mapping.get("bid").and_then(|v| v.as_str()) == Some(bid) {
+        return false;
+    }
+    // Mapping::insert keeps an existing key's position and appends new keys.
+    mapping.insert("bid".into(), bid.into());
+    true
+}
+
+/// Derive a display title from a file stem, e.g. `"ship_release"` → `"Ship Release"`.
+pub(crate) fn title_from_path(path: &Path) -> Option<String> {
+    path.file_stem()
+        .filter(|stem| !stem.is_empty())
+        .and_then(|stem| stem.to_str())
+        .map(|filestem| {
+            filestem
+                .split("_")
+                .map(titlecase)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+}
+
+/// Array-of-tables field names paired with their entries, in source order.
+type ChildEntries = Vec<(String, Vec<DataSource>)>;
+
+/// Shared [`DocCodec`] implementation for structured data files. See the [module docs](self).
+#[derive(Debug, Default, Clone)]
+pub struct DataCodec<F: DataFormat> {
+    /// The text `source` was parsed from, returned verbatim while no BID has been written.
+    content: String,
+    source: Option<DataSource>,
+    /// The Document node followed by one node per array-of-tables entry.
+    nodes: Vec<(IRNode, NodeLocation)>,
+    /// Whether `source` has been edited since parsing.
+    dirty: bool,
+    format: PhantomData<F>,
+}
+
+impl<F: DataFormat> DataCodec<F> {
+    pub fn new() -> Self {
+        DataCodec::default()
+    }
+
+    /// Split a parsed source into the root metadata node and its array-of-tables children.
+    /// `path` is the source's path, which scopes the schema lookup.
+    fn split(source: &DataSource, path: &Path) -> Result<(IRNode, ChildEntries), BuildonomyError> {
+        let schema_def = source
+            .schema()
+            .and_then(|schema| SCHEMAS.get_for(&schema, path));
+        let schema_fields = schema_def
+            .iter()
+            .flat_map(|def| def.graph_fields.iter())
+            .map(|field| field.field_name.as_str())
+            .collect::<Vec<_>>();
+        let child_fields = source.child_fields(&schema_fields);
+        let root = source.root(&child_fields).to_ir()?;
+        let children = child_fields
+            .into_iter()
+            .map(|field| {
+                let entries = source.entries(&field);
+                (field, entries)
+            })
+            .collect();
+        Ok((root, children))
+    }
+}
+
+impl<F: DataFormat> DocCodec for DataCodec<F> {
+    fn proto(&self, path: &Path) -> Result<Option<IRNode>, BuildonomyError> {
+        if path.is_relative() {
+            return Err(BuildonomyError::Codec(format!(
+                "[DataCodec::proto] supplied path must be absolute. Received \"{path:?}\""
+            )));
+        };
+        if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|ext| F::EXTENSIONS.contains(ext))
+            .is_none()
+        {
+            tracing::debug!(
+                "DataCodec::proto called with path \"{path:?}\", which does not have one of the \
+                {:?} file extensions. Returning None",
+                F::EXTENSIONS
+            );
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let (mut proto, _children) = Self::split(&F::parse_source(&content)?, path)?;
+        if proto.title().unwrap_or_default().is_empty() {
+            if let Some(title) = title_from_path(path) {
+                proto.document.insert("title", value(title));
+            }
+        }
+        proto.path = os_path_to_string(path);
+        // Document heading
+        proto.heading = 2;
+        proto.kind.insert(BeliefKind::Document);
+        Ok(Some(proto))
+    }
+
+    fn claims(&self, path: &Path) -> bool {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| F::parse_source(&content).ok())
+            .is_some_and(|source| source.has_metadata())
+    }
+
+    fn parse(
+        &mut self,
+        content: &str,
+        mut current: IRNode,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<(), BuildonomyError> {
+        // Parse strictly in this codec's format: IRNode::from_str_with_format falls back to the
+        // other formats, which would let a malformed file silently round-trip as something else.
+        let source = F::parse_source(content)?;
+        let (mut parsed, children) = Self::split(&source, Path::new(&current.path))?;
+        self.content = content.to_string();
+        self.source = Some(source);
+        self.dirty = false;
+        self.nodes = Vec::new();
+
+        parsed.path = current.path.clone();
+        parsed.traverse_schema()?;
+        current.merge(&mut parsed);
+        current.heading = 2;
+        current.kind.insert(BeliefKind::Document);
+        let doc_path = current.path.clone();
+        self.nodes.push((current, NodeLocation::Root));
+
+        let mut seen_ids = HashSet::new();
+        for (field, entries) in children {
+            for (index, entry) in entries.iter().enumerate() {
+                let mut child = entry.to_ir()?;
+                if child.title().unwrap_or_default().is_empty() {
+                    let title = child
+                        .document
+                        .get("name")
+                        .and_then(|name| name.as_str())
+                        .map(|name| name.to_string())
+                        .unwrap_or_else(|| {
+                            format!("{} {}", titlecase(&field.replace('_', " ")), index + 1)
+                        });
+                    child.document.insert("title", value(title));
+                }
+                if let Some(id) = child.id() {
+                    if !seen_ids.insert(id.clone()) {
+                        diagnostics.push(ParseDiagnostic::warning(format!(
+                            "Entry {index} of '{field}' has the same id '{id}' as an earlier \
+                            entry in {doc_path}. Give it a unique 'id' or 'title' to make it \
+                            addressable."
+                        )));
+                        // Empty-string sentinel: suppress the title fallback so the builder
+                        // assigns a bref-based id instead (mirrors MdCodec heading collisions).
+                        child.document.insert("id", value(""));
+                    }
+                }
+                child.path = doc_path.clone();
+                child.heading = 3;
+                child.traverse_schema()?;
+                self.nodes.push((
+                    child,
+                    NodeLocation::Item {
+                        field: field.clone(),
+                        index,
+                    },
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn nodes(&self) -> Vec<IRNode> {
+        self.nodes.iter().map(|(proto, _)| proto.clone()).collect()
+    }
+
+    fn inject_context(
+        &mut self,
+        node: &IRNode,
+        ctx: &BeliefContext<'_>,
+        _diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Option<BeliefNode>, BuildonomyError> {
+        let (proto, location) = self
+            .nodes
+            .iter_mut()
+            .find(|(proto, _)| proto == node)
+            .ok_or(BuildonomyError::Codec(
+                "No proto node stored in codec matching node argument".to_string(),
+            ))?;
+
+        let maybe_updated = proto.update_from_context(ctx)?;
+
+        // Write back identity only. Everything else in the context node originated from this
+        // file, so copying it back would at best be a no-op and at worst reformat the source.
+        if let (Some(bid), Some(source)) = (
+            proto.document.get("bid").and_then(|item| item.as_str()),
+            self.source.as_mut(),
+        ) {
+            if source.set_bid(location, bid) {
+                self.dirty = true;
+            }
+        }
+
+        Ok(maybe_updated)
+    }
+
+    fn finalize(
+        &mut self,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Vec<(IRNode, BeliefNode)>, BuildonomyError> {
+        if let (true, Some(source)) = (self.dirty, self.source.as_ref()) {
+            if let Err(e) = F::render_source(source, &self.content) {
+                // Leave the file as the author wrote it rather than lose part of it.
+                let path = self.nodes.first().map(|(doc, _)| doc.path.as_str());
+                diagnostics.push(ParseDiagnostic::warning(format!(
+                    "{} was not rewritten with its BIDs: {e}",
+                    path.unwrap_or_default()
+                )));
+                self.dirty = false;
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    fn generate_source(&self) -> Option<String> {
+        if !self.dirty {
+            return Some(self.content.clone());
+        }
+        let source = self.source.as_ref()?;
+        match F::render_source(source, &self.content) {
+            Ok(rendered) => Some(rendered),
+            Err(e) => {
+                tracing::warn!("[DataCodec::generate_source] {e}");
+                None
+            }
+        }
+    }
+
+    fn generate_html(&self) -> Result<Vec<(String, String)>, BuildonomyError> {
+        let (Some((doc, _)), Some(source)) = (self.nodes.first(), self.source.as_ref()) else {
+            return Ok(vec![]);
+        };
+        let doc_ap = AnchorPath::from(&doc.path);
+        if doc_ap.filestem().is_empty() {
+            return Err(BuildonomyError::Codec(format!(
+                "Data file has no filename! {}",
+                doc.path
+            )));
+        }
+        let output_filename = format!("{}.html", doc_ap.filestem());
+
+        // Render through pulldown_cmark so headings and escaped source match the markup produced
+        // for Markdown documents: the document's own fields first, then one section per child so
+        // that `file.toml#child-id` links land on an anchor.
+        let child_fields = self
+            .nodes
+            .iter()
+            .filter_map(|(_, location)| match location {
+                NodeLocation::Item { field, .. } => Some(field.clone()),
+                NodeLocation::Root => None,
+            })
+            .collect::<Vec<_>>();
+        let original = if child_fields.is_empty() {
+            self.content.as_str()
+        } else {
+            ""
+        };
+        let mut events = Vec::new();
+        push_section(
+            &mut events,
+            HeadingLevel::H1,
+            doc,
+            F::render_source(&source.root(&child_fields), original)?,
+            F::LANGUAGE,
+        );
+        for (child, location) in self.nodes.iter().skip(1) {
+            let NodeLocation::Item { field, index } = location else {
+                continue;
+            };
+            if let Some(entry) = source.entries(field).get(*index) {
+                push_section(
+                    &mut events,
+                    HeadingLevel::H2,
+                    child,
+                    F::render_source(entry, "")?,
+                    F::LANGUAGE,
+                );
+            }
+        }
+        let mut html_body = String::new();
+        pulldown_cmark::html::push_html(&mut html_body, events.into_iter());
+
+        Ok(vec![(output_filename, html_body)])
+    }
+}
+
+/// Append a heading for `node` followed by a fenced code block containing `body`.
+fn push_section(
+    events: &mut Vec<MdEvent<'static>>,
+    level: HeadingLevel,
+    node: &IRNode,
+    body: String,
+    language: &'static str,
+) {
+    events.push(MdEvent::Start(MdTag::Heading {
+        level,
+        id: node.id().map(CowStr::from),
+        classes: vec![],
+        attrs: vec![],
+    }));
+    events.push(MdEvent::Text(CowStr::from(
+        node.title().unwrap_or_default(),
+    )));
+    events.push(MdEvent::End(TagEnd::Heading(level)));
+    let body = body.trim();
+    if !body.is_empty() {
+        events.push(MdEvent::Start(MdTag::CodeBlock(CodeBlockKind::Fenced(
+            CowStr::from(language),
+        ))));
+        events.push(MdEvent::Text(CowStr::from(format!("{body}\n"))));
+        events.push(MdEvent::End(TagEnd::CodeBlock));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        beliefbase::BeliefBase,
+        codec::{
+            builder::GraphBuilder,
+            compiler::DocumentCompiler,
+            network::iter_net_docs,
+            proto_index::ProtoIndex,
+            schema_registry::{EdgeDirection, GraphField, SchemaDefinition, SCHEMAS},
+            CODECS,
+        },
+        properties::WeightKind,
+        tests::helpers::init_logging,
+    };
+    use tempfile::TempDir;
+
+    const TASK: &str = r#"# Release checklist
+title = "Ship the release"   # shown in listings
+schema = "test_data.task"
+owner = "release-team"
+
+depends_on = ["build-passes", "docs-reviewed"]
+
+[details]
+priority = 1
+"#;
+
+    fn register_task_schema() {
+        SCHEMAS.register(
+            "test_data.task".to_string(),
+            SchemaDefinition {
+                graph_fields: vec![GraphField::new(
+                    "depends_on",
+                    EdgeDirection::Downstream,
+                    WeightKind::Pragmatic,
+                )],
+                ..SchemaDefinition::default()
+            },
+        );
+    }
+
+    fn parse_file(path: &Path, content: &str) -> TomlCodec {
+        parse_with::<TomlFormat>(path, content)
+    }
+
+    fn parse_with<F: DataFormat>(path: &Path, content: &str) -> DataCodec<F> {
+        let mut codec = DataCodec::<F>::new();
+        let proto = codec
+            .proto(path)
+            .expect("proto should succeed")
+            .expect("proto should return Some");
+        codec
+            .parse(content, proto, &mut vec![])
+            .expect("parse should succeed");
+        codec
+    }
+
+    #[test]
+    fn test_toml_codec_registered() {
+        let ap = AnchorPath::new("records/task.toml");
+        assert!(CODECS.get(&ap).is_some());
+    }
+
+    #[test]
+    fn test_proto_derives_title_from_filestem() {
+        init_logging();
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("glossary_entry.toml");
+        fs::write(&path, "term = \"noet\"\n").unwrap();
+
+        let proto = TomlCodec::new().proto(&path).unwrap().unwrap();
+        assert_eq!(proto.title().as_deref(), Some("Glossary Entry"));
+        assert_eq!(proto.heading, 2);
+        assert!(proto.kind.is_document());
+
+        let not_toml = temp_dir.path().join("notes.md");
+        fs::write(&not_toml, "# Notes").unwrap();
+        assert!(TomlCodec::new().proto(&not_toml).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_single_document_with_schema_edges() {
+        init_logging();
+        register_task_schema();
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("task.toml");
+        fs::write(&path, TASK).unwrap();
+
+        let codec = parse_file(&path, TASK);
+        let nodes = codec.nodes();
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.title().as_deref(), Some("Ship the release"));
+        assert_eq!(
+            node.document.get("owner").and_then(|v| v.as_str()),
+            Some("release-team")
+        );
+        assert_eq!(node.downstream.len(), 2);
+        assert!(node
+            .downstream
+            .iter()
+            .all(|rel| rel.kind == WeightKind::Pragmatic));
+
+        let belief_node = BeliefNode::try_from(node).unwrap();
+        assert!(belief_node.payload.contains_key("details"));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("broken.toml");
+        fs::write(&path, "title = \"ok\"\n").unwrap();
+        let mut codec = TomlCodec::new();
+        let proto = codec.proto(&path).unwrap().unwrap();
+        assert!(codec
+            .parse("title = [unterminated", proto, &mut vec![])
+            .is_err());
+    }
+
+    #[test]
+    fn test_generate_source_round_trips_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("task.toml");
+        fs::write(&path, TASK).unwrap();
+
+        let codec = parse_file(&path, TASK);
+        assert_eq!(codec.generate_source().as_deref(), Some(TASK));
+    }
+
+    #[test]
+    fn test_generate_html_escapes_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("task.toml");
+        let content = "title = \"A <b>bold</b> plan\"\n";
+        fs::write(&path, content).unwrap();
+
+        let codec = parse_file(&path, content);
+        let html = codec.generate_html().unwrap();
+        assert_eq!(html.len(), 1);
+        assert_eq!(html[0].0, "task.html");
+        let id = codec.nodes()[0].id().unwrap();
+        assert!(html[0].1.contains(&format!("<h1 id=\"{id}\">")));
+        assert!(html[0].1.contains("language-toml"));
+        assert!(html[0].1.contains("&lt;b&gt;bold&lt;/b&gt;"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_content_injects_bid_preserving_format() {
+        init_logging();
+        register_task_schema();
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("index.md"),
+            "---\nid: \"test-network\"\ntitle: \"Test Network\"\n---\n\n# Test Network\n",
+        )
+        .unwrap();
+        let path = temp_dir.path().join("task.toml");
+        fs::write(&path, TASK).unwrap();
+
+        let mut builder = GraphBuilder::new(temp_dir.path(), None).unwrap();
+        let session_bb = builder.session_bb().clone();
+        let proto_index = ProtoIndex::build(builder.repo_root()).unwrap_or_default();
+        let result = builder
+            .parse_content(&path, TASK.to_string(), session_bb, proto_index)
+            .await
+            .expect("parse_content should succeed");
+
+        let rewritten = result
+            .result
+            .rewritten_content
+            .expect("a new BID should be written back to the source");
+        let reparsed = rewritten.parse::<DocumentMut>().unwrap();
+        let bid = reparsed
+            .get("bid")
+            .and_then(|v| v.as_str())
+            .expect("bid should be injected");
+        assert_eq!(
+            result.codec.nodes()[0]
+                .document
+                .get("bid")
+                .and_then(|v| v.as_str()),
+            Some(bid)
+        );
+
+        // Everything apart from the injected bid line is untouched.
+        let without_bid = rewritten
+            .lines()
+            .filter(|line| !line.starts_with("bid = "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(without_bid.trim_end(), TASK.trim_end());
+    }
+
+    const GLOSSARY_JSON: &str = r#"{
+    "title": "Glossary",
+    "zeta": true,
+    "entries": [
+        {
+            "term": "BID",
+            "title": "Belief ID"
+        },
+        {
+            "term": "Bref"
+        }
+    ],
+    "alpha": 1
+}
+"#;
+
+    const GLOSSARY_YAML: &str =
+        "title: Glossary\nentries:\n  - name: BID\n    definition: Belief ID\n  - name: Bref\n";
+
+    /// Simulate `inject_context` writing a builder-assigned BID for every node, in node order.
+    fn write_back<F: DataFormat>(codec: &mut DataCodec<F>, bids: &[&str]) -> String {
+        assert_eq!(codec.nodes.len(), bids.len());
+        let source = codec.source.as_mut().unwrap();
+        for ((_, location), bid) in codec.nodes.iter().zip(bids) {
+            codec.dirty |= source.set_bid(location, bid);
+        }
+        codec.generate_source().unwrap()
+    }
+
+    #[test]
+    fn test_json_and_yaml_codecs_registered() {
+        for path in ["data.json", "data.yaml", "data.yml"] {
+            assert!(CODECS.get(&AnchorPath::new(path)).is_some(), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_data_codecs_only_claim_noet_documents() {
+        let temp_dir = TempDir::new().unwrap();
+        for (name, content) in [
+            ("glossary.json", GLOSSARY_JSON),
+            ("glossary.yaml", GLOSSARY_YAML),
+            (
+                "package.json",
+                "{\"name\": \"noet\", \"version\": \"1.0.0\"}\n",
+            ),
+            ("tsconfig.json", "[1, 2]\n"),
+            ("ci.yml", "# build\non: push\njobs: {}\n"),
+            ("Cargo.toml", "[package]\nname = \"noet\"\n"),
+            ("task.toml", TASK),
+        ] {
+            fs::write(temp_dir.path().join(name), content).unwrap();
+        }
+        let docs = iter_net_docs(temp_dir.path())
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(docs, ["glossary.json", "glossary.yaml", "task.toml"]);
+    }
+
+    #[test]
+    fn test_json_arrays_of_objects_become_child_nodes() {
+        init_logging();
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("glossary.json");
+        fs::write(&path, GLOSSARY_JSON).unwrap();
+
+        let codec = parse_with::<JsonFormat>(&path, GLOSSARY_JSON);
+        let nodes = codec.nodes();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].heading, 2);
+        assert!(nodes[0].document.get("entries").is_none());
+        assert_eq!(
+            nodes[0].document.get("alpha").and_then(|v| v.as_integer()),
+            Some(1)
+        );
+        assert_eq!(nodes[1].title().as_deref(), Some("Belief ID"));
+        assert_eq!(nodes[2].title().as_deref(), Some("Entries 2"));
+        assert!(nodes[1..]
+            .iter()
+            .all(|node| node.heading == 3 && node.path == nodes[0].path));
+    }
+
+    #[test]
+    fn test_json_write_back_preserves_key_order_and_indent() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("glossary.json");
+        fs::write(&path, GLOSSARY_JSON).unwrap();
+
+        let mut codec = parse_with::<JsonFormat>(&path, GLOSSARY_JSON);
+        assert_eq!(codec.generate_source().as_deref(), Some(GLOSSARY_JSON));
+
+        let bids = [
+            "0192f0e4-5d1c-7a3e-8b4f-000000000001",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000002",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000003",
+        ];
+        let rewritten = write_back(&mut codec, &bids);
+        let expected = format!(
+            r#"{{
+    "title": "Glossary",
+    "zeta": true,
+    "entries": [
+        {{
+            "term": "BID",
+            "title": "Belief ID",
+            "bid": "{}"
+        }},
+        {{
+            "term": "Bref",
+            "bid": "{}"
+        }}
+    ],
+    "alpha": 1,
+    "bid": "{}"
+}}
+"#,
+            bids[1], bids[2], bids[0]
+        );
+        assert_eq!(rewritten, expected);
+    }
+
+    #[test]
+    fn test_yaml_children_and_write_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("glossary.yaml");
+        fs::write(&path, GLOSSARY_YAML).unwrap();
+
+        let mut codec = parse_with::<YamlFormat>(&path, GLOSSARY_YAML);
+        let nodes = codec.nodes();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[1].title().as_deref(), Some("BID"));
+        assert_eq!(nodes[2].title().as_deref(), Some("Bref"));
+
+        let bids = [
+            "0192f0e4-5d1c-7a3e-8b4f-000000000001",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000002",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000003",
+        ];
+        let rewritten = write_back(&mut codec, &bids);
+        assert_eq!(
+            rewritten,
+            format!(
+                "title: Glossary\nentries:\n- name: BID\n  definition: Belief ID\n  bid: {}\n\
+                 - name: Bref\n  bid: {}\nbid: {}\n",
+                bids[1], bids[2], bids[0]
+            )
+        );
+    }
+
+    #[test]
+    fn test_yaml_write_back_keeps_comments() {
+        let content = "# Terms used across the docs\n\
+                       title: Glossary  # shown in listings\n\
+                       entries:\n\
+                       # core terms\n\
+                       - bid: 0192f0e4-5d1c-7a3e-8b4f-00000000000f  # stale\n\
+                       \x20 name: BID\n\
+                       -\n\
+                       \x20 name: Bref\n\
+                       \x20 notes: |\n\
+                       \x20   Short for belief reference.\n\
+                       \n\
+                       # trailing terms\n\
+                       owner: docs-team\n";
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("glossary.yaml");
+        fs::write(&path, content).unwrap();
+
+        let mut codec = parse_with::<YamlFormat>(&path, content);
+        let bids = [
+            "0192f0e4-5d1c-7a3e-8b4f-000000000001",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000002",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000003",
+        ];
+        let rewritten = write_back(&mut codec, &bids);
+        assert_eq!(
+            rewritten,
+            format!(
+                "# Terms used across the docs\n\
+                 title: Glossary  # shown in listings\n\
+                 entries:\n\
+                 # core terms\n\
+                 - bid: {}  # stale\n\
+                 \x20 name: BID\n\
+                 -\n\
+                 \x20 name: Bref\n\
+                 \x20 notes: |\n\
+                 \x20   Short for belief reference.\n\
+                 \x20 bid: {}\n\
+                 \n\
+                 # trailing terms\n\
+                 owner: docs-team\n\
+                 bid: {}\n",
+                bids[1], bids[2], bids[0]
+            )
+        );
+    }
+
+    #[test]
+    fn test_yaml_with_comments_is_not_reserialized() {
+        let content = "title: Glossary  # shown in listings\nentries: [{name: BID}]\n";
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("glossary.yaml");
+        fs::write(&path, content).unwrap();
+
+        let mut codec = DataCodec::<YamlFormat>::new();
+        let proto = codec.proto(&path).unwrap().unwrap();
+        codec.parse(content, proto, &mut vec![]).unwrap();
+        let bids = [
+            "0192f0e4-5d1c-7a3e-8b4f-000000000001",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000002",
+        ];
+        let source = codec.source.as_mut().unwrap();
+        for ((_, location), bid) in codec.nodes.iter().zip(bids) {
+            codec.dirty |= source.set_bid(location, bid);
+        }
+        let mut diagnostics = Vec::new();
+        codec.finalize(&mut diagnostics).unwrap();
+        assert_eq!(codec.generate_source().as_deref(), Some(content));
+        assert!(matches!(
+            diagnostics.as_slice(),
+            [ParseDiagnostic::Warning { message, .. }] if message.contains("comments")
+        ));
+    }
+
+    #[test]
+    fn test_yaml_rejects_non_mapping_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("list.yaml");
+        fs::write(&path, "- a\n- b\n").unwrap();
+        assert!(YamlCodec::new().proto(&path).is_err());
+    }
+
+    #[test]
+    fn test_toml_array_of_tables_children() {
+        init_logging();
+        let content = r#"title = "Sprint"
+schema = "intention_lattice.intention"
+
+[[parent_connections]]
+parent_id = "roadmap"
+
+[[tasks]]
+title = "Write docs"  # first
+
+[[tasks]]
+title = "Write docs"
+"#;
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("sprint.toml");
+        fs::write(&path, content).unwrap();
+
+        let mut codec = TomlCodec::new();
+        let proto = codec.proto(&path).unwrap().unwrap();
+        let mut diagnostics = Vec::new();
+        codec.parse(content, proto, &mut diagnostics).unwrap();
+        let nodes = codec.nodes();
+
+        // parent_connections is a schema graph field: an edge, not a child node.
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].downstream.len(), 1);
+        assert!(nodes[0].document.get("parent_connections").is_some());
+        assert!(nodes[0].document.get("tasks").is_none());
+
+        // Duplicate titles produce a warning and the collision sentinel on the second entry.
+        assert_eq!(nodes[1].id().as_deref(), Some("write-docs"));
+        assert_eq!(nodes[2].id(), None);
+        assert!(diagnostics
+            .iter()
+            .any(|d| matches!(d, ParseDiagnostic::Warning { .. })));
+
+        let bids = [
+            "0192f0e4-5d1c-7a3e-8b4f-000000000001",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000002",
+            "0192f0e4-5d1c-7a3e-8b4f-000000000003",
+        ];
+        let rewritten = write_back(&mut codec, &bids);
+        assert_eq!(
+            rewritten,
+            format!(
+                r#"title = "Sprint"
+schema = "intention_lattice.intention"
+bid = "{}"
+
+[[parent_connections]]
+parent_id = "roadmap"
+
+[[tasks]]
+title = "Write docs"  # first
+bid = "{}"
+
+[[tasks]]
+title = "Write docs"
+bid = "{}"
+"#,
+                bids[0], bids[1], bids[2]
+            )
+        );
+
+        let html = codec.generate_html().unwrap();
+        assert!(html[0].1.contains("<h2 id=\"write-docs\">"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_content_json_children_get_bids() {
+        init_logging();
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("index.md"),
+            "---\nid: \"test-network\"\ntitle: \"Test Network\"\n---\n\n# Test Network\n",
+        )
+        .unwrap();
+        let path = temp_dir.path().join("glossary.json");
+        fs::write(&path, GLOSSARY_JSON).unwrap();
+
+        let mut builder = GraphBuilder::new(temp_dir.path(), None).unwrap();
+        let session_bb = builder.session_bb().clone();
+        let proto_index = ProtoIndex::build(builder.repo_root()).unwrap_or_default();
+        let result = builder
+            .parse_content(&path, GLOSSARY_JSON.to_string(), session_bb, proto_index)
+            .await
+            .expect("parse_content should succeed");
+
+        let rewritten = result
+            .result
+            .rewritten_content
+            .expect("new BIDs should be written back to the source");
+        let reparsed: serde_json::Value = serde_json::from_str(&rewritten).unwrap();
+        assert!(reparsed["bid"].is_string());
+        assert!(reparsed["entries"][0]["bid"].is_string());
+        assert!(reparsed["entries"][1]["bid"].is_string());
+        assert_ne!(reparsed["entries"][0]["bid"], reparsed["entries"][1]["bid"]);
+
+        // Key order survives the rewrite.
+        let title_pos = rewritten.find("\"title\"").unwrap();
+        let zeta_pos = rewritten.find("\"zeta\"").unwrap();
+        let alpha_pos = rewritten.find("\"alpha\"").unwrap();
+        assert!(title_pos < zeta_pos && zeta_pos < alpha_pos);
+    }
+
+    #[tokio::test]
+    async fn test_parse_all_leaves_plain_toml_untouched() {
+        init_logging();
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("index.md"),
+            "---\nid: \"test-network\"\ntitle: \"Test Network\"\n---\n\n# Test Network\n",
+        )
+        .unwrap();
+        let cargo = "[package]\nname = \"noet\"  # the crate\nversion = \"0.1.0\"\n";
+        fs::write(temp_dir.path().join("Cargo.toml"), cargo).unwrap();
+        fs::write(temp_dir.path().join("task.toml"), TASK).unwrap();
+
+        let mut compiler = DocumentCompiler::new(temp_dir.path(), None, None, true).unwrap();
+        compiler
+            .parse_all(BeliefBase::default(), false)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap(),
+            cargo
+        );
+        assert!(fs::read_to_string(temp_dir.path().join("task.toml"))
+            .unwrap()
+            .contains("bid = "));
+    }
+}
@@ -0,0 +1,1330 @@
+//! Structured graph edits written back to source files.
+//!
+//! [`SourceEditor`] turns an edit of the graph (create a node, set its payload, relate it to
+//! another node, rename or delete it) into the smallest change to the file that owns the node,
+//! returned as a [`SourceEdit`]. Nothing is written until the edit is applied, and the graph only
+//! follows once the changed files are reparsed; the service's `commands::OpExecutor` does both.
+//!
+//! Edits are supported for Markdown documents and their sections, and for standalone TOML
+//! documents:
+//!
+//! - Payload fields and titles are edited through `toml_edit`, in the Markdown frontmatter or the
+//!   TOML document itself, so comments and key order are kept. Section payloads live in the
+//!   frontmatter `sections` table, keyed by the section's anchor.
+//! - Relations are Markdown links in the sink's text, written in the typed link syntax
+//!   (`[Title](path.md){rel=supports}`). Removing a relation unlinks the link text in place.
+//!   Relations declared by schema graph fields are left alone.
+//! - Renaming a section rewrites its heading and pins its previous anchor (`{ #anchor }`), so the
+//!   section keeps its identity and inbound links keep resolving.
+//!
+//! Moving a file or directory is planned as a whole ([`SourceEditor::plan_move`]): the files that
+//! link into what moves are found through the graph's relations, and every relative link that the
+//! move would break, inbound or outbound, is rewritten. A [`MovePlan`] can be listed before it's
+//! applied, and [reversed](MovePlan::reverse) afterwards.
+
+use pulldown_cmark::{Event as MdEvent, Parser as MdParser, Tag as MdTag, TagEnd as MdTagEnd};
+use std::{
+    collections::BTreeSet,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+use toml::{Table, Value as TomlValue};
+use toml_edit::{DocumentMut, Item};
+use walkdir::WalkDir;
+
+use crate::{
+    beliefbase::BeliefBase,
+    codec::{
+        belief_ir::{IRNode, MetadataFormat},
+        md::{buildonomy_md_options, parse_link_attributes, MdCodec},
+        network::detect_network_file,
+    },
+    error::BuildonomyError,
+    nodekey::NodeKey,
+    paths::{os_path_to_string, string_to_os_path, to_anchor, AnchorPath},
+    properties::{BeliefNode, Bid, Weight, WeightKind},
+};
+
+/// Node fields that aren't payload. Titles are set by renaming the node.
+const RESERVED_FIELDS: [&str; 6] = ["bid", "id", "kind", "schema", "title", "sections"];
+
+/// A rewrite of one source file. `content` is `None` when the file is removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceEdit {
+    pub path: PathBuf,
+    pub content: Option<String>,
+}
+
+impl SourceEdit {
+    /// Write the edit to disk, creating parent directories as needed.
+    pub fn apply(&self) -> Result<(), BuildonomyError> {
+        match &self.content {
+            Some(content) => {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&self.path, content)?;
+            }
+            None => fs::remove_file(&self.path)?,
+        }
+        Ok(())
+    }
+}
+
+/// Moving a file or directory within the repository, with the link rewrites that keep relative
+/// links resolving. See [`SourceEditor::plan_move`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovePlan {
+    /// Moved path, relative to the repository root.
+    pub from: String,
+    /// Destination path, relative to the repository root.
+    pub to: String,
+    /// Every moved file, as `(from, to)` paths relative to the repository root.
+    pub files: Vec<(String, String)>,
+    pub links: Vec<LinkRewrite>,
+    repo_root: PathBuf,
+    /// The rewritten sources, at their paths after the move.
+    edits: Vec<SourceEdit>,
+    /// The same sources before the rewrite, at their paths after the move.
+    originals: Vec<SourceEdit>,
+}
+
+/// A link whose destination a [`MovePlan`] rewrites.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkRewrite {
+    /// The file containing the link after the move, relative to the repository root.
+    pub path: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl MovePlan {
+    /// The rewritten sources, at their paths after the move.
+    pub fn edits(&self) -> &[SourceEdit] {
+        &self.edits
+    }
+
+    /// Move the files and write the rewritten links.
+    pub fn apply(&self) -> Result<(), BuildonomyError> {
+        let to = self.repo_root.join(string_to_os_path(&self.to));
+        if to.exists() {
+            return Err(BuildonomyError::Command(format!(
+                "'{}' already exists",
+                self.to
+            )));
+        }
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let from = self.repo_root.join(string_to_os_path(&self.from));
+        fs::rename(&from, &to)?;
+        // Directories the move leaves empty go with it, so reversing it leaves no trace.
+        for dir in from.ancestors().skip(1) {
+            if dir == self.repo_root || fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+        for edit in self.edits.iter() {
+            edit.apply()?;
+        }
+        Ok(())
+    }
+
+    /// The plan that undoes this one once it has been applied, restoring the sources exactly.
+    pub fn reverse(&self) -> MovePlan {
+        let back = |path: &str| relocate(path, &self.to, &self.from).unwrap_or(path.to_string());
+        let back_edit = |edit: &SourceEdit| {
+            let path = edit
+                .path
+                .strip_prefix(&self.repo_root)
+                .map(|path| {
+                    self.repo_root
+                        .join(string_to_os_path(&back(&os_path_to_string(path))))
+                })
+                .unwrap_or_else(|_| edit.path.clone());
+            SourceEdit {
+                path,
+                content: edit.content.clone(),
+            }
+        };
+        MovePlan {
+            from: self.to.clone(),
+            to: self.from.clone(),
+            files: self
+                .files
+                .iter()
+                .map(|(from, to)| (to.clone(), from.clone()))
+                .collect(),
+            links: self
+                .links
+                .iter()
+                .map(|link| LinkRewrite {
+                    path: back(&link.path),
+                    from: link.to.clone(),
+                    to: link.from.clone(),
+                })
+                .collect(),
+            repo_root: self.repo_root.clone(),
+            edits: self.originals.iter().map(back_edit).collect(),
+            originals: self.edits.iter().map(back_edit).collect(),
+        }
+    }
+}
+
+/// A node and the file it lives in.
+struct NodeSource {
+    node: BeliefNode,
+    file: PathBuf,
+    /// File path relative to the repository root.
+    path: String,
+    /// Anchor of a section within its document.
+    anchor: Option<String>,
+    is_network: bool,
+}
+
+impl NodeSource {
+    /// The node's location relative to the repository root, as links resolve to it.
+    fn location(&self) -> String {
+        match &self.anchor {
+            Some(anchor) => format!("{}#{anchor}", self.path),
+            None => self.path.clone(),
+        }
+    }
+
+    fn format(&self) -> Result<SourceFormat, BuildonomyError> {
+        match self.file.extension().and_then(|ext| ext.to_str()) {
+            Some("md") => Ok(SourceFormat::Markdown),
+            Some("toml") if self.anchor.is_none() => Ok(SourceFormat::Toml),
+            _ => Err(BuildonomyError::Command(format!(
+                "Structured edits are not supported for '{}'",
+                self.location()
+            ))),
+        }
+    }
+
+    fn read(&self) -> Result<String, BuildonomyError> {
+        Ok(fs::read_to_string(&self.file)?)
+    }
+
+    fn edit(&self, content: String) -> SourceEdit {
+        SourceEdit {
+            path: self.file.clone(),
+            content: Some(content),
+        }
+    }
+}
+
+enum SourceFormat {
+    Markdown,
+    Toml,
+}
+
+/// Computes the source edits that carry out structured graph edits. See the
+/// [module docs](self).
+pub struct SourceEditor<'a> {
+    bb: &'a BeliefBase,
+    repo: Bid,
+    repo_root: &'a Path,
+}
+
+impl<'a> SourceEditor<'a> {
+    /// `bb` resolves nodes and their paths; `repo` is the root network, whose directory is
+    /// `repo_root`.
+    pub fn new(bb: &'a BeliefBase, repo: Bid, repo_root: &'a Path) -> Self {
+        Self {
+            bb,
+            repo,
+            repo_root,
+        }
+    }
+
+    fn locate(&self, key: &NodeKey) -> Result<NodeSource, BuildonomyError> {
+        let node = self
+            .bb
+            .get(key)
+            .ok_or_else(|| BuildonomyError::NotFound(format!("No node matches '{key}'")))?;
+        let paths = self.bb.paths();
+        let (_, path) = paths
+            .net_path(&self.repo.bref(), &node.bid)
+            .ok_or_else(|| {
+                BuildonomyError::NotFound(format!("'{}' has no path in the repository", node.title))
+            })?;
+        let is_network = paths.nets().contains(&node.bid);
+        drop(paths);
+
+        let path_ap = AnchorPath::from(&path);
+        let anchor = Some(path_ap.anchor())
+            .filter(|anchor| !anchor.is_empty())
+            .map(str::to_string);
+        let mut file = self.repo_root.join(string_to_os_path(path_ap.filepath()));
+        if is_network || file.is_dir() {
+            file = detect_network_file(&file).ok_or_else(|| {
+                BuildonomyError::NotFound(format!("'{}' has no network file", node.title))
+            })?;
+        }
+        let path = file
+            .strip_prefix(self.repo_root)
+            .map(os_path_to_string)
+            .map_err(|_| {
+                BuildonomyError::Command(format!("{file:?} is outside of the repository"))
+            })?;
+        Ok(NodeSource {
+            node,
+            file,
+            path,
+            anchor,
+            is_network,
+        })
+    }
+
+    /// Create a node titled `title` under `parent`. Under a network this creates a Markdown
+    /// document at `path` (relative to the network directory, by default the title's slug);
+    /// under a document or section it appends a subsection.
+    pub fn create_node(
+        &self,
+        parent: &NodeKey,
+        title: &str,
+        path: Option<&str>,
+        payload: &Table,
+    ) -> Result<SourceEdit, BuildonomyError> {
+        check_payload(payload.keys())?;
+        let parent = self.locate(parent)?;
+        if parent.is_network {
+            let dir = parent.file.parent().unwrap_or(self.repo_root);
+            let file = match path {
+                Some(path) => dir.join(string_to_os_path(path)),
+                None => dir.join(format!("{}.md", to_anchor(title))),
+            };
+            if file.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                return Err(BuildonomyError::Command(format!(
+                    "New documents must be Markdown files, not {file:?}"
+                )));
+            }
+            if file.exists() {
+                return Err(BuildonomyError::Command(format!("{file:?} already exists")));
+            }
+            let mut frontmatter = DocumentMut::new();
+            frontmatter.insert("title", toml_edit::value(title));
+            for (key, value) in payload {
+                frontmatter.insert(key, to_item(value)?);
+            }
+            return Ok(SourceEdit {
+                path: file,
+                content: Some(format!("---\n{frontmatter}---\n\n# {title}\n")),
+            });
+        }
+
+        let SourceFormat::Markdown = parent.format()? else {
+            return Err(BuildonomyError::Command(format!(
+                "Cannot add sections to '{}'",
+                parent.location()
+            )));
+        };
+        let content = parent.read()?;
+        let layout = MdLayout::new(&content);
+        let anchor = to_anchor(title);
+        if anchor.is_empty() || layout.section(&anchor).is_some() {
+            return Err(BuildonomyError::Command(format!(
+                "'{}' already has a section anchored at '#{anchor}'",
+                parent.path
+            )));
+        }
+        let (level, end) = match &parent.anchor {
+            Some(parent_anchor) => {
+                let idx = layout
+                    .section(parent_anchor)
+                    .ok_or_else(|| section_not_found(&parent.path, parent_anchor))?;
+                (
+                    (layout.headings[idx].level + 1).min(6),
+                    layout.section_end(idx, &content),
+                )
+            }
+            None => (2, content.len()),
+        };
+        let heading = format!("{} {title}", "#".repeat(level));
+        let mut edited = append_block(&content, end, &heading);
+        if !payload.is_empty() {
+            edited = set_frontmatter_fields(&edited, Some(&anchor), payload, &[])?;
+        }
+        Ok(parent.edit(edited))
+    }
+
+    /// Set the `set` payload fields of a node and remove the `remove` fields.
+    pub fn set_payload(
+        &self,
+        node: &NodeKey,
+        set: &Table,
+        remove: &[String],
+    ) -> Result<SourceEdit, BuildonomyError> {
+        check_payload(set.keys().chain(remove))?;
+        let node = self.locate(node)?;
+        let content = node.read()?;
+        let edited = match node.format()? {
+            SourceFormat::Markdown => {
+                set_frontmatter_fields(&content, node.anchor.as_deref(), set, remove)?
+            }
+            SourceFormat::Toml => {
+                let mut doc = parse_toml(&content, &node.path)?;
+                set_fields(doc.as_table_mut(), set, remove)?;
+                doc.to_string()
+            }
+        };
+        Ok(node.edit(edited))
+    }
+
+    /// Relate `source` to `sink` by linking to `source` from the text of `sink`. Payload fields
+    /// are written as link attributes.
+    pub fn add_relation(
+        &self,
+        source: &NodeKey,
+        sink: &NodeKey,
+        kind: WeightKind,
+        payload: &Table,
+    ) -> Result<SourceEdit, BuildonomyError> {
+        if kind == WeightKind::Section {
+            return Err(BuildonomyError::Command(
+                "Section relations are the document structure; create the node instead".to_string(),
+            ));
+        }
+        let source = self.locate(source)?;
+        let sink = self.locate(sink)?;
+        let SourceFormat::Markdown = sink.format()? else {
+            return Err(BuildonomyError::Command(format!(
+                "Cannot add links to '{}'",
+                sink.location()
+            )));
+        };
+
+        let dest = if source.path == sink.path {
+            String::new()
+        } else {
+            AnchorPath::from(&sink.path).path_to(&source.path, true)
+        };
+        let dest = match &source.anchor {
+            Some(anchor) => format!("{dest}#{anchor}"),
+            None if dest.is_empty() => {
+                return Err(BuildonomyError::Command(format!(
+                    "'{}' cannot link to itself",
+                    sink.node.title
+                )))
+            }
+            None => dest,
+        };
+        let mut attributes = Vec::new();
+        if kind != WeightKind::Epistemic {
+            attributes.push(format!(
+                "rel={}",
+                attribute_value(&TomlValue::String(kind.name()))?
+            ));
+        }
+        for (key, value) in payload {
+            attributes.push(format!("{key}={}", attribute_value(value)?));
+        }
+        let mut link = format!("[{}]({dest})", escape_link_text(&source.node.title));
+        if !attributes.is_empty() {
+            link = format!("{link}{{{}}}", attributes.join(" "));
+        }
+
+        let content = sink.read()?;
+        let layout = MdLayout::new(&content);
+        // The link belongs to the sink only if it comes before the sink's first subsection.
+        let (_, end) = layout.own_range(sink.anchor.as_deref(), &content, &sink.path)?;
+        Ok(sink.edit(append_block(&content, end, &link)))
+    }
+
+    /// Remove the links in the text of `sink` that relate `source` to it with `kind`, keeping the
+    /// link text.
+    pub fn remove_relation(
+        &self,
+        source: &NodeKey,
+        sink: &NodeKey,
+        kind: WeightKind,
+    ) -> Result<SourceEdit, BuildonomyError> {
+        let source = self.locate(source)?;
+        let sink = self.locate(sink)?;
+        let SourceFormat::Markdown = sink.format()? else {
+            return Err(BuildonomyError::Command(format!(
+                "Cannot remove links from '{}'",
+                sink.location()
+            )));
+        };
+        let content = sink.read()?;
+        let layout = MdLayout::new(&content);
+        let (start, end) = layout.own_range(sink.anchor.as_deref(), &content, &sink.path)?;
+        let location = source.location();
+        let bref = format!("bref://{}", source.node.bid.bref());
+
+        let mut edited = content.clone();
+        let mut removed = 0;
+        for link in layout.links.iter().rev() {
+            if link.range.start < start || link.range.end > end {
+                continue;
+            }
+            let targets_source = link.title.split_whitespace().any(|word| word == bref)
+                || AnchorPath::from(&sink.path).join(&link.dest).into_string() == location;
+            if !targets_source {
+                continue;
+            }
+            let link_kind = MdCodec::link_semantics(
+                &content,
+                &link.title,
+                link.end,
+                None,
+                &mut Weight::default(),
+                &mut Vec::new(),
+            );
+            if link_kind != kind {
+                continue;
+            }
+            let text = link
+                .text
+                .clone()
+                .map(|text| content[text].to_string())
+                .unwrap_or_default();
+            edited.replace_range(link.range.clone(), &text);
+            removed += 1;
+        }
+        if removed == 0 {
+            return Err(BuildonomyError::NotFound(format!(
+                "No {} link to '{}' in '{}'",
+                kind.name(),
+                source.node.title,
+                sink.location()
+            )));
+        }
+        Ok(sink.edit(edited))
+    }
+
+    /// Retitle a node. Section headings keep their previous anchor.
+    pub fn rename_node(&self, node: &NodeKey, title: &str) -> Result<SourceEdit, BuildonomyError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(BuildonomyError::Command(
+                "Titles cannot be empty".to_string(),
+            ));
+        }
+        let node = self.locate(node)?;
+        let content = node.read()?;
+        let edited = match (node.format()?, &node.anchor) {
+            (SourceFormat::Toml, _) => {
+                let mut doc = parse_toml(&content, &node.path)?;
+                doc.insert("title", toml_edit::value(title));
+                doc.to_string()
+            }
+            (SourceFormat::Markdown, Some(anchor)) => {
+                let layout = MdLayout::new(&content);
+                let heading = layout
+                    .section(anchor)
+                    .map(|idx| &layout.headings[idx])
+                    .ok_or_else(|| section_not_found(&node.path, anchor))?;
+                let mut edited = content.clone();
+                if !heading.explicit_id {
+                    edited.insert_str(heading.range.end, &format!(" {{ #{anchor} }}"));
+                }
+                edited.replace_range(heading.text.clone(), title);
+                edited
+            }
+            (SourceFormat::Markdown, None) => {
+                let layout = MdLayout::new(&content);
+                let mut edited = content.clone();
+                if let Some(heading) = layout.document_heading() {
+                    edited.replace_range(heading.text.clone(), title);
+                }
+                let mut fields = Table::new();
+                fields.insert("title".to_string(), TomlValue::String(title.to_string()));
+                set_frontmatter_fields(&edited, None, &fields, &[])?
+            }
+        };
+        Ok(node.edit(edited))
+    }
+
+    /// Delete a document's file, or a section (with its subsections) from its document.
+    pub fn delete_node(&self, node: &NodeKey) -> Result<SourceEdit, BuildonomyError> {
+        let node = self.locate(node)?;
+        if node.is_network {
+            return Err(BuildonomyError::Command(format!(
+                "'{}' is a network; remove its directory instead",
+                node.node.title
+            )));
+        }
+        let Some(anchor) = &node.anchor else {
+            return Ok(SourceEdit {
+                path: node.file.clone(),
+                content: None,
+            });
+        };
+        let SourceFormat::Markdown = node.format()? else {
+            return Err(BuildonomyError::Command(format!(
+                "Cannot delete '{}'",
+                node.location()
+            )));
+        };
+        let content = node.read()?;
+        let layout = MdLayout::new(&content);
+        let idx = layout
+            .section(anchor)
+            .ok_or_else(|| section_not_found(&node.path, anchor))?;
+        let start = layout.headings[idx].range.start;
+        let end = layout.section_end(idx, &content);
+        let mut edited = content.clone();
+        edited.replace_range(start..end, "");
+        let edited = remove_section_fields(&edited, anchor)?;
+        Ok(node.edit(edited))
+    }
+
+    /// Plan moving the file or directory at `from` to `to`, both relative to the repository root.
+    /// Markdown links into the moved files, and relative links out of them, are rewritten where
+    /// the move would break them; links in other formats are left alone.
+    pub fn plan_move(&self, from: &str, to: &str) -> Result<MovePlan, BuildonomyError> {
+        let from = repo_path(from);
+        let to = repo_path(to);
+        let source = self.repo_root.join(string_to_os_path(&from));
+        if from.is_empty() || to.is_empty() || from.starts_with("../") || to.starts_with("../") {
+            return Err(BuildonomyError::Command(
+                "Moves must stay within the repository".to_string(),
+            ));
+        }
+        if !source.exists() {
+            return Err(BuildonomyError::NotFound(format!(
+                "'{from}' does not exist"
+            )));
+        }
+        if to == from || to.starts_with(&format!("{from}/")) {
+            return Err(BuildonomyError::Command(format!(
+                "Cannot move '{from}' into itself"
+            )));
+        }
+        if self.repo_root.join(string_to_os_path(&to)).exists() {
+            return Err(BuildonomyError::Command(format!("'{to}' already exists")));
+        }
+
+        let mut files = Vec::new();
+        for entry in WalkDir::new(&source).sort_by_file_name() {
+            let entry = entry.map_err(|e| BuildonomyError::Io(e.to_string()))?;
+            if entry.file_type().is_file() {
+                let path =
+                    os_path_to_string(entry.path().strip_prefix(self.repo_root).map_err(|_| {
+                        BuildonomyError::Command(format!(
+                            "{:?} is outside of the repository",
+                            entry.path()
+                        ))
+                    })?);
+                let moved = relocate(&path, &from, &to).unwrap_or_else(|| path.clone());
+                files.push((path, moved));
+            }
+        }
+
+        let referrers = self.referrers(&from, &to);
+
+        let mut links = Vec::new();
+        let mut edits = Vec::new();
+        let mut originals = Vec::new();
+        let sources = files
+            .iter()
+            .cloned()
+            .chain(referrers.iter().map(|path| (path.clone(), path.clone())));
+        for (old_path, new_path) in sources {
+            if !old_path.ends_with(".md") {
+                continue;
+            }
+            let content = fs::read_to_string(self.repo_root.join(string_to_os_path(&old_path)))?;
+            let (edited, rewrites) = rewrite_links(&content, &old_path, &new_path, &from, &to);
+            if rewrites.is_empty() {
+                continue;
+            }
+            let path = self.repo_root.join(string_to_os_path(&new_path));
+            links.extend(rewrites.into_iter().map(|(from, to)| LinkRewrite {
+                path: new_path.clone(),
+                from,
+                to,
+            }));
+            edits.push(SourceEdit {
+                path: path.clone(),
+                content: Some(edited),
+            });
+            originals.push(SourceEdit {
+                path,
+                content: Some(content),
+            });
+        }
+
+        Ok(MovePlan {
+            from,
+            to,
+            files,
+            links,
+            repo_root: self.repo_root.to_path_buf(),
+            edits,
+            originals,
+        })
+    }
+
+    /// The files outside of a move from `from` to `to` that link into it, found through the
+    /// relations of the moved nodes.
+    fn referrers(&self, from: &str, to: &str) -> BTreeSet<String> {
+        let paths = self.bb.paths();
+        let repo = self.repo.bref();
+        let moved = paths
+            .get_map(&repo)
+            .map(|map| {
+                map.map()
+                    .iter()
+                    .filter(|(path, ..)| {
+                        relocate(AnchorPath::from(path).filepath(), from, to).is_some()
+                    })
+                    .map(|(_, bid, _)| *bid)
+                    .collect::<BTreeSet<_>>()
+            })
+            .unwrap_or_default();
+        let relations = self.bb.relations();
+        let graph = relations.as_graph();
+        let mut referrers = BTreeSet::new();
+        for edge in graph.raw_edges() {
+            let is_link = edge
+                .weight
+                .weights
+                .keys()
+                .any(|kind| *kind != WeightKind::Section);
+            if !is_link || !moved.contains(&graph[edge.source()]) {
+                continue;
+            }
+            let Some((_, path)) = paths.net_path(&repo, &graph[edge.target()]) else {
+                continue;
+            };
+            let file = self.source_file(AnchorPath::from(&path).filepath());
+            if relocate(&file, from, to).is_none() {
+                referrers.insert(file);
+            }
+        }
+        referrers
+    }
+
+    /// The file holding the node at the repository-relative `path`: a network's directory holds it
+    /// in its network file.
+    fn source_file(&self, path: &str) -> String {
+        let file = self.repo_root.join(string_to_os_path(path));
+        if file.is_dir() {
+            if let Some(network_file) = detect_network_file(&file) {
+                if let Ok(relative) = network_file.strip_prefix(self.repo_root) {
+                    return os_path_to_string(relative);
+                }
+            }
+        }
+        path.to_string()
+    }
+}
+
+/// A repository-relative path in normal form.
+fn repo_path(path: &str) -> String {
+    AnchorPath::from(path.trim_matches('/'))
+        .normalize()
+        .into_string()
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Where `path` ends up when `from` moves to `to`, or `None` if the move doesn't include it.
+fn relocate(path: &str, from: &str, to: &str) -> Option<String> {
+    if path == from {
+        Some(to.to_string())
+    } else {
+        path.strip_prefix(from)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .map(|rest| format!("{to}/{rest}"))
+    }
+}
+
+/// Rewrite the relative links of the Markdown file at `old_path`, moving to `new_path`, that would
+/// stop resolving when `from` moves to `to`. Returns the content and each rewritten destination.
+fn rewrite_links(
+    content: &str,
+    old_path: &str,
+    new_path: &str,
+    from: &str,
+    to: &str,
+) -> (String, Vec<(String, String)>) {
+    let layout = MdLayout::new(content);
+    let mut rewrites = Vec::new();
+    let mut edited = content.to_string();
+    let mut links = layout
+        .links
+        .iter()
+        .chain(layout.images.iter())
+        .collect::<Vec<_>>();
+    links.sort_by_key(|link| std::cmp::Reverse(link.range.start));
+    for link in links {
+        let dest = AnchorPath::from(&link.dest);
+        if link.dest.is_empty()
+            || link.dest.starts_with('#')
+            || !dest.schema().is_empty()
+            || dest.is_absolute()
+        {
+            continue;
+        }
+        let target = AnchorPath::from(old_path).join(&link.dest);
+        let target = AnchorPath::from(&target);
+        let target_file = target.filepath();
+        let moved_target = relocate(target_file, from, to).unwrap_or(target_file.to_string());
+        let resolved = AnchorPath::from(new_path).join(&link.dest);
+        if AnchorPath::from(&resolved).filepath() == moved_target {
+            continue;
+        }
+        let mut new_dest = AnchorPath::from(new_path).path_to(&moved_target, true);
+        if !target.anchor().is_empty() {
+            new_dest = format!("{new_dest}#{}", target.anchor());
+        }
+        let Some(range) = link.dest_range(content) else {
+            tracing::warn!(
+                "Cannot rewrite the link to '{}' in '{old_path}': its destination is not inline",
+                link.dest
+            );
+            continue;
+        };
+        edited.replace_range(range, &new_dest);
+        rewrites.push((link.dest.clone(), new_dest));
+    }
+    rewrites.reverse();
+    (edited, rewrites)
+}
+
+fn section_not_found(path: &str, anchor: &str) -> BuildonomyError {
+    BuildonomyError::NotFound(format!("No section '#{anchor}' in '{path}'"))
+}
+
+fn check_payload<'k>(keys: impl Iterator<Item = &'k String>) -> Result<(), BuildonomyError> {
+    for key in keys {
+        if RESERVED_FIELDS.contains(&key.as_str()) {
+            return Err(BuildonomyError::Command(format!(
+                "'{key}' is not a payload field"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn to_item(value: &TomlValue) -> Result<Item, BuildonomyError> {
+    value
+        .to_string()
+        .parse::<toml_edit::Value>()
+        .map(Item::Value)
+        .map_err(|e| BuildonomyError::Serialization(format!("Invalid value {value}: {e}")))
+}
+
+fn set_fields(
+    table: &mut toml_edit::Table,
+    set: &Table,
+    remove: &[String],
+) -> Result<(), BuildonomyError> {
+    for key in remove {
+        table.remove(key);
+    }
+    for (key, value) in set {
+        table.insert(key, to_item(value)?);
+    }
+    Ok(())
+}
+
+fn parse_toml(content: &str, path: &str) -> Result<DocumentMut, BuildonomyError> {
+    content
+        .parse::<DocumentMut>()
+        .map_err(|e| BuildonomyError::Codec(format!("Failed to parse '{path}': {e}")))
+}
+
+/// Edit the frontmatter of a Markdown document, adding one at the top if it has none.
+fn edit_frontmatter(
+    content: &str,
+    edit: impl FnOnce(&mut DocumentMut) -> Result<(), BuildonomyError>,
+) -> Result<String, BuildonomyError> {
+    let layout = MdLayout::new(content);
+    let (mut doc, range) = match layout.frontmatter {
+        Some(range) => {
+            let text = &content[range.clone()];
+            let doc = if text.trim().is_empty() {
+                DocumentMut::new()
+            } else {
+                IRNode::from_str_with_format(text, MetadataFormat::Toml)?.document
+            };
+            (doc, Some(range))
+        }
+        None => (DocumentMut::new(), None),
+    };
+    edit(&mut doc)?;
+    let mut frontmatter = doc.to_string();
+    if !frontmatter.ends_with('\n') {
+        frontmatter.push('\n');
+    }
+    let mut edited = content.to_string();
+    match range {
+        Some(range) => edited.replace_range(range, &frontmatter),
+        None => edited.insert_str(0, &format!("---\n{frontmatter}---\n\n")),
+    }
+    Ok(edited)
+}
+
+/// Set payload fields of a Markdown document, or of one of its sections.
+fn set_frontmatter_fields(
+    content: &str,
+    section: Option<&str>,
+    set: &Table,
+    remove: &[String],
+) -> Result<String, BuildonomyError> {
+    edit_frontmatter(content, |doc| match section {
+        None => set_fields(doc.as_table_mut(), set, remove),
+        Some(anchor) => {
+            let sections = doc
+                .entry("sections")
+                .or_insert_with(|| {
+                    let mut sections = toml_edit::Table::new();
+                    sections.set_implicit(true);
+                    Item::Table(sections)
+                })
+                .as_table_mut()
+                .ok_or_else(|| {
+                    BuildonomyError::Codec("Frontmatter 'sections' is not a table".to_string())
+                })?;
+            let section = sections
+                .entry(&format!("id://{anchor}"))
+                .or_insert_with(|| Item::Table(toml_edit::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| {
+                    BuildonomyError::Codec(format!(
+                        "Section metadata for '#{anchor}' is not a table"
+                    ))
+                })?;
+            set_fields(section, set, remove)
+        }
+    })
+}
+
+/// Drop a deleted section's entry from the frontmatter `sections` table.
+fn remove_section_fields(content: &str, anchor: &str) -> Result<String, BuildonomyError> {
+    let key = format!("id://{anchor}");
+    let has_entry = MdLayout::new(content).frontmatter.is_some_and(|range| {
+        content[range]
+            .parse::<DocumentMut>()
+            .ok()
+            .and_then(|doc| {
+                doc.get("sections")
+                    .and_then(Item::as_table)
+                    .map(|sections| sections.contains_key(&key))
+            })
+            .unwrap_or(false)
+    });
+    if !has_entry {
+        return Ok(content.to_string());
+    }
+    edit_frontmatter(content, |doc| {
+        if let Some(sections) = doc.get_mut("sections").and_then(Item::as_table_mut) {
+            sections.remove(&key);
+        }
+        Ok(())
+    })
+}
+
+/// Insert `block` as its own paragraph at `offset`.
+fn append_block(content: &str, offset: usize, block: &str) -> String {
+    let before = content[..offset].trim_end();
+    let after = &content[offset..];
+    let mut edited = before.to_string();
+    if !edited.is_empty() {
+        edited.push_str("\n\n");
+    }
+    edited.push_str(block);
+    edited.push('\n');
+    if !after.trim().is_empty() {
+        edited.push('\n');
+        edited.push_str(after.trim_start_matches('\n'));
+    }
+    edited
+}
+
+fn escape_link_text(text: &str) -> String {
+    text.replace('[', "\\[").replace(']', "\\]")
+}
+
+/// A link attribute value, quoted when it isn't a single word.
+fn attribute_value(value: &TomlValue) -> Result<String, BuildonomyError> {
+    match value {
+        TomlValue::String(text) => {
+            if !text.is_empty()
+                && !text
+                    .chars()
+                    .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '{' | '}'))
+            {
+                Ok(text.clone())
+            } else if !text.contains('"') {
+                Ok(format!("\"{text}\""))
+            } else if !text.contains('\'') {
+                Ok(format!("'{text}'"))
+            } else {
+                Err(BuildonomyError::Command(format!(
+                    "Link attributes cannot contain both quote characters: {text}"
+                )))
+            }
+        }
+        TomlValue::Integer(_) | TomlValue::Float(_) | TomlValue::Boolean(_) => {
+            Ok(value.to_string())
+        }
+        _ => Err(BuildonomyError::Command(format!(
+            "Link attributes must be strings, numbers or booleans, not {value}"
+        ))),
+    }
+}
+
+struct MdHeading {
+    level: usize,
+    /// The whole heading, without its line ending.
+    range: Range<usize>,
+    /// The heading text.
+    text: Range<usize>,
+    anchor: String,
+    explicit_id: bool,
+}
+
+struct MdLink {
+    /// The link, including any attribute block written after it.
+    range: Range<usize>,
+    /// The end of the link itself.
+    end: usize,
+    /// The link text.
+    text: Option<Range<usize>>,
+    dest: String,
+    title: String,
+}
+
+impl MdLink {
+    /// The destination of an inline link in the source. Reference links have none.
+    fn dest_range(&self, content: &str) -> Option<Range<usize>> {
+        let start = self.text.as_ref().map_or(self.range.start, |text| text.end);
+        let tail = &content[start..self.end];
+        let open = tail.find("](")? + 2;
+        let offset = tail[open..].find(&self.dest)?;
+        let dest_start = start + open + offset;
+        Some(dest_start..dest_start + self.dest.len())
+    }
+}
+
+/// A heading being parsed: its level, range, explicit id and the range of its text so far.
+type OpenHeading = (usize, Range<usize>, Option<String>, Option<Range<usize>>);
+
+/// The frontmatter, headings and links of a Markdown document, as byte ranges of its source.
+struct MdLayout {
+    /// The frontmatter text, between its delimiters.
+    frontmatter: Option<Range<usize>>,
+    headings: Vec<MdHeading>,
+    links: Vec<MdLink>,
+    images: Vec<MdLink>,
+}
+
+impl MdLayout {
+    fn new(content: &str) -> Self {
+        let mut layout = MdLayout {
+            frontmatter: None,
+            headings: Vec::new(),
+            links: Vec::new(),
+            images: Vec::new(),
+        };
+        let mut heading: Option<OpenHeading> = None;
+        let mut links: Vec<(String, String, Option<Range<usize>>)> = Vec::new();
+        let mut images: Vec<(String, String, Option<Range<usize>>)> = Vec::new();
+        let mut in_frontmatter = None;
+        for (event, range) in MdParser::new_ext(content, buildonomy_md_options()).into_offset_iter()
+        {
+            let widen = |inner: &mut Option<Range<usize>>| {
+                *inner = Some(match inner.take() {
+                    Some(inner) => inner.start.min(range.start)..inner.end.max(range.end),
+                    None => range.clone(),
+                });
+            };
+            match event {
+                MdEvent::Start(MdTag::MetadataBlock(_)) if layout.frontmatter.is_none() => {
+                    // An empty block has no text: its text would start after the opening line.
+                    let start = content[range.clone()]
+                        .find('\n')
+                        .map_or(range.end, |idx| range.start + idx + 1);
+                    in_frontmatter = Some(start..start);
+                }
+                MdEvent::End(MdTagEnd::MetadataBlock(_)) => {
+                    layout.frontmatter = in_frontmatter.take();
+                }
+                MdEvent::Text(_) if in_frontmatter.is_some() => {
+                    in_frontmatter = Some(range.clone());
+                }
+                MdEvent::Start(MdTag::Heading { level, id, .. }) => {
+                    heading = Some((
+                        level as usize,
+                        range.clone(),
+                        id.map(|id| id.to_string()),
+                        None,
+                    ));
+                }
+                MdEvent::End(MdTagEnd::Heading(_)) => {
+                    if let Some((level, range, id, text)) = heading.take() {
+                        let text = text
+                            .map(|text| {
+                                let trimmed = content[text.clone()].trim_end().len();
+                                text.start..text.start + trimmed
+                            })
+                            .unwrap_or(range.end..range.end);
+                        let anchor = to_anchor(id.as_deref().unwrap_or(&content[text.clone()]));
+                        let end = range.start + content[range.clone()].trim_end().len();
+                        layout.headings.push(MdHeading {
+                            level,
+                            range: range.start..end,
+                            text,
+                            anchor,
+                            explicit_id: id.is_some(),
+                        });
+                    }
+                }
+                MdEvent::Start(MdTag::Link {
+                    dest_url, title, ..
+                }) => {
+                    links.push((dest_url.to_string(), title.to_string(), None));
+                }
+                MdEvent::End(MdTagEnd::Link) => {
+                    if let Some((dest, title, text)) = links.pop() {
+                        let attributes =
+                            parse_link_attributes(&content[range.end..]).map_or(0, |(_, len)| len);
+                        layout.links.push(MdLink {
+                            range: range.start..range.end + attributes,
+                            end: range.end,
+                            text,
+                            dest,
+                            title,
+                        });
+                    }
+                }
+                MdEvent::Start(MdTag::Image {
+                    dest_url, title, ..
+                }) => {
+                    images.push((dest_url.to_string(), title.to_string(), None));
+                }
+                MdEvent::End(MdTagEnd::Image) => {
+                    if let Some((dest, title, text)) = images.pop() {
+                        layout.images.push(MdLink {
+                            range: range.clone(),
+                            end: range.end,
+                            text,
+                            dest,
+                            title,
+                        });
+                    }
+                }
+                _ => {
+                    if let Some((_, _, _, text)) = heading.as_mut() {
+                        widen(text);
+                    }
+                    if let Some((_, _, text)) = links.last_mut() {
+                        widen(text);
+                    }
+                    if let Some((_, _, text)) = images.last_mut() {
+                        widen(text);
+                    }
+                }
+            }
+        }
+        layout.links.sort_by_key(|link| link.range.start);
+        layout.images.sort_by_key(|image| image.range.start);
+        layout
+    }
+
+    /// An H1 heading before any other heading titles the document rather than starting a
+    /// section.
+    fn document_heading(&self) -> Option<&MdHeading> {
+        self.headings.first().filter(|heading| heading.level == 1)
+    }
+
+    fn sections(&self) -> impl Iterator<Item = (usize, &MdHeading)> {
+        let skip = usize::from(self.document_heading().is_some());
+        self.headings.iter().enumerate().skip(skip)
+    }
+
+    fn section(&self, anchor: &str) -> Option<usize> {
+        self.sections()
+            .find(|(_, heading)| heading.anchor == anchor)
+            .map(|(idx, _)| idx)
+    }
+
+    /// The end of the section at `idx`, including its subsections.
+    fn section_end(&self, idx: usize, content: &str) -> usize {
+        let level = self.headings[idx].level;
+        self.headings[idx + 1..]
+            .iter()
+            .find(|heading| heading.level <= level)
+            .map_or(content.len(), |heading| heading.range.start)
+    }
+
+    /// The text belonging to a document (`anchor` is `None`) or one of its sections, up to its
+    /// first subsection.
+    fn own_range(
+        &self,
+        anchor: Option<&str>,
+        content: &str,
+        path: &str,
+    ) -> Result<(usize, usize), BuildonomyError> {
+        let next_start = |idx: usize| {
+            self.headings
+                .get(idx)
+                .map_or(content.len(), |heading| heading.range.start)
+        };
+        match anchor {
+            None => {
+                let first_section = self.sections().next().map(|(idx, _)| idx);
+                Ok((0, first_section.map_or(content.len(), next_start)))
+            }
+            Some(anchor) => {
+                let idx = self
+                    .section(anchor)
+                    .ok_or_else(|| section_not_found(path, anchor))?;
+                Ok((self.headings[idx].range.end, next_start(idx + 1)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# Claim\n\n---\ntitle = \"Claim\"\n\n[sections.\"id://background\"]\nid = \"background\"\n---\n\nSee [the evidence](evidence.md){rel=supports} and [plain](evidence.md).\n\n## Background { #background }\n\nSome background.\n\n### Detail\n\nMore.\n\n## Next\n\nLast.\n";
+
+    #[test]
+    fn test_layout() {
+        let layout = MdLayout::new(DOC);
+        let frontmatter = &DOC[layout.frontmatter.clone().unwrap()];
+        assert!(frontmatter.starts_with("title = \"Claim\"\n"));
+        assert!(frontmatter.ends_with("id = \"background\"\n"));
+
+        assert_eq!(
+            layout.document_heading().map(|h| &DOC[h.text.clone()]),
+            Some("Claim")
+        );
+        let background = layout.section("background").unwrap();
+        assert_eq!(&DOC[layout.headings[background].text.clone()], "Background");
+        assert!(layout.headings[background].explicit_id);
+        assert!(DOC[layout.section_end(background, DOC)..].starts_with("## Next"));
+        let (_, own_end) = layout
+            .own_range(Some("background"), DOC, "claim.md")
+            .unwrap();
+        assert!(DOC[own_end..].starts_with("### Detail"));
+        let (_, doc_end) = layout.own_range(None, DOC, "claim.md").unwrap();
+        assert!(DOC[doc_end..].starts_with("## Background"));
+
+        assert_eq!(layout.links.len(), 2);
+        assert_eq!(
+            &DOC[layout.links[0].range.clone()],
+            "[the evidence](evidence.md){rel=supports}"
+        );
+        assert_eq!(&DOC[layout.links[0].text.clone().unwrap()], "the evidence");
+        assert_eq!(layout.links[1].dest, "evidence.md");
+    }
+
+    #[test]
+    fn test_set_frontmatter_fields() {
+        let mut set = Table::new();
+        set.insert("status".to_string(), TomlValue::String("draft".to_string()));
+        let edited = set_frontmatter_fields(DOC, Some("background"), &set, &[]).unwrap();
+        assert!(
+            edited.contains("id = \"background\"\nstatus = \"draft\"\n---"),
+            "{edited}"
+        );
+
+        let edited = set_frontmatter_fields(&edited, None, &set, &[]).unwrap();
+        assert!(
+            edited.starts_with("# Claim\n\n---\ntitle = \"Claim\"\nstatus = \"draft\"\n"),
+            "{edited}"
+        );
+        let edited =
+            set_frontmatter_fields(&edited, None, &Table::new(), &["status".to_string()]).unwrap();
+        assert!(
+            edited.starts_with("# Claim\n\n---\ntitle = \"Claim\"\n\n[sections"),
+            "{edited}"
+        );
+
+        let edited = set_frontmatter_fields("Text.\n", None, &set, &[]).unwrap();
+        assert_eq!(edited, "---\nstatus = \"draft\"\n---\n\nText.\n");
+    }
+
+    #[test]
+    fn test_append_block() {
+        assert_eq!(
+            append_block("# A\n\nText.\n", 11, "[B](b.md)"),
+            "# A\n\nText.\n\n[B](b.md)\n"
+        );
+        assert_eq!(
+            append_block("# A\n\nText.\n\n## B\n", 12, "[B](b.md)"),
+            "# A\n\nText.\n\n[B](b.md)\n\n## B\n"
+        );
+    }
+
+    #[test]
+    fn test_attribute_value() {
+        assert_eq!(attribute_value(&TomlValue::Float(0.7)).unwrap(), "0.7");
+        assert_eq!(
+            attribute_value(&TomlValue::String("a b".to_string())).unwrap(),
+            "\"a b\""
+        );
+        assert!(attribute_value(&TomlValue::Array(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let content = "See [A](a.md#intro \"bref://0123456789ab\"){rel=supports}, [C](../c.md), \
+                       [web](https://example.com) and ![logo](img/logo.png).\n";
+        // An inbound link: only its destination changes.
+        let (edited, rewrites) = rewrite_links(
+            content,
+            "docs/b.md",
+            "docs/b.md",
+            "docs/a.md",
+            "guides/a.md",
+        );
+        assert_eq!(
+            rewrites,
+            vec![("a.md#intro".to_string(), "../guides/a.md#intro".to_string())]
+        );
+        assert!(edited.starts_with(
+            "See [A](../guides/a.md#intro \"bref://0123456789ab\"){rel=supports}, [C](../c.md),"
+        ));
+
+        // The moved file's own relative links are rebased.
+        let (edited, rewrites) = rewrite_links(
+            content,
+            "docs/b.md",
+            "guides/deep/b.md",
+            "docs/b.md",
+            "guides/deep/b.md",
+        );
+        assert_eq!(rewrites.len(), 3);
+        assert!(edited.contains("[A](../../docs/a.md#intro"), "{edited}");
+        assert!(edited.contains("[C](../../c.md)"), "{edited}");
+        assert!(edited.contains("[web](https://example.com)"), "{edited}");
+        assert!(
+            edited.contains("![logo](../../docs/img/logo.png)"),
+            "{edited}"
+        );
+
+        // Links between files that move together keep resolving.
+        let (edited, rewrites) =
+            rewrite_links(content, "docs/b.md", "archive/b.md", "docs", "archive");
+        assert!(rewrites.is_empty());
+        assert_eq!(edited, content);
+    }
+
+    #[test]
+    fn test_relocate() {
+        assert_eq!(
+            relocate("docs/a.md", "docs", "guides").as_deref(),
+            Some("guides/a.md")
+        );
+        assert_eq!(
+            relocate("docs", "docs", "guides").as_deref(),
+            Some("guides")
+        );
+        assert_eq!(relocate("docs2/a.md", "docs", "guides"), None);
+        assert_eq!(repo_path("./docs/a/../b.md"), "docs/b.md");
+        assert_eq!(repo_path("docs/"), "docs");
+    }
+}
@@ -0,0 +1,550 @@
+//! External-process codecs.
+//!
+//! [`ExternalCodec`] adapts an executable to [`DocCodec`], so formats like AsciiDoc, reStructuredText
+//! or LaTeX can be parsed by tools written in any language without recompiling noet:
+//!
+//! - Each codec instance launches the configured command on first use and talks to it over stdio.
+//!   Every message is a single line of JSON: noet writes a [`Request`] to the process's stdin and
+//!   reads one [`Response`] line back from its stdout. The process keeps whatever per-document
+//!   state it needs between messages and should exit when stdin closes.
+//! - Requests are tagged by `method` (`proto`, `parse`, `inject_context`, `finalize`,
+//!   `generate_source`, `generate_html`) with arguments in `params`. Responses fill in only the
+//!   fields that method returns; a non-empty `error` fails the call.
+//! - Nodes cross the boundary as [`WireNode`]s: the node's metadata is a JSON object, and relation
+//!   keys use [`NodeKey`](crate::nodekey::NodeKey) syntax (`id:...`, `path:...`, `bid:...`) or plain hrefs, which are
+//!   resolved against the document path the same way Markdown links are.
+//!
+//! Context injection runs [`IRNode::update_from_context`] on noet's side; the process is sent the
+//! resolved [`BeliefNode`] so it can write BIDs and titles back into its own source on
+//! `generate_source`.
+//!
+//! Codecs are declared in a TOML file and registered with [`CodecMap::insert_codec`] through
+//! [`register_external_codecs`]:
+//!
+//! ```toml
+//! [[codec]]
+//! extension = "adoc"
+//! command = "noet-asciidoc"
+//! args = ["--stdio"]
+//! ```
+//!
+//! Relative `command` paths containing a separator are resolved against the config file's
+//! directory; bare names are looked up on `PATH`.
+
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    result::Result,
+};
+use toml_edit::DocumentMut;
+
+use crate::{
+    beliefbase::BeliefContext,
+    codec::{
+        belief_ir::{json_to_toml_string, IRNode, IntermediateRelation},
+        diagnostic::ParseDiagnostic,
+        CodecMap, DocCodec, CODECS,
+    },
+    error::BuildonomyError,
+    nodekey::href_to_nodekey,
+    paths::{os_path_to_string, AnchorPath},
+    properties::{BeliefKind, BeliefNode, Weight, WeightKind},
+};
+
+/// External codecs registered so far. [`ExternalCodec`] instances pick their command from here by
+/// matching the document path, since [`CodecFactory`](super::CodecFactory) takes no arguments.
+static EXTERNAL_CODECS: Lazy<RwLock<Vec<ExternalCodecConfig>>> =
+    Lazy::new(|| RwLock::new(Vec::new()));
+
+/// One `[[codec]]` entry of an external codec config file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalCodecConfig {
+    /// File stem to match, as for [`CodecMap::insert_codec`].
+    #[serde(default)]
+    pub stem: Option<String>,
+    /// File extension to match, without the leading `.`.
+    #[serde(default)]
+    pub extension: Option<String>,
+    /// Executable to launch.
+    pub command: PathBuf,
+    /// Arguments passed to `command`.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExternalCodecFile {
+    #[serde(default)]
+    codec: Vec<ExternalCodecConfig>,
+}
+
+impl ExternalCodecConfig {
+    /// Read the `[[codec]]` entries of a TOML config file.
+    pub fn from_file(path: &Path) -> Result<Vec<ExternalCodecConfig>, BuildonomyError> {
+        let file: ExternalCodecFile = toml::from_str(&fs::read_to_string(path)?)?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        file.codec
+            .into_iter()
+            .map(|mut config| {
+                if config.stem.is_none() && config.extension.is_none() {
+                    return Err(BuildonomyError::Codec(format!(
+                        "External codec \"{}\" in {path:?} needs a stem or an extension",
+                        config.command.display()
+                    )));
+                }
+                if config.command.is_relative() && config.command.components().count() > 1 {
+                    config.command = base.join(&config.command);
+                }
+                Ok(config)
+            })
+            .collect()
+    }
+
+    /// Register this codec with `codecs`, replacing any codec registered for the same pattern.
+    pub fn register(self, codecs: &CodecMap) {
+        let (stem, extension) = (self.stem.clone(), self.extension.clone());
+        {
+            let mut registry = EXTERNAL_CODECS.write();
+            registry.retain(|config| config.stem != stem || config.extension != extension);
+            registry.push(self);
+        }
+        codecs.insert_codec(stem, extension, || Box::new(ExternalCodec::new()));
+    }
+
+    /// The registered config for `path`, preferring a stem and extension match, then an
+    /// extension match, then a stem match — the order [`CodecMap::get`] uses.
+    fn for_path(path: &str) -> Option<ExternalCodecConfig> {
+        let ap = AnchorPath::from(path);
+        let (filestem, ext) = ap.path_parts();
+        let registry = EXTERNAL_CODECS.read();
+        let stem_matches = |config: &&ExternalCodecConfig| config.stem.as_deref() == Some(filestem);
+        let ext_matches = |config: &&ExternalCodecConfig| config.extension.as_deref() == Some(ext);
+        registry
+            .iter()
+            .find(|config| stem_matches(config) && ext_matches(config))
+            .or_else(|| {
+                registry
+                    .iter()
+                    .find(|config| config.stem.is_none() && ext_matches(config))
+            })
+            .or_else(|| {
+                registry
+                    .iter()
+                    .find(|config| stem_matches(config) && config.extension.is_none())
+            })
+            .cloned()
+    }
+}
+
+/// Register every codec declared in the config file at `path` with [`CODECS`].
+///
+/// Returns the registered configs.
+pub fn register_external_codecs(path: &Path) -> Result<Vec<ExternalCodecConfig>, BuildonomyError> {
+    let configs = ExternalCodecConfig::from_file(path)?;
+    for config in configs.iter() {
+        config.clone().register(&CODECS);
+    }
+    Ok(configs)
+}
+
+/// A relation as exchanged with an external codec.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireRelation {
+    /// [`NodeKey`](crate::nodekey::NodeKey) syntax or an href relative to the document.
+    pub key: String,
+    #[serde(default = "epistemic")]
+    pub kind: WeightKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<Weight>,
+    /// Byte offset of the relation in the document source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<usize>,
+}
+
+fn epistemic() -> WeightKind {
+    WeightKind::Epistemic
+}
+
+/// A node as exchanged with an external codec.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WireNode {
+    #[serde(default)]
+    pub path: String,
+    /// 2 for the document node, 3 and up for nested sections.
+    #[serde(default)]
+    pub heading: usize,
+    #[serde(default)]
+    pub kind: Vec<BeliefKind>,
+    /// Node metadata (`title`, `id`, `bid`, `text`, schema fields, ...).
+    #[serde(default)]
+    pub document: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub upstream: Vec<WireRelation>,
+    #[serde(default)]
+    pub downstream: Vec<WireRelation>,
+}
+
+impl WireNode {
+    fn from_ir(node: &IRNode) -> Result<WireNode, BuildonomyError> {
+        let table: toml::Table = toml::from_str(&node.document.to_string())?;
+        let document = match serde_json::to_value(table)? {
+            serde_json::Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        let relations = |relations: &[IntermediateRelation]| {
+            relations
+                .iter()
+                .map(|relation| WireRelation {
+                    key: relation.key.to_string(),
+                    kind: relation.kind,
+                    weight: relation.weight.clone(),
+                    location: relation.location,
+                })
+                .collect()
+        };
+        Ok(WireNode {
+            path: node.path.clone(),
+            heading: node.heading,
+            kind: node.kind.0.iter().collect(),
+            document,
+            upstream: relations(&node.upstream),
+            downstream: relations(&node.downstream),
+        })
+    }
+
+    /// Convert to an [`IRNode`], resolving relation hrefs against `doc_path`.
+    fn into_ir(self, doc_path: &str) -> Result<IRNode, BuildonomyError> {
+        let document = json_to_toml_string(&serde_json::Value::Object(self.document))?
+            .parse::<DocumentMut>()
+            .map_err(|e| BuildonomyError::Codec(format!("Invalid external node document: {e}")))?;
+        let relations = |relations: Vec<WireRelation>| {
+            relations
+                .into_iter()
+                .map(|relation| {
+                    let key = href_to_nodekey(&relation.key).resolve_against(doc_path);
+                    let ir = IntermediateRelation::new(key, relation.kind, relation.weight);
+                    match relation.location {
+                        Some(offset) => ir.with_location(offset),
+                        None => ir,
+                    }
+                })
+                .collect()
+        };
+        let mut node = IRNode {
+            document,
+            upstream: relations(self.upstream),
+            downstream: relations(self.downstream),
+            path: self.path,
+            heading: self.heading,
+            ..Default::default()
+        };
+        for kind in self.kind {
+            node.kind.insert(kind);
+        }
+        Ok(node)
+    }
+}
+
+/// A diagnostic reported by an external codec.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireDiagnostic {
+    /// `error`, `warning` or `info`.
+    pub level: String,
+    pub message: String,
+    /// 1-based source line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// 1-based source column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl From<WireDiagnostic> for ParseDiagnostic {
+    fn from(wire: WireDiagnostic) -> Self {
+        let diagnostic = match wire.level.as_str() {
+            "error" => ParseDiagnostic::parse_error(wire.message, 0),
+            "info" => ParseDiagnostic::info(wire.message),
+            _ => ParseDiagnostic::warning(wire.message),
+        };
+        match (wire.line, wire.column) {
+            (Some(line), column) => diagnostic.with_location(line, column.unwrap_or(1)),
+            _ => diagnostic,
+        }
+    }
+}
+
+/// A message sent to an external codec, one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Request {
+    /// Read document metadata from `path` (absolute). Answered with `node`, or no node when the
+    /// file isn't a document.
+    Proto { path: String },
+    /// Parse `content`, seeded by `node`. Answered with `nodes`: the document node first, then
+    /// its sections.
+    Parse { content: String, node: WireNode },
+    /// The resolved node for `nodes[index]` of the `parse` response.
+    InjectContext { index: usize, node: BeliefNode },
+    /// All nodes have been injected.
+    Finalize,
+    /// Answered with the updated `source`, or no source when the file is unchanged.
+    GenerateSource,
+    /// Answered with HTML `fragments` as `[filename, body]` pairs.
+    GenerateHtml,
+}
+
+/// An external codec's reply to a [`Request`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<WireNode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<WireNode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<WireDiagnostic>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fragments: Vec<(String, String)>,
+}
+
+/// A running external codec process.
+#[derive(Debug)]
+struct Session {
+    command: String,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Session {
+    fn spawn(config: &ExternalCodecConfig) -> Result<Session, BuildonomyError> {
+        let command = config.command.display().to_string();
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| {
+                BuildonomyError::Codec(format!("Failed to launch external codec {command}: {e}"))
+            })?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take().map(BufReader::new).ok_or_else(|| {
+            BuildonomyError::Codec(format!("External codec {command} has no stdout"))
+        })?;
+        Ok(Session {
+            command,
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    fn call(&mut self, request: &Request) -> Result<Response, BuildonomyError> {
+        let stdin = self.stdin.as_mut().ok_or_else(|| {
+            BuildonomyError::Codec(format!("External codec {} stdin is closed", self.command))
+        })?;
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        stdin.write_all(line.as_bytes())?;
+        stdin.flush()?;
+
+        let mut reply = String::new();
+        if self.stdout.read_line(&mut reply)? == 0 {
+            return Err(BuildonomyError::Codec(format!(
+                "External codec {} exited before answering",
+                self.command
+            )));
+        }
+        let response: Response = serde_json::from_str(&reply).map_err(|e| {
+            BuildonomyError::Codec(format!(
+                "External codec {} sent an invalid response: {e}",
+                self.command
+            ))
+        })?;
+        match response.error {
+            Some(error) if !error.is_empty() => Err(BuildonomyError::Codec(format!(
+                "[{}] {error}",
+                self.command
+            ))),
+            _ => Ok(response),
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // Closing stdin asks the process to exit; don't wait on one that ignores it.
+        self.stdin.take();
+        if !matches!(self.child.try_wait(), Ok(Some(_))) {
+            self.child.kill().ok();
+            self.child.wait().ok();
+        }
+    }
+}
+
+/// Codec backed by an external process. See the [module docs](self).
+#[derive(Debug, Default)]
+pub struct ExternalCodec {
+    /// Launched on the first request, using the config registered for that request's path.
+    session: Mutex<Option<Session>>,
+    /// The nodes returned by `parse`, in response order.
+    nodes: Vec<IRNode>,
+}
+
+impl ExternalCodec {
+    pub fn new() -> Self {
+        ExternalCodec::default()
+    }
+
+    fn call(&self, path: &str, request: &Request) -> Result<Response, BuildonomyError> {
+        let mut session = self.session.lock();
+        if session.is_none() {
+            let config = ExternalCodecConfig::for_path(path).ok_or_else(|| {
+                BuildonomyError::Codec(format!("No external codec registered for \"{path}\""))
+            })?;
+            *session = Some(Session::spawn(&config)?);
+        }
+        session
+            .as_mut()
+            .expect("session was just spawned")
+            .call(request)
+    }
+
+    fn doc_path(&self) -> String {
+        self.nodes
+            .first()
+            .map(|node| node.path.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl DocCodec for ExternalCodec {
+    fn proto(&self, path: &Path) -> Result<Option<IRNode>, BuildonomyError> {
+        if path.is_relative() {
+            return Err(BuildonomyError::Codec(format!(
+                "[ExternalCodec::proto] supplied path must be absolute. Received \"{path:?}\""
+            )));
+        };
+        let doc_path = os_path_to_string(path);
+        let response = self.call(
+            &doc_path,
+            &Request::Proto {
+                path: doc_path.clone(),
+            },
+        )?;
+        let Some(node) = response.node else {
+            return Ok(None);
+        };
+        let mut proto = node.into_ir(&doc_path)?;
+        proto.path = doc_path;
+        // Document heading
+        proto.heading = 2;
+        proto.kind.insert(BeliefKind::Document);
+        Ok(Some(proto))
+    }
+
+    fn parse(
+        &mut self,
+        content: &str,
+        mut current: IRNode,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<(), BuildonomyError> {
+        let doc_path = current.path.clone();
+        let response = self.call(
+            &doc_path,
+            &Request::Parse {
+                content: content.to_string(),
+                node: WireNode::from_ir(&current)?,
+            },
+        )?;
+        diagnostics.extend(response.diagnostics.into_iter().map(ParseDiagnostic::from));
+
+        let mut nodes = Vec::with_capacity(response.nodes.len().max(1));
+        for wire in response.nodes {
+            let mut node = wire.into_ir(&doc_path)?;
+            if node.path.is_empty() {
+                node.path = doc_path.clone();
+            }
+            node.traverse_schema()?;
+            nodes.push(node);
+        }
+        // Merge the parsed document over the proto, keeping the proto's identity.
+        let mut parsed_doc = if nodes.is_empty() {
+            IRNode::default()
+        } else {
+            nodes.remove(0)
+        };
+        let mut links = std::mem::take(&mut parsed_doc.upstream);
+        parsed_doc.path = String::new();
+        current.merge(&mut parsed_doc);
+        current.upstream.append(&mut links);
+        current.heading = 2;
+        current.kind.insert(BeliefKind::Document);
+        nodes.insert(0, current);
+        self.nodes = nodes;
+        Ok(())
+    }
+
+    fn nodes(&self) -> Vec<IRNode> {
+        self.nodes.clone()
+    }
+
+    fn inject_context(
+        &mut self,
+        node: &IRNode,
+        ctx: &BeliefContext<'_>,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Option<BeliefNode>, BuildonomyError> {
+        let index = self
+            .nodes
+            .iter()
+            .position(|ir_node| ir_node == node)
+            .ok_or(BuildonomyError::Codec(
+                "No proto node stored in codec matching node argument".to_string(),
+            ))?;
+        let maybe_updated = self.nodes[index].update_from_context(ctx)?;
+        let response = self.call(
+            &self.doc_path(),
+            &Request::InjectContext {
+                index,
+                node: ctx.node.clone(),
+            },
+        )?;
+        diagnostics.extend(response.diagnostics.into_iter().map(ParseDiagnostic::from));
+        Ok(maybe_updated)
+    }
+
+    fn finalize(
+        &mut self,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Vec<(IRNode, BeliefNode)>, BuildonomyError> {
+        let response = self.call(&self.doc_path(), &Request::Finalize)?;
+        diagnostics.extend(response.diagnostics.into_iter().map(ParseDiagnostic::from));
+        Ok(Vec::new())
+    }
+
+    fn generate_source(&self) -> Option<String> {
+        match self.call(&self.doc_path(), &Request::GenerateSource) {
+            Ok(response) => response.source,
+            Err(e) => {
+                tracing::warn!("[ExternalCodec::generate_source] {e}");
+                None
+            }
+        }
+    }
+
+    fn generate_html(&self) -> Result<Vec<(String, String)>, BuildonomyError> {
+        Ok(self
+            .call(&self.doc_path(), &Request::GenerateHtml)?
+            .fragments)
+    }
+}
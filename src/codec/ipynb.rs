@@ -0,0 +1,679 @@
+//! Jupyter notebook (`.ipynb`) codec.
+//!
+//! [`IpynbCodec`] lets analysis notebooks live in a network next to the documents that cite them:
+//!
+//! - The notebook is the Document node. Its top-level `metadata` object (`kernelspec`,
+//!   `language_info`, ...) becomes the node's payload, and noet's own keys (`bid`, `sections`) are
+//!   written back into it rather than into a frontmatter block.
+//! - Markdown cells are parsed by [`MdCodec`] as one Markdown document, so headings become section
+//!   nodes and links become relations exactly as they do in a `.md` file. A section runs across cell
+//!   boundaries until the next heading.
+//! - Code and raw cells are left alone. [`generate_html`](DocCodec::generate_html) renders code
+//!   cells in place along with their text outputs.
+//!
+//! Each cell is introduced by an `<!-- ipynb-cell N -->` comment in the Markdown handed to
+//! [`MdCodec`], which is how rewritten Markdown (heading ids, link titles) is split back into its
+//! cells on write-back. Diagnostic locations refer to that Markdown text. Notebooks are only
+//! re-serialized when something actually has to be written.
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event as MdEvent, Tag as MdTag, TagEnd};
+use std::{collections::BTreeMap, fs, path::Path, result::Result};
+use toml_edit::{value, DocumentMut, Item};
+
+use crate::{
+    beliefbase::BeliefContext,
+    codec::{
+        belief_ir::IRNode,
+        data::{title_from_path, DataFormat, DataSource, JsonFormat},
+        diagnostic::ParseDiagnostic,
+        md::MdCodec,
+        DocCodec,
+    },
+    error::BuildonomyError,
+    paths::os_path_to_string,
+    properties::{BeliefKind, BeliefNode},
+};
+
+/// Document fields owned by noet that are written back into the notebook `metadata` object.
+const NOTEBOOK_METADATA_KEYS: &[&str] = &["bid", "sections"];
+
+/// Fallback code cell language when the notebook names no kernel language.
+const DEFAULT_LANGUAGE: &str = "python";
+
+fn cell_marker(index: usize) -> String {
+    format!("<!-- ipynb-cell {index} -->")
+}
+
+fn parse_cell_marker(line: &str) -> Option<usize> {
+    line.trim()
+        .strip_prefix("<!-- ipynb-cell ")?
+        .strip_suffix(" -->")?
+        .parse()
+        .ok()
+}
+
+/// Notebook strings may be stored whole or as a list of lines; join the latter.
+fn multiline_text(text: Option<&serde_yaml::Value>) -> String {
+    match text {
+        Some(serde_yaml::Value::String(text)) => text.clone(),
+        Some(serde_yaml::Value::Sequence(lines)) => lines
+            .iter()
+            .filter_map(|line| line.as_str())
+            .collect::<String>(),
+        _ => String::new(),
+    }
+}
+
+/// Replace a cell's source, keeping the string vs. list-of-lines shape it was stored in.
+fn set_cell_source(cell: &mut serde_yaml::Value, text: &str) {
+    let Some(source) = cell.get_mut("source") else {
+        return;
+    };
+    *source = if source.is_sequence() {
+        serde_yaml::Value::Sequence(text.split_inclusive('\n').map(|line| line.into()).collect())
+    } else {
+        text.into()
+    };
+}
+
+fn cells(notebook: &serde_yaml::Value) -> &[serde_yaml::Value] {
+    notebook
+        .get("cells")
+        .and_then(|cells| cells.as_sequence())
+        .map(|cells| cells.as_slice())
+        .unwrap_or_default()
+}
+
+fn cell_type(cell: &serde_yaml::Value) -> &str {
+    cell.get("cell_type")
+        .and_then(|cell_type| cell_type.as_str())
+        .unwrap_or_default()
+}
+
+fn parse_notebook(content: &str) -> Result<serde_yaml::Value, BuildonomyError> {
+    let DataSource::Tree(notebook) = JsonFormat::parse_source(content)? else {
+        return Err(BuildonomyError::Codec(
+            "Notebook did not parse into a JSON tree".to_string(),
+        ));
+    };
+    if notebook
+        .get("cells")
+        .and_then(|cells| cells.as_sequence())
+        .is_none()
+    {
+        return Err(BuildonomyError::Codec(
+            "Notebook has no 'cells' list. Only nbformat 4 notebooks are supported".to_string(),
+        ));
+    }
+    Ok(notebook)
+}
+
+/// The notebook `metadata` object as an [`IRNode`].
+fn metadata_ir(notebook: &serde_yaml::Value) -> Result<IRNode, BuildonomyError> {
+    let metadata = notebook
+        .get("metadata")
+        .filter(|metadata| metadata.is_mapping())
+        .cloned()
+        .unwrap_or_else(|| serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
+    DataSource::Tree(metadata).to_ir()
+}
+
+/// Markdown view of the notebook handed to [`MdCodec`]: every cell's marker, followed by the
+/// cell's source for Markdown cells.
+fn notebook_to_markdown(notebook: &serde_yaml::Value) -> String {
+    let mut markdown = String::new();
+    for (index, cell) in cells(notebook).iter().enumerate() {
+        markdown.push_str(&cell_marker(index));
+        markdown.push_str("\n\n");
+        if cell_type(cell) == "markdown" {
+            let source = multiline_text(cell.get("source"));
+            let source = source.trim_matches('\n');
+            if !source.is_empty() {
+                markdown.push_str(source);
+                markdown.push_str("\n\n");
+            }
+        }
+    }
+    markdown
+}
+
+/// Split Markdown produced by [`notebook_to_markdown`] back into per-cell text. Anything before
+/// the first marker (e.g. a frontmatter block [`MdCodec`] wants to insert) is dropped.
+fn split_markdown(markdown: &str) -> BTreeMap<usize, String> {
+    let mut cells = BTreeMap::new();
+    let mut current: Option<(usize, String)> = None;
+    for line in markdown.split_inclusive('\n') {
+        if let Some(index) = parse_cell_marker(line) {
+            if let Some((index, text)) = current.take() {
+                cells.insert(index, text.trim_matches('\n').to_string());
+            }
+            current = Some((index, String::new()));
+        } else if let Some((_, text)) = current.as_mut() {
+            text.push_str(line);
+        }
+    }
+    if let Some((index, text)) = current {
+        cells.insert(index, text.trim_matches('\n').to_string());
+    }
+    cells
+}
+
+fn strip_cell_markers(text: &str) -> String {
+    text.split_inclusive('\n')
+        .filter(|line| parse_cell_marker(line).is_none())
+        .collect()
+}
+
+/// Convert a document field into the equivalent notebook metadata value.
+fn item_to_json(item: &Item) -> Option<serde_yaml::Value> {
+    let mut doc = DocumentMut::new();
+    doc.insert("value", item.clone());
+    let table = toml::from_str::<toml::Table>(&doc.to_string()).ok()?;
+    serde_yaml::to_value(table.get("value")?).ok()
+}
+
+/// Set (or remove) `metadata[key]`. Returns true if the notebook changed.
+fn sync_metadata(notebook: &mut serde_yaml::Value, key: &str, item: Option<&Item>) -> bool {
+    let Some(root) = notebook.as_mapping_mut() else {
+        return false;
+    };
+    let metadata = root
+        .entry("metadata".into())
+        .or_insert_with(|| serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
+    let Some(metadata) = metadata.as_mapping_mut() else {
+        return false;
+    };
+    match item.and_then(item_to_json) {
+        Some(new_value) if metadata.get(key) != Some(&new_value) => {
+            // Mapping::insert keeps an existing key's position and appends new keys.
+            metadata.insert(key.into(), new_value);
+            true
+        }
+        Some(_) => false,
+        None => metadata.remove(key).is_some(),
+    }
+}
+
+fn notebook_language(notebook: &serde_yaml::Value) -> String {
+    let metadata = notebook.get("metadata");
+    metadata
+        .and_then(|metadata| metadata.get("language_info"))
+        .and_then(|info| info.get("name"))
+        .or_else(|| {
+            metadata
+                .and_then(|metadata| metadata.get("kernelspec"))
+                .and_then(|kernelspec| kernelspec.get("language"))
+        })
+        .and_then(|language| language.as_str())
+        .unwrap_or(DEFAULT_LANGUAGE)
+        .to_string()
+}
+
+/// The plain-text rendering of a code cell output, if it has one.
+fn output_text(output: &serde_yaml::Value) -> Option<String> {
+    let text = match output.get("output_type")?.as_str()? {
+        "stream" => multiline_text(output.get("text")),
+        "execute_result" | "display_data" => multiline_text(output.get("data")?.get("text/plain")),
+        "error" => format!(
+            "{}: {}",
+            output.get("ename")?.as_str()?,
+            output
+                .get("evalue")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+        ),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+fn render_code_cell(cell: &serde_yaml::Value, language: &str) -> String {
+    let mut events = vec![
+        MdEvent::Html(CowStr::from("<div class=\"cell code-cell\">\n")),
+        MdEvent::Start(MdTag::CodeBlock(CodeBlockKind::Fenced(CowStr::from(
+            language.to_string(),
+        )))),
+        MdEvent::Text(CowStr::from(multiline_text(cell.get("source")))),
+        MdEvent::End(TagEnd::CodeBlock),
+    ];
+    for text in cell
+        .get("outputs")
+        .and_then(|outputs| outputs.as_sequence())
+        .into_iter()
+        .flatten()
+        .filter_map(output_text)
+    {
+        events.push(MdEvent::Html(CowStr::from("<div class=\"cell-output\">\n")));
+        events.push(MdEvent::Start(MdTag::CodeBlock(CodeBlockKind::Indented)));
+        events.push(MdEvent::Text(CowStr::from(text)));
+        events.push(MdEvent::End(TagEnd::CodeBlock));
+        events.push(MdEvent::Html(CowStr::from("</div>\n")));
+    }
+    events.push(MdEvent::Html(CowStr::from("</div>\n")));
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+/// Codec for Jupyter notebooks. See the [module docs](self).
+#[derive(Debug, Default, Clone)]
+pub struct IpynbCodec {
+    md: MdCodec,
+    /// The notebook JSON as parsed, returned verbatim while nothing needs writing back.
+    content: String,
+    /// Order-preserving notebook tree.
+    notebook: Option<serde_yaml::Value>,
+    /// The Markdown view of the cells that `md` parsed.
+    markdown: String,
+}
+
+impl IpynbCodec {
+    pub fn new() -> Self {
+        IpynbCodec::default()
+    }
+}
+
+impl DocCodec for IpynbCodec {
+    fn proto(&self, path: &Path) -> Result<Option<IRNode>, BuildonomyError> {
+        if path.is_relative() {
+            return Err(BuildonomyError::Codec(format!(
+                "[IpynbCodec::proto] supplied path must be absolute. Received \"{path:?}\""
+            )));
+        };
+        if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|&ext| ext == "ipynb")
+            .is_none()
+        {
+            tracing::debug!(
+                "IpynbCodec::proto called with path \"{path:?}\", which has a non-'ipynb' \
+                file extension. Returning None"
+            );
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let mut proto = metadata_ir(&parse_notebook(&content)?)?;
+        if proto.title().unwrap_or_default().is_empty() {
+            if let Some(title) = title_from_path(path) {
+                proto.document.insert("title", value(title));
+            }
+        }
+        proto.path = os_path_to_string(path);
+        // Document heading
+        proto.heading = 2;
+        proto.kind.insert(BeliefKind::Document);
+        Ok(Some(proto))
+    }
+
+    fn parse(
+        &mut self,
+        content: &str,
+        mut current: IRNode,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<(), BuildonomyError> {
+        let notebook = parse_notebook(content)?;
+        let mut metadata = metadata_ir(&notebook)?;
+        metadata.traverse_schema()?;
+        current.merge(&mut metadata);
+        current.heading = 2;
+        current.kind.insert(BeliefKind::Document);
+
+        let markdown = notebook_to_markdown(&notebook);
+        self.md.parse(&markdown, current, diagnostics)?;
+        self.content = content.to_string();
+        self.notebook = Some(notebook);
+        self.markdown = markdown;
+        Ok(())
+    }
+
+    fn nodes(&self) -> Vec<IRNode> {
+        self.md.nodes()
+    }
+
+    fn inject_context(
+        &mut self,
+        node: &IRNode,
+        ctx: &BeliefContext<'_>,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Option<BeliefNode>, BuildonomyError> {
+        let mut maybe_updated = self.md.inject_context(node, ctx, diagnostics)?;
+        if let Some(toml::Value::String(text)) = maybe_updated
+            .as_mut()
+            .and_then(|updated| updated.payload.get_mut("text"))
+        {
+            *text = strip_cell_markers(text);
+        }
+        Ok(maybe_updated)
+    }
+
+    fn finalize(
+        &mut self,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Vec<(IRNode, BeliefNode)>, BuildonomyError> {
+        self.md.finalize(diagnostics)
+    }
+
+    fn generate_source(&self) -> Option<String> {
+        let mut notebook = self.notebook.clone()?;
+        let mut changed = false;
+
+        if let Some((doc, _)) = self.md.current_events.first() {
+            for key in NOTEBOOK_METADATA_KEYS {
+                changed |= sync_metadata(&mut notebook, key, doc.document.get(key));
+            }
+        }
+
+        let original = split_markdown(&self.markdown);
+        let rewritten = split_markdown(&self.md.generate_source()?);
+        if original.keys().eq(rewritten.keys()) {
+            if let Some(cells) = notebook
+                .get_mut("cells")
+                .and_then(|cells| cells.as_sequence_mut())
+            {
+                for (index, text) in rewritten {
+                    let cell = &mut cells[index];
+                    if cell_type(cell) == "markdown" && original.get(&index) != Some(&text) {
+                        set_cell_source(cell, &text);
+                        changed = true;
+                    }
+                }
+            }
+        } else {
+            tracing::warn!(
+                "[IpynbCodec::generate_source] Markdown cells could not be split back apart \
+                (an unterminated code fence or HTML block?). Leaving cell sources unchanged."
+            );
+        }
+
+        if !changed {
+            return Some(self.content.clone());
+        }
+        match JsonFormat::render_source(&DataSource::Tree(notebook), &self.content) {
+            Ok(rendered) => Some(rendered),
+            Err(e) => {
+                tracing::warn!("[IpynbCodec::generate_source] {e}");
+                None
+            }
+        }
+    }
+
+    fn generate_html(&self) -> Result<Vec<(String, String)>, BuildonomyError> {
+        let Some(notebook) = self.notebook.as_ref() else {
+            return Ok(vec![]);
+        };
+        let language = notebook_language(notebook);
+        let mut fragments = self.md.generate_html()?;
+        for (_, html_body) in fragments.iter_mut() {
+            for (index, cell) in cells(notebook).iter().enumerate() {
+                let rendered = match cell_type(cell) {
+                    "code" => render_code_cell(cell, &language),
+                    _ => String::new(),
+                };
+                *html_body = html_body.replacen(&format!("{}\n", cell_marker(index)), &rendered, 1);
+            }
+        }
+        Ok(fragments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codec::{builder::GraphBuilder, proto_index::ProtoIndex},
+        nodekey::NodeKey,
+        properties::Bid,
+        tests::helpers::init_logging,
+    };
+    use tempfile::TempDir;
+
+    const NOTEBOOK: &str = r###"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Churn Analysis\n",
+    "\n",
+    "Follows the [retention design](design/retention.md)."
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "rows: 42\n"
+     ]
+    },
+    {
+     "data": {
+      "text/plain": [
+       "0.25"
+      ]
+     },
+     "execution_count": 1,
+     "metadata": {},
+     "output_type": "execute_result"
+    }
+   ],
+   "source": [
+    "df = load()\n",
+    "print(f\"rows: {len(df)}\")\n",
+    "df.churn.mean()"
+   ]
+  },
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": "## Findings\n\nChurn < 30%, see [the model](#model)."
+  },
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": "More findings in a second cell."
+  },
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": "## Model"
+  }
+ ],
+ "metadata": {
+  "kernelspec": {
+   "display_name": "Python 3",
+   "language": "python",
+   "name": "python3"
+  },
+  "language_info": {
+   "name": "python",
+   "version": "3.11.4"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"###;
+
+    fn write_notebook(content: &str) -> (TempDir, std::path::PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("churn_analysis.ipynb");
+        fs::write(&path, content).unwrap();
+        (temp_dir, path)
+    }
+
+    fn parse_file(path: &Path, content: &str) -> (IpynbCodec, Vec<ParseDiagnostic>) {
+        let mut codec = IpynbCodec::new();
+        let proto = codec
+            .proto(path)
+            .expect("proto should succeed")
+            .expect("proto should return Some");
+        let mut diagnostics = Vec::new();
+        codec
+            .parse(content, proto, &mut diagnostics)
+            .expect("parse should succeed");
+        (codec, diagnostics)
+    }
+
+    #[test]
+    fn test_markdown_cells_become_sections_and_links() {
+        init_logging();
+        let (_temp_dir, path) = write_notebook(NOTEBOOK);
+        let (codec, _) = parse_file(&path, NOTEBOOK);
+        let nodes = codec.nodes();
+
+        let titles = nodes
+            .iter()
+            .map(|node| node.title().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Churn Analysis", "Findings", "Model"]);
+        assert!(nodes[0].kind.is_document());
+
+        // Notebook metadata is carried in the document payload.
+        assert_eq!(
+            nodes[0]
+                .document
+                .get("kernelspec")
+                .and_then(|kernelspec| kernelspec.get("name"))
+                .and_then(|name| name.as_str()),
+            Some("python3")
+        );
+
+        let doc_links = nodes[0]
+            .upstream
+            .iter()
+            .map(|relation| relation.key.clone())
+            .collect::<Vec<_>>();
+        let design_path = path.parent().unwrap().join("design/retention.md");
+        assert!(
+            doc_links.contains(&NodeKey::Path {
+                net: Default::default(),
+                path: os_path_to_string(&design_path),
+            }),
+            "{doc_links:?}"
+        );
+        assert!(
+            !nodes[1].upstream.is_empty(),
+            "The Findings section should link to #model"
+        );
+        // A section spans the Markdown cells that follow its heading.
+        assert!(
+            nodes[1]
+                .document
+                .get("text")
+                .is_none_or(|text| !text.to_string().contains("ipynb-cell")),
+            "Cell markers must not leak into node text"
+        );
+    }
+
+    #[test]
+    fn test_generate_source_round_trips_unchanged() {
+        let (_temp_dir, path) = write_notebook(NOTEBOOK);
+        let (codec, _) = parse_file(&path, NOTEBOOK);
+        assert_eq!(codec.generate_source().as_deref(), Some(NOTEBOOK));
+    }
+
+    #[test]
+    fn test_split_markdown_inverts_notebook_to_markdown() {
+        let notebook = parse_notebook(NOTEBOOK).unwrap();
+        let split = split_markdown(&notebook_to_markdown(&notebook));
+        assert_eq!(split.len(), 5);
+        assert_eq!(
+            split[&0],
+            "# Churn Analysis\n\nFollows the [retention design](design/retention.md)."
+        );
+        assert_eq!(split[&1], "");
+        assert_eq!(split[&4], "## Model");
+    }
+
+    #[test]
+    fn test_generate_html_renders_code_cells_and_outputs() {
+        let (_temp_dir, path) = write_notebook(NOTEBOOK);
+        let (codec, _) = parse_file(&path, NOTEBOOK);
+        let fragments = codec.generate_html().unwrap();
+        assert_eq!(fragments.len(), 1);
+        let (filename, html) = &fragments[0];
+        assert_eq!(filename, "churn_analysis.html");
+
+        assert!(!html.contains("ipynb-cell"), "{html}");
+        assert!(html.contains("<h1"), "{html}");
+        assert!(
+            html.contains("<code class=\"language-python\">df = load()"),
+            "{html}"
+        );
+        assert!(html.contains("rows: 42"), "{html}");
+        assert!(html.contains("0.25"), "{html}");
+        assert!(html.contains("Churn &lt; 30%"), "{html}");
+        // Unresolved links are left as written, as in MdCodec.
+        assert!(html.contains("href=\"design/retention.md\""), "{html}");
+        // Code cells render between the Markdown cells that surround them.
+        let code = html.find("df = load()").unwrap();
+        assert!(html.find("retention design").unwrap() < code);
+        assert!(code < html.find("Findings").unwrap());
+    }
+
+    #[test]
+    fn test_rejects_non_notebook_json() {
+        let mut codec = IpynbCodec::new();
+        let result = codec.parse("{\"title\": \"nope\"}", IRNode::default(), &mut vec![]);
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_content_writes_bid_into_metadata() {
+        init_logging();
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("index.md"),
+            "---\nid: \"test-network\"\ntitle: \"Test Network\"\n---\n\n# Test Network\n",
+        )
+        .unwrap();
+        let path = temp_dir.path().join("churn_analysis.ipynb");
+        fs::write(&path, NOTEBOOK).unwrap();
+
+        let mut builder = GraphBuilder::new(temp_dir.path(), None).unwrap();
+        let session_bb = builder.session_bb().clone();
+        let proto_index = ProtoIndex::build(builder.repo_root()).unwrap_or_default();
+        let result = builder
+            .parse_content(&path, NOTEBOOK.to_string(), session_bb, proto_index)
+            .await
+            .expect("parse_content should succeed");
+
+        let rewritten = result
+            .result
+            .rewritten_content
+            .expect("the new BID should be written into the notebook metadata");
+        let notebook: serde_json::Value = serde_json::from_str(&rewritten).unwrap();
+        let bid = notebook["metadata"]["bid"]
+            .as_str()
+            .and_then(|bid| Bid::try_from(bid).ok())
+            .expect("metadata.bid should hold a BID");
+        assert_eq!(notebook["metadata"]["kernelspec"]["name"], "python3");
+        assert!(notebook["metadata"]["sections"].is_object(), "{rewritten}");
+
+        // Code cells and outputs are untouched; the notebook stays nbformat 4.
+        assert_eq!(notebook["cells"][1], {
+            let original: serde_json::Value = serde_json::from_str(NOTEBOOK).unwrap();
+            original["cells"][1].clone()
+        });
+        assert!(rewritten.starts_with("{\n \"cells\""), "{rewritten}");
+
+        let (reparsed, _) = parse_file(&path, &rewritten);
+        assert_eq!(
+            reparsed.nodes()[0]
+                .document
+                .get("bid")
+                .and_then(|bid| bid.as_str()),
+            Some(bid.to_string().as_str())
+        );
+        assert!(
+            !rewritten.contains("---"),
+            "No frontmatter block should leak into a Markdown cell:\n{rewritten}"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use pulldown_cmark::{
-    BrokenLink, CowStr, Event as MdEvent, HeadingLevel, LinkType, MetadataBlockKind, Options,
-    Parser as MdParser, Tag as MdTag, TagEnd as MdTagEnd,
+    BrokenLink, CodeBlockKind, CowStr, Event as MdEvent, HeadingLevel, LinkType, MetadataBlockKind,
+    Options, Parser as MdParser, Tag as MdTag, TagEnd as MdTagEnd,
 };
 use pulldown_cmark_to_cmark::{
     cmark_resume_with_source_range_and_options, Options as CmarkToCmarkOptions,
@@ -21,17 +21,22 @@ use titlecase::titlecase;
 use toml_edit::value;
 
 use crate::{
-    beliefbase::BeliefContext,
+    beliefbase::{BeliefContext, ExtendedRelation},
     codec::{
         belief_ir::{IRNode, IntermediateRelation},
-        byte_offset_to_location,
+        block, byte_offset_to_location,
         diagnostic::ParseDiagnostic,
-        DocCodec, CODECS,
+        myst::{self, Directive, DirectiveKind, ResolvedReference, Role},
+        tag, wikilink, DocCodec, CODECS,
     },
     error::BuildonomyError,
     nodekey::{href_to_nodekey, NodeKey},
     paths::{as_anchor, os_path_to_string, to_anchor, AnchorPath},
-    properties::{href_namespace, BeliefKind, BeliefNode, Bid, Bref, Weight, WeightKind},
+    properties::{
+        href_namespace, BeliefKind, BeliefNode, Bid, Bref, Weight, WeightKind, WEIGHT_DOC_PATHS,
+        WEIGHT_EMBED, WEIGHT_LINK_TITLE, WEIGHT_OWNED_BY, WEIGHT_SORT_KEY, WEIGHT_TAG,
+        WEIGHT_WIKILINK,
+    },
 };
 
 pub use pulldown_cmark;
@@ -111,8 +116,8 @@ fn link_to_relation(
         // Reference link like `[foo][bar]`
         // Reference without destination in the document, but resolved by the broken_link_callback
         LinkType::Reference => None,
-        // Prioritize 'id' nodekey over 'path' for wikilinks only
-        LinkType::WikiLink { .. } => Some(href_to_nodekey(&format!("id:{dest_url}"))),
+        // Wikilinks name notes by id, title or alias unless they look like a path
+        LinkType::WikiLink { .. } => Some(wikilink::to_nodekey(dest_url, false)),
         LinkType::ReferenceUnknown => Some(href_to_nodekey(dest_url)),
 
         // Collapsed link like `[foo][]`
@@ -195,6 +200,46 @@ impl LinkAccumulator {
         false
     }
 
+    /// Push the link's events back out unchanged, with the source range spanning the whole link
+    /// on its end event.
+    fn push_events(
+        self,
+        end_range: Option<Range<usize>>,
+        events_out: &mut VecDeque<(MdEvent<'static>, Option<Range<usize>>)>,
+    ) {
+        let start_event = if self.is_image {
+            MdEvent::Start(MdTag::Image {
+                link_type: self.link_type,
+                dest_url: self.rel_url,
+                title: self.title,
+                id: self.id,
+            })
+        } else {
+            MdEvent::Start(MdTag::Link {
+                link_type: self.link_type,
+                dest_url: self.rel_url,
+                title: self.title,
+                id: self.id,
+            })
+        };
+        events_out.push_back((start_event, None));
+        for title_event in self.title_events.into_iter() {
+            events_out.push_back((title_event, None));
+        }
+        let new_range = match (self.range, end_range) {
+            (Some(link_range), Some(link_end_range)) => Some(link_range.start..link_end_range.end),
+            (Some(link_range), _) => Some(link_range.clone()),
+            (_, Some(link_end_range)) => Some(link_end_range.clone()),
+            _ => None,
+        };
+        let end_event = if self.is_image {
+            MdEvent::End(MdTagEnd::Image)
+        } else {
+            MdEvent::End(MdTagEnd::Link)
+        };
+        events_out.push_back((end_event, new_range));
+    }
+
     fn title_string(&self) -> String {
         let title_string = self
             .title_events
@@ -218,13 +263,16 @@ impl LinkAccumulator {
 
 /// Parsed components from a markdown link title attribute.
 ///
-/// Title attribute format: `"bref://abc123 {\"auto_title\":true} User Words"`
+/// Title attribute format: `"bref://abc123 {\"auto_title\":true,\"rel\":\"supports\"} User Words"`
 #[derive(Debug, Clone, PartialEq)]
 struct TitleAttributeParts {
     /// Bref extracted from title attribute (e.g., "bref://abc123")
     bref: Option<Bref>,
     /// Whether link text should auto-update when target title changes
     auto_title: bool,
+    /// The relation kind the link declares, from the `rel` metadata key or a title that is
+    /// just the name of a title kind (e.g. `"supports"`)
+    rel: Option<String>,
     /// Any additional user-provided words in the title attribute
     user_words: Option<String>,
 }
@@ -236,23 +284,39 @@ struct TitleAttributeParts {
 ///
 /// # Arguments
 /// * `bref` - The bref string (should already include "bref://" prefix)
-/// * `auto_title` - If true, adds {"auto_title":true} metadata
+/// * `auto_title` - If true, adds `"auto_title":true` metadata
+/// * `rel` - Optional relation kind, added as `"rel"` metadata
 /// * `user_words` - Optional user-provided text to append
 ///
 /// # Examples
 /// ```
 /// use noet_core::codec::md::build_title_attribute;
-/// let attr = build_title_attribute("bref://abc123", false, None);
+/// let attr = build_title_attribute("bref://abc123", false, None, None);
 /// assert_eq!(attr, "bref://abc123");
 ///
-/// let attr = build_title_attribute("bref://abc123", true, Some("My Note"));
+/// let attr = build_title_attribute("bref://abc123", true, None, Some("My Note"));
 /// assert_eq!(attr, "bref://abc123 {\"auto_title\":true} My Note");
+///
+/// let attr = build_title_attribute("bref://abc123", false, Some("supports"), None);
+/// assert_eq!(attr, "bref://abc123 {\"rel\":\"supports\"}");
 /// ```
-pub fn build_title_attribute(bref: &str, auto_title: bool, user_words: Option<&str>) -> String {
+pub fn build_title_attribute(
+    bref: &str,
+    auto_title: bool,
+    rel: Option<&str>,
+    user_words: Option<&str>,
+) -> String {
     let mut parts = vec![bref.to_string()];
 
+    let mut metadata = serde_json::Map::new();
     if auto_title {
-        parts.push("{\"auto_title\":true}".to_string());
+        metadata.insert("auto_title".to_string(), serde_json::Value::Bool(true));
+    }
+    if let Some(rel) = rel {
+        metadata.insert("rel".to_string(), serde_json::Value::from(rel));
+    }
+    if !metadata.is_empty() {
+        parts.push(serde_json::Value::Object(metadata).to_string());
     }
 
     if let Some(words) = user_words {
@@ -277,15 +341,29 @@ pub fn build_title_attribute(bref: &str, auto_title: bool, user_words: Option<&s
 /// let parts = parse_title_attribute("bref://abc123 {\"auto_title\":true} My Note");
 /// assert_eq!(parts.auto_title, true);
 /// assert_eq!(parts.user_words, Some("My Note".to_string()));
+///
+/// let parts = parse_title_attribute("supports");
+/// assert_eq!(parts.rel, Some("supports".to_string()));
 /// ```
 ///
 /// Note: This function is tested via unit tests in the `tests` module.
 fn parse_title_attribute(title: &str) -> TitleAttributeParts {
     let mut bref = None;
     let mut auto_title = false;
+    let mut rel = None;
     let mut word_parts = Vec::new();
     let mut in_json = false;
     let mut json_buffer = String::new();
+    let mut apply_config = |json: &str| {
+        if let Ok(config) = serde_json::from_str::<serde_json::Value>(json) {
+            if let Some(auto_val) = config.get("auto_title") {
+                auto_title = auto_val.as_bool().unwrap_or(false);
+            }
+            if let Some(rel_val) = config.get("rel").and_then(|rel_val| rel_val.as_str()) {
+                rel = Some(rel_val.to_string());
+            }
+        }
+    };
 
     for word in title.split_whitespace() {
         if word.starts_with("bref://") {
@@ -306,11 +384,7 @@ fn parse_title_attribute(title: &str) -> TitleAttributeParts {
             json_buffer.push_str(word);
             if word.ends_with('}') {
                 // Single-word JSON object
-                if let Ok(config) = serde_json::from_str::<serde_json::Value>(&json_buffer) {
-                    if let Some(auto_val) = config.get("auto_title") {
-                        auto_title = auto_val.as_bool().unwrap_or(false);
-                    }
-                }
+                apply_config(&json_buffer);
                 in_json = false;
                 json_buffer.clear();
             }
@@ -319,11 +393,7 @@ fn parse_title_attribute(title: &str) -> TitleAttributeParts {
             json_buffer.push(' ');
             json_buffer.push_str(word);
             if word.ends_with('}') {
-                if let Ok(config) = serde_json::from_str::<serde_json::Value>(&json_buffer) {
-                    if let Some(auto_val) = config.get("auto_title") {
-                        auto_title = auto_val.as_bool().unwrap_or(false);
-                    }
-                }
+                apply_config(&json_buffer);
                 in_json = false;
                 json_buffer.clear();
             }
@@ -333,6 +403,13 @@ fn parse_title_attribute(title: &str) -> TitleAttributeParts {
         }
     }
 
+    // A title that is only the name of a title kind declares that kind. The set is fixed, so
+    // the reading doesn't depend on which kinds the process happens to have registered.
+    if rel.is_none() && word_parts.len() == 1 && is_title_kind(word_parts[0]) {
+        rel = Some(word_parts[0].to_string());
+        word_parts.clear();
+    }
+
     let user_words = if word_parts.is_empty() {
         None
     } else {
@@ -342,10 +419,275 @@ fn parse_title_attribute(title: &str) -> TitleAttributeParts {
     TitleAttributeParts {
         bref,
         auto_title,
+        rel,
         user_words,
     }
 }
 
+/// Whether a title consisting of just `word` declares a relation kind: the non-structural built-in
+/// kinds and the [WeightKind::LINK_KINDS].
+fn is_title_kind(word: &str) -> bool {
+    WeightKind::LINK_KINDS.contains(&word)
+        || matches!(
+            WeightKind::try_from(word),
+            Ok(WeightKind::Epistemic | WeightKind::Pragmatic)
+        )
+}
+
+/// Parse an attribute block written directly after a link, e.g. `{rel=contradicts confidence=0.7}`.
+/// Values may be quoted to include spaces. Returns the attributes and the block's length in bytes,
+/// or `None` unless `text` starts with a block whose entries are all `key=value` pairs.
+pub(crate) fn parse_link_attributes(text: &str) -> Option<(Vec<(String, String)>, usize)> {
+    let bytes = text.as_bytes();
+    if bytes.first() != Some(&b'{') {
+        return None;
+    }
+    let mut attributes = Vec::new();
+    let mut idx = 1;
+    loop {
+        while bytes.get(idx).is_some_and(|b| b.is_ascii_whitespace()) {
+            idx += 1;
+        }
+        if *bytes.get(idx)? == b'}' {
+            break;
+        }
+        let key_start = idx;
+        while bytes
+            .get(idx)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_' || *b == b'-')
+        {
+            idx += 1;
+        }
+        if idx == key_start || bytes.get(idx) != Some(&b'=') {
+            return None;
+        }
+        let key = &text[key_start..idx];
+        idx += 1;
+        let value = match bytes.get(idx) {
+            Some(quote @ (b'"' | b'\'')) => {
+                let value_start = idx + 1;
+                let len = text[value_start..].find(*quote as char)?;
+                idx = value_start + len + 1;
+                &text[value_start..value_start + len]
+            }
+            _ => {
+                let value_start = idx;
+                while bytes
+                    .get(idx)
+                    .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'}')
+                {
+                    idx += 1;
+                }
+                &text[value_start..idx]
+            }
+        };
+        attributes.push((key.to_string(), value.to_string()));
+    }
+    (!attributes.is_empty()).then_some((attributes, idx + 1))
+}
+
+/// Drop the attribute blocks written after links from rendered output; they only declare the
+/// link's relation semantics.
+fn strip_link_attributes(events: Vec<MdEvent<'static>>) -> Vec<MdEvent<'static>> {
+    let mut out: Vec<MdEvent<'static>> = Vec::with_capacity(events.len());
+    for event in events {
+        let after_link = matches!(
+            out.last(),
+            Some(MdEvent::End(MdTagEnd::Link | MdTagEnd::Image))
+        );
+        match event {
+            MdEvent::Text(text) if after_link => match parse_link_attributes(&text) {
+                Some((_, len)) if len == text.len() => {}
+                Some((_, len)) => out.push(MdEvent::Text(CowStr::from(text[len..].to_string()))),
+                None => out.push(MdEvent::Text(text)),
+            },
+            event => out.push(event),
+        }
+    }
+    out
+}
+
+/// The link attribute naming the relation kind, e.g. `{rel=contradicts}`.
+const LINK_REL_ATTRIBUTE: &str = "rel";
+
+/// Weight payload keys set by the codecs themselves, which link attributes may not override.
+const RESERVED_WEIGHT_KEYS: [&str; 7] = [
+    WEIGHT_OWNED_BY,
+    WEIGHT_SORT_KEY,
+    WEIGHT_DOC_PATHS,
+    WEIGHT_LINK_TITLE,
+    WEIGHT_WIKILINK,
+    WEIGHT_EMBED,
+    WEIGHT_TAG,
+];
+
+/// Interpret a link attribute value as a boolean, integer or float where it reads as one.
+fn link_attribute_value(value: &str) -> toml::Value {
+    if let Ok(flag) = value.parse::<bool>() {
+        toml::Value::Boolean(flag)
+    } else if let Ok(int) = value.parse::<i64>() {
+        toml::Value::Integer(int)
+    } else if let Ok(float) = value.parse::<f64>() {
+        toml::Value::Float(float)
+    } else {
+        toml::Value::String(value.to_string())
+    }
+}
+
+/// Normalize a link key against the document path, then regularize it into the network-relative
+/// form used by [`BeliefContext`] keys.
+///
+/// Returns an error message when the document's absolute path does not align with the context's
+/// repo-relative path, in which case the link cannot be rewritten safely.
+fn regularize_link_key(
+    key: &NodeKey,
+    ctx: &BeliefContext<'_>,
+    doc_abs_path: &str,
+) -> Result<NodeKey, String> {
+    let normalized_abs = key.resolve_against(doc_abs_path);
+    // ctx.root_path may differ from doc_abs_path (node.path) in several ways:
+    //   1. Network dir form:  doc="/tmp/.../subnet1"   ctx="subnet1/index.md"
+    //   2. Index file form:   doc="/tmp/.../subnet1/index.md"  ctx="subnet1"
+    //   3. Extensionless key: doc="/tmp/.../test.md"   ctx="test"
+    //
+    // We need to find root_abs_path = the absolute prefix that, when concatenated
+    // with ctx_stem, gives doc_stem (i.e. doc_stem.ends_with(ctx_stem)).
+    //
+    // Strategy: reduce both paths to their containing directory using AnchorPath.
+    //
+    // ctx_filepath comes from ctx.root_path, which may be:
+    //   - a real file path:     "array/symbol.iterator/index.md"
+    //   - a dotted dir name:    "array/symbol.iterator"   (document node, no index.md)
+    //   - a plain dir name:     "array"
+    //
+    // AnchorPath::new() mis-classifies "array/symbol.iterator" as a file with
+    // extension "iterator", so dir() returns only "array" — losing the last component.
+    // Use new_dir() + drop_index_file() for the same treatment as doc_stem below.
+    //
+    // doc_abs_path is the absolute filesystem path of the document being parsed.
+    // It may be a real file (".../array/index.md") or a dotted directory name
+    // (".../array/symbol.iterator") that AnchorPath::new() would mis-classify as
+    // a file with extension "iterator". Using new_dir() unconditionally forces
+    // directory semantics, giving dir() = the full path. Then drop_index_file()
+    // strips a trailing "/index.md" (or bare "index.anything") to normalise the
+    // file-path case back to the parent directory, matching what ctx_stem produces.
+    //
+    //   ".../array/index.md"       → new_dir → dir = ".../array/index.md"
+    //                              → drop_index_file → ".../array"            ✓
+    //   ".../array/symbol.iterator"→ new_dir → dir = ".../array/symbol.iterator"
+    //                              → drop_index_file → ".../array/symbol.iterator" ✓
+    //   ".../array"                → new_dir → dir = ".../array"
+    //                              → drop_index_file → ".../array"            ✓
+    //
+    // AnchorPath is drive-letter-aware (C:/... is a plain absolute path, not a
+    // URL schema), so dir() preserves the drive prefix correctly.
+    // Forward slashes are always used here (os_path_to_string guarantees that).
+
+    /// Strip a trailing "index" file segment (with any extension, e.g. "index.md",
+    /// "index.html") from an absolute path, returning the parent directory.
+    /// A bare "index" (no extension) is also stripped.
+    /// Non-index last components are returned unchanged.
+    ///
+    /// "…/array/index.md"  → "…/array"
+    /// "…/array"           → "…/array"   (unchanged)
+    /// "index.md"          → ""           (repo root)
+    fn drop_index_file(p: &str) -> &str {
+        let last_slash = p.rfind('/').map(|i| i + 1).unwrap_or(0);
+        let last_component = &p[last_slash..];
+        if last_component == "index" || last_component.starts_with("index.") {
+            // Strip the last component and the preceding slash (if any).
+            &p[..last_slash.saturating_sub(1)]
+        } else {
+            p
+        }
+    }
+
+    let ctx_filepath = AnchorPath::new(&ctx.root_path).filepath();
+    let ctx_stem = drop_index_file(AnchorPath::new_dir(ctx_filepath).dir());
+    let doc_stem = drop_index_file(AnchorPath::new_dir(doc_abs_path).dir());
+
+    if !doc_stem.ends_with(ctx_stem) {
+        tracing::warn!(
+            "[regularize_link_key] Path mismatch: proto abs path \"{doc_abs_path}\" \
+            does not align with ctx repo-relative path \"{ctx_filepath}\". \
+            Leaving link unchanged."
+        );
+        return Err(format!(
+            "Could not rewrite link: document path \"{doc_abs_path}\" \
+            does not align with context path \"{ctx_filepath}\""
+        ));
+    }
+    // root_abs_path is the absolute prefix before the repo-relative portion.
+    let root_abs_path = &doc_stem[0..(doc_stem.len() - ctx_stem.len())];
+    Ok(normalized_abs.regularize_unchecked(ctx.root_net, &ctx.root_path, root_abs_path))
+}
+
+/// Find the upstream relation (document, section or asset this node links to) matching a
+/// regularized link key.
+fn find_source_relation<'a, 'b>(
+    link_key: &NodeKey,
+    ctx: &BeliefContext<'_>,
+    sources: &'b [ExtendedRelation<'a>],
+) -> Option<&'b ExtendedRelation<'a>> {
+    sources.iter().find(|rel| {
+        rel.other
+            .keys(Some(ctx.root_net), None, ctx.beliefbase())
+            .iter()
+            .chain(
+                rel.other
+                    .keys(Some(rel.home_net), None, ctx.beliefbase())
+                    .iter(),
+            )
+            .any(|ctx_source_key| ctx_source_key == link_key)
+    })
+}
+
+/// Find the upstream document a wikilink names by title or alias rather than by id.
+fn find_named_source<'a, 'b>(
+    link_key: &NodeKey,
+    sources: &'b [ExtendedRelation<'a>],
+) -> Option<&'b ExtendedRelation<'a>> {
+    let NodeKey::Id { id, .. } = link_key else {
+        return None;
+    };
+    sources.iter().find(|rel| {
+        rel.other.kind.is_document()
+            && (to_anchor(&rel.other.title) == *id || rel.other.aliases().contains(id))
+    })
+}
+
+/// Relative link from the context node's document to a resolved relation, including the
+/// section anchor when the target is a heading.
+fn relative_link_path(relation: &ExtendedRelation<'_>, ctx: &BeliefContext<'_>) -> String {
+    if relation.home_net == href_namespace() {
+        relation.root_path.clone()
+    } else {
+        // 1. Calculate relative path from source to target
+        // Strip any existing anchor from home_path to avoid double anchors
+        let ctx_ap = AnchorPath::from(&ctx.root_path);
+
+        let mut relative_path = ctx_ap.path_to(&relation.root_path, true);
+        let relative_ap = AnchorPath::from(&relative_path);
+
+        if relation.other.kind.is_anchor() {
+            if let Some(id) = relation.other.id.as_deref() {
+                // Links to blocks keep Obsidian's `#^block-id` form.
+                let anchor = if relation.other.payload.contains_key(block::BLOCK_KEY) {
+                    format!("#^{}", to_anchor(id))
+                } else {
+                    as_anchor(id)
+                };
+                relative_path = relative_ap.join(anchor).into();
+            }
+        }
+        tracing::debug!(
+            "path_to(from: {ctx_ap}, -> to: {}) => {relative_path}",
+            relation.root_path
+        );
+        relative_path
+    }
+}
+
 fn check_for_link_and_push(
     events_in: &mut VecDeque<(MdEvent<'static>, Option<Range<usize>>)>,
     ctx: &BeliefContext<'_>,
@@ -375,6 +717,17 @@ fn check_for_link_and_push(
                 .pop()
                 .expect("Process_link is only true if collector stack is not empty.");
 
+            // Wikilinks are never rewritten: the wikilink syntax is the source of truth, and
+            // inject_context resolves them for rendering only.
+            if matches!(link_data.link_type, LinkType::WikiLink { .. }) {
+                link_data.push_events(range, events_out);
+                if stop_event_match {
+                    break;
+                }
+                maybe_event = events_in.pop_front();
+                continue;
+            }
+
             let link_text = link_data.title_string();
 
             // Parse the title attribute to check for existing Bref
@@ -400,40 +753,7 @@ fn check_for_link_and_push(
                         true => LinkType::Shortcut,
                         false => LinkType::Reference,
                     };
-                    let start_event = if link_data.is_image {
-                        MdEvent::Start(MdTag::Image {
-                            link_type: link_data.link_type,
-                            dest_url: link_data.rel_url,
-                            title: link_data.title,
-                            id: link_data.id,
-                        })
-                    } else {
-                        MdEvent::Start(MdTag::Link {
-                            link_type: link_data.link_type,
-                            dest_url: link_data.rel_url,
-                            title: link_data.title,
-                            id: link_data.id,
-                        })
-                    };
-                    events_out.push_back((start_event, None));
-                    for title_event in link_data.title_events.into_iter() {
-                        events_out.push_back((title_event, None));
-                    }
-
-                    let new_range = match (link_data.range, range) {
-                        (Some(link_range), Some(link_end_range)) => {
-                            Some(link_range.start..link_end_range.end)
-                        }
-                        (Some(link_range), _) => Some(link_range.clone()),
-                        (_, Some(link_end_range)) => Some(link_end_range.clone()),
-                        _ => None,
-                    };
-                    let end_event = if link_data.is_image {
-                        MdEvent::End(MdTagEnd::Image)
-                    } else {
-                        MdEvent::End(MdTagEnd::Link)
-                    };
-                    events_out.push_back((end_event, new_range));
+                    link_data.push_events(range, events_out);
 
                     if stop_event_match {
                         break;
@@ -443,176 +763,43 @@ fn check_for_link_and_push(
                 }
             };
 
-            // Normalize the key against the repo-relative doc path, then regularize
-            let normalized_abs = key.resolve_against(doc_abs_path);
-            // ctx.root_path may differ from doc_abs_path (node.path) in several ways:
-            //   1. Network dir form:  doc="/tmp/.../subnet1"   ctx="subnet1/index.md"
-            //   2. Index file form:   doc="/tmp/.../subnet1/index.md"  ctx="subnet1"
-            //   3. Extensionless key: doc="/tmp/.../test.md"   ctx="test"
-            //
-            // We need to find root_abs_path = the absolute prefix that, when concatenated
-            // with ctx_stem, gives doc_stem (i.e. doc_stem.ends_with(ctx_stem)).
-            //
-            // Strategy: reduce both paths to their containing directory using AnchorPath.
-            //
-            // ctx_filepath comes from ctx.root_path, which may be:
-            //   - a real file path:     "array/symbol.iterator/index.md"
-            //   - a dotted dir name:    "array/symbol.iterator"   (document node, no index.md)
-            //   - a plain dir name:     "array"
-            //
-            // AnchorPath::new() mis-classifies "array/symbol.iterator" as a file with
-            // extension "iterator", so dir() returns only "array" — losing the last component.
-            // Use new_dir() + drop_index_file() for the same treatment as doc_stem below.
-            //
-            // doc_abs_path is the absolute filesystem path of the document being parsed.
-            // It may be a real file (".../array/index.md") or a dotted directory name
-            // (".../array/symbol.iterator") that AnchorPath::new() would mis-classify as
-            // a file with extension "iterator". Using new_dir() unconditionally forces
-            // directory semantics, giving dir() = the full path. Then drop_index_file()
-            // strips a trailing "/index.md" (or bare "index.anything") to normalise the
-            // file-path case back to the parent directory, matching what ctx_stem produces.
-            //
-            //   ".../array/index.md"       → new_dir → dir = ".../array/index.md"
-            //                              → drop_index_file → ".../array"            ✓
-            //   ".../array/symbol.iterator"→ new_dir → dir = ".../array/symbol.iterator"
-            //                              → drop_index_file → ".../array/symbol.iterator" ✓
-            //   ".../array"                → new_dir → dir = ".../array"
-            //                              → drop_index_file → ".../array"            ✓
-            //
-            // AnchorPath is drive-letter-aware (C:/... is a plain absolute path, not a
-            // URL schema), so dir() preserves the drive prefix correctly.
-            // Forward slashes are always used here (os_path_to_string guarantees that).
-
-            /// Strip a trailing "index" file segment (with any extension, e.g. "index.md",
-            /// "index.html") from an absolute path, returning the parent directory.
-            /// A bare "index" (no extension) is also stripped.
-            /// Non-index last components are returned unchanged.
-            ///
-            /// "…/array/index.md"  → "…/array"
-            /// "…/array"           → "…/array"   (unchanged)
-            /// "index.md"          → ""           (repo root)
-            fn drop_index_file(p: &str) -> &str {
-                let last_slash = p.rfind('/').map(|i| i + 1).unwrap_or(0);
-                let last_component = &p[last_slash..];
-                if last_component == "index" || last_component.starts_with("index.") {
-                    // Strip the last component and the preceding slash (if any).
-                    &p[..last_slash.saturating_sub(1)]
-                } else {
-                    p
-                }
-            }
-
-            let ctx_filepath = AnchorPath::new(&ctx.root_path).filepath();
-            let ctx_stem = drop_index_file(AnchorPath::new_dir(ctx_filepath).dir());
-            let doc_stem = drop_index_file(AnchorPath::new_dir(doc_abs_path).dir());
-
-            if !doc_stem.ends_with(ctx_stem) {
-                // Mismatch means we cannot safely compute root_abs_path.
-                // Emit the link unchanged (same as the "Can't parse" path above) and
-                // record a diagnostic rather than panicking or producing garbage paths.
-                tracing::warn!(
-                    "[check_for_link_and_push] Path mismatch: proto abs path \"{doc_abs_path}\" \
-                    does not align with ctx repo-relative path \"{ctx_filepath}\". \
-                    Leaving link unchanged."
-                );
-                diagnostics.push(ParseDiagnostic::warning(format!(
-                    "Could not rewrite link: document path \"{doc_abs_path}\" \
-                    does not align with context path \"{ctx_filepath}\""
-                )));
-                let link_text_cow = CowStr::from(link_text.clone());
-                link_data.link_type = match link_text.is_empty() || link_text_cow == link_data.id {
-                    true => LinkType::Shortcut,
-                    false => LinkType::Reference,
-                };
-                let start_event = if link_data.is_image {
-                    MdEvent::Start(MdTag::Image {
-                        link_type: link_data.link_type,
-                        dest_url: link_data.rel_url,
-                        title: link_data.title,
-                        id: link_data.id,
-                    })
-                } else {
-                    MdEvent::Start(MdTag::Link {
-                        link_type: link_data.link_type,
-                        dest_url: link_data.rel_url,
-                        title: link_data.title,
-                        id: link_data.id,
-                    })
-                };
-                events_out.push_back((start_event, None));
-                for title_event in link_data.title_events.into_iter() {
-                    events_out.push_back((title_event, None));
-                }
-                let new_range = match (link_data.range, range) {
-                    (Some(link_range), Some(link_end_range)) => {
-                        Some(link_range.start..link_end_range.end)
+            let regularized = match regularize_link_key(&key, ctx, doc_abs_path) {
+                Ok(regularized) => regularized,
+                Err(message) => {
+                    // Mismatch means we cannot safely compute root_abs_path.
+                    // Emit the link unchanged (same as the "Can't parse" path above) and
+                    // record a diagnostic rather than panicking or producing garbage paths.
+                    diagnostics.push(ParseDiagnostic::warning(message));
+                    let link_text_cow = CowStr::from(link_text.clone());
+                    link_data.link_type =
+                        match link_text.is_empty() || link_text_cow == link_data.id {
+                            true => LinkType::Shortcut,
+                            false => LinkType::Reference,
+                        };
+                    link_data.push_events(range, events_out);
+                    if stop_event_match {
+                        break;
                     }
-                    (Some(link_range), _) => Some(link_range.clone()),
-                    (_, Some(link_end_range)) => Some(link_end_range.clone()),
-                    _ => None,
-                };
-                let end_event = if link_data.is_image {
-                    MdEvent::End(MdTagEnd::Image)
-                } else {
-                    MdEvent::End(MdTagEnd::Link)
-                };
-                events_out.push_back((end_event, new_range));
-                if stop_event_match {
-                    break;
+                    maybe_event = events_in.pop_front();
+                    continue;
                 }
-                maybe_event = events_in.pop_front();
-                continue;
             };
-            // root_abs_path is the absolute prefix before the repo-relative portion.
-            let root_abs_path = &doc_stem[0..(doc_stem.len() - ctx_stem.len())];
-            let regularized =
-                normalized_abs.regularize_unchecked(ctx.root_net, &ctx.root_path, root_abs_path);
-            let keys = [regularized];
 
             // Check sources (upstream) for the link target. Assets and document links are sources
             // (upstream)
             let sources = ctx.sources();
-
-            let maybe_keyed_relation = keys.iter().find_map(|link_key| {
-                // First check sources (upstream relations - documents this node sources from (links
-                // to))
-                sources.iter().find(|rel| {
-                    rel.other
-                        .keys(Some(ctx.root_net), None, ctx.beliefbase())
-                        .iter()
-                        .chain(
-                            rel.other
-                                .keys(Some(rel.home_net), None, ctx.beliefbase())
-                                .iter(),
-                        )
-                        .any(|ctx_source_key| ctx_source_key == link_key)
-                })
-            });
+            let maybe_keyed_relation = find_source_relation(&regularized, ctx, &sources);
 
             if let Some(relation) = maybe_keyed_relation {
                 // Generate canonical format: [text](relative/path.md#anchor "bref://abc config")
-
-                let relative_path = if relation.home_net == href_namespace() {
-                    relation.root_path.clone()
-                } else {
-                    // 1. Calculate relative path from source to target
-                    // Strip any existing anchor from home_path to avoid double anchors
-                    let ctx_ap = AnchorPath::from(&ctx.root_path);
-
-                    let mut relative_path = ctx_ap.path_to(&relation.root_path, true);
-                    let relative_ap = AnchorPath::from(&relative_path);
-
-                    if relation.other.kind.is_anchor() {
-                        if let Some(id) = relation.other.id.as_deref() {
-                            relative_path = relative_ap.join(as_anchor(id)).into();
-                        }
-                    }
-                    tracing::debug!(
-                        "path_to(from: {ctx_ap}, -> to: {}) => {relative_path}",
-                        relation.root_path
-                    );
-                    relative_path
-                };
+                // A target found by bref before its document is parsed has no path yet, so the
+                // link keeps its own.
+                let relative_path =
+                    if relation.root_path.is_empty() && relation.home_net != href_namespace() {
+                        link_data.rel_url.to_string()
+                    } else {
+                        relative_link_path(relation, ctx)
+                    };
 
                 // 3. Build title attribute: "bref://abc123 {config} user words"
                 let bref_str = format!("bref://{}", relation.other.bid.bref());
@@ -633,6 +820,7 @@ fn check_for_link_and_push(
                 let new_title_attr = build_title_attribute(
                     &bref_str,
                     should_auto_title,
+                    title_parts.rel.as_deref(),
                     title_parts.user_words.as_deref(),
                 );
 
@@ -943,6 +1131,34 @@ fn merge_metadata_into_node(node: &mut IRNode, metadata: &toml_edit::Table) {
     }
 }
 
+/// Rewrite a resolved document link to point at the generated HTML page.
+///
+/// Anchor-only links are normalized; links with a known codec extension (e.g. `.md`) get an
+/// `.html` extension. Anything else is returned unchanged.
+pub(crate) fn rewrite_href_to_html(dest_url: &str) -> String {
+    let url_ap = AnchorPath::from(dest_url);
+    if url_ap.is_anchor() {
+        tracing::debug!("is anchor");
+        as_anchor(url_ap.anchor())
+    } else if !url_ap.ext().is_empty() && CODECS.get(&url_ap).is_some() {
+        // Only rewrite links that have a known codec extension (e.g. .md).
+        // Extensionless paths (Gemfile, Makefile, bare dirs) must not be
+        // rewritten — they have no extension for CODECS to match cleanly
+        // and the (None,None) wildcard would produce wrong "Gemfile/index.html".
+        // Block anchors (`#^block-id`) are rendered as plain element ids.
+        let res = url_ap
+            .normalize()
+            .as_anchor_path()
+            .replace_extension("html")
+            .replacen("#^", "#", 1);
+        tracing::debug!("replacing {dest_url} with {res}");
+        res
+    } else {
+        tracing::debug!("no codec extension for {dest_url}, leaving unchanged");
+        dest_url.to_string()
+    }
+}
+
 pub fn to_html(content: &str, output: &mut String) -> Result<(), BuildonomyError> {
     let parser = MdParser::new_ext(content, buildonomy_md_options());
     pulldown_cmark::html::write_html_fmt(output, parser)?;
@@ -990,6 +1206,9 @@ fn read_frontmatter<R: Read>(reader: R) -> std::io::Result<Option<String>> {
     }
 }
 
+/// Heading level of `^block` nodes: below any section (an H6 is level 8).
+const BLOCK_HEADING: usize = 9;
+
 #[derive(Debug, Default, Clone)]
 pub struct MdCodec {
     pub current_events: Vec<ProtoNodeWithEvents>,
@@ -1000,6 +1219,16 @@ pub struct MdCodec {
     seen_ids: HashSet<String>,
     /// Byte offset of the most recently opened heading start tag, for position hints in diagnostics
     heading_start_offset: Option<usize>,
+    /// MyST directives found in the document, in source order
+    directives: Vec<Directive>,
+    /// MyST directive fence being accumulated: (info string, byte offset, body)
+    directive_fence: Option<(String, usize, String)>,
+    /// Label from a MyST `(label)=` target, applied to the next heading
+    pending_target: Option<String>,
+    /// MyST role and directive targets resolved during inject_context, used when rendering HTML
+    resolved_references: HashMap<NodeKey, ResolvedReference>,
+    /// Wikilink targets (as written) resolved during inject_context, used when rendering HTML
+    resolved_wikilinks: HashMap<String, ResolvedReference>,
 }
 
 impl MdCodec {
@@ -1010,16 +1239,183 @@ impl MdCodec {
             matched_sections: HashSet::new(),
             seen_ids: HashSet::new(),
             heading_start_offset: None,
+            directives: Vec::new(),
+            directive_fence: None,
+            pending_target: None,
+            resolved_references: HashMap::new(),
+            resolved_wikilinks: HashMap::new(),
+        }
+    }
+
+    /// MyST directives found in the document, in source order.
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
+
+    /// Build a MyST directive once its fence closes, pushing its references onto `current`.
+    ///
+    /// Unknown directives are left as plain code blocks and reported as a warning.
+    fn directive_from_fence(
+        content: &str,
+        info: &str,
+        fence_offset: usize,
+        body: &str,
+        current: &mut IRNode,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Option<Directive> {
+        let (name, argument) = myst::parse_directive_info(info)?;
+        let Some(kind) = DirectiveKind::lookup(name) else {
+            let (line, col) = byte_offset_to_location(content, fence_offset);
+            diagnostics.push(
+                ParseDiagnostic::warning(format!(
+                    "Unknown MyST directive '{{{name}}}'. It will be rendered as a plain code \
+                     block."
+                ))
+                .with_location(line, col),
+            );
+            return None;
+        };
+        let directive = Directive::new(kind, name, argument, body).with_location(fence_offset);
+        for reference in directive.references() {
+            let node_key = href_to_nodekey(&reference.href).resolve_against(&current.path);
+            let weight = Some(reference.weight).filter(|weight| !weight.payload.is_empty());
+            current.upstream.push(
+                IntermediateRelation::new(node_key, WeightKind::Epistemic, weight)
+                    .with_location(fence_offset),
+            );
+        }
+        Some(directive)
+    }
+
+    /// Add a node for each `^block` in the parsed sections, right after its section so the
+    /// builder places it below that section. Blocks whose anchor is already taken by a heading
+    /// or an earlier block are skipped with a warning.
+    fn push_blocks(&mut self, diagnostics: &mut Vec<ParseDiagnostic>) {
+        for (proto, events) in std::mem::take(&mut self.current_events) {
+            let md_events = events
+                .iter()
+                .map(|(event, _)| event.clone())
+                .collect::<Vec<_>>();
+            let mut block_nodes = Vec::new();
+            for block in block::find_blocks(&md_events) {
+                if !self.seen_ids.insert(block.id.clone()) {
+                    let (line, col) = events[block.start]
+                        .1
+                        .as_ref()
+                        .map(|range| byte_offset_to_location(&self.content, range.start))
+                        .unwrap_or((0, 0));
+                    diagnostics.push(
+                        ParseDiagnostic::warning(format!(
+                            "Block id '^{}' is already used as an anchor in this document. The \
+                             block will not be addressable.",
+                            block.id
+                        ))
+                        .with_location(line, col),
+                    );
+                    continue;
+                }
+                let mut node = IRNode {
+                    path: proto.path.clone(),
+                    heading: BLOCK_HEADING,
+                    ..Default::default()
+                };
+                node.document.insert("id", value(block.id.clone()));
+                node.document.insert("title", value(block.title()));
+                node.document.insert(block::BLOCK_KEY, value(true));
+                node.document.insert("text", value(block.text));
+                block_nodes.push((node, VecDeque::new()));
+            }
+            self.current_events.push((proto, events));
+            self.current_events.extend(block_nodes);
+        }
+    }
+
+    /// The relation kind a link declares, from an attribute block written right after it or its
+    /// title attribute, defaulting to `Epistemic`. Other attributes are added to the link's
+    /// weight payload. Unregistered kinds are reported and fall back to `Epistemic`.
+    pub(crate) fn link_semantics(
+        content: &str,
+        title: &str,
+        link_end: usize,
+        location: Option<usize>,
+        weight: &mut Weight,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> WeightKind {
+        let warn = |diagnostics: &mut Vec<ParseDiagnostic>, message: String| {
+            let diagnostic = ParseDiagnostic::warning(message);
+            diagnostics.push(match location {
+                Some(offset) => {
+                    let (line, col) = byte_offset_to_location(content, offset);
+                    diagnostic.with_location(line, col)
+                }
+                None => diagnostic,
+            });
+        };
+        let mut rel = parse_title_attribute(title).rel;
+        let attributes = content
+            .get(link_end..)
+            .and_then(parse_link_attributes)
+            .map(|(attributes, _)| attributes)
+            .unwrap_or_default();
+        for (key, value) in attributes {
+            if key == LINK_REL_ATTRIBUTE {
+                rel = Some(value);
+            } else if RESERVED_WEIGHT_KEYS.contains(&key.as_str()) {
+                warn(
+                    diagnostics,
+                    format!("Link attribute '{key}' is reserved and was ignored."),
+                );
+            } else {
+                weight.payload.insert(key, link_attribute_value(&value));
+            }
         }
+        match rel.map(|rel| (WeightKind::try_from(rel.as_str()), rel)) {
+            None => WeightKind::Epistemic,
+            Some((Ok(WeightKind::Section), rel)) => {
+                warn(
+                    diagnostics,
+                    format!("Links cannot declare structural '{rel}' relations."),
+                );
+                WeightKind::Epistemic
+            }
+            Some((Ok(kind), _)) => kind,
+            Some((Err(_), rel)) => {
+                warn(
+                    diagnostics,
+                    format!(
+                        "Unknown relation kind '{rel}'; declare it in a schema. The link was \
+                         read as an Epistemic relation."
+                    ),
+                );
+                WeightKind::Epistemic
+            }
+        }
+    }
+
+    /// Relate `current` to a tag, once per tag.
+    fn push_tag(current: &mut IRNode, tag: String, location: usize) {
+        let key = tag::to_nodekey(&tag);
+        if current.upstream.iter().any(|relation| relation.key == key) {
+            return;
+        }
+        let mut weight = Weight::default();
+        weight.set::<String>(WEIGHT_TAG, tag).ok();
+        current.upstream.push(
+            IntermediateRelation::new(key, WeightKind::Epistemic, Some(weight))
+                .with_location(location),
+        );
     }
 
     pub fn events_to_text<'a, I>(content: &str, events: I) -> Option<String>
     where
         I: Iterator<Item = (MdEvent<'a>, Option<Range<usize>>)>,
     {
+        // Wikilinks are written back in their original syntax, which cmark can't produce.
+        let events = wikilink::collapse_to_source(events.collect());
         // Single pass: collect shortcuts and events simultaneously using inspect
         let mut shortcuts = Vec::new();
         let events_vec: Vec<(MdEvent<'a>, Option<Range<usize>>)> = events
+            .into_iter()
             .inspect(|(e, _r)| {
                 if let MdEvent::Start(MdTag::Link {
                     link_type: LinkType::Shortcut | LinkType::Reference,
@@ -1092,29 +1488,9 @@ impl MdCodec {
                     title,
                     id,
                 }) => {
-                    let url_ap = AnchorPath::from(&dest_url);
                     let should_rewrite = title.contains("bref://");
                     let new_url = if should_rewrite {
-                        if url_ap.is_anchor() {
-                            tracing::debug!("is anchor");
-                            CowStr::from(as_anchor(url_ap.anchor()))
-                        } else if !url_ap.ext().is_empty() && CODECS.get(&url_ap).is_some() {
-                            // Only rewrite links that have a known codec extension (e.g. .md).
-                            // Extensionless paths (Gemfile, Makefile, bare dirs) must not be
-                            // rewritten — they have no extension for CODECS to match cleanly
-                            // and the (None,None) wildcard would produce wrong "Gemfile/index.html".
-                            let res = CowStr::from(
-                                url_ap
-                                    .normalize()
-                                    .as_anchor_path()
-                                    .replace_extension("html"),
-                            );
-                            tracing::debug!("replacing {dest_url} with {res}");
-                            res
-                        } else {
-                            tracing::debug!("no codec extension for {dest_url}, leaving unchanged");
-                            dest_url
-                        }
+                        CowStr::from(rewrite_href_to_html(&dest_url))
                     } else {
                         tracing::debug!("no bref element in title attribute for {dest_url}");
                         dest_url
@@ -1189,10 +1565,29 @@ impl MdCodec {
                     }
                 })
             })
-            .map(rewrite_md_links_to_html);
+            .map(rewrite_md_links_to_html)
+            .collect::<Vec<_>>();
+        let events = strip_link_attributes(events);
+
+        // Strip block markers, then replace wikilinks and embeds, then MyST directives, roles and
+        // targets, with their HTML rendering.
+        let doc_path = self
+            .current_events
+            .first()
+            .map(|(proto, _)| proto.path.as_str())
+            .unwrap_or_default();
+        let block_ids = self
+            .current_events
+            .iter()
+            .filter(|(proto, _)| proto.heading == BLOCK_HEADING)
+            .filter_map(|(proto, _)| proto.id())
+            .collect::<HashSet<_>>();
+        let events = block::Renderer::new(&block_ids).render(events);
+        let events = wikilink::Renderer::new(doc_path, &self.resolved_wikilinks).render(events);
+        let events = myst::Renderer::new(doc_path, &self.resolved_references).render(events);
 
         let mut html_body = String::new();
-        pulldown_cmark::html::push_html(&mut html_body, events);
+        pulldown_cmark::html::push_html(&mut html_body, events.into_iter());
         html_body
     }
 }
@@ -1278,6 +1673,8 @@ impl DocCodec for MdCodec {
             None
         };
 
+        let resolved_references = &mut self.resolved_references;
+        let resolved_wikilinks = &mut self.resolved_wikilinks;
         let proto_events = self
             .current_events
             .iter_mut()
@@ -1310,7 +1707,10 @@ impl DocCodec for MdCodec {
 
         // Network-level collision detection and ID injection
         let mut id_changed = false;
-        if proto_events.0.heading > 2 {
+        // Blocks have no events of their own: their marker and text are in their section's
+        // events, and their text payload is set at parse time.
+        let is_block = proto_events.0.heading == BLOCK_HEADING;
+        if proto_events.0.heading > 2 && !is_block {
             // This is a heading node (not document)
             // Use ctx.node.id() (which has collision-corrected value from push)
             let final_id = ctx.node.id();
@@ -1370,7 +1770,70 @@ impl DocCodec for MdCodec {
             None,
             diagnostics,
         );
-        let maybe_text = if frontmatter_changed.is_some()
+
+        // Resolve MyST role and directive targets so render_html_body can link to them. The
+        // source is never rewritten for these: the MyST syntax is the source of truth.
+        let sources = ctx.sources();
+        for relation in node.upstream.iter().filter(|relation| {
+            relation.weight.as_ref().is_some_and(|weight| {
+                weight.payload.contains_key("role") || weight.payload.contains_key("directive")
+            })
+        }) {
+            let Ok(regularized) = regularize_link_key(&relation.key, ctx, &node.path) else {
+                continue;
+            };
+            // Targets whose path isn't known yet stay unresolved until a later pass.
+            if let Some(source) = find_source_relation(&regularized, ctx, &sources)
+                .filter(|source| !source.root_path.is_empty())
+            {
+                resolved_references.insert(
+                    relation.key.clone(),
+                    ResolvedReference {
+                        href: relative_link_path(source, ctx),
+                        title: source.other.title.clone(),
+                    },
+                );
+            }
+        }
+        // Wikilinks likewise. Notes named by title or alias don't match the target's keys, and
+        // their heading (dropped from the key) is added back as an anchor.
+        for relation in node.upstream.iter() {
+            let Some(target) = relation
+                .weight
+                .as_ref()
+                .and_then(|weight| weight.get::<String>(WEIGHT_WIKILINK))
+            else {
+                continue;
+            };
+            let Ok(regularized) = regularize_link_key(&relation.key, ctx, &node.path) else {
+                continue;
+            };
+            let Some(source) = find_source_relation(&regularized, ctx, &sources)
+                .or_else(|| find_named_source(&regularized, &sources))
+                .filter(|source| !source.root_path.is_empty())
+            else {
+                continue;
+            };
+            let mut href = relative_link_path(source, ctx);
+            if let (NodeKey::Id { .. }, (_, Some(heading))) =
+                (&relation.key, wikilink::split_target(&target))
+            {
+                let anchor = to_anchor(heading);
+                if !anchor.is_empty() {
+                    href = AnchorPath::from(&href).join(as_anchor(&anchor)).into();
+                }
+            }
+            resolved_wikilinks.insert(
+                target,
+                ResolvedReference {
+                    href,
+                    title: source.other.title.clone(),
+                },
+            );
+        }
+        let maybe_text = if is_block {
+            None
+        } else if frontmatter_changed.is_some()
             || sections_metadata_merged
             || link_changed
             || id_changed
@@ -1523,7 +1986,10 @@ impl DocCodec for MdCodec {
 
                 // Include any other metadata fields (excluding internal fields)
                 for (key, val) in section_proto.document.iter() {
-                    if !matches!(key, "bid" | "id" | "title" | "text" | "schema" | "heading") {
+                    if !matches!(
+                        key,
+                        "bid" | "id" | "title" | "text" | "schema" | "heading" | block::BLOCK_KEY
+                    ) {
                         section_metadata.insert(key, val.clone());
                     }
                 }
@@ -1610,10 +2076,18 @@ impl DocCodec for MdCodec {
         self.matched_sections.clear();
         self.seen_ids.clear();
         self.heading_start_offset = None;
+        self.directives.clear();
+        self.directive_fence = None;
+        self.pending_target = None;
+        self.resolved_references.clear();
+        self.resolved_wikilinks.clear();
         let mut first_heading = true;
         let mut proto_events = VecDeque::new();
         let mut link_stack: Vec<LinkAccumulator> = Vec::new();
-        for (event, offset) in MdParser::new_with_broken_link_callback(
+        // Inline tags are only read from prose: not from code blocks or the frontmatter.
+        let mut in_code_block = false;
+        let mut metadata_offset = None;
+        for (mut event, offset) in MdParser::new_with_broken_link_callback(
             &self.content,
             buildonomy_md_options(),
             Some(|link: BrokenLink<'_>| {
@@ -1634,48 +2108,178 @@ impl DocCodec for MdCodec {
                 let link_data = link_stack.pop().expect(
                     "Push relation is only true if link_data is some and the link end tag is found",
                 );
-                if let Some(node_key) = link_to_relation(
-                    &link_data.link_type,
-                    &link_data.rel_url,
-                    &CowStr::from(link_data.title_string()),
-                    &link_data.id.clone(),
-                ) {
+                let is_wikilink = matches!(link_data.link_type, LinkType::WikiLink { .. });
+                let maybe_node_key = if is_wikilink {
+                    Some(wikilink::to_nodekey(&link_data.rel_url, link_data.is_image))
+                } else {
+                    link_to_relation(
+                        &link_data.link_type,
+                        &link_data.rel_url,
+                        &CowStr::from(link_data.title_string()),
+                        &link_data.id.clone(),
+                    )
+                };
+                if let Some(node_key) = maybe_node_key {
                     let node_key = node_key.resolve_against(&current.path);
                     let title = link_data.title_string();
-                    let payload = if !title.is_empty()
+                    let mut weight = Weight::default();
+                    if !title.is_empty()
                         && title != link_data.rel_url.as_ref()
                         && title != link_data.id.as_ref()
                     {
-                        let mut weight = Weight::default();
-                        weight.set::<String>("title", title).ok();
-                        Some(weight)
-                    } else {
-                        None
-                    };
-                    let mut relation =
-                        IntermediateRelation::new(node_key, WeightKind::Epistemic, payload);
-                    if let Some(byte_offset) = link_data.range.as_ref().map(|r| r.start) {
+                        weight.set::<String>(WEIGHT_LINK_TITLE, title).ok();
+                    }
+                    if is_wikilink {
+                        weight
+                            .set::<String>(WEIGHT_WIKILINK, link_data.rel_url.to_string())
+                            .ok();
+                        if link_data.is_image {
+                            weight.set(WEIGHT_EMBED, true).ok();
+                        }
+                    }
+                    let location = link_data.range.as_ref().map(|r| r.start);
+                    let kind = Self::link_semantics(
+                        &self.content,
+                        &link_data.title,
+                        offset.end,
+                        location,
+                        &mut weight,
+                        diagnostics,
+                    );
+                    let payload = Some(weight).filter(|weight| !weight.payload.is_empty());
+                    let mut relation = IntermediateRelation::new(node_key, kind, payload);
+                    if let Some(byte_offset) = location {
                         relation = relation.with_location(byte_offset);
                     }
                     current.upstream.push(relation);
                 }
             }
 
+            // MyST targets: a `(label)=` paragraph immediately before a heading supplies the
+            // heading's anchor. The label is set on the heading event so it takes the same path as
+            // an explicit `{#anchor}`.
+            match &mut event {
+                MdEvent::Start(MdTag::Heading { id, .. }) => {
+                    if let Some(label) = self.pending_target.take() {
+                        if id.is_none() {
+                            *id = Some(CowStr::from(label));
+                        }
+                    }
+                }
+                MdEvent::Start(_) => self.pending_target = None,
+                MdEvent::Text(text) => {
+                    let starts_paragraph = matches!(
+                        proto_events.back(),
+                        Some((MdEvent::Start(MdTag::Paragraph), _))
+                    );
+                    self.pending_target = myst::parse_target(text)
+                        .filter(|_| starts_paragraph)
+                        .map(to_anchor);
+                }
+                MdEvent::Code(_) | MdEvent::InlineHtml(_) | MdEvent::Html(_) => {
+                    self.pending_target = None
+                }
+                _ => {}
+            }
+
+            // MyST directives and roles. Events are kept as-is for write-back; we only record
+            // the typed directive and turn role/directive targets into relations.
+            match event.borrow() {
+                MdEvent::Start(MdTag::CodeBlock(CodeBlockKind::Fenced(info)))
+                    if myst::parse_directive_info(info).is_some() =>
+                {
+                    self.directive_fence = Some((info.to_string(), offset.start, String::new()));
+                }
+                MdEvent::Text(text) => {
+                    if let Some((_, _, body)) = self.directive_fence.as_mut() {
+                        body.push_str(text);
+                    }
+                }
+                MdEvent::End(MdTagEnd::CodeBlock) => {
+                    if let Some((info, fence_offset, body)) = self.directive_fence.take() {
+                        if let Some(directive) = Self::directive_from_fence(
+                            &self.content,
+                            &info,
+                            fence_offset,
+                            &body,
+                            &mut current,
+                            diagnostics,
+                        ) {
+                            self.directives.push(directive);
+                        }
+                    }
+                }
+                MdEvent::Code(content) => {
+                    let role = match proto_events.back() {
+                        Some((MdEvent::Text(text), _)) => myst::split_role_prefix(text)
+                            .and_then(|(_, name)| Role::parse(name, content)),
+                        _ => None,
+                    };
+                    if let Some(role) = role {
+                        // Point the location at the `{name}` prefix rather than the content.
+                        let role_offset = offset.start.saturating_sub(role.name.len() + 2);
+                        let reference = role.reference();
+                        let node_key =
+                            href_to_nodekey(&reference.href).resolve_against(&current.path);
+                        current.upstream.push(
+                            IntermediateRelation::new(
+                                node_key,
+                                WeightKind::Epistemic,
+                                Some(reference.weight),
+                            )
+                            .with_location(role_offset),
+                        );
+                    }
+                }
+                _ => {}
+            }
+
+            // Inline #tags, outside of code, links and the frontmatter.
+            match event.borrow() {
+                MdEvent::Start(MdTag::CodeBlock(_)) => in_code_block = true,
+                MdEvent::End(MdTagEnd::CodeBlock) => in_code_block = false,
+                MdEvent::Text(text)
+                    if !in_code_block && metadata_offset.is_none() && link_stack.is_empty() =>
+                {
+                    let preceded_by_word = self.content[..offset.start]
+                        .chars()
+                        .next_back()
+                        .is_some_and(char::is_alphanumeric);
+                    // Text with escapes or entities doesn't map byte-for-byte onto the source.
+                    let verbatim = self.content.get(offset.clone()) == Some(text.as_ref());
+                    for (idx, tag) in tag::parse_inline(text, preceded_by_word) {
+                        let location = offset.start + if verbatim { idx } else { 0 };
+                        Self::push_tag(&mut current, tag, location);
+                    }
+                }
+                _ => {}
+            }
+
             // log::debug!("[codec::md]: {:?}", event);
             match event.borrow() {
                 MdEvent::Start(MdTag::MetadataBlock(_)) => {
                     debug_assert!(current.accumulator.is_none());
                     current.accumulator = Some(String::new());
+                    metadata_offset = Some(offset.start);
                 }
                 MdEvent::End(MdTagEnd::MetadataBlock(_)) => {
                     let toml_string = current.accumulator.take().expect(
                         "to never encounter an end tag before a start tag and always initialize \
                          accum to Some in the start tag",
                     );
+                    let location = metadata_offset.take().unwrap_or_default();
 
                     match IRNode::from_str(&toml_string) {
                         Ok(mut proto) => {
                             current.merge(&mut proto);
+                            let tags = current
+                                .document
+                                .get("tags")
+                                .map(tag::from_frontmatter)
+                                .unwrap_or_default();
+                            for tag in tags {
+                                Self::push_tag(&mut current, tag, location);
+                            }
                         }
                         Err(e) => {
                             // Fallback to simple deserialization if TomlCodec fails
@@ -1856,6 +2460,7 @@ impl DocCodec for MdCodec {
         // Do NOT eagerly insert id from title for the final node either.
         // Title→id derivation is handled lazily by BeliefNode::id() (properties.rs).
         self.current_events.push((current, proto_events));
+        self.push_blocks(diagnostics);
         // tracing::debug!("Parsed a total of {} nodes", self.current_events.len());
 
         // panic!(
@@ -2680,6 +3285,87 @@ schema = "Document"
         assert_eq!(parts.user_words, Some("Just some words".to_string()));
     }
 
+    #[test]
+    fn test_parse_title_attribute_rel() {
+        let parts = parse_title_attribute("bref://abc123456789 {\"rel\":\"refutes\"} See also");
+        assert_eq!(parts.rel, Some("refutes".to_string()));
+        assert_eq!(parts.user_words, Some("See also".to_string()));
+
+        // A lone word is only a relation kind when it is one of the fixed title kinds, whatever
+        // else has been registered.
+        let parts = parse_title_attribute("elaborates");
+        assert_eq!(parts.rel, Some("elaborates".to_string()));
+        assert_eq!(parts.user_words, None);
+        let parts = parse_title_attribute("note");
+        assert_eq!(parts.rel, None);
+        assert_eq!(parts.user_words, Some("note".to_string()));
+        WeightKind::register("annotates").unwrap();
+        assert_eq!(parse_title_attribute("annotates").rel, None);
+        assert_eq!(parse_title_attribute("section").rel, None);
+    }
+
+    #[test]
+    fn test_parse_link_attributes() {
+        let (attributes, len) =
+            parse_link_attributes("{rel=contradicts confidence=0.7 note=\"two words\"} and more")
+                .unwrap();
+        assert_eq!(
+            attributes,
+            vec![
+                ("rel".to_string(), "contradicts".to_string()),
+                ("confidence".to_string(), "0.7".to_string()),
+                ("note".to_string(), "two words".to_string()),
+            ]
+        );
+        assert_eq!(
+            len,
+            "{rel=contradicts confidence=0.7 note=\"two words\"}".len()
+        );
+        assert!(parse_link_attributes("{#anchor}").is_none());
+        assert!(parse_link_attributes("{rel=open").is_none());
+        assert!(parse_link_attributes(" {rel=x}").is_none());
+        assert_eq!(link_attribute_value("0.7"), toml::Value::Float(0.7));
+        assert_eq!(link_attribute_value("3"), toml::Value::Integer(3));
+        assert_eq!(
+            link_attribute_value("yes"),
+            toml::Value::String("yes".into())
+        );
+    }
+
+    #[test]
+    fn test_link_semantics_unknown_rel() {
+        let content = "[x](y.md){rel=typo confidence=0.7}";
+        let mut weight = Weight::default();
+        let mut diagnostics = Vec::new();
+        let kind = MdCodec::link_semantics(
+            content,
+            "",
+            "[x](y.md)".len(),
+            Some(0),
+            &mut weight,
+            &mut diagnostics,
+        );
+        assert_eq!(kind, WeightKind::Epistemic);
+        assert_eq!(weight.get::<f64>("confidence"), Some(0.7));
+        assert!(matches!(
+            diagnostics.as_slice(),
+            [ParseDiagnostic::Warning { message, .. }] if message.contains("'typo'")
+        ));
+        assert!(WeightKind::try_from("typo").is_err());
+    }
+
+    #[test]
+    fn test_build_title_attribute_with_rel() {
+        let attr = build_title_attribute("bref://abc123456789", true, Some("supports"), None);
+        assert_eq!(
+            attr,
+            "bref://abc123456789 {\"auto_title\":true,\"rel\":\"supports\"}"
+        );
+        let parts = parse_title_attribute(&attr);
+        assert!(parts.auto_title);
+        assert_eq!(parts.rel, Some("supports".to_string()));
+    }
+
     #[test]
     fn test_parse_title_attribute_empty() {
         let parts = parse_title_attribute("");
@@ -2690,25 +3376,25 @@ schema = "Document"
 
     #[test]
     fn test_build_title_attribute_bref_only() {
-        let attr = build_title_attribute("bref://abc123456789", false, None);
+        let attr = build_title_attribute("bref://abc123456789", false, None, None);
         assert_eq!(attr, "bref://abc123456789");
     }
 
     #[test]
     fn test_build_title_attribute_with_auto_title() {
-        let attr = build_title_attribute("bref://abc123456789", true, None);
+        let attr = build_title_attribute("bref://abc123456789", true, None, None);
         assert_eq!(attr, "bref://abc123456789 {\"auto_title\":true}");
     }
 
     #[test]
     fn test_build_title_attribute_with_user_words() {
-        let attr = build_title_attribute("bref://abc123456789", false, Some("My Note"));
+        let attr = build_title_attribute("bref://abc123456789", false, None, Some("My Note"));
         assert_eq!(attr, "bref://abc123456789 My Note");
     }
 
     #[test]
     fn test_build_title_attribute_full() {
-        let attr = build_title_attribute("bref://abc123456789", true, Some("My Note"));
+        let attr = build_title_attribute("bref://abc123456789", true, None, Some("My Note"));
         assert_eq!(attr, "bref://abc123456789 {\"auto_title\":true} My Note");
     }
 
@@ -2754,6 +3440,7 @@ schema = "Document"
         let rebuilt = build_title_attribute(
             &format!("bref://{}", parts.bref.unwrap()),
             parts.auto_title,
+            parts.rel.as_deref(),
             parts.user_words.as_deref(),
         );
         assert_eq!(original, rebuilt);
@@ -3102,6 +3789,7 @@ title = "Link Test"
 
 Link to [another doc](./other.md "bref://doc123 auto title").
 Link with anchor [section link](docs/page.md#section-1 "bref://doc456").
+Link to a block [block link](docs/page.md#^claim-1 "bref://doc457").
 External .md link without bref [external](https://example.com/doc.md).
 Already HTML [html link](./page.html "bref://doc789").
 "#;
@@ -3133,6 +3821,11 @@ Already HTML [html link](./page.html "bref://doc789").
             _path,
             html_content
         );
+        // Block anchors are rendered without the caret
+        assert!(
+            html_content.contains("href=\"docs/page.html#claim-1\""),
+            "Expected href=\"docs/page.html#claim-1\" but got:\n{html_content}"
+        );
 
         // Verify .md links WITHOUT bref:// are NOT rewritten (we didn't parse them)
         assert!(
@@ -3157,6 +3850,194 @@ Already HTML [html link](./page.html "bref://doc789").
         );
     }
 
+    const MYST_DOC: &str = r#"# MyST Doc
+
+(setup)=
+## Setup
+
+See {ref}`the setup notes <setup>` and {doc}`guide/install`.
+
+```{note}
+Remember to read [the FAQ](faq.md).
+```
+
+```{figure} images/diagram.png
+:align: center
+:width: 80%
+
+The architecture.
+```
+
+```{toctree}
+:maxdepth: 2
+:hidden:
+
+intro
+Usage <usage>
+```
+
+```{unknown-thing}
+body
+```
+"#;
+
+    fn parse_myst_doc() -> (MdCodec, Vec<ParseDiagnostic>) {
+        use crate::codec::DocCodec;
+
+        let mut codec = MdCodec::new();
+        let proto = IRNode {
+            path: "docs/myst.md".to_string(),
+            ..Default::default()
+        };
+        let mut diagnostics = vec![];
+        codec
+            .parse(MYST_DOC, proto, &mut diagnostics)
+            .expect("Parse failed");
+        (codec, diagnostics)
+    }
+
+    #[test]
+    fn test_myst_directives_parse_to_typed_ir() {
+        init_logging();
+        let (codec, diagnostics) = parse_myst_doc();
+
+        let kinds: Vec<_> = codec.directives().iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DirectiveKind::Admonition,
+                DirectiveKind::Figure,
+                DirectiveKind::Toctree
+            ]
+        );
+
+        let figure = &codec.directives()[1];
+        assert_eq!(figure.argument, "images/diagram.png");
+        assert_eq!(
+            figure.options.get("align").map(String::as_str),
+            Some("center")
+        );
+        assert_eq!(figure.options.get("width").map(String::as_str), Some("80%"));
+        assert_eq!(figure.body.trim(), "The architecture.");
+
+        let toctree = &codec.directives()[2];
+        assert!(toctree.options.contains_key("hidden"));
+        let entries = toctree.toctree_entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].title.as_deref(), Some("Usage"));
+
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| format!("{d:?}").contains("Unknown MyST directive '{unknown-thing}'")),
+            "Expected an unknown directive warning, got {diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_myst_roles_and_directives_become_relations() {
+        init_logging();
+        let (codec, _diagnostics) = parse_myst_doc();
+        let nodes = codec.nodes();
+
+        let setup = nodes
+            .iter()
+            .find(|n| n.title().as_deref() == Some("Setup"))
+            .expect("Setup section");
+        assert_eq!(setup.id().as_deref(), Some("setup"));
+
+        let relations: Vec<_> = nodes.iter().flat_map(|n| n.upstream.iter()).collect();
+        let with_key = |key: NodeKey| {
+            relations
+                .iter()
+                .find(|r| r.key == key)
+                .unwrap_or_else(|| panic!("Missing relation to {key}: {relations:?}"))
+        };
+
+        let ref_weight = with_key(href_to_nodekey("id:setup"))
+            .weight
+            .clone()
+            .expect("role weight");
+        assert_eq!(ref_weight.get::<String>("role").as_deref(), Some("ref"));
+        assert_eq!(
+            ref_weight.get::<String>("title").as_deref(),
+            Some("the setup notes")
+        );
+
+        let doc_key = href_to_nodekey("guide/install.md").resolve_against("docs/myst.md");
+        let doc_weight = with_key(doc_key).weight.clone().expect("role weight");
+        assert_eq!(doc_weight.get::<String>("role").as_deref(), Some("doc"));
+
+        let figure_key = href_to_nodekey("images/diagram.png").resolve_against("docs/myst.md");
+        let figure_weight = with_key(figure_key).weight.clone().expect("figure weight");
+        assert_eq!(
+            figure_weight.get::<String>("directive").as_deref(),
+            Some("figure")
+        );
+        assert_eq!(
+            figure_weight.get::<String>("align").as_deref(),
+            Some("center")
+        );
+
+        let toc_key = href_to_nodekey("usage.md").resolve_against("docs/myst.md");
+        let toc_weight = with_key(toc_key).weight.clone().expect("toctree weight");
+        assert_eq!(toc_weight.get::<bool>("hidden"), Some(true));
+
+        // Links inside admonition bodies are tracked like ordinary links
+        with_key(href_to_nodekey("faq.md").resolve_against("docs/myst.md"));
+    }
+
+    #[test]
+    fn test_myst_source_round_trip_preserves_syntax() {
+        use crate::codec::DocCodec;
+
+        let (codec, _diagnostics) = parse_myst_doc();
+        let source = codec.generate_source().expect("source");
+        assert!(source.contains("(setup)="), "{source}");
+        assert!(
+            source.contains("See {ref}`the setup notes <setup>` and {doc}`guide/install`."),
+            "{source}"
+        );
+        assert!(
+            source.contains("{toctree}\n:maxdepth: 2\n:hidden:\n"),
+            "{source}"
+        );
+        assert!(
+            source.contains("{figure} images/diagram.png\n:align: center"),
+            "{source}"
+        );
+    }
+
+    #[test]
+    fn test_myst_html_rendering() {
+        use crate::codec::DocCodec;
+
+        let (codec, _diagnostics) = parse_myst_doc();
+        let fragments = codec.generate_html().expect("HTML generation failed");
+        let (_path, html) = &fragments[0];
+
+        assert!(
+            !html.contains("(setup)="),
+            "Targets are not rendered:\n{html}"
+        );
+        assert!(
+            html.contains("href=\"#setup\""),
+            "Unresolved ref roles fall back to the local anchor:\n{html}"
+        );
+        assert!(html.contains("href=\"guide/install.html\""), "{html}");
+        assert!(html.contains("class=\"admonition note\""), "{html}");
+        assert!(html.contains("<figure"), "{html}");
+        assert!(html.contains("images/diagram.png"), "{html}");
+        assert!(
+            !html.contains("toctree"),
+            "Hidden toctrees render nothing:\n{html}"
+        );
+        assert!(
+            html.contains("unknown-thing"),
+            "Unknown directives stay code blocks:\n{html}"
+        );
+    }
+
     // Note: Integration test for static asset tracking needed with full GraphBuilder flow
     // MdCodec::parse only creates IRNodes; relations are created by GraphBuilder
 }
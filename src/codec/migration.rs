@@ -0,0 +1,492 @@
+//! Versioned schema migrations
+//!
+//! A [`SchemaDefinition`] carries a `version`, starting at 1, and the [`SchemaMigration`]s that
+//! upgrade a document from each older version to the next. Documents record the version they
+//! conform to in their `schema_version` field; a document without one is at version 1.
+//!
+//! Migrations run when a document's schema is traversed during parsing. An upgraded document
+//! records the new `schema_version`, and write-back persists the upgraded frontmatter.
+//! [`plan_migrations`] reports which documents would change without parsing or writing them, and
+//! [`apply_migrations`] writes back just those documents.
+//!
+//! Migrations are either declarative rules (rename, move, default) or functions registered in
+//! code:
+//!
+//! ```rust
+//! use noet_core::codec::{
+//!     migration::{MigrationRule, SchemaMigration},
+//!     schema_registry::SchemaDefinition,
+//!     SCHEMAS,
+//! };
+//!
+//! SCHEMAS.register(
+//!     "my_app.task".to_string(),
+//!     SchemaDefinition {
+//!         version: 3,
+//!         migrations: vec![
+//!             SchemaMigration::new(1, vec![MigrationRule::rename("owner", "assignee")]),
+//!             SchemaMigration::new(
+//!                 2,
+//!                 vec![
+//!                     MigrationRule::relocate("due", "dates.due"),
+//!                     MigrationRule::custom(|doc| {
+//!                         Ok(doc.remove("legacy_flag").is_some())
+//!                     }),
+//!                 ],
+//!             ),
+//!         ],
+//!         ..SchemaDefinition::default()
+//!     },
+//! );
+//! ```
+//!
+//! Schema files declare the same through a top-level `version` and `[[migrations]]` tables (see
+//! [`SchemaRegistry::load_network`](super::schema_registry::SchemaRegistry::load_network)):
+//!
+//! ```toml
+//! version = 2
+//!
+//! [[migrations]]
+//! from = 1
+//! rename = { owner = "assignee" }
+//! move = { due = "dates.due" }
+//! default = { priority = 3 }
+//! ```
+
+use crate::{
+    codec::{
+        compiler::{DocumentCompiler, ParseResult},
+        network::iter_net_docs,
+        proto_index::ProtoIndex,
+        schema_registry::SchemaDefinition,
+        CODECS, SCHEMAS,
+    },
+    error::BuildonomyError,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use toml::{value::Table as TomlTable, Value as TomlValue};
+
+/// Frontmatter field recording the schema version a document conforms to.
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// A migration function. Returns whether it changed the document, or why it could not.
+pub type MigrationFn = Arc<dyn Fn(&mut TomlTable) -> Result<bool, String> + Send + Sync>;
+
+/// One change a [`SchemaMigration`] makes to a document. Field paths are dotted (`dates.due`).
+#[derive(Clone)]
+pub enum MigrationRule {
+    /// Rename a field within its table, keeping its value.
+    Rename { from: String, to: String },
+    /// Move a field to another path, creating intermediate tables as needed.
+    Move { from: String, to: String },
+    /// Set a field the document does not have.
+    Default { field: String, value: TomlValue },
+    /// Run a function over the whole document.
+    Custom(MigrationFn),
+}
+
+impl MigrationRule {
+    pub fn rename(from: impl Into<String>, to: impl Into<String>) -> Self {
+        MigrationRule::Rename {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    pub fn relocate(from: impl Into<String>, to: impl Into<String>) -> Self {
+        MigrationRule::Move {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    pub fn default_value(field: impl Into<String>, value: TomlValue) -> Self {
+        MigrationRule::Default {
+            field: field.into(),
+            value,
+        }
+    }
+
+    pub fn custom(
+        migration: impl Fn(&mut TomlTable) -> Result<bool, String> + Send + Sync + 'static,
+    ) -> Self {
+        MigrationRule::Custom(Arc::new(migration))
+    }
+
+    fn apply(&self, doc: &mut TomlTable) -> Result<bool, String> {
+        match self {
+            MigrationRule::Rename { from, to } => {
+                let to = match from.rsplit_once('.') {
+                    Some((parent, _)) => format!("{parent}.{to}"),
+                    None => to.clone(),
+                };
+                move_field(doc, from, &to)
+            }
+            MigrationRule::Move { from, to } => move_field(doc, from, to),
+            MigrationRule::Default { field, value } => {
+                if get_field(doc, field).is_some() {
+                    return Ok(false);
+                }
+                insert_field(doc, field, value.clone())?;
+                Ok(true)
+            }
+            MigrationRule::Custom(migration) => migration(doc),
+        }
+    }
+}
+
+impl fmt::Debug for MigrationRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationRule::Rename { from, to } => write!(f, "Rename({from} -> {to})"),
+            MigrationRule::Move { from, to } => write!(f, "Move({from} -> {to})"),
+            MigrationRule::Default { field, value } => write!(f, "Default({field} = {value})"),
+            MigrationRule::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Upgrades a document from `from_version` to `from_version + 1` by applying its rules in order.
+#[derive(Debug, Clone)]
+pub struct SchemaMigration {
+    pub from_version: u32,
+    pub rules: Vec<MigrationRule>,
+}
+
+impl SchemaMigration {
+    pub fn new(from_version: u32, rules: Vec<MigrationRule>) -> Self {
+        SchemaMigration {
+            from_version,
+            rules,
+        }
+    }
+
+    pub(crate) fn apply(&self, doc: &mut TomlTable) -> Result<(), String> {
+        for rule in self.rules.iter() {
+            rule.apply(doc)?;
+        }
+        Ok(())
+    }
+}
+
+/// The schema version a document conforms to.
+pub fn document_version(doc: &TomlTable) -> u32 {
+    doc.get(SCHEMA_VERSION_FIELD)
+        .and_then(|version| version.as_integer())
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(1)
+}
+
+fn get_field<'a>(doc: &'a TomlTable, path: &str) -> Option<&'a TomlValue> {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (
+            parent
+                .split('.')
+                .try_fold(doc, |table, key| table.get(key)?.as_table())?,
+            key,
+        ),
+        None => (doc, path),
+    };
+    parent.get(key)
+}
+
+fn remove_field(doc: &mut TomlTable, path: &str) -> Option<TomlValue> {
+    match path.split_once('.') {
+        Some((key, rest)) => remove_field(doc.get_mut(key)?.as_table_mut()?, rest),
+        None => doc.remove(path),
+    }
+}
+
+fn insert_field(doc: &mut TomlTable, path: &str, value: TomlValue) -> Result<(), String> {
+    match path.split_once('.') {
+        Some((key, rest)) => {
+            let child = doc
+                .entry(key.to_string())
+                .or_insert_with(|| TomlValue::Table(TomlTable::new()));
+            let TomlValue::Table(child) = child else {
+                return Err(format!("'{key}' is not a table"));
+            };
+            insert_field(child, rest, value)
+        }
+        None => {
+            doc.insert(path.to_string(), value);
+            Ok(())
+        }
+    }
+}
+
+fn move_field(doc: &mut TomlTable, from: &str, to: &str) -> Result<bool, String> {
+    if from == to || get_field(doc, from).is_none() {
+        return Ok(false);
+    }
+    if get_field(doc, to).is_some() {
+        return Err(format!(
+            "cannot move '{from}' to '{to}', which is already set"
+        ));
+    }
+    let value = remove_field(doc, from).expect("field checked above");
+    insert_field(doc, to, value)?;
+    Ok(true)
+}
+
+/// A migration as written in a schema file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct MigrationSpec {
+    from: u32,
+    rename: BTreeMap<String, String>,
+    #[serde(rename = "move")]
+    relocate: BTreeMap<String, String>,
+    default: BTreeMap<String, TomlValue>,
+}
+
+impl MigrationSpec {
+    pub(crate) fn into_migration(self) -> Result<SchemaMigration, BuildonomyError> {
+        if self.from == 0 {
+            return Err(BuildonomyError::Serialization(
+                "Migration has no 'from' version (versions start at 1)".to_string(),
+            ));
+        }
+        let rules = self
+            .rename
+            .into_iter()
+            .map(|(from, to)| MigrationRule::rename(from, to))
+            .chain(
+                self.relocate
+                    .into_iter()
+                    .map(|(from, to)| MigrationRule::relocate(from, to)),
+            )
+            .chain(
+                self.default
+                    .into_iter()
+                    .map(|(field, value)| MigrationRule::default_value(field, value)),
+            )
+            .collect();
+        Ok(SchemaMigration::new(self.from, rules))
+    }
+}
+
+/// A document that migrations would upgrade.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingMigration {
+    pub path: PathBuf,
+    pub schema: String,
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Top-level frontmatter fields the upgrade adds, removes or changes.
+    pub changed_fields: Vec<String>,
+}
+
+/// Find the documents under `root` whose schema migrations would upgrade them, without parsing
+/// or writing anything. Loads the schema files of every network under `root` first.
+pub fn plan_migrations(root: &Path) -> Result<Vec<PendingMigration>, BuildonomyError> {
+    let mut pending = Vec::new();
+    for network_dir in ProtoIndex::discover_network_dirs(root) {
+        for (path, e) in SCHEMAS.load_network(&network_dir) {
+            tracing::warn!("[plan_migrations] Failed to load schema file {path:?}: {e}");
+        }
+        for path in iter_net_docs(&network_dir) {
+            if !path.is_file() {
+                continue;
+            }
+            let Some(factory) = CODECS.path_get(&path) else {
+                continue;
+            };
+            let Some(proto) = factory().proto(&path)? else {
+                continue;
+            };
+            let Some(schema) = proto
+                .document
+                .get("schema")
+                .and_then(|schema| schema.as_str())
+                .map(|schema| schema.to_string())
+            else {
+                continue;
+            };
+            let Some(schema_def) = SCHEMAS.get_for(&schema, &path) else {
+                continue;
+            };
+            let before = toml::from_str::<TomlTable>(&proto.document.to_string())?;
+            let mut after = before.clone();
+            let Some(from_version) = schema_def
+                .migrate(&mut after)
+                .map_err(|e| BuildonomyError::Codec(format!("{path:?}: schema '{schema}' {e}")))?
+            else {
+                continue;
+            };
+            let mut changed_fields = before
+                .keys()
+                .chain(after.keys())
+                .filter(|key| before.get(*key) != after.get(*key))
+                .cloned()
+                .collect::<Vec<_>>();
+            changed_fields.sort();
+            changed_fields.dedup();
+            pending.push(PendingMigration {
+                path,
+                schema,
+                from_version,
+                to_version: schema_def.version,
+                changed_fields,
+            });
+        }
+    }
+    Ok(pending)
+}
+
+/// Parse the networks under `root` and write back the `pending` documents, upgraded. Every other
+/// file is left as it is, even where parsing would rewrite it (to inject a BID, say).
+pub async fn apply_migrations(
+    root: &Path,
+    pending: &[PendingMigration],
+) -> Result<Vec<ParseResult>, BuildonomyError> {
+    // The migrated graph itself is not needed, only the upgraded sources.
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut compiler = DocumentCompiler::new(root, Some(tx), None, false)?;
+    let cache = compiler.builder().doc_bb().clone();
+    let results = compiler.parse_all(cache, true).await?;
+
+    let targets = pending
+        .iter()
+        .filter_map(|migration| migration.path.canonicalize().ok())
+        .collect::<BTreeSet<_>>();
+    let mut written = BTreeSet::new();
+    // A document parsed more than once is written as it was last parsed.
+    for result in results.iter().rev() {
+        let Ok(path) = result.path.canonicalize() else {
+            continue;
+        };
+        if !targets.contains(&path) || !written.insert(path.clone()) {
+            continue;
+        }
+        if let Some(contents) = result.rewritten_content.as_ref() {
+            tokio::fs::write(&path, contents).await?;
+        }
+    }
+    Ok(results)
+}
+
+/// Upgrade `doc` to `schema_def.version`. Returns the version it was at if it was upgraded.
+pub(crate) fn migrate(
+    schema_def: &SchemaDefinition,
+    doc: &mut TomlTable,
+) -> Result<Option<u32>, String> {
+    let from_version = document_version(doc);
+    if from_version >= schema_def.version {
+        if from_version > schema_def.version {
+            tracing::warn!(
+                "[migrate] Document is at schema version {from_version}, newer than the \
+                 registered version {}",
+                schema_def.version
+            );
+        }
+        return Ok(None);
+    }
+    for version in from_version..schema_def.version {
+        for migration in schema_def
+            .migrations
+            .iter()
+            .filter(|migration| migration.from_version == version)
+        {
+            migration
+                .apply(doc)
+                .map_err(|e| format!("migration from version {version} failed: {e}"))?;
+        }
+    }
+    doc.insert(
+        SCHEMA_VERSION_FIELD.to_string(),
+        TomlValue::Integer(schema_def.version as i64),
+    );
+    Ok(Some(from_version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(source: &str) -> TomlTable {
+        toml::from_str(source).unwrap()
+    }
+
+    fn task_schema() -> SchemaDefinition {
+        SchemaDefinition {
+            version: 3,
+            migrations: vec![
+                SchemaMigration::new(
+                    1,
+                    vec![
+                        MigrationRule::rename("owner", "assignee"),
+                        MigrationRule::default_value("priority", TomlValue::Integer(3)),
+                    ],
+                ),
+                SchemaMigration::new(
+                    2,
+                    vec![
+                        MigrationRule::relocate("due", "dates.due"),
+                        MigrationRule::custom(|doc| Ok(doc.remove("legacy").is_some())),
+                    ],
+                ),
+            ],
+            ..SchemaDefinition::default()
+        }
+    }
+
+    #[test]
+    fn test_migrate_step_by_step() {
+        let schema = task_schema();
+
+        let mut v1 = doc("owner = \"ana\"\ndue = \"2024-01-01\"\nlegacy = true\n");
+        assert_eq!(migrate(&schema, &mut v1), Ok(Some(1)));
+        assert_eq!(
+            v1,
+            doc("assignee = \"ana\"\npriority = 3\nschema_version = 3\n\
+                 [dates]\ndue = \"2024-01-01\"\n")
+        );
+
+        // Version 2 documents only take the second step.
+        let mut v2 = doc("schema_version = 2\nowner = \"ana\"\ndue = \"2024-01-01\"\n");
+        assert_eq!(migrate(&schema, &mut v2), Ok(Some(2)));
+        assert_eq!(v2.get("owner"), Some(&TomlValue::String("ana".to_string())));
+        assert!(v2.get("priority").is_none());
+
+        let mut current = doc("schema_version = 3\nowner = \"ana\"\n");
+        assert_eq!(migrate(&schema, &mut current), Ok(None));
+        assert_eq!(current, doc("schema_version = 3\nowner = \"ana\"\n"));
+    }
+
+    #[test]
+    fn test_migrate_conflict() {
+        let mut conflicting = doc("due = 1\n[dates]\ndue = 2\n");
+        let result = migrate(&task_schema(), &mut conflicting);
+        assert_eq!(
+            result,
+            Err(
+                "migration from version 2 failed: cannot move 'due' to 'dates.due', which is \
+                 already set"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_migration_spec() {
+        let spec: MigrationSpec = toml::from_str(
+            "from = 1\nrename = { owner = \"assignee\" }\nmove = { due = \"dates.due\" }\n\
+             default = { priority = 3 }\n",
+        )
+        .unwrap();
+        let migration = spec.into_migration().unwrap();
+        assert_eq!(migration.from_version, 1);
+        assert_eq!(
+            format!("{:?}", migration.rules),
+            "[Rename(owner -> assignee), Move(due -> dates.due), Default(priority = 3)]"
+        );
+        assert!(MigrationSpec::default().into_migration().is_err());
+    }
+}
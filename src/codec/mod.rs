@@ -48,8 +48,19 @@
 //!
 //! ## Built-in Codecs
 //!
-//! - **Markdown** (`.md`) - via [`md::MdCodec`]
+//! - **Markdown** (`.md`) - via [`md::MdCodec`], including MyST directives and roles ([`myst`]),
+//!   Obsidian-style wikilinks and embeds ([`wikilink`]), `#tags` ([`tag`]) and `^block`
+//!   references ([`block`])
 //! - **NetworkCodec** (`index.md`) - via [`network::NetworkCodec`]
+//! - **TOML** (`.toml`) - via [`data::TomlCodec`]
+//! - **JSON** (`.json`) - via [`data::JsonCodec`]
+//! - **YAML** (`.yaml`, `.yml`) - via [`data::YamlCodec`]
+//! - **Org-mode** (`.org`) - via [`org::OrgCodec`]
+//! - **Jupyter notebooks** (`.ipynb`) - via [`ipynb::IpynbCodec`]
+//! - **Rust sources** (`.rs`) - doc comments via [`rustdoc::RustDocCodec`]
+//!
+//! Formats handled by an out-of-tree executable can be registered from a config file with
+//! [`external::register_external_codecs`]; see [`external`] for the stdio protocol.
 //!
 //! Register custom codecs via [`CodecMap::insert_codec`] (by stem/extension):
 //!
@@ -113,26 +124,62 @@
 //!
 //! ## Schema Registration
 //!
-//! Schemas define how TOML fields map to graph edges. Register custom schemas via [`SCHEMAS`]:
+//! Schemas define how TOML fields map to graph edges, and which typed fields a document's payload
+//! carries. Register custom schemas via [`SCHEMAS`]:
 //!
 //! ```rust
-//! use noet_core::codec::{SCHEMAS, schema_registry::{SchemaDefinition, GraphField, EdgeDirection}};
+//! use noet_core::codec::{
+//!     SCHEMAS,
+//!     schema_registry::{EdgeDirection, FieldDefinition, FieldType, GraphField, SchemaDefinition},
+//! };
 //! use noet_core::properties::WeightKind;
 //!
 //! SCHEMAS.register(
 //!     "my_app.task".to_string(),
 //!     SchemaDefinition {
 //!         graph_fields: vec![GraphField {
-//!             field_name: "dependencies",
-//!             direction: EdgeDirection::Downstream,
-//!             weight_kind: WeightKind::Pragmatic,
-//!             required: false,
-//!             payload_fields: vec!["notes"],
+//!             payload_fields: vec!["notes".to_string()],
+//!             ..GraphField::new("dependencies", EdgeDirection::Downstream, WeightKind::Pragmatic)
 //!         }],
+//!         fields: vec![
+//!             FieldDefinition {
+//!                 required: true,
+//!                 enum_values: vec!["open".to_string(), "done".to_string()],
+//!                 ..FieldDefinition::new("status", FieldType::String)
+//!             },
+//!             FieldDefinition {
+//!                 default: Some(toml::Value::Integer(3)),
+//!                 ..FieldDefinition::new("priority", FieldType::Integer)
+//!             },
+//!         ],
+//!         ..SchemaDefinition::default()
 //!     },
 //! );
 //! ```
 //!
+//! Documents declaring `schema = "my_app.task"` are checked as they are parsed: missing or
+//! mistyped fields are reported as errors and values outside `enum_values` or `pattern` as
+//! warnings, located at the offending frontmatter line. Defaults fill absent fields in the node
+//! payload; the source is not rewritten.
+//!
+//! A network can also define schemas without code. The compiler loads every `.toml` or `.json`
+//! file in the network's `schemas/` directory, plus those listed in the `schemas` field of its
+//! `index.md`. These schemas are scoped to the network and its subnets, where they shadow global
+//! schemas of the same name. The `WatchService` reloads them when they change. A TOML schema file
+//! holds `[fields.<name>]` tables (`type`, `required`, `enum`, `default`, `pattern`) and
+//! `[graph_fields.<name>]` tables (`direction`, `weight_kind`, `payload_fields`, `target_schemas`,
+//! `max_count`, `acyclic`). A `weight_kind` other than the built-in kinds is registered as a new
+//! relation kind when the schema loads. JSON Schema files are also supported. See
+//! `schema_registry::SchemaRegistry::load_network`.
+//!
+//! Schemas are versioned. Documents record the version they conform to in `schema_version`,
+//! and older documents are upgraded step by step as they are parsed; see [`migration`].
+//!
+//! Graph fields can also constrain the relations they create. Once a document is finalized,
+//! each resolved relation is checked against its field's `target_schemas` (the schemas the
+//! related node may declare), `max_count` and `acyclic` settings. Violations are reported as
+//! errors at the link, or at the field's frontmatter line when the link has no offset.
+//!
 //! ## Architecture Details
 //!
 //! For detailed information about the parsing architecture, including:
@@ -156,7 +203,14 @@ use std::{path::Path, result::Result, sync::Arc, time::Duration};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::{
     beliefbase::BeliefContext,
-    codec::{md::MdCodec, network::NetworkCodec},
+    codec::{
+        data::{JsonCodec, TomlCodec, YamlCodec},
+        ipynb::IpynbCodec,
+        md::MdCodec,
+        network::NetworkCodec,
+        org::OrgCodec,
+        rustdoc::RustDocCodec,
+    },
     error::BuildonomyError,
     paths::os_path_to_string,
     properties::BeliefNode,
@@ -175,19 +229,41 @@ pub mod assets;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod belief_ir;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod block;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod builder;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod compiler;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod data;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod diagnostic;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod edit;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod external;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ipynb;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod md;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod migration;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod myst;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod network;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod org;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod proto_index;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod rustdoc;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod schema_registry;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tag;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod wikilink;
 
 // Re-export for backward compatibility
 #[cfg(not(target_arch = "wasm32"))]
@@ -225,7 +301,8 @@ pub static CODECS: Lazy<CodecMap> = Lazy::new(CodecMap::create);
 pub static CODECS: Lazy<CodecMap> = Lazy::new(CodecMap::create);
 
 /// List of built-in codec extensions (synchronized between WASM and non-WASM builds).
-pub const BUILTIN_EXTENSIONS: &[&str] = &["md"];
+pub const BUILTIN_EXTENSIONS: &[&str] =
+    &["md", "toml", "json", "yaml", "yml", "org", "ipynb", "rs"];
 
 /// Codec registration entry: (optional_stem, optional_extension, factory).
 ///
@@ -248,6 +325,13 @@ pub trait DocCodec: Sync {
     /// Parse a path into a proto node by reading the metadata frontmatter (if any)
     fn proto(&self, path: &Path) -> Result<Option<IRNode>, BuildonomyError>;
 
+    /// Whether the file at `path`, which this codec is registered for, is a document a network
+    /// should parse. Codecs for general-purpose formats use this to leave alone the files that
+    /// only share their extension, such as a `package.json`. Defaults to `true`.
+    fn claims(&self, _path: &Path) -> bool {
+        true
+    }
+
     fn parse(
         &mut self,
         // The source content to be parsed by the DocCodec implementation
@@ -459,15 +543,55 @@ impl CodecMap {
         self.get(&ap)
     }
 
+    /// Get the codec factory for the file at `path` if it is a document a network should parse:
+    /// [`CodecMap::path_get`], narrowed by the codec's [`DocCodec::claims`].
+    pub fn document_get(&self, path: &std::path::Path) -> Option<CodecFactory> {
+        self.path_get(path)
+            .filter(|factory| !path.is_file() || factory().claims(path))
+    }
+
     /// Create a new `CodecMap` with built-in codecs registered.
     ///
     /// Built-in codecs:
     /// - Markdown: registered by extension `.md`
     /// - NetworkCodec: registered by constand file name `index.md`
+    /// - TOML, JSON and YAML data files: registered by extensions `.toml`, `.json`, `.yaml` and
+    ///   `.yml`. JSON and YAML files are only claimed when they carry noet metadata (see
+    ///   [`data`]).
+    /// - Org-mode: registered by extension `.org`
+    /// - Jupyter notebooks: registered by extension `.ipynb`
+    /// - Rust sources: registered by extension `.rs`
     pub fn create() -> Self {
         CodecMap(Arc::new(RwLock::new(vec![
             // Markdown files by extension
             (None, Some("md".to_string()), || Box::new(MdCodec::new())),
+            // Standalone data documents by extension
+            (
+                None,
+                Some("toml".to_string()),
+                || Box::new(TomlCodec::new()),
+            ),
+            (
+                None,
+                Some("json".to_string()),
+                || Box::new(JsonCodec::new()),
+            ),
+            (
+                None,
+                Some("yaml".to_string()),
+                || Box::new(YamlCodec::new()),
+            ),
+            (None, Some("yml".to_string()), || Box::new(YamlCodec::new())),
+            // Org-mode documents by extension
+            (None, Some("org".to_string()), || Box::new(OrgCodec::new())),
+            // Jupyter notebooks by extension
+            (None, Some("ipynb".to_string()), || {
+                Box::new(IpynbCodec::new())
+            }),
+            // Rust doc comments by extension
+            (None, Some("rs".to_string()), || {
+                Box::new(RustDocCodec::new())
+            }),
             // Network files by constant filename index.md
             (Some("index".to_string()), Some("md".to_string()), || {
                 Box::new(NetworkCodec::default())
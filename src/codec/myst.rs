@@ -0,0 +1,880 @@
+//! MyST directive and role support for [`MdCodec`](super::md::MdCodec).
+//!
+//! MyST extends CommonMark with two extension points, both of which pulldown-cmark already
+//! tokenizes into recognisable event shapes (see
+//! `docs/project/trades/TRADE_55_MYST_DIRECTIVE_SYNTAX.md` for the empirical analysis):
+//!
+//! - **Directives** use the backtick-fence form. `` ````{note} `` arrives as
+//!   `Start(CodeBlock(Fenced("{note}")))`, the body as a single `Text` event. The colon-fence
+//!   form (`:::{note}`) is not supported: under `ENABLE_DEFINITION_LIST` it is parsed as a
+//!   definition list and cannot round-trip. Authors should use 4 backticks for top-level
+//!   directives; a 3-backtick fence is normalised to 4 on the first write-back.
+//! - **Roles** (`` {ref}`label` ``) arrive as a `Text` event ending in `{name}` immediately
+//!   followed by a `Code` event holding the role content.
+//! - **Targets** (`(label)=` on the line before a heading) give that heading an explicit anchor,
+//!   so Sphinx-style `{ref}` labels resolve without rewriting the source.
+//!
+//! `MdCodec::parse` keeps the original events (and source ranges) untouched, so write-back is
+//! byte-for-byte. Directives and roles are only interpreted twice: at parse time, where they
+//! become typed [`Directive`]s and `NodeKey` relations, and at render time, where
+//! [`Renderer`] replaces them with HTML.
+//!
+//! Supported directives are listed in [`DirectiveKind`]; supported roles in [`RoleKind`].
+//! Unknown directives are passed through as code blocks with a warning diagnostic; unknown roles
+//! are passed through silently (many Sphinx roles, e.g. `{abbr}`, are presentational only).
+
+use pulldown_cmark::{
+    CodeBlockKind, CowStr, Event as MdEvent, LinkType, Parser as MdParser, Tag as MdTag,
+    TagEnd as MdTagEnd,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    codec::{
+        md::{buildonomy_md_options, rewrite_href_to_html},
+        network::NETWORK_CHILDREN_MARKER,
+        CODECS,
+    },
+    nodekey::{href_to_nodekey, NodeKey},
+    paths::{to_anchor, AnchorPath},
+    properties::Weight,
+};
+
+/// Admonition directive names, rendered as `<div class="admonition {name}">`.
+pub const ADMONITIONS: &[&str] = &[
+    "admonition",
+    "attention",
+    "caution",
+    "danger",
+    "error",
+    "hint",
+    "important",
+    "note",
+    "seealso",
+    "tip",
+    "warning",
+];
+
+/// Maximum `{include}` nesting depth when rendering, guarding against include cycles.
+const MAX_RENDER_DEPTH: usize = 8;
+
+/// The directives noet understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectiveKind {
+    /// `{note}`, `{warning}`, ..., and the generic `{admonition} Title`. See [`ADMONITIONS`].
+    Admonition,
+    /// `{figure} path/to/image.png` with an optional caption body.
+    Figure,
+    /// `{include} path/to/file.md`, inlined at render time.
+    Include,
+    /// `{toctree}` listing documents, one per line.
+    Toctree,
+    /// `{network_children}`: placement marker for the generated child listing of a network
+    /// index. Rendered as [`NETWORK_CHILDREN_MARKER`].
+    NetworkChildren,
+}
+
+impl DirectiveKind {
+    /// Map a directive name to its kind, returning `None` for unknown directives.
+    pub fn lookup(directive_name: &str) -> Option<DirectiveKind> {
+        match directive_name {
+            name if ADMONITIONS.contains(&name) => Some(DirectiveKind::Admonition),
+            "figure" => Some(DirectiveKind::Figure),
+            "include" => Some(DirectiveKind::Include),
+            "toctree" => Some(DirectiveKind::Toctree),
+            "network_children" => Some(DirectiveKind::NetworkChildren),
+            // "toc" => Some(DirectiveKind::Toc), // TODO: in-page table of contents
+            _ => None,
+        }
+    }
+}
+
+/// Given a `Fenced` code block info string, return `(name, argument)` if it has the
+/// `{name}` or `{name} argument` directive form.
+pub fn parse_directive_info(info: &str) -> Option<(&str, &str)> {
+    let rest = info.trim().strip_prefix('{')?;
+    let close = rest.find('}')?;
+    let name = rest[..close].trim();
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        return None;
+    }
+    Some((name, rest[close + 1..].trim()))
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')
+}
+
+/// Split leading directive options off a directive body.
+///
+/// Both MyST option forms are accepted: `:key: value` lines, or a `---` delimited YAML block.
+/// Options without a value (`:hidden:`) map to an empty string.
+fn split_options(raw_body: &str) -> (BTreeMap<String, String>, String) {
+    let mut options = BTreeMap::new();
+    let lines = raw_body.lines().collect::<Vec<_>>();
+    let mut body_start = 0;
+
+    if lines.first().map(|line| line.trim()) == Some("---") {
+        if let Some(end) = lines.iter().skip(1).position(|line| line.trim() == "---") {
+            let yaml = lines[1..end + 1].join("\n");
+            if let Ok(serde_yaml::Value::Mapping(mapping)) = serde_yaml::from_str(&yaml) {
+                for (key, value) in mapping {
+                    let Some(key) = key.as_str() else { continue };
+                    let value = match value {
+                        serde_yaml::Value::String(value) => value,
+                        serde_yaml::Value::Null => String::new(),
+                        other => serde_yaml::to_string(&other)
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string(),
+                    };
+                    options.insert(key.to_string(), value);
+                }
+            }
+            body_start = end + 2;
+        }
+    } else {
+        for line in lines.iter() {
+            let Some(rest) = line.trim().strip_prefix(':') else {
+                break;
+            };
+            let Some((key, value)) = rest.split_once(':') else {
+                break;
+            };
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                break;
+            }
+            options.insert(key.to_string(), value.trim().to_string());
+            body_start += 1;
+        }
+    }
+
+    let body = lines[body_start.min(lines.len())..]
+        .iter()
+        .skip_while(|line| line.trim().is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+    (options, body)
+}
+
+/// Convert a Sphinx docname (`../guide/intro`) to a document href (`../guide/intro.md`).
+///
+/// Docnames are extensionless; targets that already carry a registered codec extension, and
+/// external URLs, are returned unchanged.
+pub fn docname_to_href(docname: &str) -> String {
+    let docname = docname.trim();
+    if docname.contains("://") || docname.starts_with("mailto:") {
+        return docname.to_string();
+    }
+    let ext = AnchorPath::from(docname).ext().to_string();
+    if !ext.is_empty() && CODECS.extensions().contains(&ext) {
+        docname.to_string()
+    } else {
+        format!("{docname}.md")
+    }
+}
+
+/// Split the MyST/Sphinx explicit title form `Title <target>` into `(Some(title), target)`.
+fn split_explicit_title(content: &str) -> (Option<String>, String) {
+    let content = content.trim();
+    if let Some(open) = content.rfind(" <").filter(|_| content.ends_with('>')) {
+        let title = content[..open].trim();
+        let target = content[open + 2..content.len() - 1].trim();
+        if !title.is_empty() && !target.is_empty() {
+            return (Some(title.to_string()), target.to_string());
+        }
+    }
+    (None, content.to_string())
+}
+
+/// A reference to another node declared by a directive or role.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    /// Link target, in the same form a Markdown link destination would take.
+    pub href: String,
+    /// Relation weight payload: the directive/role name, its options, and any explicit title.
+    pub weight: Weight,
+}
+
+/// An entry of a `{toctree}` directive body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    pub title: Option<String>,
+    pub target: String,
+}
+
+/// A parsed MyST directive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Directive {
+    pub kind: DirectiveKind,
+    pub name: String,
+    /// Text following `{name}` on the opening fence line.
+    pub argument: String,
+    /// `:key: value` (or YAML) options at the top of the body.
+    pub options: BTreeMap<String, String>,
+    /// Body with the options removed.
+    pub body: String,
+    /// Byte offset of the opening fence in the source document.
+    pub location: Option<usize>,
+}
+
+impl Directive {
+    pub fn new(kind: DirectiveKind, name: &str, argument: &str, raw_body: &str) -> Directive {
+        let (options, body) = split_options(raw_body);
+        Directive {
+            kind,
+            name: name.to_string(),
+            argument: argument.to_string(),
+            options,
+            body,
+            location: None,
+        }
+    }
+
+    /// Parse a fenced code block info string and body, returning `None` when the info string is
+    /// not a directive or names an unknown directive.
+    pub fn parse(info: &str, raw_body: &str) -> Option<Directive> {
+        let (name, argument) = parse_directive_info(info)?;
+        let kind = DirectiveKind::lookup(name)?;
+        Some(Directive::new(kind, name, argument, raw_body))
+    }
+
+    pub fn with_location(mut self, byte_offset: usize) -> Self {
+        self.location = Some(byte_offset);
+        self
+    }
+
+    /// Title shown for an admonition: the argument for `{admonition}`, otherwise the name.
+    pub fn admonition_title(&self) -> String {
+        match self.name.as_str() {
+            "admonition" => self.argument.clone(),
+            "seealso" => "See also".to_string(),
+            name => {
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    /// Entries of a `{toctree}` body. Glob patterns and the special `self` entry are skipped.
+    pub fn toctree_entries(&self) -> Vec<TocEntry> {
+        if self.kind != DirectiveKind::Toctree {
+            return Vec::new();
+        }
+        self.body
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.contains('*'))
+            .map(split_explicit_title)
+            .filter(|(_, target)| target != "self")
+            .map(|(title, target)| TocEntry { title, target })
+            .collect()
+    }
+
+    fn weight(&self) -> Weight {
+        let mut weight = Weight::default();
+        weight.set::<String>("directive", self.name.clone()).ok();
+        for (key, option) in self.options.iter() {
+            if option.is_empty() {
+                weight.set(key, true).ok();
+            } else {
+                weight.set::<String>(key, option.clone()).ok();
+            }
+        }
+        weight
+    }
+
+    /// Nodes this directive refers to: the figure image, the included file, toctree entries, and
+    /// any links or roles inside admonition and figure bodies.
+    pub fn references(&self) -> Vec<Reference> {
+        let mut references = Vec::new();
+        match self.kind {
+            DirectiveKind::Figure | DirectiveKind::Include if !self.argument.is_empty() => {
+                references.push(Reference {
+                    href: self.argument.clone(),
+                    weight: self.weight(),
+                });
+            }
+            DirectiveKind::Toctree => {
+                for entry in self.toctree_entries() {
+                    let mut weight = self.weight();
+                    if let Some(title) = entry.title {
+                        weight.set::<String>("title", title).ok();
+                    }
+                    references.push(Reference {
+                        href: docname_to_href(&entry.target),
+                        weight,
+                    });
+                }
+            }
+            _ => {}
+        }
+        if matches!(self.kind, DirectiveKind::Admonition | DirectiveKind::Figure) {
+            references.extend(inline_references(&self.body));
+        }
+        references
+    }
+}
+
+/// Links and roles inside a Markdown fragment (a directive body).
+fn inline_references(markdown: &str) -> Vec<Reference> {
+    let mut references = Vec::new();
+    let mut previous_text: Option<String> = None;
+    for event in MdParser::new_ext(markdown, buildonomy_md_options()) {
+        match &event {
+            MdEvent::Start(MdTag::Link {
+                link_type: LinkType::Inline | LinkType::Autolink,
+                dest_url,
+                ..
+            })
+            | MdEvent::Start(MdTag::Image {
+                link_type: LinkType::Inline,
+                dest_url,
+                ..
+            }) if !dest_url.starts_with('#') => references.push(Reference {
+                href: dest_url.to_string(),
+                weight: Weight::default(),
+            }),
+            MdEvent::Code(content) => {
+                if let Some(role) = previous_text
+                    .as_deref()
+                    .and_then(split_role_prefix)
+                    .and_then(|(_, name)| Role::parse(name, content))
+                {
+                    references.push(role.reference());
+                }
+            }
+            _ => {}
+        }
+        previous_text = match event {
+            MdEvent::Text(text) => Some(text.to_string()),
+            _ => None,
+        };
+    }
+    references
+}
+
+/// The roles noet understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoleKind {
+    /// `` {ref}`label` ``: a labelled section (heading anchor or `(label)=` target).
+    Ref,
+    /// `` {doc}`path/to/doc` ``: a document, by extensionless docname.
+    Doc,
+}
+
+/// A parsed MyST role.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Role {
+    pub kind: RoleKind,
+    pub name: String,
+    pub target: String,
+    /// Explicit title from the `` {ref}`Title <label>` `` form.
+    pub title: Option<String>,
+}
+
+impl Role {
+    /// Parse a role from its name and content, returning `None` for unknown roles.
+    pub fn parse(name: &str, content: &str) -> Option<Role> {
+        let kind = match name {
+            "ref" => RoleKind::Ref,
+            "doc" => RoleKind::Doc,
+            _ => return None,
+        };
+        let (title, target) = split_explicit_title(content);
+        if target.is_empty() {
+            return None;
+        }
+        Some(Role {
+            kind,
+            name: name.to_string(),
+            target,
+            title,
+        })
+    }
+
+    /// Link target for this role: `id:{label}` for `{ref}`, the document path for `{doc}`.
+    pub fn href(&self) -> String {
+        match self.kind {
+            RoleKind::Ref => format!("id:{}", to_anchor(&self.target)),
+            RoleKind::Doc => docname_to_href(&self.target),
+        }
+    }
+
+    pub fn node_key(&self) -> NodeKey {
+        href_to_nodekey(&self.href())
+    }
+
+    pub fn reference(&self) -> Reference {
+        let mut weight = Weight::default();
+        weight.set::<String>("role", self.name.clone()).ok();
+        if let Some(title) = self.title.as_ref() {
+            weight.set::<String>("title", title.clone()).ok();
+        }
+        Reference {
+            href: self.href(),
+            weight,
+        }
+    }
+}
+
+/// Split a `Text` event that precedes a `Code` event into `(text_before_role, role_name)` if it
+/// ends with a `{name}` role prefix.
+pub fn split_role_prefix(text: &str) -> Option<(&str, &str)> {
+    let without_close = text.strip_suffix('}')?;
+    let open = without_close.rfind('{')?;
+    let name = &without_close[open + 1..];
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        return None;
+    }
+    Some((&text[..open], name))
+}
+
+/// Parse a MyST target line, `(label)=`, returning the label.
+pub fn parse_target(text: &str) -> Option<&str> {
+    let label = text.trim().strip_prefix('(')?.strip_suffix(")=")?.trim();
+    (!label.is_empty() && !label.contains(char::is_whitespace)).then_some(label)
+}
+
+/// A role or directive reference resolved against the BeliefBase during `inject_context`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedReference {
+    /// Link from the referencing document to the target (e.g. `../guide/intro.md#setup`).
+    pub href: String,
+    /// The target node's title.
+    pub title: String,
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn html(fragment: String) -> MdEvent<'static> {
+    MdEvent::Html(CowStr::from(fragment))
+}
+
+/// Replaces directive, role and target events with their HTML rendering.
+pub struct Renderer<'a> {
+    doc_path: &'a str,
+    resolved: &'a HashMap<NodeKey, ResolvedReference>,
+    depth: usize,
+}
+
+impl<'a> Renderer<'a> {
+    /// `doc_path` is the absolute path of the document being rendered; `resolved` maps role and
+    /// directive targets (resolved against `doc_path`) to their links.
+    pub fn new(doc_path: &'a str, resolved: &'a HashMap<NodeKey, ResolvedReference>) -> Self {
+        Renderer {
+            doc_path,
+            resolved,
+            depth: 0,
+        }
+    }
+
+    fn lookup(&self, href: &str) -> Option<&ResolvedReference> {
+        self.resolved
+            .get(&href_to_nodekey(href).resolve_against(self.doc_path))
+    }
+
+    pub fn render(&self, events: Vec<MdEvent<'static>>) -> Vec<MdEvent<'static>> {
+        let mut out = Vec::with_capacity(events.len());
+        let mut idx = 0;
+        while idx < events.len() {
+            match &events[idx] {
+                MdEvent::Start(MdTag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                    let end = events[idx..]
+                        .iter()
+                        .position(|event| matches!(event, MdEvent::End(MdTagEnd::CodeBlock)))
+                        .map(|offset| idx + offset)
+                        .unwrap_or(events.len() - 1);
+                    let body = events[idx + 1..end]
+                        .iter()
+                        .filter_map(|event| match event {
+                            MdEvent::Text(text) => Some(text.as_ref()),
+                            _ => None,
+                        })
+                        .collect::<String>();
+                    if let Some(directive) = Directive::parse(info, &body) {
+                        self.render_directive(&directive, &mut out);
+                        idx = end + 1;
+                        continue;
+                    }
+                }
+                MdEvent::Start(MdTag::Paragraph) => {
+                    if let (Some(MdEvent::Text(text)), Some(MdEvent::End(MdTagEnd::Paragraph))) =
+                        (events.get(idx + 1), events.get(idx + 2))
+                    {
+                        if parse_target(text).is_some() {
+                            idx += 3;
+                            continue;
+                        }
+                    }
+                }
+                MdEvent::Code(content) => {
+                    let role = match out.last() {
+                        Some(MdEvent::Text(text)) => {
+                            split_role_prefix(text).and_then(|(before, name)| {
+                                Role::parse(name, content).map(|role| (before.to_string(), role))
+                            })
+                        }
+                        _ => None,
+                    };
+                    if let Some((before, role)) = role {
+                        out.pop();
+                        if !before.is_empty() {
+                            out.push(MdEvent::Text(CowStr::from(before)));
+                        }
+                        self.render_role(&role, &mut out);
+                        idx += 1;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            out.push(events[idx].clone());
+            idx += 1;
+        }
+        out
+    }
+
+    fn render_markdown(&self, markdown: &str) -> Vec<MdEvent<'static>> {
+        let events = MdParser::new_ext(markdown, buildonomy_md_options())
+            .map(|event| event.into_static())
+            .collect();
+        Renderer {
+            depth: self.depth + 1,
+            ..*self
+        }
+        .render(events)
+    }
+
+    fn render_role(&self, role: &Role, out: &mut Vec<MdEvent<'static>>) {
+        let href = role.href();
+        let resolved = self.lookup(&href);
+        let dest_url = match (resolved, role.kind) {
+            (Some(resolved), _) => rewrite_href_to_html(&resolved.href),
+            (None, RoleKind::Ref) => format!("#{}", to_anchor(&role.target)),
+            (None, RoleKind::Doc) => rewrite_href_to_html(&href),
+        };
+        let text = role
+            .title
+            .clone()
+            .or_else(|| resolved.map(|resolved| resolved.title.clone()))
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| role.target.clone());
+        out.push(MdEvent::Start(MdTag::Link {
+            link_type: LinkType::Inline,
+            dest_url: CowStr::from(dest_url),
+            title: CowStr::from(""),
+            id: CowStr::from(""),
+        }));
+        out.push(MdEvent::Text(CowStr::from(text)));
+        out.push(MdEvent::End(MdTagEnd::Link));
+    }
+
+    fn render_directive(&self, directive: &Directive, out: &mut Vec<MdEvent<'static>>) {
+        let class = directive
+            .options
+            .get("class")
+            .map(|class| format!(" {}", escape_html(class)))
+            .unwrap_or_default();
+        let id = directive
+            .options
+            .get("name")
+            .map(|name| format!(" id=\"{}\"", escape_html(&to_anchor(name))))
+            .unwrap_or_default();
+        match directive.kind {
+            DirectiveKind::NetworkChildren => {
+                out.push(html(format!("{NETWORK_CHILDREN_MARKER}\n")));
+            }
+            DirectiveKind::Admonition => {
+                out.push(html(format!(
+                    "<div class=\"admonition {}{class}\"{id}>\n<p class=\"admonition-title\">{}</p>\n",
+                    escape_html(&directive.name),
+                    escape_html(&directive.admonition_title()),
+                )));
+                out.extend(self.render_markdown(&directive.body));
+                out.push(html("</div>\n".to_string()));
+            }
+            DirectiveKind::Figure => {
+                let align = directive
+                    .options
+                    .get("align")
+                    .map(|align| format!(" align-{}", escape_html(align)))
+                    .unwrap_or_default();
+                let figclass = directive
+                    .options
+                    .get("figclass")
+                    .map(|class| format!(" {}", escape_html(class)))
+                    .unwrap_or_default();
+                let mut img = format!(
+                    "<img src=\"{}\" alt=\"{}\"",
+                    escape_html(&directive.argument),
+                    escape_html(directive.options.get("alt").map_or("", |alt| alt.as_str()))
+                );
+                for attr in ["width", "height"] {
+                    if let Some(value) = directive.options.get(attr) {
+                        img.push_str(&format!(" {attr}=\"{}\"", escape_html(value)));
+                    }
+                }
+                img.push_str(" />");
+                out.push(html(format!(
+                    "<figure class=\"figure{align}{figclass}{class}\"{id}>\n{img}\n"
+                )));
+                if !directive.body.trim().is_empty() {
+                    out.push(html("<figcaption>\n".to_string()));
+                    out.extend(self.render_markdown(&directive.body));
+                    out.push(html("</figcaption>\n".to_string()));
+                }
+                out.push(html("</figure>\n".to_string()));
+            }
+            DirectiveKind::Include => self.render_include(directive, out),
+            DirectiveKind::Toctree => {
+                if directive.options.contains_key("hidden") {
+                    return;
+                }
+                let mut nav = format!("<nav class=\"toctree{class}\"{id}>\n");
+                if let Some(caption) = directive.options.get("caption") {
+                    nav.push_str(&format!(
+                        "<p class=\"caption\">{}</p>\n",
+                        escape_html(caption)
+                    ));
+                }
+                nav.push_str("<ul>\n");
+                for entry in directive.toctree_entries() {
+                    let href = docname_to_href(&entry.target);
+                    let resolved = self.lookup(&href);
+                    let dest_url = resolved
+                        .map(|resolved| rewrite_href_to_html(&resolved.href))
+                        .unwrap_or_else(|| rewrite_href_to_html(&href));
+                    let title = entry
+                        .title
+                        .or_else(|| resolved.map(|resolved| resolved.title.clone()))
+                        .filter(|title| !title.is_empty())
+                        .unwrap_or(entry.target);
+                    nav.push_str(&format!(
+                        "<li><a href=\"{}\">{}</a></li>\n",
+                        escape_html(&dest_url),
+                        escape_html(&title)
+                    ));
+                }
+                nav.push_str("</ul>\n</nav>\n");
+                out.push(html(nav));
+            }
+        }
+    }
+
+    fn render_include(&self, directive: &Directive, out: &mut Vec<MdEvent<'static>>) {
+        let target = Path::new(self.doc_path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(&directive.argument);
+        let content = match fs::read_to_string(&target) {
+            Ok(content) if self.depth < MAX_RENDER_DEPTH => content,
+            Ok(_) => {
+                tracing::warn!(
+                    "{{include}} nesting deeper than {MAX_RENDER_DEPTH} levels at {target:?}; \
+                    not rendering"
+                );
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("Could not read {{include}} target {target:?}: {e}");
+                out.push(html(format!(
+                    "<!-- include not found: {} -->\n",
+                    escape_html(&directive.argument)
+                )));
+                return;
+            }
+        };
+        let language = directive
+            .options
+            .get("code")
+            .or_else(|| directive.options.get("language"));
+        if directive.options.contains_key("literal") || language.is_some() {
+            out.push(MdEvent::Start(MdTag::CodeBlock(CodeBlockKind::Fenced(
+                CowStr::from(language.cloned().unwrap_or_default()),
+            ))));
+            out.push(MdEvent::Text(CowStr::from(content)));
+            out.push(MdEvent::End(MdTagEnd::CodeBlock));
+        } else {
+            out.extend(self.render_markdown(&content));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_directive_info() {
+        assert_eq!(
+            parse_directive_info("{network_children}"),
+            Some(("network_children", ""))
+        );
+        assert_eq!(
+            parse_directive_info("{figure} image.png"),
+            Some(("figure", "image.png"))
+        );
+        assert_eq!(parse_directive_info("rust"), None);
+        assert_eq!(parse_directive_info(""), None);
+        assert_eq!(parse_directive_info("{}"), None);
+        assert_eq!(parse_directive_info("{not a name}"), None);
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(
+            DirectiveKind::lookup("network_children"),
+            Some(DirectiveKind::NetworkChildren)
+        );
+        assert_eq!(
+            DirectiveKind::lookup("note"),
+            Some(DirectiveKind::Admonition)
+        );
+        assert_eq!(
+            DirectiveKind::lookup("toctree"),
+            Some(DirectiveKind::Toctree)
+        );
+        assert_eq!(DirectiveKind::lookup("unknown"), None);
+        assert_eq!(DirectiveKind::lookup(""), None);
+    }
+
+    #[test]
+    fn test_directive_options() {
+        let directive = Directive::parse(
+            "{figure} images/plot.png",
+            ":alt: A plot\n:width: 80%\n\nThe *caption*.\n",
+        )
+        .unwrap();
+        assert_eq!(directive.kind, DirectiveKind::Figure);
+        assert_eq!(directive.argument, "images/plot.png");
+        assert_eq!(directive.options["alt"], "A plot");
+        assert_eq!(directive.options["width"], "80%");
+        assert_eq!(directive.body, "The *caption*.");
+
+        let yaml = Directive::parse(
+            "{toctree}",
+            "---\nmaxdepth: 2\ncaption: Contents\n---\nintro\n",
+        )
+        .unwrap();
+        assert_eq!(yaml.options["maxdepth"], "2");
+        assert_eq!(yaml.options["caption"], "Contents");
+        assert_eq!(yaml.body, "intro");
+    }
+
+    #[test]
+    fn test_toctree_references() {
+        let directive = Directive::parse(
+            "{toctree}",
+            ":maxdepth: 2\n:hidden:\n\nintro\nGetting Started <guide/start>\nself\napi/*\nnotes.org\n",
+        )
+        .unwrap();
+        let references = directive.references();
+        let hrefs = references
+            .iter()
+            .map(|reference| reference.href.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(hrefs, vec!["intro.md", "guide/start.md", "notes.org"]);
+        assert_eq!(
+            references[0].weight.get::<String>("directive").as_deref(),
+            Some("toctree")
+        );
+        assert_eq!(
+            references[0].weight.get::<String>("maxdepth").as_deref(),
+            Some("2")
+        );
+        assert_eq!(references[0].weight.get::<bool>("hidden"), Some(true));
+        assert_eq!(
+            references[1].weight.get::<String>("title").as_deref(),
+            Some("Getting Started")
+        );
+    }
+
+    #[test]
+    fn test_admonition_body_references() {
+        let directive = Directive::parse(
+            "{note}",
+            "See [the guide](guide.md) and {ref}`Setup <setup-label>`.\n",
+        )
+        .unwrap();
+        let hrefs = directive
+            .references()
+            .into_iter()
+            .map(|reference| reference.href)
+            .collect::<Vec<_>>();
+        assert_eq!(hrefs, vec!["guide.md", "id:setup-label"]);
+    }
+
+    #[test]
+    fn test_roles() {
+        assert_eq!(split_role_prefix("See {ref}"), Some(("See ", "ref")));
+        assert_eq!(split_role_prefix("{doc}"), Some(("", "doc")));
+        assert_eq!(split_role_prefix("no role"), None);
+        assert_eq!(split_role_prefix("set {}"), None);
+
+        let role = Role::parse("ref", "Install guide <install>").unwrap();
+        assert_eq!(role.kind, RoleKind::Ref);
+        assert_eq!(role.title.as_deref(), Some("Install guide"));
+        assert_eq!(role.href(), "id:install");
+        assert!(matches!(role.node_key(), NodeKey::Id { .. }));
+
+        let role = Role::parse("doc", "../guide/intro").unwrap();
+        assert_eq!(role.href(), "../guide/intro.md");
+        assert!(matches!(role.node_key(), NodeKey::Path { .. }));
+
+        assert_eq!(Role::parse("abbr", "MyST"), None);
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(parse_target("(my-label)="), Some("my-label"));
+        assert_eq!(parse_target("(my label)="), None);
+        assert_eq!(parse_target("(label)"), None);
+    }
+
+    #[test]
+    fn test_render_unresolved_roles_and_admonitions() {
+        let resolved = HashMap::new();
+        let renderer = Renderer::new("/docs/index.md", &resolved);
+        let markdown = "(top)=\n\n# Top\n\nSee {ref}`top` and {doc}`Intro <intro>`.\n\n\
+                        ````{warning}\nCareful with **this**.\n````\n\n```{unknown}\nkept\n```\n";
+        let events = MdParser::new_ext(markdown, buildonomy_md_options())
+            .map(|event| event.into_static())
+            .collect();
+        let mut body = String::new();
+        pulldown_cmark::html::push_html(&mut body, renderer.render(events).into_iter());
+
+        assert!(!body.contains("(top)="), "{body}");
+        assert!(body.contains("See <a href=\"#top\">top</a>"), "{body}");
+        assert!(body.contains("<a href=\"intro.html\">Intro</a>"), "{body}");
+        assert!(
+            body.contains("<div class=\"admonition warning\">\n<p class=\"admonition-title\">Warning</p>\n<p>Careful with <strong>this</strong>.</p>\n</div>"),
+            "{body}"
+        );
+        assert!(
+            body.contains("<code class=\"language-{unknown}\">kept"),
+            "{body}"
+        );
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
         belief_ir::IntermediateRelation,
         diagnostic::ParseDiagnostic,
         md::{build_title_attribute, MdCodec},
-        DocCodec, IRNode, CODECS,
+        DocCodec, IRNode, CODECS, SCHEMAS,
     },
     error::BuildonomyError,
     nodekey::NodeKey,
@@ -82,7 +82,11 @@ pub(crate) fn iter_net_docs<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
                 // extensionless files (Gemfile, Makefile, etc.) from being classified
                 // as directories by AnchorPath and matching the (None, None) wildcard.
                 let p_ap_file = AnchorPath::new_file(&p_str);
-                if CODECS.get(&p_ap_file).is_some() {
+                if CODECS
+                    .get(&p_ap_file)
+                    .is_some_and(|factory| factory().claims(&p))
+                    && SCHEMAS.schema_network(&p).is_none()
+                {
                     if subnets.iter().any(|subnet_path| p.starts_with(subnet_path)) {
                         // Don't include subnet files
                         None
@@ -388,7 +392,7 @@ impl NetworkCodec {
                     if bid == &edge.other.bid {
                         Some(format!(
                             " title=\"{}\"",
-                            build_title_attribute(&format!("bref://{}", bref), false, None)
+                            build_title_attribute(&format!("bref://{}", bref), false, None, None)
                         ))
                     } else {
                         None
@@ -0,0 +1,1436 @@
+//! Emacs Org-mode codec.
+//!
+//! [`OrgCodec`] mirrors [`MdCodec`](super::md::MdCodec) for `.org` files:
+//!
+//! - The file itself is the Document node. `#+TITLE:` sets its title (falling back to the
+//!   filestem), other `#+KEYWORD:` lines and a file-level `:PROPERTIES:` drawer become payload.
+//! - Headlines become section nodes. `*` maps to the same heading depth as a Markdown `#`, so the
+//!   builder wires up `WeightKind::Section` edges exactly as it does for Markdown.
+//! - `:PROPERTIES:` drawers map to node payload (keys are lowercased). `:ID:` holds the node's BID
+//!   (Org's `org-id` uses UUIDs too), `:CUSTOM_ID:` its anchor id, and `:SCHEMA:` its schema.
+//! - `[[target][description]]` links become relations: `file:x.org::*Heading`,
+//!   `file:x.org::#custom-id`, `*Heading`, `#custom-id`, `id:UUID` and external URLs.
+//!
+//! Write-back only ever touches property drawers: BIDs are injected into (or a drawer is created
+//! for) each node, leaving the rest of the file byte-for-byte intact.
+
+use pulldown_cmark::{
+    CodeBlockKind, CowStr, Event as MdEvent, HeadingLevel, LinkType, Tag, TagEnd,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+    result::Result,
+};
+use titlecase::titlecase;
+use toml_edit::{value, Array};
+
+use crate::{
+    beliefbase::BeliefContext,
+    codec::{
+        belief_ir::{IRNode, IntermediateRelation},
+        diagnostic::ParseDiagnostic,
+        DocCodec, CODECS,
+    },
+    error::BuildonomyError,
+    nodekey::href_to_nodekey,
+    paths::{os_path_to_string, to_anchor, AnchorPath},
+    properties::{BeliefKind, BeliefNode, Bid, Weight, WeightKind},
+};
+
+/// Headline TODO keywords stripped from titles and stored under `todo`.
+const TODO_KEYWORDS: &[&str] = &["TODO", "DONE"];
+
+/// Affiliated keywords attach to the following element rather than the document.
+const AFFILIATED_KEYWORDS: &[&str] = &["NAME", "CAPTION", "RESULTS", "HEADER", "PLOT"];
+
+/// Link targets rendered as `<img>` when the link has no description.
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp"];
+
+/// Line span of a `:PROPERTIES:` ... `:END:` drawer (both indices inclusive).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Drawer {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Clone)]
+struct OrgNode {
+    proto: IRNode,
+    /// Org headline depth (number of stars). 0 for the document node.
+    level: usize,
+    /// Raw headline text (inline markup intact), rendered as the HTML heading.
+    title_source: String,
+    /// Line after which a new property drawer would be inserted (the headline, or its planning
+    /// line). `None` for the document node, whose drawer goes at the top of the file.
+    anchor_line: Option<usize>,
+    drawer: Option<Drawer>,
+    /// Drawer property currently holding the BID (`ID` or `BID`), if any.
+    bid_property: Option<String>,
+    /// Whether `:ID:` is already used for a non-UUID identifier.
+    id_is_custom: bool,
+    /// Lines rendered as this node's body in HTML (exclusive end).
+    body: (usize, usize),
+    /// Properties to write into this node's drawer on `generate_source`.
+    edits: BTreeMap<String, String>,
+}
+
+/// Codec for Emacs Org-mode (`.org`) documents. See the [module docs](self).
+#[derive(Debug, Default, Clone)]
+pub struct OrgCodec {
+    content: String,
+    lines: Vec<String>,
+    nodes: Vec<OrgNode>,
+}
+
+impl OrgCodec {
+    pub fn new() -> Self {
+        OrgCodec::default()
+    }
+}
+
+/// Split `content` into lines, returning each line with the byte offset it starts at.
+pub(crate) fn split_lines(content: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    content
+        .split_inclusive('\n')
+        .map(|raw| {
+            let start = offset;
+            offset += raw.len();
+            (start, raw.trim_end_matches(['\n', '\r']))
+        })
+        .collect()
+}
+
+/// Parse a headline into `(level, title_source, todo, tags)`.
+fn parse_headline(line: &str) -> Option<(usize, String, Option<String>, Vec<String>)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    if level == 0 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let mut rest = rest.trim();
+
+    let mut todo = None;
+    if let Some((word, remainder)) = rest.split_once(' ').or(Some((rest, ""))) {
+        if TODO_KEYWORDS.contains(&word) {
+            todo = Some(word.to_string());
+            rest = remainder.trim_start();
+        }
+    }
+    if rest.len() >= 4 && rest.starts_with("[#") && rest.as_bytes()[3] == b']' {
+        rest = rest[4..].trim_start();
+    }
+
+    let mut tags = Vec::new();
+    if let Some(idx) = rest.rfind(char::is_whitespace) {
+        let candidate = &rest[idx + 1..];
+        if candidate.len() > 2
+            && candidate.starts_with(':')
+            && candidate.ends_with(':')
+            && candidate[1..candidate.len() - 1]
+                .split(':')
+                .all(|tag| !tag.is_empty() && tag.chars().all(is_tag_char))
+        {
+            tags = candidate[1..candidate.len() - 1]
+                .split(':')
+                .map(|tag| tag.to_string())
+                .collect();
+            rest = rest[..idx].trim_end();
+        }
+    }
+    Some((level, rest.to_string(), todo, tags))
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || "_@#%".contains(c)
+}
+
+fn is_planning_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    ["SCHEDULED:", "DEADLINE:", "CLOSED:"]
+        .iter()
+        .any(|prefix| trimmed.starts_with(prefix))
+}
+
+/// Parse `#+KEY: value` into `(KEY, value)`, skipping block delimiters.
+fn parse_keyword(line: &str) -> Option<(String, String)> {
+    let rest = line.trim_start().strip_prefix("#+")?;
+    let (key, value) = rest.split_once(':')?;
+    let key = key.to_uppercase();
+    if key.is_empty() || key.starts_with("BEGIN") || key.starts_with("END") {
+        return None;
+    }
+    Some((key, value.trim().to_string()))
+}
+
+/// Parse a drawer line `:KEY: value` into `(KEY, value)`.
+fn parse_property(line: &str) -> Option<(String, String)> {
+    let rest = line.trim().strip_prefix(':')?;
+    let (key, value) = rest.split_once(':')?;
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((key.to_string(), value.trim().to_string()))
+}
+
+fn is_drawer_end(line: &str) -> bool {
+    line.trim().eq_ignore_ascii_case(":END:")
+}
+
+/// Convert an Org link target into an href understood by [`href_to_nodekey`].
+fn org_target_to_href(target: &str) -> Option<String> {
+    let target = target.trim();
+    if target.is_empty() {
+        return None;
+    }
+    if let Some(id) = target.strip_prefix("id:") {
+        return Some(match Bid::try_from(id) {
+            Ok(_) => format!("bid:{id}"),
+            Err(_) => format!("id:{id}"),
+        });
+    }
+    if let Some(heading) = target.strip_prefix('*') {
+        return Some(format!("#{}", to_anchor(heading)));
+    }
+    if let Some(custom_id) = target.strip_prefix('#') {
+        return Some(format!("#{}", to_anchor(custom_id)));
+    }
+    let (path, search) = match target.strip_prefix("file:") {
+        Some(file) => match file.split_once("::") {
+            Some((path, search)) => (path, Some(search)),
+            None => (file, None),
+        },
+        None => {
+            let is_path = ["/", "./", "../", "~/"]
+                .iter()
+                .any(|prefix| target.starts_with(prefix));
+            if is_path || target.contains("://") || target.starts_with("mailto:") {
+                (target, None)
+            } else {
+                // Org "fuzzy" links search for a matching headline or target in this file.
+                return Some(format!("#{}", to_anchor(target)));
+            }
+        }
+    };
+    let anchor = search.and_then(|search| {
+        search
+            .strip_prefix('*')
+            .or_else(|| search.strip_prefix('#'))
+            .map(to_anchor)
+    });
+    Some(match anchor {
+        Some(anchor) if !anchor.is_empty() => format!("{path}#{anchor}"),
+        _ => path.to_string(),
+    })
+}
+
+/// A parsed `[[target][description]]` link found in a line.
+struct OrgLink<'a> {
+    /// Byte offset of the opening `[[` within the line.
+    start: usize,
+    /// Byte offset just past the closing `]]` within the line.
+    end: usize,
+    target: &'a str,
+    description: Option<&'a str>,
+}
+
+/// Find the next Org link starting at or after `from`.
+fn next_link(line: &str, from: usize) -> Option<OrgLink<'_>> {
+    let start = from + line.get(from..)?.find("[[")?;
+    let inner_start = start + 2;
+    let close = inner_start + line[inner_start..].find("]]")?;
+    let inner = &line[inner_start..close];
+    let (target, description) = match inner.split_once("][") {
+        Some((target, description)) => (target, Some(description)),
+        None => (inner, None),
+    };
+    Some(OrgLink {
+        start,
+        end: close + 2,
+        target,
+        description,
+    })
+}
+
+/// Inline Org markup converted to pulldown_cmark events.
+fn inline_events(text: &str) -> Vec<MdEvent<'static>> {
+    fn pre_ok(prev: Option<char>) -> bool {
+        prev.is_none_or(|c| c.is_whitespace() || "-({'\"".contains(c))
+    }
+    fn post_ok(next: Option<char>) -> bool {
+        next.is_none_or(|c| c.is_whitespace() || "-.,;:!?')}\"".contains(c))
+    }
+
+    let mut events = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        if rest.starts_with("[[") {
+            if let Some(link) = next_link(text, i).filter(|link| link.start == i) {
+                if !plain.is_empty() {
+                    events.push(MdEvent::Text(CowStr::from(std::mem::take(&mut plain))));
+                }
+                events.extend(link_events(&link));
+                i = link.end;
+                continue;
+            }
+        }
+        let c = rest.chars().next().expect("i < text.len()");
+        let prev = text[..i].chars().next_back();
+        if "*/=~+".contains(c) && pre_ok(prev) {
+            let body_start = i + 1;
+            let close = text[body_start..]
+                .char_indices()
+                .filter(|(_, ch)| *ch == c)
+                .map(|(idx, _)| body_start + idx)
+                .find(|&idx| {
+                    let body = &text[body_start..idx];
+                    !body.is_empty()
+                        && !body.starts_with(char::is_whitespace)
+                        && !body.ends_with(char::is_whitespace)
+                        && post_ok(text[idx + 1..].chars().next())
+                });
+            if let Some(close) = close {
+                if !plain.is_empty() {
+                    events.push(MdEvent::Text(CowStr::from(std::mem::take(&mut plain))));
+                }
+                let body = &text[body_start..close];
+                match c {
+                    '=' | '~' => events.push(MdEvent::Code(CowStr::from(body.to_string()))),
+                    _ => {
+                        let (start, end) = match c {
+                            '*' => (Tag::Strong, TagEnd::Strong),
+                            '/' => (Tag::Emphasis, TagEnd::Emphasis),
+                            _ => (Tag::Strikethrough, TagEnd::Strikethrough),
+                        };
+                        events.push(MdEvent::Start(start));
+                        events.extend(inline_events(body));
+                        events.push(MdEvent::End(end));
+                    }
+                }
+                i = close + 1;
+                continue;
+            }
+        }
+        plain.push(c);
+        i += c.len_utf8();
+    }
+    if !plain.is_empty() {
+        events.push(MdEvent::Text(CowStr::from(plain)));
+    }
+    events
+}
+
+/// Render a link, rewriting document links to their `.html` output as `MdCodec` does.
+fn link_events(link: &OrgLink<'_>) -> Vec<MdEvent<'static>> {
+    let label = link.description.unwrap_or(link.target).to_string();
+    let Some(href) = org_target_to_href(link.target) else {
+        return vec![MdEvent::Text(CowStr::from(label))];
+    };
+    if href.starts_with("bid:") || href.starts_with("id:") {
+        // Identity links need the BeliefBase to resolve; render the label only.
+        return vec![MdEvent::Text(CowStr::from(label))];
+    }
+    let url_ap = AnchorPath::from(&href);
+    if link.description.is_none() && IMAGE_EXTENSIONS.contains(&url_ap.ext()) {
+        return vec![
+            MdEvent::Start(Tag::Image {
+                link_type: LinkType::Inline,
+                dest_url: CowStr::from(href.clone()),
+                title: CowStr::from(""),
+                id: CowStr::from(""),
+            }),
+            MdEvent::Text(CowStr::from(href)),
+            MdEvent::End(TagEnd::Image),
+        ];
+    }
+    let dest_url =
+        if !url_ap.is_anchor() && !url_ap.ext().is_empty() && CODECS.get(&url_ap).is_some() {
+            url_ap
+                .normalize()
+                .as_anchor_path()
+                .replace_extension("html")
+        } else {
+            href
+        };
+    let mut events = vec![MdEvent::Start(Tag::Link {
+        link_type: LinkType::Inline,
+        dest_url: CowStr::from(dest_url),
+        title: CowStr::from(""),
+        id: CowStr::from(""),
+    })];
+    match link.description {
+        Some(description) => events.extend(inline_events(description)),
+        None => events.push(MdEvent::Text(CowStr::from(label))),
+    }
+    events.push(MdEvent::End(TagEnd::Link));
+    events
+}
+
+/// Plain-text rendering of inline markup, used for headline titles.
+fn plain_text(text: &str) -> String {
+    inline_events(text)
+        .into_iter()
+        .filter_map(|event| match event {
+            MdEvent::Text(text) | MdEvent::Code(text) => Some(text.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Store drawer properties on `node`, mapping Org's identity properties onto IRNode fields.
+fn apply_properties(
+    node: &mut OrgNode,
+    properties: Vec<(String, String)>,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) {
+    let mut appended = BTreeMap::<String, String>::new();
+    for (key, property_value) in properties {
+        let (key, append) = match key.strip_suffix('+') {
+            Some(key) => (key.to_string(), true),
+            None => (key, false),
+        };
+        match key.to_uppercase().as_str() {
+            upper @ ("ID" | "BID") => match Bid::try_from(property_value.as_str()) {
+                Ok(bid) if bid.is_reserved() => {
+                    diagnostics.push(ParseDiagnostic::warning(format!(
+                        "Ignoring reserved BID '{property_value}' in :{key}: property of {}",
+                        node.proto.path
+                    )))
+                }
+                Ok(_) => {
+                    // An explicit :BID: wins over an :ID: UUID.
+                    if upper == "BID" || node.bid_property.as_deref() != Some("BID") {
+                        node.proto
+                            .document
+                            .insert("bid", value(property_value.clone()));
+                        node.bid_property = Some(key.clone());
+                    }
+                }
+                Err(_) if upper == "ID" => {
+                    node.id_is_custom = true;
+                    node.proto.document.insert("org_id", value(property_value));
+                }
+                Err(_) => diagnostics.push(ParseDiagnostic::warning(format!(
+                    "Ignoring malformed :BID: property '{property_value}' in {}",
+                    node.proto.path
+                ))),
+            },
+            "CUSTOM_ID" => {
+                node.proto
+                    .document
+                    .insert("id", value(to_anchor(&property_value)));
+            }
+            _ => {
+                let lower = key.to_lowercase();
+                let property_value = match (append, appended.get(&lower)) {
+                    (true, Some(existing)) => format!("{existing} {property_value}"),
+                    _ => property_value,
+                };
+                appended.insert(lower.clone(), property_value.clone());
+                node.proto.document.insert(&lower, value(property_value));
+            }
+        }
+    }
+}
+
+/// Parse an Org file into the document node followed by one node per headline.
+///
+/// `current` seeds the document node (path, proto metadata).
+fn parse_org(
+    content: &str,
+    current: IRNode,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> Result<Vec<OrgNode>, BuildonomyError> {
+    let lines = split_lines(content);
+    let doc_path = current.path.clone();
+    let mut nodes = vec![OrgNode {
+        proto: current,
+        level: 0,
+        title_source: String::new(),
+        anchor_line: None,
+        drawer: None,
+        bid_property: None,
+        id_is_custom: false,
+        body: (0, lines.len()),
+        edits: BTreeMap::new(),
+    }];
+    let mut seen_ids = HashSet::new();
+    let mut filetags = Vec::new();
+    let mut in_block = false;
+    let mut idx = 0;
+    while idx < lines.len() {
+        let (line_offset, line) = lines[idx];
+        let trimmed = line.trim_start();
+
+        // Source/example blocks are opaque: no headlines, drawers or links inside.
+        let upper = trimmed.to_uppercase();
+        if upper.starts_with("#+BEGIN_") {
+            in_block = true;
+        } else if upper.starts_with("#+END_") {
+            in_block = false;
+        }
+        if in_block {
+            idx += 1;
+            continue;
+        }
+
+        if let Some((level, title_source, todo, tags)) = parse_headline(line) {
+            let title = plain_text(&title_source);
+            if let Some(last) = nodes.last_mut() {
+                last.body.1 = idx;
+            }
+            let mut proto = IRNode {
+                path: doc_path.clone(),
+                heading: level + 2,
+                ..Default::default()
+            };
+            proto.document.insert("title", value(title.clone()));
+            if let Some(todo) = todo {
+                proto.document.insert("todo", value(todo));
+            }
+            if !tags.is_empty() {
+                proto
+                    .document
+                    .insert("tags", value(Array::from_iter(tags.iter())));
+            }
+            let mut node = OrgNode {
+                proto,
+                level,
+                title_source,
+                anchor_line: Some(idx),
+                drawer: None,
+                bid_property: None,
+                id_is_custom: false,
+                body: (idx + 1, lines.len()),
+                edits: BTreeMap::new(),
+            };
+            let mut next = idx + 1;
+            if next < lines.len() && is_planning_line(lines[next].1) {
+                node.anchor_line = Some(next);
+                next += 1;
+            }
+            if let Some((drawer, properties)) = read_drawer(&lines, next) {
+                node.drawer = Some(drawer);
+                apply_properties(&mut node, properties, diagnostics);
+                next = drawer.end + 1;
+            }
+            node.body.0 = next;
+
+            if title.is_empty() {
+                diagnostics.push(
+                    ParseDiagnostic::warning(format!(
+                        "Headline without a title in {doc_path} is not addressable"
+                    ))
+                    .with_location(idx + 1, 1),
+                );
+            }
+            if let Some(id) = node.proto.id() {
+                if !seen_ids.insert(id.clone()) {
+                    diagnostics.push(
+                        ParseDiagnostic::warning(format!(
+                            "Headline '{title}' has the same anchor '#{id}' as an earlier headline. \
+                            Add a unique :CUSTOM_ID: property to make it addressable."
+                        ))
+                        .with_location(idx + 1, 1),
+                    );
+                    // Empty-string sentinel: suppress the title fallback so the builder assigns
+                    // a bref-based id instead (mirrors MdCodec heading collisions).
+                    node.proto.document.insert("id", value(""));
+                }
+            }
+            nodes.push(node);
+            idx = next;
+            continue;
+        }
+
+        let node = nodes
+            .last_mut()
+            .expect("nodes always holds the document node");
+        if node.level == 0 {
+            if let Some((key, keyword_value)) = parse_keyword(line) {
+                match key.as_str() {
+                    "TITLE" => {
+                        node.proto
+                            .document
+                            .insert("title", value(plain_text(&keyword_value)));
+                    }
+                    "FILETAGS" => filetags.extend(
+                        keyword_value
+                            .split(':')
+                            .filter(|tag| !tag.trim().is_empty())
+                            .map(|tag| tag.trim().to_string()),
+                    ),
+                    key if AFFILIATED_KEYWORDS.contains(&key) || key.starts_with("ATTR_") => {}
+                    key => {
+                        node.proto
+                            .document
+                            .insert(&key.to_lowercase(), value(keyword_value));
+                    }
+                }
+                idx += 1;
+                continue;
+            }
+            // Org only recognises a file-level drawer before any content (comments excepted).
+            if node.drawer.is_none()
+                && trimmed.eq_ignore_ascii_case(":PROPERTIES:")
+                && lines[..idx].iter().all(|(_, prior)| {
+                    prior.trim().is_empty() || prior.trim_start().starts_with('#')
+                })
+            {
+                if let Some((drawer, properties)) = read_drawer(&lines, idx) {
+                    node.drawer = Some(drawer);
+                    apply_properties(node, properties, diagnostics);
+                    idx = drawer.end + 1;
+                    continue;
+                }
+            }
+        }
+
+        // Comment lines may contain bracket text that is not meant as a link.
+        if !trimmed.starts_with("# ") {
+            let mut from = 0;
+            while let Some(link) = next_link(line, from) {
+                from = link.end;
+                let Some(href) = org_target_to_href(link.target) else {
+                    continue;
+                };
+                let node_key = href_to_nodekey(&href).resolve_against(&doc_path);
+                let payload = link
+                    .description
+                    .map(plain_text)
+                    .filter(|title| !title.is_empty() && title != link.target)
+                    .map(|title| {
+                        let mut weight = Weight::default();
+                        weight.set::<String>("title", title).ok();
+                        weight
+                    });
+                node.proto.upstream.push(
+                    IntermediateRelation::new(node_key, WeightKind::Epistemic, payload)
+                        .with_location(line_offset + link.start),
+                );
+            }
+        }
+        idx += 1;
+    }
+
+    if !filetags.is_empty() {
+        nodes[0]
+            .proto
+            .document
+            .insert("tags", value(Array::from_iter(filetags.iter())));
+    }
+    for node in nodes.iter_mut() {
+        node.proto.traverse_schema()?;
+    }
+    Ok(nodes)
+}
+
+/// Read a `:PROPERTIES:` drawer starting at line `start`, if there is one.
+fn read_drawer(lines: &[(usize, &str)], start: usize) -> Option<(Drawer, Vec<(String, String)>)> {
+    if !lines
+        .get(start)?
+        .1
+        .trim()
+        .eq_ignore_ascii_case(":PROPERTIES:")
+    {
+        return None;
+    }
+    let mut properties = Vec::new();
+    for (idx, (_, line)) in lines.iter().enumerate().skip(start + 1) {
+        if is_drawer_end(line) {
+            return Some((Drawer { start, end: idx }, properties));
+        }
+        if let Some(property) = parse_property(line) {
+            properties.push(property);
+        }
+    }
+    None
+}
+
+impl OrgCodec {
+    /// Queue `key = new_value` for this node's drawer, returning true if it changes the source.
+    fn queue_edit(node: &mut OrgNode, key: &str, new_value: &str, current: Option<&str>) -> bool {
+        if current == Some(new_value) {
+            return false;
+        }
+        node.edits.insert(key.to_string(), new_value.to_string());
+        true
+    }
+
+    fn render_body(&self, start: usize, end: usize, events: &mut Vec<MdEvent<'static>>) {
+        enum Block {
+            Paragraph(Vec<String>),
+            List { ordered: bool, items: Vec<String> },
+        }
+        fn flush(block: &mut Option<Block>, events: &mut Vec<MdEvent<'static>>) {
+            match block.take() {
+                Some(Block::Paragraph(lines)) => {
+                    events.push(MdEvent::Start(Tag::Paragraph));
+                    for (idx, line) in lines.iter().enumerate() {
+                        if idx > 0 {
+                            events.push(MdEvent::SoftBreak);
+                        }
+                        events.extend(inline_events(line));
+                    }
+                    events.push(MdEvent::End(TagEnd::Paragraph));
+                }
+                Some(Block::List { ordered, items }) => {
+                    events.push(MdEvent::Start(Tag::List(ordered.then_some(1))));
+                    for item in items {
+                        events.push(MdEvent::Start(Tag::Item));
+                        events.extend(inline_events(&item));
+                        events.push(MdEvent::End(TagEnd::Item));
+                    }
+                    events.push(MdEvent::End(TagEnd::List(ordered)));
+                }
+                None => {}
+            }
+        }
+        fn list_item(line: &str) -> Option<(bool, &str)> {
+            let trimmed = line.trim_start();
+            for bullet in ["- ", "+ "] {
+                if let Some(item) = trimmed.strip_prefix(bullet) {
+                    return Some((false, item));
+                }
+            }
+            let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+            let rest = &trimmed[digits..];
+            if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+                return Some((true, &rest[2..]));
+            }
+            None
+        }
+
+        let mut block: Option<Block> = None;
+        let mut idx = start;
+        while idx < end {
+            let line = self.lines[idx].as_str();
+            let trimmed = line.trim_start();
+            let upper = trimmed.to_uppercase();
+
+            if let Some(kind) = upper.strip_prefix("#+BEGIN_") {
+                flush(&mut block, events);
+                let kind = kind
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                let language = trimmed
+                    .split_whitespace()
+                    .nth(1)
+                    .filter(|_| kind == "SRC")
+                    .unwrap_or_default()
+                    .to_string();
+                let close = format!("#+END_{kind}");
+                let block_end = (idx + 1..end)
+                    .find(|&j| {
+                        self.lines[j]
+                            .trim_start()
+                            .to_uppercase()
+                            .starts_with(&close)
+                    })
+                    .unwrap_or(end);
+                let inner = self.lines[idx + 1..block_end].to_vec();
+                if kind == "QUOTE" {
+                    events.push(MdEvent::Start(Tag::BlockQuote(None)));
+                    self.render_lines(&inner, events);
+                    events.push(MdEvent::End(TagEnd::BlockQuote(None)));
+                } else {
+                    events.push(MdEvent::Start(Tag::CodeBlock(CodeBlockKind::Fenced(
+                        CowStr::from(language),
+                    ))));
+                    let mut code = inner.join("\n");
+                    code.push('\n');
+                    events.push(MdEvent::Text(CowStr::from(code)));
+                    events.push(MdEvent::End(TagEnd::CodeBlock));
+                }
+                idx = block_end + 1;
+                continue;
+            }
+
+            let is_drawer = trimmed.starts_with(':')
+                && trimmed.ends_with(':')
+                && trimmed.len() > 2
+                && !trimmed[1..trimmed.len() - 1].contains(char::is_whitespace);
+            if is_drawer && !is_drawer_end(trimmed) {
+                // Skip property/logbook drawers entirely.
+                flush(&mut block, events);
+                idx = (idx + 1..end)
+                    .find(|&j| is_drawer_end(&self.lines[j]))
+                    .map(|j| j + 1)
+                    .unwrap_or(idx + 1);
+                continue;
+            }
+            if trimmed.is_empty() {
+                flush(&mut block, events);
+            } else if trimmed.starts_with("#+")
+                || trimmed.starts_with("# ")
+                || trimmed == "#"
+                || is_planning_line(trimmed)
+                || is_drawer_end(trimmed)
+            {
+                // Keywords, comments and planning lines are metadata, not content.
+            } else if let Some((ordered, item)) = list_item(line) {
+                match block.as_mut() {
+                    Some(Block::List {
+                        ordered: list_ordered,
+                        items,
+                    }) if *list_ordered == ordered => items.push(item.to_string()),
+                    _ => {
+                        flush(&mut block, events);
+                        block = Some(Block::List {
+                            ordered,
+                            items: vec![item.to_string()],
+                        });
+                    }
+                }
+            } else {
+                match block.as_mut() {
+                    Some(Block::Paragraph(lines)) => lines.push(trimmed.to_string()),
+                    Some(Block::List { items, .. }) if line.starts_with(char::is_whitespace) => {
+                        // Indented continuation of the previous list item.
+                        if let Some(last) = items.last_mut() {
+                            last.push(' ');
+                            last.push_str(trimmed);
+                        }
+                    }
+                    _ => {
+                        flush(&mut block, events);
+                        block = Some(Block::Paragraph(vec![trimmed.to_string()]));
+                    }
+                }
+            }
+            idx += 1;
+        }
+        flush(&mut block, events);
+    }
+
+    /// Render free-standing lines (e.g. the inside of a quote block).
+    fn render_lines(&self, lines: &[String], events: &mut Vec<MdEvent<'static>>) {
+        let nested = OrgCodec {
+            lines: lines.to_vec(),
+            ..Default::default()
+        };
+        nested.render_body(0, lines.len(), events);
+    }
+}
+
+impl DocCodec for OrgCodec {
+    fn proto(&self, path: &Path) -> Result<Option<IRNode>, BuildonomyError> {
+        if path.is_relative() {
+            return Err(BuildonomyError::Codec(format!(
+                "[OrgCodec::proto] supplied path must be absolute. Received \"{path:?}\""
+            )));
+        };
+        if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|&ext| ext == "org")
+            .is_none()
+        {
+            tracing::debug!(
+                "OrgCodec::proto called with path \"{path:?}\", which has a non-'org' \
+                file extension. Returning None"
+            );
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let seed = IRNode {
+            path: os_path_to_string(path),
+            ..Default::default()
+        };
+        let mut proto = parse_org(&content, seed, &mut Vec::new())?
+            .into_iter()
+            .next()
+            .map(|node| node.proto)
+            .unwrap_or_default();
+        // Section links belong to their headline; proto only carries document metadata.
+        proto.upstream.clear();
+        proto.downstream.clear();
+        if proto.title().unwrap_or_default().is_empty() {
+            if let Some(filestem) = path
+                .file_stem()
+                .filter(|stem| !stem.is_empty())
+                .and_then(|stem| stem.to_str())
+            {
+                let title = filestem
+                    .split("_")
+                    .map(titlecase)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                proto.document.insert("title", value(title));
+            }
+        }
+        proto.path = os_path_to_string(path);
+        // Document heading
+        proto.heading = 2;
+        proto.kind.insert(BeliefKind::Document);
+        Ok(Some(proto))
+    }
+
+    fn parse(
+        &mut self,
+        content: &str,
+        mut current: IRNode,
+        diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<(), BuildonomyError> {
+        self.content = content.to_string();
+        self.lines = split_lines(content)
+            .into_iter()
+            .map(|(_, line)| line.to_string())
+            .collect();
+
+        let seed = IRNode {
+            path: current.path.clone(),
+            ..Default::default()
+        };
+        let mut nodes = parse_org(content, seed, diagnostics)?;
+        // Merge parsed document metadata over the proto (which may carry a filestem title).
+        let mut parsed_doc = std::mem::take(&mut nodes[0].proto);
+        let mut links = std::mem::take(&mut parsed_doc.upstream);
+        current.merge(&mut parsed_doc);
+        current.upstream.append(&mut links);
+        current.heading = 2;
+        current.kind.insert(BeliefKind::Document);
+        nodes[0].proto = current;
+        self.nodes = nodes;
+        Ok(())
+    }
+
+    fn nodes(&self) -> Vec<IRNode> {
+        self.nodes.iter().map(|node| node.proto.clone()).collect()
+    }
+
+    fn inject_context(
+        &mut self,
+        node: &IRNode,
+        ctx: &BeliefContext<'_>,
+        _diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Option<BeliefNode>, BuildonomyError> {
+        let org_node = self
+            .nodes
+            .iter_mut()
+            .find(|org_node| &org_node.proto == node)
+            .ok_or(BuildonomyError::Codec(
+                "No proto node stored in codec matching node argument".to_string(),
+            ))?;
+
+        // Anchor as written in the source (CUSTOM_ID or title-derived), before the context merge.
+        let source_id = org_node.proto.id();
+        let mut maybe_updated = org_node.proto.update_from_context(ctx)?;
+
+        // Headline anchors are collision-corrected by the builder. Persist the corrected anchor
+        // as :CUSTOM_ID: so links to it keep resolving (mirrors MdCodec's {#id} injection).
+        if org_node.proto.heading > 2 {
+            let final_id = ctx.node.id();
+            if source_id.as_ref() != Some(&final_id) {
+                org_node
+                    .proto
+                    .document
+                    .insert("id", value(final_id.clone()));
+                Self::queue_edit(org_node, "CUSTOM_ID", &final_id, None);
+                maybe_updated = Some(BeliefNode::try_from(&org_node.proto)?);
+            }
+        }
+
+        let bid = org_node
+            .proto
+            .document
+            .get("bid")
+            .and_then(|item| item.as_str())
+            .map(|bid| bid.to_string());
+        if let Some(bid) = bid {
+            let key = match org_node.bid_property.clone() {
+                Some(key) => key,
+                None if org_node.id_is_custom => "BID".to_string(),
+                None => "ID".to_string(),
+            };
+            let current = org_node
+                .bid_property
+                .is_some()
+                .then(|| {
+                    org_node
+                        .drawer
+                        .and_then(|drawer| {
+                            self.lines[drawer.start + 1..drawer.end]
+                                .iter()
+                                .filter_map(|line| parse_property(line))
+                                .find(|(k, _)| k.eq_ignore_ascii_case(&key))
+                        })
+                        .map(|(_, v)| v)
+                })
+                .flatten();
+            Self::queue_edit(org_node, &key, &bid, current.as_deref());
+        }
+
+        Ok(maybe_updated)
+    }
+
+    fn finalize(
+        &mut self,
+        _diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Vec<(IRNode, BeliefNode)>, BuildonomyError> {
+        Ok(Vec::new())
+    }
+
+    fn generate_source(&self) -> Option<String> {
+        if self.nodes.iter().all(|node| node.edits.is_empty()) {
+            return Some(self.content.clone());
+        }
+        let mut replaced = BTreeMap::<usize, String>::new();
+        let mut inserted = BTreeMap::<usize, Vec<String>>::new();
+        for node in self.nodes.iter().filter(|node| !node.edits.is_empty()) {
+            match node.drawer {
+                Some(drawer) => {
+                    let indent = self.lines[drawer.end]
+                        .chars()
+                        .take_while(|c| c.is_whitespace())
+                        .collect::<String>();
+                    for (key, new_value) in node.edits.iter() {
+                        let existing = (drawer.start + 1..drawer.end).find(|&idx| {
+                            parse_property(&self.lines[idx])
+                                .is_some_and(|(k, _)| k.eq_ignore_ascii_case(key))
+                        });
+                        match existing {
+                            Some(idx) => {
+                                let line = &self.lines[idx];
+                                let key_end = line
+                                    .find(':')
+                                    .and_then(|first| {
+                                        line[first + 1..].find(':').map(|k| first + k + 2)
+                                    })
+                                    .unwrap_or(line.len());
+                                replaced.insert(idx, format!("{} {new_value}", &line[..key_end]));
+                            }
+                            None => inserted
+                                .entry(drawer.end)
+                                .or_default()
+                                .push(format!("{indent}:{key}: {new_value}")),
+                        }
+                    }
+                }
+                None => {
+                    let at = node.anchor_line.map(|line| line + 1).unwrap_or(0);
+                    let entry = inserted.entry(at).or_default();
+                    entry.push(":PROPERTIES:".to_string());
+                    for (key, new_value) in node.edits.iter() {
+                        entry.push(format!(":{key}: {new_value}"));
+                    }
+                    entry.push(":END:".to_string());
+                }
+            }
+        }
+
+        let mut out = Vec::with_capacity(self.lines.len() + inserted.len() * 3);
+        for idx in 0..=self.lines.len() {
+            if let Some(new_lines) = inserted.get(&idx) {
+                out.extend(new_lines.iter().cloned());
+            }
+            if let Some(line) = self.lines.get(idx) {
+                out.push(replaced.get(&idx).unwrap_or(line).clone());
+            }
+        }
+        let mut source = out.join("\n");
+        if self.content.ends_with('\n') || self.content.is_empty() {
+            source.push('\n');
+        }
+        Some(source)
+    }
+
+    fn generate_html(&self) -> Result<Vec<(String, String)>, BuildonomyError> {
+        let doc_path = self
+            .nodes
+            .first()
+            .map(|node| node.proto.path.clone())
+            .filter(|path| !path.is_empty())
+            .unwrap_or("document.org".to_string());
+        let doc_ap = AnchorPath::from(&doc_path);
+        if doc_ap.filestem().is_empty() {
+            return Err(BuildonomyError::Codec(format!(
+                "Org file has no filename! {doc_path}",
+            )));
+        }
+        let output_filename = format!("{}.html", doc_ap.filestem());
+
+        let mut events = Vec::new();
+        for node in self.nodes.iter() {
+            if node.level > 0 {
+                let level = match node.level {
+                    1 => HeadingLevel::H1,
+                    2 => HeadingLevel::H2,
+                    3 => HeadingLevel::H3,
+                    4 => HeadingLevel::H4,
+                    5 => HeadingLevel::H5,
+                    _ => HeadingLevel::H6,
+                };
+                events.push(MdEvent::Start(Tag::Heading {
+                    level,
+                    id: node
+                        .proto
+                        .id()
+                        .filter(|id| !id.is_empty())
+                        .map(CowStr::from),
+                    classes: vec![],
+                    attrs: vec![],
+                }));
+                events.extend(inline_events(&node.title_source));
+                events.push(MdEvent::End(TagEnd::Heading(level)));
+            }
+            self.render_body(node.body.0, node.body.1, &mut events);
+        }
+
+        let mut html_body = String::new();
+        pulldown_cmark::html::push_html(&mut html_body, events.into_iter());
+        Ok(vec![(output_filename, html_body)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codec::{builder::GraphBuilder, proto_index::ProtoIndex},
+        nodekey::NodeKey,
+        tests::helpers::init_logging,
+    };
+    use petgraph::visit::EdgeRef;
+    use tempfile::TempDir;
+
+    const NOTES: &str = r#"#+TITLE: Field Notes
+#+AUTHOR: Ada
+#+FILETAGS: :research:notes:
+
+Intro text linking to [[file:other.org::*Some Heading][the other file]].
+
+* TODO [#A] Observations :field:
+SCHEDULED: <2025-01-01 Wed>
+:PROPERTIES:
+:CUSTOM_ID: observations
+:EFFORT: 1:00
+:END:
+We saw *many* things, see [[*Analysis]].
+
+** Birds
+- one =sparrow=
+- two [[https://example.com][links]]
+
+* Analysis
+#+BEGIN_SRC rust
+// [[not-a-link]]
+fn main() {}
+#+END_SRC
+"#;
+
+    fn parse_file(path: &Path, content: &str) -> (OrgCodec, Vec<ParseDiagnostic>) {
+        let mut codec = OrgCodec::new();
+        let proto = codec
+            .proto(path)
+            .expect("proto should succeed")
+            .expect("proto should return Some");
+        let mut diagnostics = Vec::new();
+        codec
+            .parse(content, proto, &mut diagnostics)
+            .expect("parse should succeed");
+        (codec, diagnostics)
+    }
+
+    fn write_notes(content: &str) -> (TempDir, std::path::PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("field_notes.org");
+        fs::write(&path, content).unwrap();
+        (temp_dir, path)
+    }
+
+    #[test]
+    fn test_org_codec_registered() {
+        assert!(CODECS.get(&AnchorPath::new("notes/journal.org")).is_some());
+    }
+
+    #[test]
+    fn test_headlines_become_sections() {
+        init_logging();
+        let (_temp_dir, path) = write_notes(NOTES);
+        let (codec, diagnostics) = parse_file(&path, NOTES);
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+        let nodes = codec.nodes();
+        let summary = nodes
+            .iter()
+            .map(|node| (node.heading, node.title().unwrap_or_default()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (2, "Field Notes".to_string()),
+                (3, "Observations".to_string()),
+                (4, "Birds".to_string()),
+                (3, "Analysis".to_string()),
+            ]
+        );
+        assert!(nodes[0].kind.contains(BeliefKind::Document));
+        assert_eq!(nodes[0].document["author"].as_str(), Some("Ada"));
+        assert_eq!(
+            nodes[0].document["tags"].as_array().map(|tags| tags.len()),
+            Some(2)
+        );
+        assert_eq!(nodes[1].id().as_deref(), Some("observations"));
+        assert_eq!(nodes[1].document["todo"].as_str(), Some("TODO"));
+        assert_eq!(nodes[1].document["effort"].as_str(), Some("1:00"));
+        assert_eq!(nodes[3].id().as_deref(), Some("analysis"));
+    }
+
+    #[test]
+    fn test_links_become_relations() {
+        init_logging();
+        let (_temp_dir, path) = write_notes(NOTES);
+        let (codec, _) = parse_file(&path, NOTES);
+        let nodes = codec.nodes();
+
+        let doc_links = &nodes[0].upstream;
+        assert_eq!(doc_links.len(), 1, "{doc_links:?}");
+        let expected = href_to_nodekey("other.org#some-heading").resolve_against(&nodes[0].path);
+        assert_eq!(doc_links[0].key, expected);
+        assert_eq!(doc_links[0].kind, WeightKind::Epistemic);
+        let offset = doc_links[0].location.unwrap();
+        assert!(NOTES[offset..].starts_with("[[file:other.org"));
+
+        let section_links = &nodes[1].upstream;
+        assert_eq!(section_links.len(), 1, "{section_links:?}");
+        assert_eq!(
+            section_links[0].key,
+            href_to_nodekey("#analysis").resolve_against(&nodes[0].path)
+        );
+
+        assert_eq!(
+            nodes[2].upstream.len(),
+            1,
+            "external links are relations too"
+        );
+        assert!(
+            nodes[3].upstream.is_empty(),
+            "links inside src blocks are ignored"
+        );
+    }
+
+    #[test]
+    fn test_org_target_to_href() {
+        assert_eq!(
+            org_target_to_href("file:notes/x.org::*A Heading").as_deref(),
+            Some("notes/x.org#a-heading")
+        );
+        assert_eq!(
+            org_target_to_href("file:x.org::#custom").as_deref(),
+            Some("x.org#custom")
+        );
+        assert_eq!(
+            org_target_to_href("file:x.org::42").as_deref(),
+            Some("x.org")
+        );
+        assert_eq!(org_target_to_href("#custom").as_deref(), Some("#custom"));
+        assert_eq!(
+            org_target_to_href("Fuzzy Target").as_deref(),
+            Some("#fuzzy-target")
+        );
+        assert_eq!(org_target_to_href("id:my-id").as_deref(), Some("id:my-id"));
+        let bid = Bid::new(Bid::nil());
+        assert_eq!(
+            org_target_to_href(&format!("id:{bid}")),
+            Some(format!("bid:{bid}"))
+        );
+        assert!(matches!(
+            href_to_nodekey(&org_target_to_href(&format!("id:{bid}")).unwrap()),
+            NodeKey::Bid { .. }
+        ));
+    }
+
+    #[test]
+    fn test_id_property_maps_to_bid() {
+        init_logging();
+        let bid = Bid::new(Bid::nil());
+        let content = format!("* Heading\n:PROPERTIES:\n:ID: {bid}\n:END:\nBody\n");
+        let (_temp_dir, path) = write_notes(&content);
+        let (codec, _) = parse_file(&path, &content);
+        let nodes = codec.nodes();
+        assert_eq!(
+            nodes[1].document.get("bid").and_then(|v| v.as_str()),
+            Some(bid.to_string().as_str())
+        );
+
+        // A non-UUID :ID: is kept as payload rather than treated as a BID.
+        let content = "* Heading\n:PROPERTIES:\n:ID: my-custom-id\n:END:\n";
+        let (codec, _) = parse_file(&path, content);
+        let nodes = codec.nodes();
+        assert!(nodes[1].document.get("bid").is_none());
+        assert_eq!(nodes[1].document["org_id"].as_str(), Some("my-custom-id"));
+    }
+
+    #[test]
+    fn test_duplicate_headlines_warn() {
+        init_logging();
+        let content = "* Notes\nfirst\n* Notes\nsecond\n";
+        let (_temp_dir, path) = write_notes(content);
+        let (codec, diagnostics) = parse_file(&path, content);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        let nodes = codec.nodes();
+        assert_eq!(nodes[2].document["id"].as_str(), Some(""));
+        assert_eq!(nodes[2].id(), None);
+    }
+
+    #[test]
+    fn test_generate_source_injects_into_drawers() {
+        init_logging();
+        let (_temp_dir, path) = write_notes(NOTES);
+        let (mut codec, _) = parse_file(&path, NOTES);
+        assert_eq!(codec.generate_source().as_deref(), Some(NOTES));
+
+        let bids = (0..codec.nodes.len())
+            .map(|_| Bid::new(Bid::nil()).to_string())
+            .collect::<Vec<_>>();
+        for (node, bid) in codec.nodes.iter_mut().zip(bids.iter()) {
+            OrgCodec::queue_edit(node, "ID", bid, None);
+        }
+        let source = codec.generate_source().unwrap();
+
+        // Existing drawer gains an :ID: line before :END:, after the planning line.
+        assert!(source.contains(&format!(
+            "SCHEDULED: <2025-01-01 Wed>\n:PROPERTIES:\n:CUSTOM_ID: observations\n:EFFORT: 1:00\n:ID: {}\n:END:\n",
+            bids[1]
+        )));
+        // Headlines without a drawer get one right after the headline.
+        assert!(source.contains(&format!(
+            "** Birds\n:PROPERTIES:\n:ID: {}\n:END:\n- one",
+            bids[2]
+        )));
+        // The document drawer goes at the top of the file.
+        assert!(source.starts_with(&format!(":PROPERTIES:\n:ID: {}\n:END:\n#+TITLE:", bids[0])));
+
+        // Reparsing picks every BID back up.
+        let (reparsed, diagnostics) = parse_file(&path, &source);
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        for (node, bid) in reparsed.nodes().iter().zip(bids.iter()) {
+            assert_eq!(
+                node.document.get("bid").and_then(|v| v.as_str()),
+                Some(bid.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_source_replaces_existing_id() {
+        init_logging();
+        let old = Bid::new(Bid::nil());
+        let new = Bid::new(Bid::nil());
+        let content = format!("* Heading\n  :PROPERTIES:\n  :ID:       {old}\n  :END:\n");
+        let (_temp_dir, path) = write_notes(&content);
+        let (mut codec, _) = parse_file(&path, &content);
+        let node = &mut codec.nodes[1];
+        assert_eq!(node.bid_property.as_deref(), Some("ID"));
+        OrgCodec::queue_edit(node, "ID", &new.to_string(), Some(&old.to_string()));
+        assert_eq!(
+            codec.generate_source().unwrap(),
+            format!("* Heading\n  :PROPERTIES:\n  :ID: {new}\n  :END:\n")
+        );
+    }
+
+    #[test]
+    fn test_generate_html_matches_markdown_fragments() {
+        init_logging();
+        let (_temp_dir, path) = write_notes(NOTES);
+        let (codec, _) = parse_file(&path, NOTES);
+        let html = codec.generate_html().unwrap();
+        assert_eq!(html.len(), 1);
+        assert_eq!(html[0].0, "field_notes.html");
+        let body = &html[0].1;
+
+        assert!(
+            body.contains("<a href=\"other.html#some-heading\">the other file</a>"),
+            "{body}"
+        );
+        assert!(body.contains("<h1 id=\"observations\">"), "{body}");
+        assert!(body.contains("<h2 id=\"birds\">Birds</h2>"), "{body}");
+        assert!(body.contains("<strong>many</strong>"), "{body}");
+        assert!(body.contains("<li>one <code>sparrow</code></li>"), "{body}");
+        assert!(
+            body.contains("<a href=\"https://example.com\">links</a>"),
+            "{body}"
+        );
+        assert!(
+            body.contains("<pre><code class=\"language-rust\">"),
+            "{body}"
+        );
+        assert!(!body.contains("PROPERTIES"), "{body}");
+        assert!(!body.contains("SCHEDULED"), "{body}");
+        assert!(!body.contains("#+TITLE"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_parse_content_builds_sections_and_writes_bids() {
+        init_logging();
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("index.md"),
+            "---\nid: \"test-network\"\ntitle: \"Test Network\"\n---\n\n# Test Network\n",
+        )
+        .unwrap();
+        let path = temp_dir.path().join("field_notes.org");
+        fs::write(&path, NOTES).unwrap();
+
+        let mut builder = GraphBuilder::new(temp_dir.path(), None).unwrap();
+        let session_bb = builder.session_bb().clone();
+        let proto_index = ProtoIndex::build(builder.repo_root()).unwrap_or_default();
+        let result = builder
+            .parse_content(&path, NOTES.to_string(), session_bb, proto_index)
+            .await
+            .expect("parse_content should succeed");
+
+        let rewritten = result
+            .result
+            .rewritten_content
+            .expect("new BIDs should be written back into property drawers");
+        let (reparsed, _) = parse_file(&path, &rewritten);
+        let bids = reparsed
+            .nodes()
+            .iter()
+            .map(|node| {
+                node.document
+                    .get("bid")
+                    .and_then(|v| v.as_str())
+                    .and_then(|bid| Bid::try_from(bid).ok())
+                    .expect("every node should have a BID written back")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(bids.len(), 4);
+        // Only drawers were touched.
+        assert!(rewritten.contains("We saw *many* things, see [[*Analysis]]."));
+
+        // Section edges follow headline nesting: Birds sits under Observations, and the
+        // top-level headlines under the document.
+        let doc_bb = builder.doc_bb_mut();
+        let relations = doc_bb.relations();
+        let graph = relations.as_graph();
+        let section_edges = graph
+            .edge_references()
+            .filter(|edge| edge.weight().get(&WeightKind::Section).is_some())
+            .map(|edge| (graph[edge.source()], graph[edge.target()]))
+            .collect::<HashSet<_>>();
+        assert!(
+            section_edges.contains(&(bids[1], bids[0])),
+            "{section_edges:?}"
+        );
+        assert!(
+            section_edges.contains(&(bids[2], bids[1])),
+            "{section_edges:?}"
+        );
+        assert!(
+            section_edges.contains(&(bids[3], bids[0])),
+            "{section_edges:?}"
+        );
+    }
+}
@@ -0,0 +1,1849 @@
+//! Rust source codec.
+//!
+//! [`RustDocCodec`] indexes the documentation in `.rs` files so rustdoc and design documents share
+//! one graph (see `docs/project/ISSUE_27_RUSTDOC_INTEGRATION.md`):
+//!
+//! - The file is the Document node for its module. Its title is the module path
+//!   (`codec::compiler`), derived from the file's location below `src/`, and its `//!` comments
+//!   are the document text. Crate roots are titled after the crate directory.
+//! - Public items become section nodes carrying their `///` comments: structs, enums, unions,
+//!   traits and their items, functions, inherent `pub fn`s, type aliases, consts, statics,
+//!   `pub mod` blocks and `#[macro_export]` macros. An item's `id` is its path with `.`
+//!   separators (`codec.compiler.documentcompiler`), which is what `id:codec::compiler::DocumentCompiler`
+//!   parses to, so design documents can link to items and have those links checked.
+//! - Intra-doc links (``[`DocumentCompiler`]``, `[crate::codec::md]`, `[Self::new]`) and Markdown
+//!   links to other documents become `Epistemic` relations. Item paths resolve like rustdoc's
+//!   for the common cases: through `use` imports, `crate`/`self`/`super`/`Self` prefixes and the
+//!   current module. Links into `std`, `core`, `alloc` and prelude names are skipped.
+//!
+//! Code is never rewritten. `// bid: <uuid>` comments above an item (or on the first line of the
+//! file, for the module) are read as BIDs; [`RustDocCodec::with_bid_comments`] opts into writing
+//! them back.
+//!
+//! The scanner is lexical rather than a full Rust parser: it skips strings, comments and bodies,
+//! and only looks at item headers. Block doc comments (`/** */`) are not indexed.
+
+use pulldown_cmark::{
+    BrokenLink, CodeBlockKind, CowStr, Event as MdEvent, HeadingLevel, Parser as MdParser, Tag,
+    TagEnd,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+    result::Result,
+};
+use toml_edit::value;
+
+use crate::{
+    beliefbase::BeliefContext,
+    codec::{
+        belief_ir::{IRNode, IntermediateRelation},
+        diagnostic::ParseDiagnostic,
+        md::{buildonomy_md_options, rewrite_href_to_html},
+        org::split_lines,
+        DocCodec,
+    },
+    error::BuildonomyError,
+    nodekey::{href_to_nodekey, NodeKey},
+    paths::{os_path_to_string, to_anchor, AnchorPath},
+    properties::{BeliefKind, BeliefNode, Bid, Weight, WeightKind},
+};
+
+/// Crates whose items are never part of the graph.
+const EXTERNAL_CRATES: &[&str] = &["std", "core", "alloc"];
+
+/// Primitive and prelude names rustdoc resolves without an import.
+const PRELUDE_NAMES: &[&str] = &[
+    "bool",
+    "char",
+    "str",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "f32",
+    "f64",
+    "String",
+    "Vec",
+    "Option",
+    "Some",
+    "None",
+    "Result",
+    "Ok",
+    "Err",
+    "Box",
+    "Clone",
+    "Copy",
+    "Default",
+    "Debug",
+    "Drop",
+    "Eq",
+    "PartialEq",
+    "Ord",
+    "PartialOrd",
+    "Hash",
+    "Iterator",
+    "IntoIterator",
+    "From",
+    "Into",
+    "TryFrom",
+    "TryInto",
+    "AsRef",
+    "AsMut",
+    "Send",
+    "Sync",
+    "Sized",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "ToString",
+    "ToOwned",
+];
+
+/// Lexical token of a Rust source file. Whitespace and plain comments are dropped.
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    Punct(char),
+    Literal,
+    Lifetime,
+    /// A `///` (outer) or `//!` (inner) doc comment line.
+    Doc {
+        inner: bool,
+        text: String,
+    },
+    /// A `// bid: <uuid>` comment.
+    Bid(String),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    tok: Tok,
+    start: usize,
+}
+
+fn is_ident_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'_' || byte >= 0x80
+}
+
+fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// Byte index just past the closing quote of a string whose body starts at `idx`.
+fn skip_string(bytes: &[u8], mut idx: usize) -> usize {
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\\' => idx += 2,
+            b'"' => return idx + 1,
+            _ => idx += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Byte index just past the closing quote of a char literal whose body starts at `idx`.
+fn skip_char(bytes: &[u8], mut idx: usize) -> usize {
+    if bytes.get(idx) == Some(&b'\\') {
+        idx += 2;
+    }
+    while idx < bytes.len() && bytes[idx] != b'\'' {
+        idx += 1;
+    }
+    (idx + 1).min(bytes.len())
+}
+
+fn doc_text(rest: &str) -> String {
+    rest.strip_prefix(' ')
+        .unwrap_or(rest)
+        .trim_end()
+        .to_string()
+}
+
+fn tokenize(src: &str) -> Vec<Token> {
+    let bytes = src.as_bytes();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let start = idx;
+        let byte = bytes[idx];
+        if byte.is_ascii_whitespace() {
+            idx += 1;
+        } else if bytes[idx..].starts_with(b"//") {
+            idx = src[idx..].find('\n').map_or(bytes.len(), |n| idx + n);
+            let line = &src[start..idx];
+            let tok = if line.starts_with("///") && !line.starts_with("////") {
+                Some(Tok::Doc {
+                    inner: false,
+                    text: doc_text(&line[3..]),
+                })
+            } else if let Some(rest) = line.strip_prefix("//!") {
+                Some(Tok::Doc {
+                    inner: true,
+                    text: doc_text(rest),
+                })
+            } else {
+                line[2..]
+                    .trim()
+                    .strip_prefix("bid:")
+                    .map(|bid| Tok::Bid(bid.trim().to_string()))
+            };
+            if let Some(tok) = tok {
+                tokens.push(Token { tok, start });
+            }
+        } else if bytes[idx..].starts_with(b"/*") {
+            let mut depth = 0;
+            while idx < bytes.len() {
+                if bytes[idx..].starts_with(b"/*") {
+                    depth += 1;
+                    idx += 2;
+                } else if bytes[idx..].starts_with(b"*/") {
+                    depth -= 1;
+                    idx += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    idx += 1;
+                }
+            }
+        } else if byte == b'"' {
+            idx = skip_string(bytes, idx + 1);
+            tokens.push(Token {
+                tok: Tok::Literal,
+                start,
+            });
+        } else if byte == b'\'' {
+            // Char literal ('a', '\n', 'é') or lifetime/label ('a, 'static).
+            let body = idx + 1;
+            let width = match bytes.get(body) {
+                Some(b) if *b >= 0xF0 => 4,
+                Some(b) if *b >= 0xE0 => 3,
+                Some(b) if *b >= 0xC0 => 2,
+                _ => 1,
+            };
+            let tok = if bytes.get(body) == Some(&b'\\') || bytes.get(body + width) == Some(&b'\'')
+            {
+                idx = skip_char(bytes, body);
+                Tok::Literal
+            } else {
+                idx = body;
+                while idx < bytes.len() && is_ident_byte(bytes[idx]) {
+                    idx += 1;
+                }
+                Tok::Lifetime
+            };
+            tokens.push(Token { tok, start });
+        } else if byte.is_ascii_digit() {
+            while idx < bytes.len()
+                && (is_ident_byte(bytes[idx])
+                    || (bytes[idx] == b'.'
+                        && bytes.get(idx + 1).is_some_and(|b| b.is_ascii_digit())))
+            {
+                idx += 1;
+            }
+            tokens.push(Token {
+                tok: Tok::Literal,
+                start,
+            });
+        } else if is_ident_start(byte) {
+            while idx < bytes.len() && is_ident_byte(bytes[idx]) {
+                idx += 1;
+            }
+            let word = &src[start..idx];
+            let tok = match (word, bytes.get(idx)) {
+                ("b" | "c", Some(b'"')) => {
+                    idx = skip_string(bytes, idx + 1);
+                    Tok::Literal
+                }
+                ("b", Some(b'\'')) => {
+                    idx = skip_char(bytes, idx + 1);
+                    Tok::Literal
+                }
+                ("r" | "br" | "cr", Some(b'"' | b'#')) => {
+                    let hashes = bytes[idx..].iter().take_while(|b| **b == b'#').count();
+                    if bytes.get(idx + hashes) == Some(&b'"') {
+                        let closing = format!("\"{}", "#".repeat(hashes));
+                        let body = idx + hashes + 1;
+                        idx = src[body..]
+                            .find(&closing)
+                            .map_or(bytes.len(), |n| body + n + closing.len());
+                        Tok::Literal
+                    } else if word == "r" && hashes == 1 {
+                        // Raw identifier (r#type)
+                        let ident_start = idx + 1;
+                        idx = ident_start;
+                        while idx < bytes.len() && is_ident_byte(bytes[idx]) {
+                            idx += 1;
+                        }
+                        Tok::Ident(src[ident_start..idx].to_string())
+                    } else {
+                        Tok::Ident(word.to_string())
+                    }
+                }
+                _ => Tok::Ident(word.to_string()),
+            };
+            tokens.push(Token { tok, start });
+        } else {
+            idx += 1;
+            tokens.push(Token {
+                tok: Tok::Punct(byte as char),
+                start,
+            });
+        }
+    }
+    tokens
+}
+
+/// A public item found by the scanner.
+#[derive(Debug, Clone)]
+struct RustItem {
+    kind: &'static str,
+    /// Path from the crate root, e.g. `["codec", "compiler", "DocumentCompiler"]`.
+    path: Vec<String>,
+    /// Module the item's doc links resolve in.
+    module: Vec<String>,
+    /// Type `Self` refers to in the item's doc links.
+    self_path: Option<Vec<String>>,
+    /// Doc comment lines with the byte offset of each `///`.
+    docs: Vec<(usize, String)>,
+    signature: String,
+    /// Enclosing trait, inline module or (for impl items) type, if it is indexed in this file.
+    parent: Option<usize>,
+    /// Type an inherent impl item belongs to; resolved to `parent` once all items are known.
+    owner: Option<Vec<String>>,
+    /// Byte offset of the item's first doc comment, attribute or BID comment.
+    start: usize,
+    /// Existing `// bid:` comment: byte offset and value.
+    bid: Option<(usize, String)>,
+}
+
+/// Where `//!` comments in a block belong.
+#[derive(Debug, Clone, Copy)]
+enum DocTarget {
+    File,
+    Item(usize),
+    Discard,
+}
+
+/// The block an item is declared in.
+#[derive(Debug, Clone)]
+struct Scope {
+    module: Vec<String>,
+    /// Path items declared in the block are nested under.
+    prefix: Vec<String>,
+    /// Whether items in the block can be part of the public API.
+    exported: bool,
+    /// Whether items need their own `pub` (trait items share the trait's visibility).
+    needs_pub: bool,
+    self_path: Option<Vec<String>>,
+    parent: Option<usize>,
+    owner: Option<Vec<String>>,
+    doc_target: DocTarget,
+}
+
+/// Doc comments, attributes and BID comment collected ahead of an item.
+#[derive(Debug, Default)]
+struct Pending {
+    docs: Vec<(usize, String)>,
+    bid: Option<(usize, String)>,
+    attrs: Vec<String>,
+    start: Option<usize>,
+}
+
+/// Result of scanning one `.rs` file.
+#[derive(Debug, Default)]
+struct ParsedFile {
+    module: Vec<String>,
+    docs: Vec<(usize, String)>,
+    bid: Option<(usize, String)>,
+    items: Vec<RustItem>,
+    /// `use` imports per module: local name to path as written.
+    imports: HashMap<Vec<String>, HashMap<String, Vec<String>>>,
+    /// Modules declared with `mod name;` or `mod name { .. }`.
+    declared_mods: HashSet<Vec<String>>,
+}
+
+struct Scanner<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    file: ParsedFile,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|token| &token.tok)
+    }
+
+    fn peek_at(&self, ahead: usize) -> Option<&Tok> {
+        self.tokens.get(self.pos + ahead).map(|token| &token.tok)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.src.len(), |token| token.start)
+    }
+
+    fn is_ident(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Ident(ident)) if ident == word)
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Tok::Punct(c))
+    }
+
+    fn ident(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Tok::Ident(ident)) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Some(ident)
+            }
+            _ => None,
+        }
+    }
+
+    /// Skip a balanced group starting at the current `open` token.
+    fn skip_group(&mut self, open: char, close: char) {
+        let mut depth = 0;
+        while let Some(tok) = self.peek() {
+            if *tok == Tok::Punct(open) {
+                depth += 1;
+            } else if *tok == Tok::Punct(close) {
+                depth -= 1;
+                if depth == 0 {
+                    self.pos += 1;
+                    return;
+                }
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Advance to the `{` or `;` ending an item header, without consuming it.
+    fn seek_terminator(&mut self) {
+        let mut depth = 0usize;
+        while let Some(tok) = self.peek() {
+            match tok {
+                Tok::Punct('(' | '[') => depth += 1,
+                Tok::Punct(')' | ']') => depth = depth.saturating_sub(1),
+                Tok::Punct('{' | ';') if depth == 0 => return,
+                Tok::Punct('}') if depth == 0 => return,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Skip the rest of an item: its `;` or its `{ .. }` body.
+    fn skip_item_rest(&mut self) {
+        self.seek_terminator();
+        if self.is_punct('{') {
+            self.skip_group('{', '}');
+        } else if self.is_punct(';') {
+            self.pos += 1;
+        }
+    }
+
+    /// Item header text from `start` to the current token, on one line.
+    fn signature(&self, start: usize) -> String {
+        self.src[start..self.offset()]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn scan(mut self) -> ParsedFile {
+        let scope = Scope {
+            module: self.file.module.clone(),
+            prefix: self.file.module.clone(),
+            exported: true,
+            needs_pub: true,
+            self_path: None,
+            parent: None,
+            owner: None,
+            doc_target: DocTarget::File,
+        };
+        self.scan_block(&scope, false);
+        self.file
+    }
+
+    fn scan_block(&mut self, scope: &Scope, nested: bool) {
+        let mut pending = Pending::default();
+        while let Some(token) = self.tokens.get(self.pos).cloned() {
+            match token.tok {
+                Tok::Doc { inner: true, text } => {
+                    match scope.doc_target {
+                        DocTarget::File => self.file.docs.push((token.start, text)),
+                        DocTarget::Item(idx) => self.file.items[idx].docs.push((token.start, text)),
+                        DocTarget::Discard => {}
+                    }
+                    self.pos += 1;
+                }
+                Tok::Doc { inner: false, text } => {
+                    pending.start.get_or_insert(token.start);
+                    pending.docs.push((token.start, text));
+                    self.pos += 1;
+                }
+                Tok::Bid(bid) => {
+                    if matches!(scope.doc_target, DocTarget::File) && token.start == 0 {
+                        self.file.bid = Some((token.start, bid));
+                    } else {
+                        pending.start.get_or_insert(token.start);
+                        pending.bid = Some((token.start, bid));
+                    }
+                    self.pos += 1;
+                }
+                Tok::Punct('#') => {
+                    self.pos += 1;
+                    let inner = self.is_punct('!');
+                    if inner {
+                        self.pos += 1;
+                    } else {
+                        pending.start.get_or_insert(token.start);
+                    }
+                    let attr_start = self.pos;
+                    self.skip_group('[', ']');
+                    if !inner {
+                        for token in &self.tokens[attr_start..self.pos] {
+                            if let Tok::Ident(ident) = &token.tok {
+                                pending.attrs.push(ident.clone());
+                            }
+                        }
+                    }
+                }
+                Tok::Punct('}') => {
+                    self.pos += 1;
+                    if nested {
+                        return;
+                    }
+                }
+                Tok::Punct(';') => {
+                    self.pos += 1;
+                    pending = Pending::default();
+                }
+                _ => {
+                    let start = pending.start.unwrap_or(token.start);
+                    self.scan_item(scope, std::mem::take(&mut pending), start);
+                }
+            }
+        }
+    }
+
+    fn scan_item(&mut self, scope: &Scope, pending: Pending, start: usize) {
+        let header_start = self.offset();
+        let mut has_pub = false;
+        if self.is_ident("pub") {
+            self.pos += 1;
+            if self.is_punct('(') {
+                // pub(crate), pub(super), pub(in path): not public API
+                self.skip_group('(', ')');
+            } else {
+                has_pub = true;
+            }
+        }
+        loop {
+            match self.peek() {
+                Some(Tok::Ident(word))
+                    if matches!(word.as_str(), "async" | "unsafe" | "default" | "auto") =>
+                {
+                    self.pos += 1
+                }
+                Some(Tok::Ident(word)) if word == "extern" => {
+                    self.pos += 1;
+                    if self.peek() == Some(&Tok::Literal) {
+                        self.pos += 1;
+                    }
+                }
+                Some(Tok::Ident(word))
+                    if word == "const"
+                        && matches!(
+                            self.peek_at(1),
+                            Some(Tok::Ident(next))
+                                if matches!(next.as_str(), "fn" | "unsafe" | "async" | "extern")
+                        ) =>
+                {
+                    self.pos += 1
+                }
+                _ => break,
+            }
+        }
+        let public = scope.exported && (has_pub || !scope.needs_pub);
+        let Some(keyword) = self.ident() else {
+            self.skip_item_rest();
+            return;
+        };
+        let record = |scanner: &mut Self, kind: &'static str, name: String, sig: String| {
+            let path = child(&scope.prefix, &name);
+            let self_path = match kind {
+                "struct" | "enum" | "union" | "trait" | "type" => Some(path.clone()),
+                _ => scope.self_path.clone(),
+            };
+            scanner.file.items.push(RustItem {
+                kind,
+                path,
+                module: scope.module.clone(),
+                self_path,
+                docs: pending.docs.clone(),
+                signature: sig,
+                parent: scope.parent,
+                owner: scope.owner.clone(),
+                start,
+                bid: pending.bid.clone(),
+            });
+            scanner.file.items.len() - 1
+        };
+        match keyword.as_str() {
+            "fn" | "struct" | "enum" | "union" | "type" | "const" | "static" => {
+                if keyword == "static" && self.is_ident("mut") {
+                    self.pos += 1;
+                }
+                let name = self.ident();
+                self.seek_terminator();
+                let mut sig = self.signature(header_start);
+                if matches!(keyword.as_str(), "const" | "static") {
+                    if let Some((decl, _)) = sig.split_once(" = ") {
+                        sig = decl.to_string();
+                    }
+                }
+                if self.is_punct('{') {
+                    self.skip_group('{', '}');
+                } else if self.is_punct(';') {
+                    self.pos += 1;
+                }
+                match name {
+                    Some(name) if public && name != "_" => {
+                        let kind = match keyword.as_str() {
+                            "fn" => "fn",
+                            "struct" => "struct",
+                            "enum" => "enum",
+                            "union" => "union",
+                            "type" => "type",
+                            "const" => "const",
+                            _ => "static",
+                        };
+                        record(self, kind, name, sig);
+                    }
+                    _ => {}
+                }
+            }
+            "trait" => {
+                let Some(name) = self.ident() else {
+                    self.skip_item_rest();
+                    return;
+                };
+                self.seek_terminator();
+                let sig = self.signature(header_start);
+                let item = public.then(|| record(self, "trait", name.clone(), sig));
+                if self.is_punct('{') {
+                    self.pos += 1;
+                    let path = child(&scope.prefix, &name);
+                    let inner = Scope {
+                        module: scope.module.clone(),
+                        prefix: path.clone(),
+                        exported: public,
+                        needs_pub: false,
+                        self_path: Some(path),
+                        parent: item,
+                        owner: None,
+                        doc_target: DocTarget::Discard,
+                    };
+                    self.scan_block(&inner, true);
+                }
+            }
+            "mod" => {
+                let Some(name) = self.ident() else {
+                    self.skip_item_rest();
+                    return;
+                };
+                let path = child(&scope.module, &name);
+                self.file.declared_mods.insert(path.clone());
+                self.seek_terminator();
+                if self.is_punct('{') {
+                    let sig = self.signature(header_start);
+                    let item = public.then(|| record(self, "mod", name, sig));
+                    self.pos += 1;
+                    let inner = Scope {
+                        module: path.clone(),
+                        prefix: path,
+                        exported: public,
+                        needs_pub: true,
+                        self_path: None,
+                        parent: item,
+                        owner: None,
+                        doc_target: item.map_or(DocTarget::Discard, DocTarget::Item),
+                    };
+                    self.scan_block(&inner, true);
+                } else if self.is_punct(';') {
+                    self.pos += 1;
+                }
+            }
+            "impl" => {
+                let (self_ty, trait_impl) = self.impl_header();
+                if self.is_punct('{') {
+                    self.pos += 1;
+                    let owner = resolve_path(&self.file, &scope.module, None, &self_ty);
+                    let inner = Scope {
+                        module: scope.module.clone(),
+                        prefix: owner.clone().unwrap_or_default(),
+                        exported: scope.exported && !trait_impl && owner.is_some(),
+                        needs_pub: true,
+                        self_path: owner.clone(),
+                        parent: None,
+                        owner,
+                        doc_target: DocTarget::Discard,
+                    };
+                    self.scan_block(&inner, true);
+                } else if self.is_punct(';') {
+                    self.pos += 1;
+                }
+            }
+            "use" => {
+                let mut leaves = Vec::new();
+                self.use_tree(Vec::new(), &mut leaves);
+                let imports = self.file.imports.entry(scope.module.clone()).or_default();
+                for (alias, path) in leaves {
+                    imports.insert(alias, path);
+                }
+                self.skip_item_rest();
+            }
+            "macro_rules" => {
+                if self.is_punct('!') {
+                    self.pos += 1;
+                }
+                let name = self.ident();
+                let sig = self.signature(header_start);
+                match self.peek() {
+                    Some(Tok::Punct('{')) => self.skip_group('{', '}'),
+                    Some(Tok::Punct('(')) => self.skip_group('(', ')'),
+                    Some(Tok::Punct('[')) => self.skip_group('[', ']'),
+                    _ => {}
+                }
+                if let Some(name) =
+                    name.filter(|_| pending.attrs.iter().any(|a| a == "macro_export"))
+                {
+                    // Exported macros live at the crate root.
+                    self.file.items.push(RustItem {
+                        kind: "macro",
+                        path: vec![name],
+                        module: scope.module.clone(),
+                        self_path: None,
+                        docs: pending.docs.clone(),
+                        signature: sig,
+                        parent: None,
+                        owner: None,
+                        start,
+                        bid: pending.bid.clone(),
+                    });
+                }
+            }
+            _ => self.skip_item_rest(),
+        }
+    }
+
+    /// Read an `impl` header up to its `{`, returning the self type path as written and whether
+    /// it is a trait impl.
+    fn impl_header(&mut self) -> (Vec<String>, bool) {
+        let mut angle = 0usize;
+        let mut paren = 0usize;
+        let mut trait_impl = false;
+        let mut path: Vec<String> = Vec::new();
+        let mut after_path_sep = false;
+        let mut in_where = false;
+        while let Some(tok) = self.peek().cloned() {
+            match tok {
+                Tok::Punct('{' | ';') if angle == 0 && paren == 0 => break,
+                Tok::Punct('<') => angle += 1,
+                Tok::Punct('>') => {
+                    let arrow = self.pos > 0
+                        && self.tokens[self.pos - 1].tok == Tok::Punct('-')
+                        && self.tokens[self.pos - 1].start + 1 == self.tokens[self.pos].start;
+                    if !arrow {
+                        angle = angle.saturating_sub(1);
+                    }
+                }
+                Tok::Punct('(' | '[') => paren += 1,
+                Tok::Punct(')' | ']') => paren = paren.saturating_sub(1),
+                Tok::Punct(':') if angle == 0 && paren == 0 => {
+                    after_path_sep = self.peek_at(1) == Some(&Tok::Punct(':'));
+                    if after_path_sep {
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                    continue;
+                }
+                Tok::Ident(word) if angle == 0 && paren == 0 && !in_where => match word.as_str() {
+                    "for" => {
+                        trait_impl = true;
+                        path.clear();
+                    }
+                    "where" => in_where = true,
+                    "dyn" | "mut" | "const" | "unsafe" => {}
+                    _ if after_path_sep => path.push(word),
+                    _ => path = vec![word],
+                },
+                _ => {}
+            }
+            after_path_sep = false;
+            self.pos += 1;
+        }
+        (path, trait_impl)
+    }
+
+    /// Collect `(local name, path)` pairs from a `use` tree.
+    fn use_tree(&mut self, mut prefix: Vec<String>, leaves: &mut Vec<(String, Vec<String>)>) {
+        let mut alias = None;
+        loop {
+            match self.peek() {
+                Some(Tok::Ident(word)) if word == "as" => {
+                    self.pos += 1;
+                    alias = self.ident();
+                }
+                Some(Tok::Ident(word)) => {
+                    prefix.push(word.clone());
+                    self.pos += 1;
+                }
+                Some(Tok::Punct(':')) => self.pos += 1,
+                Some(Tok::Punct('{')) => {
+                    self.pos += 1;
+                    loop {
+                        self.use_tree(prefix.clone(), leaves);
+                        match self.peek() {
+                            Some(Tok::Punct(',')) => self.pos += 1,
+                            Some(Tok::Punct('}')) => {
+                                self.pos += 1;
+                                return;
+                            }
+                            _ => return,
+                        }
+                    }
+                }
+                Some(Tok::Punct('*')) => {
+                    self.pos += 1;
+                    return;
+                }
+                _ => break,
+            }
+        }
+        if prefix.last().is_some_and(|last| last == "self") {
+            prefix.pop();
+        }
+        if let Some(name) = alias.or_else(|| prefix.last().cloned()) {
+            if name != "_" && !prefix.is_empty() {
+                leaves.push((name, prefix));
+            }
+        }
+    }
+}
+
+/// Module path of a source file, from its location below the crate's `src/` directory.
+fn module_path(path: &str) -> Vec<String> {
+    let parts = path.split('/').collect::<Vec<_>>();
+    let Some(src_idx) = parts.iter().rposition(|part| *part == "src") else {
+        return Vec::new();
+    };
+    let mut segments = parts[src_idx + 1..]
+        .iter()
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>();
+    let Some(file) = segments.pop() else {
+        return Vec::new();
+    };
+    let stem = file.strip_suffix(".rs").unwrap_or(&file).to_string();
+    if segments.first().is_some_and(|first| first == "bin") {
+        return Vec::new();
+    }
+    match stem.as_str() {
+        "lib" | "main" if segments.is_empty() => segments,
+        "mod" => segments,
+        _ => {
+            segments.push(stem);
+            segments
+        }
+    }
+}
+
+/// Title of a crate root document: the crate directory (parent of `src/`), else the filestem.
+fn crate_title(path: &str) -> String {
+    let parts = path.split('/').collect::<Vec<_>>();
+    parts
+        .iter()
+        .rposition(|part| *part == "src")
+        .filter(|idx| *idx > 0)
+        .map(|idx| parts[idx - 1].to_string())
+        .unwrap_or_else(|| AnchorPath::from(path).filestem().to_string())
+}
+
+/// Node id for an item path: `codec::compiler::DocumentCompiler` -> `codec.compiler.documentcompiler`.
+pub fn item_id(path: &[String]) -> String {
+    to_anchor(&path.join("."))
+}
+
+fn child(module: &[String], name: &str) -> Vec<String> {
+    let mut path = module.to_vec();
+    path.push(name.to_string());
+    path
+}
+
+/// Resolve an item path as written in `module` to a path from the crate root. Returns `None` for
+/// paths outside the crate.
+fn resolve_path(
+    file: &ParsedFile,
+    module: &[String],
+    self_path: Option<&[String]>,
+    segments: &[String],
+) -> Option<Vec<String>> {
+    let (first, rest) = segments.split_first()?;
+    let mut base = match first.as_str() {
+        "crate" => Vec::new(),
+        "self" => module.to_vec(),
+        "super" => {
+            let mut base = module.to_vec();
+            base.pop()?;
+            let mut rest = rest;
+            while rest.first().is_some_and(|seg| seg == "super") {
+                base.pop()?;
+                rest = &rest[1..];
+            }
+            base.extend(rest.iter().cloned());
+            return Some(base);
+        }
+        "Self" => self_path?.to_vec(),
+        _ => {
+            if let Some(import) = file
+                .imports
+                .get(module)
+                .and_then(|imports| imports.get(first))
+            {
+                // Imports start at `crate`, `self`, `super`, a local module or another crate.
+                let import_base = match import.first().map(String::as_str) {
+                    Some("crate" | "self" | "super") => resolve_path(file, module, None, import)?,
+                    Some(head) if file.declared_mods.contains(&child(module, head)) => {
+                        [module, import.as_slice()].concat()
+                    }
+                    _ => return None,
+                };
+                let mut base = import_base;
+                base.extend(rest.iter().cloned());
+                return Some(base);
+            }
+            if EXTERNAL_CRATES.contains(&first.as_str()) {
+                return None;
+            }
+            let local = child(module, first);
+            if rest.is_empty()
+                || file.declared_mods.contains(&local)
+                || file.items.iter().any(|item| item.path == local)
+            {
+                if rest.is_empty() && PRELUDE_NAMES.contains(&first.as_str()) {
+                    return None;
+                }
+                let mut base = module.to_vec();
+                base.extend(segments.iter().cloned());
+                return Some(base);
+            }
+            // Multi-segment paths are read from the crate root.
+            return Some(segments.to_vec());
+        }
+    };
+    base.extend(rest.iter().cloned());
+    Some(base)
+}
+
+/// Where a doc comment link points.
+#[derive(Debug, Clone, PartialEq)]
+enum DocLink {
+    /// An item path from the crate root.
+    Item(Vec<String>),
+    /// A document path or URL.
+    Href(String),
+}
+
+/// Classify a doc comment link destination. Returns `None` for links that aren't relations
+/// (page anchors, out-of-crate items, bracketed prose).
+fn classify_link(
+    file: &ParsedFile,
+    module: &[String],
+    self_path: Option<&[String]>,
+    dest: &str,
+) -> Option<DocLink> {
+    let dest = dest.trim().trim_matches('`');
+    if dest.is_empty() || dest.starts_with('#') {
+        return None;
+    }
+    // Strip rustdoc disambiguators (struct@Foo, Foo(), foo!)
+    let mut candidate = dest
+        .split_once('@')
+        .filter(|(prefix, _)| prefix.chars().all(|c| c.is_ascii_alphabetic()))
+        .map_or(dest, |(_, rest)| rest);
+    candidate = candidate.strip_suffix("()").unwrap_or(candidate);
+    candidate = candidate.strip_suffix('!').unwrap_or(candidate);
+    let segments = candidate
+        .split("::")
+        .map(|seg| seg.to_string())
+        .collect::<Vec<_>>();
+    let is_path = segments.iter().all(|seg| {
+        seg.bytes().next().is_some_and(is_ident_start) && seg.bytes().all(is_ident_byte)
+    });
+    if is_path {
+        return resolve_path(file, module, self_path, &segments).map(DocLink::Item);
+    }
+    if dest.contains(char::is_whitespace) || dest.contains(',') {
+        return None;
+    }
+    (dest.contains('/') || dest.contains('.') || dest.contains(':'))
+        .then(|| DocLink::Href(dest.to_string()))
+}
+
+/// Link destinations and texts in a doc comment, with the byte offset of the line they're on.
+fn doc_links(docs: &[(usize, String)]) -> Vec<(String, String, usize)> {
+    let text = docs
+        .iter()
+        .map(|(_, line)| line.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut line_starts = Vec::with_capacity(docs.len());
+    let mut offset = 0;
+    for (_, line) in docs {
+        line_starts.push(offset);
+        offset += line.len() + 1;
+    }
+    let mut links = Vec::new();
+    let mut current: Option<(String, String, usize)> = None;
+    for (event, range) in MdParser::new_with_broken_link_callback(
+        &text,
+        buildonomy_md_options(),
+        Some(|link: BrokenLink<'_>| Some((link.reference.into_static(), CowStr::from("")))),
+    )
+    .into_offset_iter()
+    {
+        match event {
+            MdEvent::Start(Tag::Link { dest_url, .. }) => {
+                let line = line_starts.partition_point(|start| *start <= range.start) - 1;
+                current = Some((dest_url.to_string(), String::new(), docs[line].0));
+            }
+            MdEvent::Text(text) | MdEvent::Code(text) => {
+                if let Some((_, title, _)) = current.as_mut() {
+                    title.push_str(&text);
+                }
+            }
+            MdEvent::End(TagEnd::Link) => links.extend(current.take()),
+            _ => {}
+        }
+    }
+    links
+}
+
+#[derive(Debug, Clone)]
+struct RustNode {
+    proto: IRNode,
+    /// Nesting below the document: 0 for the document node, 1 for top-level items.
+    depth: usize,
+    title: String,
+    signature: String,
+    docs: String,
+    /// HTML targets for the doc comment's link destinations (`None` renders the link as text).
+    html_links: HashMap<String, Option<String>>,
+    /// Byte offset a new BID comment is inserted before.
+    start: usize,
+    /// Existing `// bid:` comment: byte offset and value.
+    bid_comment: Option<(usize, String)>,
+    /// BID to write as a comment on `generate_source`.
+    edit: Option<String>,
+}
+
+/// Codec for Rust source (`.rs`) files. See the [module docs](self).
+#[derive(Debug, Default, Clone)]
+pub struct RustDocCodec {
+    content: String,
+    lines: Vec<String>,
+    line_starts: Vec<usize>,
+    nodes: Vec<RustNode>,
+    bid_comments: bool,
+}
+
+impl RustDocCodec {
+    pub fn new() -> Self {
+        RustDocCodec::default()
+    }
+
+    /// A codec that persists BIDs as `// bid: <uuid>` comments above each indexed item.
+    pub fn with_bid_comments() -> Self {
+        RustDocCodec {
+            bid_comments: true,
+            ..Default::default()
+        }
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset) - 1
+    }
+}
+
+/// Scan a Rust file into the document node followed by one node per public item.
+///
+/// `current` seeds the document node (path, proto metadata).
+fn parse_rust(content: &str, mut current: IRNode) -> Result<Vec<RustNode>, BuildonomyError> {
+    let doc_path = current.path.clone();
+    let module = module_path(&doc_path);
+    let scanner = Scanner {
+        src: content,
+        tokens: tokenize(content),
+        pos: 0,
+        file: ParsedFile {
+            module: module.clone(),
+            ..Default::default()
+        },
+    };
+    let mut file = scanner.scan();
+
+    // Nest inherent impl items under their type when it is indexed in this file.
+    let paths = file
+        .items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| (item.path.clone(), idx))
+        .collect::<HashMap<_, _>>();
+    for item in file.items.iter_mut() {
+        if let Some(owner) = item.owner.as_ref() {
+            item.parent = paths.get(owner).copied();
+        }
+    }
+    let local_ids = file
+        .items
+        .iter()
+        .map(|item| item_id(&item.path))
+        .collect::<HashSet<_>>();
+
+    let relations = |docs: &[(usize, String)],
+                     module: &[String],
+                     self_path: Option<&[String]>|
+     -> (Vec<IntermediateRelation>, HashMap<String, Option<String>>) {
+        let mut relations = Vec::new();
+        let mut html_links = HashMap::new();
+        for (dest, text, offset) in doc_links(docs) {
+            let (key, html) = match classify_link(&file, module, self_path, &dest) {
+                Some(DocLink::Item(path)) => {
+                    let id = item_id(&path);
+                    let html = local_ids.contains(&id).then(|| format!("#{id}"));
+                    let key = NodeKey::Id {
+                        net: Bid::nil().bref(),
+                        id,
+                    };
+                    (key, html)
+                }
+                Some(DocLink::Href(href)) => (
+                    href_to_nodekey(&href).resolve_against(&doc_path),
+                    Some(rewrite_href_to_html(&href)),
+                ),
+                None => {
+                    html_links.insert(dest, None);
+                    continue;
+                }
+            };
+            html_links.insert(dest.clone(), html);
+            let payload = Some(text)
+                .filter(|title| !title.is_empty() && title != dest.trim_matches('`'))
+                .map(|title| {
+                    let mut weight = Weight::default();
+                    weight.set::<String>("title", title).ok();
+                    weight
+                });
+            relations.push(
+                IntermediateRelation::new(key, WeightKind::Epistemic, payload)
+                    .with_location(offset),
+            );
+        }
+        (relations, html_links)
+    };
+    let join_docs = |docs: &[(usize, String)]| {
+        docs.iter()
+            .map(|(_, line)| line.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let title = if module.is_empty() {
+        crate_title(&doc_path)
+    } else {
+        module.join("::")
+    };
+    current.document.insert("title", value(title.clone()));
+    if !module.is_empty() {
+        current.document.insert("id", value(item_id(&module)));
+    }
+    current.document.insert("item", value("mod"));
+    current
+        .document
+        .insert("item_path", value(module.join("::")));
+    let docs = join_docs(&file.docs);
+    if !docs.is_empty() {
+        current.document.insert("text", value(docs.clone()));
+    }
+    if let Some((_, bid)) = file.bid.as_ref() {
+        current.document.insert("bid", value(bid.clone()));
+    }
+    let (doc_relations, html_links) = relations(&file.docs, &module, None);
+    current.upstream.extend(doc_relations);
+    current.traverse_schema()?;
+    let mut nodes = vec![RustNode {
+        proto: current,
+        depth: 0,
+        title,
+        signature: String::new(),
+        docs,
+        html_links,
+        start: 0,
+        bid_comment: file.bid.clone(),
+        edit: None,
+    }];
+
+    // Depth-first over the item tree, keeping source order among siblings.
+    let mut children = BTreeMap::<Option<usize>, Vec<usize>>::new();
+    for (idx, item) in file.items.iter().enumerate() {
+        children.entry(item.parent).or_default().push(idx);
+    }
+    let mut stack = children
+        .get(&None)
+        .map(|roots| roots.iter().rev().map(|idx| (*idx, 1)).collect::<Vec<_>>())
+        .unwrap_or_default();
+    while let Some((idx, depth)) = stack.pop() {
+        if let Some(kids) = children.get(&Some(idx)) {
+            stack.extend(kids.iter().rev().map(|kid| (*kid, depth + 1)));
+        }
+        let item = &file.items[idx];
+        let name = item.path.last().cloned().unwrap_or_default();
+        let title = match (
+            item.parent,
+            item.owner.as_ref().and_then(|owner| owner.last()),
+        ) {
+            (None, Some(owner)) => format!("{owner}::{name}"),
+            _ => name,
+        };
+        let mut proto = IRNode {
+            path: doc_path.clone(),
+            heading: depth + 2,
+            ..Default::default()
+        };
+        proto.document.insert("title", value(title.clone()));
+        proto.document.insert("id", value(item_id(&item.path)));
+        proto.document.insert("item", value(item.kind));
+        proto
+            .document
+            .insert("item_path", value(item.path.join("::")));
+        proto
+            .document
+            .insert("signature", value(item.signature.clone()));
+        let docs = join_docs(&item.docs);
+        if !docs.is_empty() {
+            proto.document.insert("text", value(docs.clone()));
+        }
+        if let Some((_, bid)) = item.bid.as_ref() {
+            proto.document.insert("bid", value(bid.clone()));
+        }
+        let (item_relations, html_links) =
+            relations(&item.docs, &item.module, item.self_path.as_deref());
+        proto.upstream.extend(item_relations);
+        proto.traverse_schema()?;
+        nodes.push(RustNode {
+            proto,
+            depth,
+            title,
+            signature: item.signature.clone(),
+            docs,
+            html_links,
+            start: item.start,
+            bid_comment: item.bid.clone(),
+            edit: None,
+        });
+    }
+    Ok(nodes)
+}
+
+impl DocCodec for RustDocCodec {
+    fn proto(&self, path: &Path) -> Result<Option<IRNode>, BuildonomyError> {
+        if path.is_relative() {
+            return Err(BuildonomyError::Codec(format!(
+                "[RustDocCodec::proto] supplied path must be absolute. Received \"{path:?}\""
+            )));
+        };
+        if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|&ext| ext == "rs")
+            .is_none()
+        {
+            tracing::debug!(
+                "RustDocCodec::proto called with path \"{path:?}\", which has a non-'rs' \
+                file extension. Returning None"
+            );
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let seed = IRNode {
+            path: os_path_to_string(path),
+            ..Default::default()
+        };
+        let mut proto = parse_rust(&content, seed)?
+            .into_iter()
+            .next()
+            .map(|node| node.proto)
+            .unwrap_or_default();
+        // Doc links belong to the parsed document; proto only carries document metadata.
+        proto.upstream.clear();
+        proto.downstream.clear();
+        proto.path = os_path_to_string(path);
+        // Document heading
+        proto.heading = 2;
+        proto.kind.insert(BeliefKind::Document);
+        Ok(Some(proto))
+    }
+
+    fn parse(
+        &mut self,
+        content: &str,
+        mut current: IRNode,
+        _diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<(), BuildonomyError> {
+        self.content = content.to_string();
+        let lines = split_lines(content);
+        self.line_starts = lines.iter().map(|(start, _)| *start).collect();
+        if self.line_starts.is_empty() {
+            self.line_starts.push(0);
+        }
+        self.lines = lines
+            .into_iter()
+            .map(|(_, line)| line.to_string())
+            .collect();
+
+        let seed = IRNode {
+            path: current.path.clone(),
+            ..Default::default()
+        };
+        let mut nodes = parse_rust(content, seed)?;
+        let mut parsed_doc = std::mem::take(&mut nodes[0].proto);
+        let mut links = std::mem::take(&mut parsed_doc.upstream);
+        current.merge(&mut parsed_doc);
+        current.upstream.append(&mut links);
+        current.heading = 2;
+        current.kind.insert(BeliefKind::Document);
+        nodes[0].proto = current;
+        self.nodes = nodes;
+        Ok(())
+    }
+
+    fn nodes(&self) -> Vec<IRNode> {
+        self.nodes.iter().map(|node| node.proto.clone()).collect()
+    }
+
+    fn inject_context(
+        &mut self,
+        node: &IRNode,
+        ctx: &BeliefContext<'_>,
+        _diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Option<BeliefNode>, BuildonomyError> {
+        let rust_node = self
+            .nodes
+            .iter_mut()
+            .find(|rust_node| &rust_node.proto == node)
+            .ok_or(BuildonomyError::Codec(
+                "No proto node stored in codec matching node argument".to_string(),
+            ))?;
+        let maybe_updated = rust_node.proto.update_from_context(ctx)?;
+
+        if self.bid_comments {
+            let bid = rust_node
+                .proto
+                .document
+                .get("bid")
+                .and_then(|item| item.as_str())
+                .map(|bid| bid.to_string());
+            let current = rust_node.bid_comment.as_ref().map(|(_, bid)| bid.as_str());
+            if bid.is_some() && bid.as_deref() != current {
+                rust_node.edit = bid;
+            }
+        }
+        Ok(maybe_updated)
+    }
+
+    fn finalize(
+        &mut self,
+        _diagnostics: &mut Vec<ParseDiagnostic>,
+    ) -> Result<Vec<(IRNode, BeliefNode)>, BuildonomyError> {
+        Ok(Vec::new())
+    }
+
+    fn generate_source(&self) -> Option<String> {
+        if !self.bid_comments || self.nodes.iter().all(|node| node.edit.is_none()) {
+            return Some(self.content.clone());
+        }
+        let mut replaced = BTreeMap::<usize, String>::new();
+        let mut inserted = BTreeMap::<usize, Vec<String>>::new();
+        for node in self.nodes.iter() {
+            let Some(bid) = node.edit.as_ref() else {
+                continue;
+            };
+            let line = match node.bid_comment.as_ref() {
+                Some((offset, _)) => self.line_of(*offset),
+                None => self.line_of(node.start),
+            };
+            let indent = self
+                .lines
+                .get(line)
+                .map(|line| {
+                    line.chars()
+                        .take_while(|c| c.is_whitespace())
+                        .collect::<String>()
+                })
+                .unwrap_or_default();
+            let comment = format!("{indent}// bid: {bid}");
+            match node.bid_comment {
+                Some(_) => {
+                    replaced.insert(line, comment);
+                }
+                None => inserted.entry(line).or_default().push(comment),
+            }
+        }
+
+        let mut out = Vec::with_capacity(self.lines.len() + inserted.len());
+        for idx in 0..=self.lines.len() {
+            if let Some(new_lines) = inserted.get(&idx) {
+                out.extend(new_lines.iter().cloned());
+            }
+            if let Some(line) = self.lines.get(idx) {
+                out.push(replaced.get(&idx).unwrap_or(line).clone());
+            }
+        }
+        let mut source = out.join("\n");
+        if self.content.ends_with('\n') || self.content.is_empty() {
+            source.push('\n');
+        }
+        Some(source)
+    }
+
+    fn generate_html(&self) -> Result<Vec<(String, String)>, BuildonomyError> {
+        let doc_path = self
+            .nodes
+            .first()
+            .map(|node| node.proto.path.clone())
+            .filter(|path| !path.is_empty())
+            .unwrap_or("lib.rs".to_string());
+        let doc_ap = AnchorPath::from(&doc_path);
+        if doc_ap.filestem().is_empty() {
+            return Err(BuildonomyError::Codec(format!(
+                "Rust file has no filename! {doc_path}",
+            )));
+        }
+        let output_filename = format!("{}.html", doc_ap.filestem());
+
+        let mut events: Vec<MdEvent<'_>> = Vec::new();
+        for node in self.nodes.iter() {
+            if node.depth > 0 {
+                let level = match node.depth {
+                    1 => HeadingLevel::H1,
+                    2 => HeadingLevel::H2,
+                    3 => HeadingLevel::H3,
+                    4 => HeadingLevel::H4,
+                    5 => HeadingLevel::H5,
+                    _ => HeadingLevel::H6,
+                };
+                events.push(MdEvent::Start(Tag::Heading {
+                    level,
+                    id: node.proto.id().map(CowStr::from),
+                    classes: vec![],
+                    attrs: vec![],
+                }));
+                events.push(MdEvent::Code(CowStr::from(node.title.clone())));
+                events.push(MdEvent::End(TagEnd::Heading(level)));
+                events.push(MdEvent::Start(Tag::CodeBlock(CodeBlockKind::Fenced(
+                    CowStr::from("rust"),
+                ))));
+                events.push(MdEvent::Text(CowStr::from(format!("{}\n", node.signature))));
+                events.push(MdEvent::End(TagEnd::CodeBlock));
+            }
+            let mut unlinked = 0usize;
+            let parser = MdParser::new_with_broken_link_callback(
+                &node.docs,
+                buildonomy_md_options(),
+                Some(|link: BrokenLink<'_>| Some((link.reference.into_static(), CowStr::from("")))),
+            );
+            for event in parser {
+                match event {
+                    MdEvent::Start(Tag::Link {
+                        link_type,
+                        dest_url,
+                        title,
+                        id,
+                    }) => match node.html_links.get(dest_url.as_ref()) {
+                        Some(None) => unlinked += 1,
+                        Some(Some(href)) => events.push(
+                            MdEvent::Start(Tag::Link {
+                                link_type,
+                                dest_url: CowStr::from(href.clone()),
+                                title,
+                                id,
+                            })
+                            .into_static(),
+                        ),
+                        None => events.push(
+                            MdEvent::Start(Tag::Link {
+                                link_type,
+                                dest_url,
+                                title,
+                                id,
+                            })
+                            .into_static(),
+                        ),
+                    },
+                    MdEvent::End(TagEnd::Link) if unlinked > 0 => unlinked -= 1,
+                    event => events.push(event.into_static()),
+                }
+            }
+        }
+
+        let mut html_body = String::new();
+        pulldown_cmark::html::push_html(&mut html_body, events.into_iter());
+        Ok(vec![(output_filename, html_body)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        beliefbase::BeliefBase, codec::compiler::DocumentCompiler, tests::helpers::init_logging,
+    };
+    use tempfile::TempDir;
+
+    const COMPILER_RS: &str = r##"//! Multi-pass compilation, see [the design](../../docs/design/compiler.md).
+
+use crate::codec::md::MdCodec;
+use super::builder::{GraphBuilder as Builder, self};
+
+/// Compiles a network. Parses with [`MdCodec`] and [`Builder`].
+///
+/// Start with [`DocumentCompiler::new`]; [`String`]s and [`std::fs`] are not relations.
+#[derive(Debug)]
+pub struct DocumentCompiler {
+    /// Fields aren't indexed
+    pub queue: Vec<String>,
+}
+
+impl DocumentCompiler {
+    /// Create a compiler. Returns [`Self`].
+    pub fn new() -> Self {
+        let brace = '{';
+        let text = "pub fn not_an_item() { /// nor a doc";
+        let raw = r#"pub struct Hidden"#;
+        DocumentCompiler { queue: vec![] }
+    }
+
+    fn private_helper<'a>(&self, value: &'a str) -> &'a str {
+        value
+    }
+}
+
+impl std::fmt::Display for DocumentCompiler {
+    /// Trait impl items are not indexed
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "compiler")
+    }
+}
+
+// bid: 10000000-0000-4000-8000-000000000001
+/// A pluggable compile step, run by [`super::builder::GraphBuilder`].
+pub trait Step {
+    /// Run the step.
+    fn run(&mut self);
+}
+
+/// Maximum passes before giving up.
+pub const MAX_PASSES: usize = 10;
+
+pub(crate) fn internal() {}
+
+fn private() {}
+
+#[cfg(test)]
+mod tests {
+    /// Not public API
+    pub fn helper() {}
+}
+"##;
+
+    fn parse_file(path: &Path, content: &str) -> RustDocCodec {
+        let mut codec = RustDocCodec::new();
+        let seed = IRNode {
+            path: os_path_to_string(path),
+            ..Default::default()
+        };
+        codec
+            .parse(content, seed, &mut Vec::new())
+            .expect("parse should succeed");
+        codec
+    }
+
+    fn compiler_path() -> &'static Path {
+        Path::new("/repo/src/codec/compiler.rs")
+    }
+
+    fn node_by_id<'a>(nodes: &'a [IRNode], id: &str) -> &'a IRNode {
+        nodes
+            .iter()
+            .find(|node| node.id().as_deref() == Some(id))
+            .unwrap_or_else(|| panic!("no node with id {id}"))
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path("/repo/src/lib.rs"), Vec::<String>::new());
+        assert_eq!(module_path("/repo/src/main.rs"), Vec::<String>::new());
+        assert_eq!(module_path("/repo/src/bin/noet.rs"), Vec::<String>::new());
+        assert_eq!(module_path("/repo/src/codec/mod.rs"), vec!["codec"]);
+        assert_eq!(
+            module_path("/repo/src/codec/compiler.rs"),
+            vec!["codec", "compiler"]
+        );
+        assert_eq!(crate_title("/work/noet-core/src/lib.rs"), "noet-core");
+        assert_eq!(
+            item_id(&["codec".to_string(), "DocumentCompiler".to_string()]),
+            "codec.documentcompiler"
+        );
+    }
+
+    #[test]
+    fn test_public_items_become_nodes() {
+        let codec = parse_file(compiler_path(), COMPILER_RS);
+        let nodes = codec.nodes();
+        let summary = nodes
+            .iter()
+            .map(|node| (node.id().unwrap_or_default(), node.heading))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("codec.compiler".to_string(), 2),
+                ("codec.compiler.documentcompiler".to_string(), 3),
+                ("codec.compiler.documentcompiler.new".to_string(), 4),
+                ("codec.compiler.step".to_string(), 3),
+                ("codec.compiler.step.run".to_string(), 4),
+                ("codec.compiler.max_passes".to_string(), 3),
+            ]
+        );
+
+        let doc = &nodes[0];
+        assert_eq!(doc.title().as_deref(), Some("codec::compiler"));
+        assert!(doc.kind.contains(BeliefKind::Document));
+        assert_eq!(
+            doc.document.get("text").and_then(|v| v.as_str()),
+            Some("Multi-pass compilation, see [the design](../../docs/design/compiler.md).")
+        );
+
+        let compiler = node_by_id(&nodes, "codec.compiler.documentcompiler");
+        assert_eq!(compiler.title().as_deref(), Some("DocumentCompiler"));
+        assert_eq!(
+            compiler.document.get("item").and_then(|v| v.as_str()),
+            Some("struct")
+        );
+        assert_eq!(
+            compiler.document.get("item_path").and_then(|v| v.as_str()),
+            Some("codec::compiler::DocumentCompiler")
+        );
+        assert_eq!(
+            compiler.document.get("signature").and_then(|v| v.as_str()),
+            Some("pub struct DocumentCompiler")
+        );
+
+        let new = node_by_id(&nodes, "codec.compiler.documentcompiler.new");
+        assert_eq!(new.title().as_deref(), Some("new"));
+        assert_eq!(
+            new.document.get("signature").and_then(|v| v.as_str()),
+            Some("pub fn new() -> Self")
+        );
+
+        let step = node_by_id(&nodes, "codec.compiler.step");
+        assert_eq!(
+            step.document.get("bid").and_then(|v| v.as_str()),
+            Some("10000000-0000-4000-8000-000000000001")
+        );
+        assert_eq!(
+            node_by_id(&nodes, "codec.compiler.max_passes")
+                .document
+                .get("signature")
+                .and_then(|v| v.as_str()),
+            Some("pub const MAX_PASSES: usize")
+        );
+    }
+
+    #[test]
+    fn test_doc_links_become_relations() {
+        let codec = parse_file(compiler_path(), COMPILER_RS);
+        let nodes = codec.nodes();
+        let id_key = |id: &str| NodeKey::Id {
+            net: Bid::nil().bref(),
+            id: id.to_string(),
+        };
+
+        let doc_links = nodes[0]
+            .upstream
+            .iter()
+            .map(|rel| rel.key.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            doc_links,
+            vec![href_to_nodekey("../../docs/design/compiler.md").resolve_against(&nodes[0].path)]
+        );
+
+        let compiler = node_by_id(&nodes, "codec.compiler.documentcompiler");
+        let links = compiler
+            .upstream
+            .iter()
+            .map(|rel| rel.key.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![
+                // `use crate::codec::md::MdCodec`
+                id_key("codec.md.mdcodec"),
+                // `use super::builder::GraphBuilder as Builder`
+                id_key("codec.builder.graphbuilder"),
+                id_key("codec.compiler.documentcompiler.new"),
+            ]
+        );
+        assert!(compiler
+            .upstream
+            .iter()
+            .all(|rel| rel.kind == WeightKind::Epistemic && rel.location.is_some()));
+
+        let new = node_by_id(&nodes, "codec.compiler.documentcompiler.new");
+        assert_eq!(
+            new.upstream.iter().map(|rel| &rel.key).collect::<Vec<_>>(),
+            vec![&id_key("codec.compiler.documentcompiler")]
+        );
+        let step = node_by_id(&nodes, "codec.compiler.step");
+        assert_eq!(
+            step.upstream.iter().map(|rel| &rel.key).collect::<Vec<_>>(),
+            vec![&id_key("codec.builder.graphbuilder")]
+        );
+
+        // Design documents address items with the same key.
+        assert_eq!(
+            href_to_nodekey("id:codec::compiler::DocumentCompiler"),
+            id_key("codec.compiler.documentcompiler")
+        );
+    }
+
+    #[test]
+    fn test_source_is_untouched_unless_bid_comments_enabled() {
+        let mut codec = parse_file(compiler_path(), COMPILER_RS);
+        codec.nodes[1].edit = Some("10000000-0000-4000-8000-000000000002".to_string());
+        assert_eq!(codec.generate_source().as_deref(), Some(COMPILER_RS));
+
+        let mut codec = RustDocCodec {
+            bid_comments: true,
+            ..parse_file(compiler_path(), COMPILER_RS)
+        };
+        // New comment above the struct's docs, existing comment replaced in place.
+        codec.nodes[1].edit = Some("10000000-0000-4000-8000-000000000002".to_string());
+        codec.nodes[3].edit = Some("10000000-0000-4000-8000-000000000003".to_string());
+        let source = codec.generate_source().unwrap();
+        assert!(source
+            .contains("// bid: 10000000-0000-4000-8000-000000000002\n/// Compiles a network."));
+        assert!(source.contains(
+            "// bid: 10000000-0000-4000-8000-000000000003\n/// A pluggable compile step"
+        ));
+        assert!(!source.contains("000000000001"));
+        let original_lines = COMPILER_RS.lines().count();
+        assert_eq!(source.lines().count(), original_lines + 1);
+
+        // Comments are read back as BIDs.
+        let reparsed = parse_file(compiler_path(), &source);
+        assert_eq!(
+            reparsed.nodes[1]
+                .proto
+                .document
+                .get("bid")
+                .and_then(|v| v.as_str()),
+            Some("10000000-0000-4000-8000-000000000002")
+        );
+    }
+
+    #[test]
+    fn test_html_rendering() {
+        let codec = parse_file(compiler_path(), COMPILER_RS);
+        let html = codec.generate_html().unwrap();
+        assert_eq!(html.len(), 1);
+        let (filename, body) = &html[0];
+        assert_eq!(filename, "compiler.html");
+        assert!(body.contains("<a href=\"../../docs/design/compiler.html\">the design</a>"));
+        assert!(body.contains(
+            "<h1 id=\"codec.compiler.documentcompiler\"><code>DocumentCompiler</code></h1>"
+        ));
+        assert!(body.contains(
+            "<pre><code class=\"language-rust\">pub struct DocumentCompiler\n</code></pre>"
+        ));
+        // Links to items in this file become anchors; others render as code.
+        assert!(body.contains("<a href=\"#codec.compiler.documentcompiler.new\">"));
+        assert!(body.contains("Parses with <code>MdCodec</code>"));
+        assert!(!body.contains("not_an_item"));
+    }
+
+    #[tokio::test]
+    async fn test_design_docs_link_to_items() {
+        init_logging();
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("index.md"),
+            "---\nid: \"test-network\"\ntitle: \"Test Network\"\n---\n\n# Test Network\n",
+        )
+        .unwrap();
+        let src_dir = temp_dir.path().join("src").join("codec");
+        fs::create_dir_all(&src_dir).unwrap();
+        let source = "//! The compiler.\n\n/// Compiles a network.\npub struct DocumentCompiler;\n";
+        let rs_path = src_dir.join("compiler.rs");
+        fs::write(&rs_path, source).unwrap();
+        fs::write(
+            temp_dir.path().join("architecture.md"),
+            "# Architecture\n\n\
+            See [the compiler](id:codec::compiler::DocumentCompiler) and \
+            [a missing item](id:codec::compiler::Missing).\n",
+        )
+        .unwrap();
+
+        let mut compiler = DocumentCompiler::new(temp_dir.path(), None, Some(2), true).unwrap();
+        let results = compiler
+            .parse_all(BeliefBase::default(), false)
+            .await
+            .unwrap();
+
+        let unresolved_links = results
+            .iter()
+            .flat_map(|result| result.diagnostics.iter())
+            .filter_map(|diagnostic| match diagnostic {
+                ParseDiagnostic::Warning { message, .. } if message.contains("unresolved link") => {
+                    Some(message.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(unresolved_links.len(), 1, "{unresolved_links:?}");
+        assert!(
+            unresolved_links[0].contains("missing"),
+            "{unresolved_links:?}"
+        );
+
+        // BIDs aren't written into Rust sources by default.
+        assert_eq!(fs::read_to_string(&rs_path).unwrap(), source);
+    }
+}
@@ -1,14 +1,34 @@
-// Schema registry for graph field definitions
+// Schema registry for graph field and typed payload field definitions
 //
 // This module provides a global registry for schema definitions that specify
-// how TOML fields map to graph edges. Schemas can be registered at runtime
-// by both noet-core and downstream libraries.
+// how TOML fields map to graph edges, and which typed fields a document's
+// payload must carry. Schemas can be registered at runtime by both noet-core
+// and downstream libraries, or loaded from schema files declared by a network
+// (see `SchemaRegistry::load_network`).
 
-use crate::properties::WeightKind;
+use crate::{
+    codec::{
+        md::MdCodec,
+        migration::{self, MigrationRule, MigrationSpec, SchemaMigration},
+        network::detect_network_file,
+        DocCodec, NETWORK_NAME,
+    },
+    error::BuildonomyError,
+    paths::{os_path_to_string, string_to_os_path},
+    properties::{BeliefNode, WeightKind},
+};
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
-use std::{collections::HashMap, sync::Arc, time::Duration};
-use toml::Value as TomlValue;
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use toml::{value::Table as TomlTable, Value as TomlValue};
 
 /// Global singleton schema registry with built-in schemas
 pub static SCHEMAS: Lazy<SchemaRegistry> = Lazy::new(SchemaRegistry::create);
@@ -21,16 +41,353 @@ pub enum EdgeDirection {
 
 #[derive(Debug, Clone)]
 pub struct GraphField {
-    pub field_name: &'static str,
+    pub field_name: String,
     pub direction: EdgeDirection,
     pub weight_kind: WeightKind,
     pub required: bool,
-    pub payload_fields: Vec<&'static str>, // Fields to extract into edge payload
+    pub payload_fields: Vec<String>, // Fields to extract into edge payload
+    /// Schemas the related nodes must declare. Empty allows any node.
+    pub target_schemas: Vec<String>,
+    /// Most relations a node may declare through this field.
+    pub max_count: Option<usize>,
+    /// Whether this field's relations must not close a cycle of edges of its weight kind.
+    pub acyclic: bool,
+}
+
+impl GraphField {
+    /// An optional graph field with no edge payload and no relation constraints.
+    pub fn new(
+        field_name: impl Into<String>,
+        direction: EdgeDirection,
+        weight_kind: WeightKind,
+    ) -> Self {
+        GraphField {
+            field_name: field_name.into(),
+            direction,
+            weight_kind,
+            required: false,
+            payload_fields: vec![],
+            target_schemas: vec![],
+            max_count: None,
+            acyclic: false,
+        }
+    }
+}
+
+/// A way in which a node's resolved relations break the constraints of a [`GraphField`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RelationViolation {
+    WrongTarget {
+        field_name: String,
+        target: String,
+        found: Option<String>,
+        allowed: Vec<String>,
+    },
+    TooMany {
+        field_name: String,
+        max: usize,
+        found: usize,
+    },
+    Cycle {
+        field_name: String,
+        target: String,
+    },
+}
+
+impl RelationViolation {
+    pub fn field_name(&self) -> &str {
+        match self {
+            RelationViolation::WrongTarget { field_name, .. }
+            | RelationViolation::TooMany { field_name, .. }
+            | RelationViolation::Cycle { field_name, .. } => field_name,
+        }
+    }
+}
+
+impl fmt::Display for RelationViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelationViolation::WrongTarget {
+                field_name,
+                target,
+                found,
+                allowed,
+            } => {
+                let found = match found {
+                    Some(schema) => format!("schema '{schema}'"),
+                    None => "no schema".to_string(),
+                };
+                write!(
+                    f,
+                    "field '{field_name}' relates to '{target}', which has {found}, expected one \
+                     of: {}",
+                    allowed.join(", ")
+                )
+            }
+            RelationViolation::TooMany {
+                field_name,
+                max,
+                found,
+            } => write!(
+                f,
+                "field '{field_name}' has {found} relations, at most {max} allowed"
+            ),
+            RelationViolation::Cycle { field_name, target } => write!(
+                f,
+                "field '{field_name}' relation to '{target}' forms a cycle"
+            ),
+        }
+    }
+}
+
+/// The TOML type a payload field must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    String,
+    Integer,
+    /// Integers are accepted where a float is expected.
+    Float,
+    Boolean,
+    Datetime,
+    Array,
+    Table,
+}
+
+impl FieldType {
+    pub fn matches(&self, value: &TomlValue) -> bool {
+        matches!(
+            (self, value),
+            (FieldType::String, TomlValue::String(_))
+                | (FieldType::Integer, TomlValue::Integer(_))
+                | (
+                    FieldType::Float,
+                    TomlValue::Float(_) | TomlValue::Integer(_)
+                )
+                | (FieldType::Boolean, TomlValue::Boolean(_))
+                | (FieldType::Datetime, TomlValue::Datetime(_))
+                | (FieldType::Array, TomlValue::Array(_))
+                | (FieldType::Table, TomlValue::Table(_))
+        )
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FieldType::String => "string",
+            FieldType::Integer => "integer",
+            FieldType::Float => "float",
+            FieldType::Boolean => "boolean",
+            FieldType::Datetime => "datetime",
+            FieldType::Array => "array",
+            FieldType::Table => "table",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A typed field of the node payload (any frontmatter key besides `bid`, `title`, `schema` and
+/// `id`).
+#[derive(Debug, Clone)]
+pub struct FieldDefinition {
+    pub field_name: String,
+    pub field_type: FieldType,
+    pub required: bool,
+    /// Allowed values of a string field, or of each string in an array field. Empty allows any.
+    pub enum_values: Vec<String>,
+    /// Value materialised into the payload when the field is absent.
+    pub default: Option<TomlValue>,
+    /// Pattern a string field must match.
+    pub pattern: Option<Regex>,
+}
+
+impl FieldDefinition {
+    /// An optional field of the given type with no further constraints.
+    pub fn new(field_name: impl Into<String>, field_type: FieldType) -> Self {
+        FieldDefinition {
+            field_name: field_name.into(),
+            field_type,
+            required: false,
+            enum_values: vec![],
+            default: None,
+            pattern: None,
+        }
+    }
+}
+
+/// A way in which a payload breaks its schema's [`FieldDefinition`]s.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldViolation {
+    Missing {
+        field_name: String,
+    },
+    WrongType {
+        field_name: String,
+        expected: FieldType,
+        found: String,
+    },
+    NotAllowed {
+        field_name: String,
+        value: String,
+        allowed: Vec<String>,
+    },
+    NoMatch {
+        field_name: String,
+        value: String,
+        pattern: String,
+    },
+}
+
+impl FieldViolation {
+    pub fn field_name(&self) -> &str {
+        match self {
+            FieldViolation::Missing { field_name }
+            | FieldViolation::WrongType { field_name, .. }
+            | FieldViolation::NotAllowed { field_name, .. }
+            | FieldViolation::NoMatch { field_name, .. } => field_name,
+        }
+    }
+
+    /// Missing and mistyped fields are errors; values outside an enum or pattern are warnings.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            FieldViolation::Missing { .. } | FieldViolation::WrongType { .. }
+        )
+    }
+}
+
+impl fmt::Display for FieldViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldViolation::Missing { field_name } => {
+                write!(f, "required field '{field_name}' is missing")
+            }
+            FieldViolation::WrongType {
+                field_name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "field '{field_name}' has type {found}, expected {expected}"
+            ),
+            FieldViolation::NotAllowed {
+                field_name,
+                value,
+                allowed,
+            } => write!(
+                f,
+                "field '{field_name}' has value '{value}', expected one of: {}",
+                allowed.join(", ")
+            ),
+            FieldViolation::NoMatch {
+                field_name,
+                value,
+                pattern,
+            } => write!(
+                f,
+                "field '{field_name}' has value '{value}', which does not match '{pattern}'"
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct SchemaDefinition {
     pub graph_fields: Vec<GraphField>,
+    /// Typed payload fields, checked by [`SchemaDefinition::validate`].
+    pub fields: Vec<FieldDefinition>,
+    /// Current version of the schema, starting at 1.
+    pub version: u32,
+    /// Upgrades from older versions, applied by [`SchemaDefinition::migrate`].
+    pub migrations: Vec<SchemaMigration>,
+}
+
+impl Default for SchemaDefinition {
+    fn default() -> Self {
+        SchemaDefinition {
+            graph_fields: vec![],
+            fields: vec![],
+            version: 1,
+            migrations: vec![],
+        }
+    }
+}
+
+impl SchemaDefinition {
+    /// Upgrade a document to the current schema version, applying each migration step in turn
+    /// and recording the new `schema_version`. Returns the version the document was at if it
+    /// was upgraded.
+    pub fn migrate(&self, doc: &mut TomlTable) -> Result<Option<u32>, String> {
+        migration::migrate(self, doc)
+    }
+
+    /// Check a node payload against the schema's typed fields.
+    pub fn validate(&self, payload: &TomlTable) -> Vec<FieldViolation> {
+        let mut violations = Vec::new();
+        for field in self.fields.iter() {
+            let field_name = field.field_name.clone();
+            let Some(value) = payload.get(&field.field_name) else {
+                if field.required && field.default.is_none() {
+                    violations.push(FieldViolation::Missing { field_name });
+                }
+                continue;
+            };
+            if !field.field_type.matches(value) {
+                violations.push(FieldViolation::WrongType {
+                    field_name,
+                    expected: field.field_type,
+                    found: value.type_str().to_string(),
+                });
+                continue;
+            }
+            let strings = match value {
+                TomlValue::String(string) => vec![string.as_str()],
+                TomlValue::Array(items) => items.iter().filter_map(|item| item.as_str()).collect(),
+                _ => vec![],
+            };
+            for string in strings {
+                if !field.enum_values.is_empty() && !field.enum_values.iter().any(|v| v == string) {
+                    violations.push(FieldViolation::NotAllowed {
+                        field_name: field_name.clone(),
+                        value: string.to_string(),
+                        allowed: field.enum_values.clone(),
+                    });
+                }
+                if let Some(pattern) = field.pattern.as_ref() {
+                    if !pattern.is_match(string) {
+                        violations.push(FieldViolation::NoMatch {
+                            field_name: field_name.clone(),
+                            value: string.to_string(),
+                            pattern: pattern.as_str().to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        violations
+    }
+
+    /// Insert the default of every absent field into the payload. Returns true if any were added.
+    pub fn apply_defaults(&self, payload: &mut TomlTable) -> bool {
+        let mut changed = false;
+        for field in self.fields.iter() {
+            if let Some(default) = field.default.as_ref() {
+                if !payload.contains_key(&field.field_name) {
+                    payload.insert(field.field_name.clone(), default.clone());
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Schemas loaded from the schema files of one network.
+#[derive(Debug, Default)]
+struct NetworkSchemas {
+    files: BTreeSet<PathBuf>,
+    schemas: HashMap<String, Arc<SchemaDefinition>>,
 }
 
 /// Thread-safe registry for schema definitions
@@ -38,40 +395,68 @@ pub struct SchemaDefinition {
 /// Schemas map document types to graph field definitions, specifying how
 /// TOML fields create edges in the belief graph.
 ///
+/// Schemas registered in code are global. Schemas loaded from a network's schema files are
+/// scoped to that network and its subnets, where they take precedence over global schemas of the
+/// same name; look them up with [`SchemaRegistry::get_for`].
+///
 /// Pattern matches [`CodecMap`](super::CodecMap) for consistency.
-pub struct SchemaRegistry(Arc<RwLock<HashMap<String, Arc<SchemaDefinition>>>>);
+pub struct SchemaRegistry {
+    schemas: Arc<RwLock<HashMap<String, Arc<SchemaDefinition>>>>,
+    /// File-defined schemas by canonical network directory.
+    networks: Arc<RwLock<BTreeMap<PathBuf, NetworkSchemas>>>,
+}
 
 impl Clone for SchemaRegistry {
     fn clone(&self) -> Self {
-        SchemaRegistry(self.0.clone())
+        SchemaRegistry {
+            schemas: self.schemas.clone(),
+            networks: self.networks.clone(),
+        }
     }
 }
 
 impl SchemaRegistry {
     /// Create registry with built-in schemas
     pub fn create() -> Self {
-        let registry = SchemaRegistry(Arc::new(RwLock::new(HashMap::new())));
+        let registry = SchemaRegistry {
+            schemas: Arc::new(RwLock::new(HashMap::new())),
+            networks: Arc::new(RwLock::new(BTreeMap::new())),
+        };
 
         // Register built-in schemas
         registry.register(
             "intention_lattice.intention".to_string(),
             SchemaDefinition {
                 graph_fields: vec![GraphField {
-                    field_name: "parent_connections",
-                    direction: EdgeDirection::Downstream,
-                    weight_kind: WeightKind::Pragmatic,
-                    payload_fields: vec!["relationship_semantics", "motivation_kinds", "notes"],
-                    required: false,
+                    payload_fields: vec![
+                        "relationship_semantics".to_string(),
+                        "motivation_kinds".to_string(),
+                        "notes".to_string(),
+                    ],
+                    ..GraphField::new(
+                        "parent_connections",
+                        EdgeDirection::Downstream,
+                        WeightKind::Pragmatic,
+                    )
                 }],
+                version: 2,
+                migrations: vec![SchemaMigration::new(
+                    1,
+                    vec![MigrationRule::custom(|doc| {
+                        Ok(doc
+                            .get_mut("parent_connections")
+                            .and_then(|connections| connections.as_array_mut())
+                            .is_some_and(|connections| migrate_array_field(connections)))
+                    })],
+                )],
+                ..SchemaDefinition::default()
             },
         );
 
         // Register network configuration schema (no graph fields)
         registry.register(
             "noet.network_config".to_string(),
-            SchemaDefinition {
-                graph_fields: vec![],
-            },
+            SchemaDefinition::default(),
         );
 
         registry
@@ -81,14 +466,14 @@ impl SchemaRegistry {
     ///
     /// If a schema with this name already exists, it will be overwritten and a log message emitted.
     pub fn register(&self, schema_name: String, definition: SchemaDefinition) {
-        while self.0.is_locked() {
+        while self.schemas.is_locked() {
             tracing::info!(
                 "[SchemaRegistry::register] Waiting for write access to schema registry"
             );
             std::thread::sleep(Duration::from_millis(100));
         }
 
-        let mut writer = self.0.write();
+        let mut writer = self.schemas.write();
 
         if writer.contains_key(&schema_name) {
             tracing::info!(
@@ -102,29 +487,405 @@ impl SchemaRegistry {
 
     /// Retrieve a schema definition by name
     ///
-    /// Returns a cheap Arc clone if the schema exists.
+    /// Returns a cheap Arc clone if the schema exists. Only schemas registered in code are
+    /// visible; use [`SchemaRegistry::get_for`] to include the schemas of a document's network.
     pub fn get(&self, schema_name: &str) -> Option<Arc<SchemaDefinition>> {
-        while self.0.is_locked_exclusive() {
+        while self.schemas.is_locked_exclusive() {
             tracing::info!("[SchemaRegistry::get] Waiting for read access to schema registry");
             std::thread::sleep(Duration::from_millis(100));
         }
 
-        let reader = self.0.read();
+        let reader = self.schemas.read();
         reader.get(schema_name).cloned()
     }
 
+    /// Retrieve the schema definition a document at `path` sees under `schema_name`: the one
+    /// defined by its innermost enclosing network, falling back to the global registry.
+    pub fn get_for(&self, schema_name: &str, path: &Path) -> Option<Arc<SchemaDefinition>> {
+        while self.networks.is_locked_exclusive() {
+            tracing::info!("[SchemaRegistry::get_for] Waiting for read access to schema registry");
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        let scoped = {
+            let reader = self.networks.read();
+            reader
+                .iter()
+                .filter(|(network_dir, _)| path.starts_with(network_dir))
+                .max_by_key(|(network_dir, network)| {
+                    (
+                        network.schemas.contains_key(schema_name),
+                        network_dir.components().count(),
+                    )
+                })
+                .and_then(|(_, network)| network.schemas.get(schema_name).cloned())
+        };
+        scoped.or_else(|| self.get(schema_name))
+    }
+
+    /// Insert the defaults of a node's schema fields into its payload. Returns true if any were
+    /// added. `path` is the path of the node's document, which scopes the schema lookup.
+    pub fn apply_defaults(&self, node: &mut BeliefNode, path: &Path) -> bool {
+        node.schema
+            .as_deref()
+            .and_then(|schema| self.get_for(schema, path))
+            .is_some_and(|schema_def| schema_def.apply_defaults(&mut node.payload))
+    }
+
     /// List all registered schema names
     pub fn list_schemas(&self) -> Vec<String> {
-        while self.0.is_locked_exclusive() {
+        while self.schemas.is_locked_exclusive() {
             tracing::info!(
                 "[SchemaRegistry::list_schemas] Waiting for read access to schema registry"
             );
             std::thread::sleep(Duration::from_millis(100));
         }
 
-        let reader = self.0.read();
+        let reader = self.schemas.read();
         reader.keys().cloned().collect()
     }
+
+    /// (Re)load the schema files of the network rooted at `network_dir`, replacing the schemas it
+    /// loaded before. Returns the files that failed to load, with the reason.
+    ///
+    /// A network's schema files are every `.toml` and `.json` file in its `schemas/` directory,
+    /// plus the files listed (relative to the network directory) in the `schemas` field of its
+    /// `index.md` frontmatter. A schema is named by its `name` (TOML) or `$id` (JSON Schema)
+    /// field, or else by its file stem.
+    pub fn load_network(&self, network_dir: &Path) -> Vec<(PathBuf, BuildonomyError)> {
+        let network_dir = canonical(network_dir);
+        let mut errors = Vec::new();
+        let mut network = NetworkSchemas::default();
+        for file in schema_files(&network_dir, &mut errors) {
+            match SchemaFile::load(&file) {
+                Ok((name, definition)) => {
+                    if network.schemas.contains_key(&name) {
+                        tracing::info!(
+                            "[SchemaRegistry::load_network] {file:?} overwrites schema '{name}'"
+                        );
+                    }
+                    network.schemas.insert(name, Arc::new(definition));
+                }
+                Err(e) => errors.push((file.clone(), e)),
+            }
+            network.files.insert(file);
+        }
+
+        while self.networks.is_locked() {
+            tracing::info!(
+                "[SchemaRegistry::load_network] Waiting for write access to schema registry"
+            );
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        let mut writer = self.networks.write();
+        if network.files.is_empty() {
+            writer.remove(&network_dir);
+        } else {
+            writer.insert(network_dir, network);
+        }
+        errors
+    }
+
+    /// The network directory whose schemas `path` defines, if `path` is a schema file: either one
+    /// loaded by [`SchemaRegistry::load_network`] or any file in a network's `schemas/`
+    /// directory. Schema files are not parsed as documents.
+    pub fn schema_network(&self, path: &Path) -> Option<PathBuf> {
+        let path = canonical(path);
+        {
+            let reader = self.networks.read();
+            if let Some((network_dir, _)) = reader
+                .iter()
+                .find(|(_, network)| network.files.contains(&path))
+            {
+                return Some(network_dir.clone());
+            }
+        }
+        let schemas_dir = path.parent()?;
+        if schemas_dir.file_name()? != SCHEMAS_DIR {
+            return None;
+        }
+        let network_dir = schemas_dir.parent()?;
+        detect_network_file(network_dir).map(|_| network_dir.to_path_buf())
+    }
+}
+
+/// Directory, relative to a network directory, holding the network's schema files.
+pub const SCHEMAS_DIR: &str = "schemas";
+
+fn canonical(path: &Path) -> PathBuf {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    string_to_os_path(&os_path_to_string(&path))
+}
+
+/// The schema files of a network, in load order: its `schemas/` directory, then the files its
+/// `index.md` declares.
+fn schema_files(network_dir: &Path, errors: &mut Vec<(PathBuf, BuildonomyError)>) -> Vec<PathBuf> {
+    let mut files = fs::read_dir(network_dir.join(SCHEMAS_DIR))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext == "toml" || ext == "json")
+        })
+        .map(|path| canonical(&path))
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let index_path = network_dir.join(NETWORK_NAME);
+    let declared = match MdCodec::new().proto(&index_path) {
+        Ok(proto) => proto
+            .and_then(|proto| proto.document.get("schemas").cloned())
+            .map(|item| match item.as_str() {
+                Some(file) => vec![file.to_string()],
+                None => item
+                    .as_array()
+                    .into_iter()
+                    .flat_map(|array| array.iter())
+                    .filter_map(|value| value.as_str().map(|file| file.to_string()))
+                    .collect(),
+            })
+            .unwrap_or_default(),
+        Err(e) => {
+            errors.push((index_path, e));
+            vec![]
+        }
+    };
+    for file in declared {
+        let path = canonical(&network_dir.join(&file));
+        if !path.is_file() {
+            errors.push((
+                path,
+                BuildonomyError::NotFound(format!(
+                    "Schema file '{file}' declared by {network_dir:?} does not exist"
+                )),
+            ));
+        } else if !files.contains(&path) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// A schema file: TOML in the shape of [`SchemaDefinition`], or a JSON Schema.
+///
+/// ```toml
+/// name = "my_app.task"
+///
+/// [fields.status]
+/// type = "string"
+/// required = true
+/// enum = ["open", "done"]
+///
+/// [graph_fields.dependencies]
+/// direction = "downstream"
+/// weight_kind = "Pragmatic"
+/// payload_fields = ["notes"]
+/// ```
+///
+/// JSON Schemas describe typed fields through `properties`, `required`, `type`, `enum`,
+/// `default`, `pattern` and `format: "date-time"`. A property with an `x-noet-graph` object
+/// (holding `direction`, `weight_kind` and `payload_fields`) is a graph field instead.
+///
+/// A top-level `version` and `[[migrations]]` tables (`x-noet-version` and `x-noet-migrations`
+/// in a JSON Schema) version the schema; see [`migration`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SchemaFile {
+    name: Option<String>,
+    version: Option<u32>,
+    fields: BTreeMap<String, FieldSpec>,
+    graph_fields: BTreeMap<String, GraphFieldSpec>,
+    migrations: Vec<MigrationSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FieldSpec {
+    #[serde(rename = "type")]
+    field_type: String,
+    #[serde(default)]
+    required: bool,
+    #[serde(default, rename = "enum")]
+    enum_values: Vec<String>,
+    default: Option<TomlValue>,
+    pattern: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphFieldSpec {
+    direction: String,
+    weight_kind: String,
+    #[serde(default)]
+    required: bool,
+    #[serde(default)]
+    payload_fields: Vec<String>,
+    #[serde(default)]
+    target_schemas: Vec<String>,
+    max_count: Option<usize>,
+    #[serde(default)]
+    acyclic: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JsonSchema {
+    #[serde(rename = "$id")]
+    id: Option<String>,
+    properties: BTreeMap<String, JsonProperty>,
+    required: Vec<String>,
+    #[serde(rename = "x-noet-version")]
+    version: Option<u32>,
+    #[serde(rename = "x-noet-migrations")]
+    migrations: Vec<MigrationSpec>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JsonProperty {
+    #[serde(rename = "type")]
+    property_type: Option<String>,
+    format: Option<String>,
+    #[serde(rename = "enum")]
+    enum_values: Vec<String>,
+    default: Option<serde_json::Value>,
+    pattern: Option<String>,
+    #[serde(rename = "x-noet-graph")]
+    graph: Option<GraphFieldSpec>,
+}
+
+impl SchemaFile {
+    /// Read a schema file into its name and definition.
+    fn load(path: &Path) -> Result<(String, SchemaDefinition), BuildonomyError> {
+        let content = fs::read_to_string(path)?;
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|stem| stem.trim_end_matches(".schema").to_string())
+            .unwrap_or_default();
+        let file = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => SchemaFile::from_json_schema(serde_json::from_str(&content)?)?,
+            _ => toml::from_str::<SchemaFile>(&content)?,
+        };
+        let name = file.name.clone().unwrap_or(stem);
+        Ok((name, file.into_definition()?))
+    }
+
+    fn from_json_schema(schema: JsonSchema) -> Result<SchemaFile, BuildonomyError> {
+        let mut file = SchemaFile {
+            name: schema.id,
+            version: schema.version,
+            migrations: schema.migrations,
+            ..Default::default()
+        };
+        for (name, property) in schema.properties {
+            let required = schema.required.contains(&name);
+            if let Some(mut graph) = property.graph {
+                graph.required |= required;
+                file.graph_fields.insert(name, graph);
+                continue;
+            }
+            let field_type = match (
+                property.property_type.as_deref(),
+                property.format.as_deref(),
+            ) {
+                (Some("string"), Some("date-time" | "date")) => "datetime",
+                (Some("number"), _) => "float",
+                (Some("object"), _) => "table",
+                (Some(other), _) => other,
+                (None, _) => {
+                    return Err(BuildonomyError::Serialization(format!(
+                        "JSON Schema property '{name}' has no type"
+                    )))
+                }
+            }
+            .to_string();
+            let default = property
+                .default
+                .map(|value| {
+                    TomlValue::try_from(&value).map_err(|e| {
+                        BuildonomyError::Serialization(format!(
+                            "Default of JSON Schema property '{name}' has no TOML equivalent: {e}"
+                        ))
+                    })
+                })
+                .transpose()?;
+            file.fields.insert(
+                name,
+                FieldSpec {
+                    field_type,
+                    required,
+                    enum_values: property.enum_values,
+                    default,
+                    pattern: property.pattern,
+                },
+            );
+        }
+        Ok(file)
+    }
+
+    fn into_definition(self) -> Result<SchemaDefinition, BuildonomyError> {
+        let mut definition = SchemaDefinition {
+            version: self.version.unwrap_or(1),
+            migrations: self
+                .migrations
+                .into_iter()
+                .map(MigrationSpec::into_migration)
+                .collect::<Result<_, _>>()?,
+            ..SchemaDefinition::default()
+        };
+        for (field_name, spec) in self.fields {
+            let field_type = match spec.field_type.as_str() {
+                "string" => FieldType::String,
+                "integer" => FieldType::Integer,
+                "float" => FieldType::Float,
+                "boolean" => FieldType::Boolean,
+                "datetime" => FieldType::Datetime,
+                "array" => FieldType::Array,
+                "table" => FieldType::Table,
+                other => {
+                    return Err(BuildonomyError::Serialization(format!(
+                        "Field '{field_name}' has unknown type '{other}'"
+                    )))
+                }
+            };
+            let pattern = spec.pattern.as_deref().map(Regex::new).transpose()?;
+            definition.fields.push(FieldDefinition {
+                field_name,
+                field_type,
+                required: spec.required,
+                enum_values: spec.enum_values,
+                default: spec.default,
+                pattern,
+            });
+        }
+        for (field_name, spec) in self.graph_fields {
+            let direction = match spec.direction.to_lowercase().as_str() {
+                "upstream" => EdgeDirection::Upstream,
+                "downstream" => EdgeDirection::Downstream,
+                other => {
+                    return Err(BuildonomyError::Serialization(format!(
+                        "Graph field '{field_name}' has unknown direction '{other}'"
+                    )))
+                }
+            };
+            // Schemas are where application-defined relation kinds are declared.
+            let weight_kind = WeightKind::try_from(spec.weight_kind.as_str())
+                .or_else(|_| WeightKind::register(&spec.weight_kind))?;
+            definition.graph_fields.push(GraphField {
+                field_name,
+                direction,
+                weight_kind,
+                required: spec.required,
+                payload_fields: spec.payload_fields,
+                target_schemas: spec.target_schemas,
+                max_count: spec.max_count,
+                acyclic: spec.acyclic,
+            });
+        }
+        Ok(definition)
+    }
 }
 
 /// Migrate old relationship_profile format to new relationship_semantics format
@@ -191,30 +952,6 @@ pub fn migrate_array_field(array: &mut [TomlValue]) -> bool {
     migrated
 }
 
-/// Apply schema-specific migrations to a document
-///
-/// This function is called during TOML parsing to automatically migrate
-/// old schema formats to current versions.
-pub fn migrate_schema(schema_name: &str, document: &mut TomlValue) -> bool {
-    match schema_name {
-        "intention_lattice.intention" => {
-            let mut migrated = false;
-
-            // Migrate parent_connections array
-            if let TomlValue::Table(table) = document {
-                if let Some(TomlValue::Array(connections)) = table.get_mut("parent_connections") {
-                    if migrate_array_field(connections) {
-                        migrated = true;
-                    }
-                }
-            }
-
-            migrated
-        }
-        _ => false,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,12 +964,7 @@ mod tests {
         assert!(registry.get("intention_lattice.intention").is_some());
 
         // Register custom schema
-        registry.register(
-            "test.schema".to_string(),
-            SchemaDefinition {
-                graph_fields: vec![],
-            },
-        );
+        registry.register("test.schema".to_string(), SchemaDefinition::default());
 
         assert!(registry.get("test.schema").is_some());
     }
@@ -243,22 +975,19 @@ mod tests {
 
         let schema1 = SchemaDefinition {
             graph_fields: vec![GraphField {
-                field_name: "field1",
-                direction: EdgeDirection::Upstream,
-                weight_kind: WeightKind::Pragmatic,
                 required: true,
-                payload_fields: vec![],
+                ..GraphField::new("field1", EdgeDirection::Upstream, WeightKind::Pragmatic)
             }],
+            ..SchemaDefinition::default()
         };
 
         let schema2 = SchemaDefinition {
-            graph_fields: vec![GraphField {
-                field_name: "field2",
-                direction: EdgeDirection::Downstream,
-                weight_kind: WeightKind::Epistemic,
-                required: false,
-                payload_fields: vec![],
-            }],
+            graph_fields: vec![GraphField::new(
+                "field2",
+                EdgeDirection::Downstream,
+                WeightKind::Epistemic,
+            )],
+            ..SchemaDefinition::default()
         };
 
         registry.register("test.overwrite".to_string(), schema1);
@@ -269,6 +998,89 @@ mod tests {
         assert_eq!(retrieved.graph_fields[0].field_name, "field2");
     }
 
+    fn task_schema() -> SchemaDefinition {
+        SchemaDefinition {
+            graph_fields: vec![],
+            fields: vec![
+                FieldDefinition {
+                    required: true,
+                    enum_values: vec!["open".to_string(), "done".to_string()],
+                    ..FieldDefinition::new("status", FieldType::String)
+                },
+                FieldDefinition {
+                    required: true,
+                    default: Some(TomlValue::Integer(3)),
+                    ..FieldDefinition::new("priority", FieldType::Float)
+                },
+                FieldDefinition {
+                    pattern: Some(Regex::new(r"^[A-Z]+-\d+$").unwrap()),
+                    ..FieldDefinition::new("ticket", FieldType::String)
+                },
+                FieldDefinition::new("labels", FieldType::Array),
+            ],
+            ..SchemaDefinition::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_fields() {
+        let schema = task_schema();
+        let valid: TomlTable =
+            toml::from_str("status = \"open\"\npriority = 1\nticket = \"NOET-12\"").unwrap();
+        assert!(schema.validate(&valid).is_empty());
+
+        let invalid: TomlTable =
+            toml::from_str("priority = \"high\"\nticket = \"noet\"\nlabels = \"a\"").unwrap();
+        let violations = schema.validate(&invalid);
+        assert_eq!(
+            violations,
+            vec![
+                FieldViolation::Missing {
+                    field_name: "status".to_string()
+                },
+                FieldViolation::WrongType {
+                    field_name: "priority".to_string(),
+                    expected: FieldType::Float,
+                    found: "string".to_string(),
+                },
+                FieldViolation::NoMatch {
+                    field_name: "ticket".to_string(),
+                    value: "noet".to_string(),
+                    pattern: r"^[A-Z]+-\d+$".to_string(),
+                },
+                FieldViolation::WrongType {
+                    field_name: "labels".to_string(),
+                    expected: FieldType::Array,
+                    found: "string".to_string(),
+                },
+            ]
+        );
+        assert!(violations[0].is_error());
+        assert!(!violations[2].is_error());
+
+        let not_allowed: TomlTable = toml::from_str("status = \"blocked\"").unwrap();
+        let violations = schema.validate(&not_allowed);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "field 'status' has value 'blocked', expected one of: open, done"
+        );
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let schema = task_schema();
+        let mut payload: TomlTable = toml::from_str("status = \"open\"").unwrap();
+        assert!(schema.apply_defaults(&mut payload));
+        assert_eq!(payload.get("priority"), Some(&TomlValue::Integer(3)));
+        assert!(!payload.contains_key("ticket"));
+
+        // Fields that are present are left alone.
+        payload.insert("priority".to_string(), TomlValue::Integer(1));
+        assert!(!schema.apply_defaults(&mut payload));
+        assert_eq!(payload.get("priority"), Some(&TomlValue::Integer(1)));
+    }
+
     #[test]
     fn test_list_schemas() {
         let registry = SchemaRegistry::create();
@@ -295,12 +1107,14 @@ mod tests {
             "downstream.custom".to_string(),
             SchemaDefinition {
                 graph_fields: vec![GraphField {
-                    field_name: "related_items",
-                    direction: EdgeDirection::Downstream,
-                    weight_kind: WeightKind::Epistemic,
-                    required: false,
-                    payload_fields: vec!["tags", "priority"],
+                    payload_fields: vec!["tags".to_string(), "priority".to_string()],
+                    ..GraphField::new(
+                        "related_items",
+                        EdgeDirection::Downstream,
+                        WeightKind::Epistemic,
+                    )
                 }],
+                ..SchemaDefinition::default()
             },
         );
 
@@ -321,12 +1135,7 @@ mod tests {
             .map(|i| {
                 thread::spawn(move || {
                     // Register schema from thread
-                    SCHEMAS.register(
-                        format!("concurrent.test{i}"),
-                        SchemaDefinition {
-                            graph_fields: vec![],
-                        },
-                    );
+                    SCHEMAS.register(format!("concurrent.test{i}"), SchemaDefinition::default());
 
                     // Read schema from thread
                     SCHEMAS.get("intention_lattice.intention")
@@ -343,4 +1152,136 @@ mod tests {
             assert!(SCHEMAS.get(&format!("concurrent.test{i}")).is_some());
         }
     }
+
+    #[test]
+    fn test_load_network_schema_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::write(
+            root.join("index.md"),
+            "---\ntitle: Root\nschemas: [shared/review.schema.json]\n---\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("schemas")).unwrap();
+        fs::write(
+            root.join("schemas/task.toml"),
+            r#"
+[fields.status]
+type = "string"
+required = true
+enum = ["open", "done"]
+
+[fields.priority]
+type = "integer"
+default = 3
+
+[graph_fields.depends_on]
+direction = "downstream"
+weight_kind = "Pragmatic"
+payload_fields = ["notes"]
+target_schemas = ["task"]
+acyclic = true
+"#,
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("shared")).unwrap();
+        fs::write(
+            root.join("shared/review.schema.json"),
+            r#"{
+  "$id": "load_tests.review",
+  "properties": {
+    "score": {"type": "number", "default": 0.5},
+    "due": {"type": "string", "format": "date-time"},
+    "reviewers": {"type": "array", "x-noet-graph": {
+      "direction": "upstream", "weight_kind": "Epistemic", "max_count": 2
+    }}
+  },
+  "required": ["due"]
+}"#,
+        )
+        .unwrap();
+        let subnet = root.join("sub");
+        fs::create_dir_all(subnet.join("schemas")).unwrap();
+        fs::write(subnet.join("index.md"), "# Sub\n").unwrap();
+        fs::write(
+            subnet.join("schemas/task.toml"),
+            "[fields.owner]\ntype = \"string\"\nrequired = true\n",
+        )
+        .unwrap();
+
+        let registry = SchemaRegistry::create();
+        assert!(registry.load_network(root).is_empty());
+        assert!(registry.load_network(&subnet).is_empty());
+        let root = canonical(root);
+        let subnet = canonical(&subnet);
+
+        let task = registry.get_for("task", &root.join("doc.md")).unwrap();
+        let status = &task
+            .fields
+            .iter()
+            .find(|f| f.field_name == "status")
+            .unwrap();
+        assert_eq!(status.field_type, FieldType::String);
+        assert!(status.required);
+        assert_eq!(status.enum_values, vec!["open", "done"]);
+        let priority = task.fields.iter().find(|f| f.field_name == "priority");
+        assert_eq!(
+            priority.and_then(|f| f.default.clone()),
+            Some(TomlValue::Integer(3))
+        );
+        assert_eq!(task.graph_fields[0].field_name, "depends_on");
+        assert_eq!(task.graph_fields[0].direction, EdgeDirection::Downstream);
+        assert_eq!(task.graph_fields[0].payload_fields, vec!["notes"]);
+        assert_eq!(task.graph_fields[0].target_schemas, vec!["task"]);
+        assert_eq!(task.graph_fields[0].max_count, None);
+        assert!(task.graph_fields[0].acyclic);
+
+        let review = registry
+            .get_for("load_tests.review", &subnet.join("doc.md"))
+            .unwrap();
+        let due = review
+            .fields
+            .iter()
+            .find(|f| f.field_name == "due")
+            .unwrap();
+        assert_eq!(due.field_type, FieldType::Datetime);
+        assert!(due.required);
+        assert_eq!(review.graph_fields[0].field_name, "reviewers");
+        assert_eq!(review.graph_fields[0].weight_kind, WeightKind::Epistemic);
+        assert_eq!(review.graph_fields[0].max_count, Some(2));
+        assert!(review.graph_fields[0].target_schemas.is_empty());
+
+        // The subnet's definition shadows its parent's; neither is global.
+        let sub_task = registry.get_for("task", &subnet.join("doc.md")).unwrap();
+        assert_eq!(sub_task.fields[0].field_name, "owner");
+        assert!(registry.get("task").is_none());
+        assert!(registry
+            .get_for("task", &root.parent().unwrap().join("doc.md"))
+            .is_none());
+
+        assert_eq!(
+            registry.schema_network(&root.join("shared/review.schema.json")),
+            Some(root.clone())
+        );
+        assert_eq!(
+            registry.schema_network(&subnet.join("schemas/task.toml")),
+            Some(subnet.clone())
+        );
+        assert_eq!(registry.schema_network(&root.join("doc.md")), None);
+
+        // Reloading replaces the network's schemas and reports broken files.
+        fs::write(
+            root.join("schemas/task.toml"),
+            "[fields.status]\ntype = \"text\"\n",
+        )
+        .unwrap();
+        let errors = registry.load_network(&root);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, root.join("schemas/task.toml"));
+        assert!(errors[0].1.to_string().contains("unknown type 'text'"));
+        assert!(registry.get_for("task", &root.join("doc.md")).is_none());
+        assert!(registry
+            .get_for("load_tests.review", &root.join("doc.md"))
+            .is_some());
+    }
 }
@@ -0,0 +1,174 @@
+//! `#tag` support for [`MdCodec`](super::md::MdCodec).
+//!
+//! Tags are written inline (`#rfc`, `#status/draft`) or listed in frontmatter
+//! (`tags = ["rfc", "status/draft"]`, or a comma separated string). Following Obsidian, a tag is
+//! made of letters, digits, `_`, `-` and `/`, must contain at least one non-digit (so `#123` is
+//! not a tag), and is matched case-insensitively. Tags are normalized to lowercase without the
+//! leading `#`.
+//!
+//! Every tag is a [`BeliefKind::Symbol`](crate::properties::BeliefKind::Symbol) node in the
+//! [`tag_namespace`] network, addressed by its tag path (`NodeKey::Path { net: tag_namespace,
+//! path: "status/draft" }`). The codec adds an Epistemic relation from the tag to each document or
+//! section that uses it, and the builder generates tag nodes on first use. Nested tags form a
+//! hierarchy: the builder relates each tag to its parent (`status` → `status/draft`) with an
+//! Epistemic edge, generating the parent as needed. Because tagged nodes and child tags are both
+//! downstream of a tag, [`tagged_query`] finds everything tagged with a tag or any of its children.
+//!
+//! Tags are plain text: the source is never rewritten.
+
+use toml_edit::Item;
+
+use crate::{
+    nodekey::NodeKey,
+    properties::{tag_namespace, WeightKind, WeightSet},
+    query::{Expression, NeighborsExpression, Query, MAX_TRAVERSAL},
+};
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Normalize a tag as written (with or without the leading `#`), or `None` if it is not a valid
+/// tag.
+pub fn normalize(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let raw = raw.strip_prefix('#').unwrap_or(raw);
+    if raw.is_empty() || !raw.chars().all(is_tag_char) {
+        return None;
+    }
+    let tag = raw
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+        .to_lowercase();
+    tag.chars()
+        .any(|c| !c.is_ascii_digit() && c != '/')
+        .then_some(tag)
+}
+
+/// The inline `#tags` in `text`, as byte offsets of their `#` and normalized tags.
+///
+/// A `#` starts a tag only at the start of `text` or after whitespace or an opening bracket;
+/// `preceded_by_word` tells whether the text itself follows a word character (e.g. the text event
+/// after `**bold**`), in which case a leading `#` is not a tag.
+pub fn parse_inline(text: &str, preceded_by_word: bool) -> Vec<(usize, String)> {
+    let mut tags = Vec::new();
+    let mut prev = None;
+    for (idx, c) in text.char_indices() {
+        let at_boundary = match prev {
+            None => !preceded_by_word,
+            Some(prev) => prev == '(' || prev == '[' || char::is_whitespace(prev),
+        };
+        prev = Some(c);
+        if c != '#' || !at_boundary {
+            continue;
+        }
+        let rest = &text[idx + 1..];
+        let len = rest.find(|c| !is_tag_char(c)).unwrap_or(rest.len());
+        let raw = rest[..len].trim_end_matches('/');
+        if let Some(tag) = normalize(raw) {
+            tags.push((idx, tag));
+        }
+    }
+    tags
+}
+
+/// The tags listed in a frontmatter `tags` value: an array of tags, or a string of tags separated
+/// by commas or whitespace.
+pub fn from_frontmatter(item: &Item) -> Vec<String> {
+    if let Some(tags) = item.as_str() {
+        tags.split(|c: char| c == ',' || c.is_whitespace())
+            .filter_map(normalize)
+            .collect()
+    } else if let Some(tags) = item.as_array() {
+        tags.iter()
+            .filter_map(|tag| tag.as_str())
+            .filter_map(normalize)
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// The key of the tag node for a normalized tag.
+pub fn to_nodekey(tag: &str) -> NodeKey {
+    NodeKey::Path {
+        net: tag_namespace().bref(),
+        path: tag.to_string(),
+    }
+}
+
+/// The parent of a nested tag (`status` for `status/draft`).
+pub fn parent(tag: &str) -> Option<&str> {
+    tag.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+/// Query for a tag, its child tags, and every node tagged with any of them.
+///
+/// The result is balanced, so besides the tagged documents and sections it holds the tag nodes
+/// (always [`BeliefKind::Trace`](crate::properties::BeliefKind::Trace)) and Trace nodes for the
+/// structural context of the tagged nodes. Filter on `kind.is_complete()` for the tagged nodes.
+pub fn tagged_query(tag: &str) -> Option<Query> {
+    let tag = normalize(tag)?;
+    Some(Query {
+        seed: Expression::from(&to_nodekey(&tag)),
+        traverse: Some(NeighborsExpression {
+            filter: Some(WeightSet::from(WeightKind::Epistemic)),
+            upstream: 0,
+            downstream: MAX_TRAVERSAL,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("#RFC"), Some("rfc".to_string()));
+        assert_eq!(normalize("status/Draft"), Some("status/draft".to_string()));
+        assert_eq!(normalize("#a//b/"), Some("a/b".to_string()));
+        assert_eq!(normalize("#123"), None);
+        assert_eq!(normalize("#"), None);
+        assert_eq!(normalize("not a tag"), None);
+    }
+
+    #[test]
+    fn test_parse_inline() {
+        assert_eq!(
+            parse_inline("An #rfc about #status/draft/.", false),
+            vec![(3, "rfc".to_string()), (14, "status/draft".to_string())]
+        );
+        assert_eq!(parse_inline("#rfc at the start", false).len(), 1);
+        assert!(parse_inline("#rfc after bold", true).is_empty());
+        // Issue numbers, anchors in words and headings-like markers are not tags.
+        assert!(parse_inline("Fixes #123, see C# and page#section, # heading", false).is_empty());
+        assert_eq!(
+            parse_inline("(#todo)", false),
+            vec![(1, "todo".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_from_frontmatter() {
+        let doc = "tags = [\"RFC\", \"#status/draft\", \"42\"]\nkeywords = \"a, #b c\"\n"
+            .parse::<toml_edit::DocumentMut>()
+            .unwrap();
+        assert_eq!(
+            from_frontmatter(&doc["tags"]),
+            vec!["rfc".to_string(), "status/draft".to_string()]
+        );
+        assert_eq!(
+            from_frontmatter(&doc["keywords"]),
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parent() {
+        assert_eq!(parent("status/draft/old"), Some("status/draft"));
+        assert_eq!(parent("status"), None);
+    }
+}
@@ -0,0 +1,393 @@
+//! Obsidian-style wikilink support for [`MdCodec`](super::md::MdCodec).
+//!
+//! With `ENABLE_WIKILINKS`, pulldown-cmark tokenizes wikilinks as links and images whose
+//! `link_type` is `LinkType::WikiLink`:
+//!
+//! - `[[Note]]` and `[[Note|alias]]` arrive as `Start(Link { dest_url: "Note", .. })`, with the
+//!   alias (or the target, when there is no alias) as the link text.
+//! - `[[Note#Heading]]` keeps the heading in `dest_url` (`"Note#Heading"`).
+//! - `![[Note]]` embeds arrive as `Start(Image { dest_url: "Note", .. })`.
+//! - `[[Note#^block-id]]` and `![[Note#^block-id]]` link and embed a single
+//!   [block](super::block).
+//!
+//! A target naming a note (no `/` and no known extension) becomes a `NodeKey::Id` of the
+//! anchored name. The builder resolves it against node ids, then document titles, then the
+//! `aliases` listed in document frontmatter, warning when several documents match. Targets with a
+//! `/` or a file extension are paths relative to the linking document; `.md` is implied for
+//! extensionless paths, as in Obsidian.
+//!
+//! Like MyST syntax, wikilinks are the source of truth: they are never rewritten on write-back
+//! ([`collapse_to_source`]), and are only resolved to links and transclusions by [`Renderer`] at
+//! HTML render time. Embedded notes are rendered as plain markdown; wikilinks inside the embedded
+//! content are not resolved.
+
+use pulldown_cmark::{
+    CowStr, Event as MdEvent, HeadingLevel, LinkType, Parser as MdParser, Tag as MdTag,
+    TagEnd as MdTagEnd,
+};
+use std::{collections::HashMap, fs, ops::Range, path::Path};
+
+use crate::{
+    codec::{
+        block,
+        md::{buildonomy_md_options, rewrite_href_to_html},
+        myst::ResolvedReference,
+        CODECS,
+    },
+    nodekey::{href_to_nodekey, NodeKey},
+    paths::{to_anchor, AnchorPath},
+};
+
+/// Split a wikilink target into the note and the optional heading (`Note#Heading`).
+pub fn split_target(target: &str) -> (&str, Option<&str>) {
+    match target.split_once('#') {
+        Some((note, heading)) => (note.trim(), Some(heading.trim())),
+        None => (target.trim(), None),
+    }
+}
+
+/// Map a wikilink target to the key of the node it references.
+///
+/// Note names resolve by id, title or alias and so ignore the heading; [`Renderer`] adds it back
+/// as an anchor. Embeds of files with a non-markdown extension (`![[diagram.png]]`) are assets.
+pub fn to_nodekey(target: &str, is_embed: bool) -> NodeKey {
+    let (note, heading) = split_target(target);
+    let anchor = heading.map(to_anchor).filter(|anchor| !anchor.is_empty());
+    if note.is_empty() {
+        // `[[#Heading]]` links a section of the linking document.
+        return href_to_nodekey(&format!("#{}", anchor.unwrap_or_default()));
+    }
+    let note_ap = AnchorPath::new(note);
+    let has_codec = !note_ap.ext().is_empty() && CODECS.get(&note_ap).is_some();
+    let is_asset = is_embed && !note_ap.ext().is_empty() && !has_codec;
+    if note.contains('/') || has_codec || is_asset {
+        let mut path = note.to_string();
+        if note_ap.ext().is_empty() {
+            path.push_str(".md");
+        }
+        if let Some(anchor) = anchor {
+            path = format!("{path}#{anchor}");
+        }
+        href_to_nodekey(&path)
+    } else {
+        href_to_nodekey(&format!("id:{note}"))
+    }
+}
+
+/// Reconstruct the wikilink syntax for a link or embed.
+pub fn to_source(target: &str, text: &str, is_embed: bool) -> String {
+    let bang = if is_embed { "!" } else { "" };
+    if text.is_empty() || text == target {
+        format!("{bang}[[{target}]]")
+    } else {
+        format!("{bang}[[{target}|{text}]]")
+    }
+}
+
+/// Replace wikilink spans with their original syntax, as inline HTML so the markdown writer
+/// emits it verbatim (it would otherwise write wikilinks as inline links).
+pub fn collapse_to_source<'a>(
+    events: Vec<(MdEvent<'a>, Option<Range<usize>>)>,
+) -> Vec<(MdEvent<'a>, Option<Range<usize>>)> {
+    let mut out = Vec::with_capacity(events.len());
+    let mut iter = events.into_iter();
+    while let Some((event, range)) = iter.next() {
+        let (target, is_embed) = match &event {
+            MdEvent::Start(MdTag::Link {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            }) => (dest_url.to_string(), false),
+            MdEvent::Start(MdTag::Image {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            }) => (dest_url.to_string(), true),
+            _ => {
+                out.push((event, range));
+                continue;
+            }
+        };
+        let mut text = String::new();
+        for (inner, _) in iter.by_ref() {
+            match inner {
+                MdEvent::End(MdTagEnd::Link | MdTagEnd::Image) => break,
+                MdEvent::Text(t) | MdEvent::Code(t) => text.push_str(&t),
+                _ => {}
+            }
+        }
+        out.push((
+            MdEvent::InlineHtml(CowStr::from(to_source(&target, &text, is_embed))),
+            None,
+        ));
+    }
+    out
+}
+
+/// Events of the section of `events` headed by `anchor`, or `None` if no heading matches.
+fn section_events(events: Vec<MdEvent<'static>>, anchor: &str) -> Option<Vec<MdEvent<'static>>> {
+    let mut start: Option<(usize, HeadingLevel)> = None;
+    let mut heading_text = String::new();
+    let mut section_end = events.len();
+    for (idx, event) in events.iter().enumerate() {
+        match event {
+            MdEvent::Start(MdTag::Heading { level, id, .. }) => {
+                if let Some((_, start_level)) = start {
+                    if *level <= start_level {
+                        section_end = idx;
+                        break;
+                    }
+                } else if id.as_deref().is_some_and(|id| to_anchor(id) == anchor) {
+                    start = Some((idx, *level));
+                } else {
+                    heading_text.clear();
+                }
+            }
+            MdEvent::Text(text) | MdEvent::Code(text) => heading_text.push_str(text),
+            MdEvent::End(MdTagEnd::Heading(level))
+                if start.is_none() && to_anchor(&heading_text) == anchor =>
+            {
+                let heading_start = events[..idx]
+                    .iter()
+                    .rposition(|event| matches!(event, MdEvent::Start(MdTag::Heading { .. })))
+                    .unwrap_or(0);
+                start = Some((heading_start, *level));
+            }
+            _ => {}
+        }
+    }
+    let (section_start, _) = start?;
+    Some(events[section_start..section_end].to_vec())
+}
+
+fn html(fragment: String) -> MdEvent<'static> {
+    MdEvent::Html(CowStr::from(fragment))
+}
+
+/// Replaces wikilinks with links to their resolved targets, and embeds with the target's
+/// content.
+pub struct Renderer<'a> {
+    doc_path: &'a str,
+    resolved: &'a HashMap<String, ResolvedReference>,
+}
+
+impl<'a> Renderer<'a> {
+    /// `doc_path` is the absolute path of the document being rendered; `resolved` maps wikilink
+    /// targets, as written, to their links relative to `doc_path`.
+    pub fn new(doc_path: &'a str, resolved: &'a HashMap<String, ResolvedReference>) -> Self {
+        Renderer { doc_path, resolved }
+    }
+
+    pub fn render(&self, events: Vec<MdEvent<'static>>) -> Vec<MdEvent<'static>> {
+        let mut out = Vec::with_capacity(events.len());
+        let mut idx = 0;
+        while idx < events.len() {
+            match &events[idx] {
+                MdEvent::Start(MdTag::Link {
+                    link_type: LinkType::WikiLink { .. },
+                    dest_url,
+                    title,
+                    id,
+                }) => {
+                    if let Some(resolved) = self.resolved.get(dest_url.as_ref()) {
+                        out.push(MdEvent::Start(MdTag::Link {
+                            link_type: LinkType::Inline,
+                            dest_url: CowStr::from(rewrite_href_to_html(&resolved.href)),
+                            title: title.clone(),
+                            id: id.clone(),
+                        }));
+                        idx += 1;
+                        continue;
+                    }
+                }
+                MdEvent::Start(MdTag::Image {
+                    link_type: LinkType::WikiLink { .. },
+                    dest_url,
+                    title,
+                    id,
+                }) => {
+                    if let Some(resolved) = self.resolved.get(dest_url.as_ref()) {
+                        let end = events[idx..]
+                            .iter()
+                            .position(|event| matches!(event, MdEvent::End(MdTagEnd::Image)))
+                            .map(|offset| idx + offset)
+                            .unwrap_or(events.len() - 1);
+                        match self.embed(dest_url, resolved) {
+                            Some(embedded) => {
+                                // An embed alone in its paragraph replaces the paragraph.
+                                let own_paragraph =
+                                    matches!(out.last(), Some(MdEvent::Start(MdTag::Paragraph)))
+                                        && matches!(
+                                            events.get(end + 1),
+                                            Some(MdEvent::End(MdTagEnd::Paragraph))
+                                        );
+                                if own_paragraph {
+                                    out.pop();
+                                }
+                                out.push(html(format!(
+                                    "<div class=\"wikilink-embed\" data-target=\"{}\">\n",
+                                    escape_attr(dest_url)
+                                )));
+                                out.extend(embedded);
+                                out.push(html("</div>\n".to_string()));
+                                idx = if own_paragraph { end + 2 } else { end + 1 };
+                            }
+                            None => {
+                                out.push(MdEvent::Start(MdTag::Image {
+                                    link_type: LinkType::Inline,
+                                    dest_url: CowStr::from(resolved.href.clone()),
+                                    title: title.clone(),
+                                    id: id.clone(),
+                                }));
+                                idx += 1;
+                            }
+                        }
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            out.push(events[idx].clone());
+            idx += 1;
+        }
+        out
+    }
+
+    /// The rendered content of an embedded note, or `None` if the target is not a document
+    /// (e.g. an image), in which case the embed renders as an image.
+    fn embed(&self, target: &str, resolved: &ResolvedReference) -> Option<Vec<MdEvent<'static>>> {
+        let href_ap = AnchorPath::new(&resolved.href);
+        let filepath = href_ap.filepath();
+        if !href_ap.ext().is_empty() && CODECS.get(&AnchorPath::new(filepath)).is_none() {
+            return None;
+        }
+        let mut path = Path::new(self.doc_path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(filepath);
+        if path.is_dir() {
+            path = path.join("index.md");
+        }
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::warn!("Could not read embedded note {path:?}: {e}");
+                return Some(vec![html(format!(
+                    "<!-- embed not found: {} -->\n",
+                    escape_attr(target)
+                ))]);
+            }
+        };
+        let events = MdParser::new_ext(&content, buildonomy_md_options())
+            .map(|event| event.into_static())
+            .collect::<Vec<_>>();
+        let heading = split_target(target).1;
+        let anchor = heading.map(to_anchor).filter(|anchor| !anchor.is_empty());
+        Some(match anchor {
+            // `![[Note#^block-id]]` embeds a single block.
+            Some(anchor) if heading.is_some_and(|heading| heading.starts_with('^')) => {
+                block::block_events(&events, &anchor).unwrap_or_default()
+            }
+            Some(anchor) => section_events(events, &anchor).unwrap_or_default(),
+            None => events,
+        })
+    }
+}
+
+fn escape_attr(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_target() {
+        assert_eq!(split_target("Note"), ("Note", None));
+        assert_eq!(
+            split_target("Note#Some Heading"),
+            ("Note", Some("Some Heading"))
+        );
+        assert_eq!(split_target("#Heading"), ("", Some("Heading")));
+    }
+
+    #[test]
+    fn test_to_nodekey() {
+        assert_eq!(
+            to_nodekey("My Note", false),
+            NodeKey::Id {
+                net: Default::default(),
+                id: "my-note".to_string()
+            }
+        );
+        // The heading is resolved at render time; the relation targets the note.
+        assert_eq!(
+            to_nodekey("My Note#Intro", false),
+            to_nodekey("My Note", false)
+        );
+        assert!(matches!(
+            to_nodekey("folder/Note", false),
+            NodeKey::Path { path, .. } if path == "folder/Note.md"
+        ));
+        assert!(matches!(
+            to_nodekey("folder/Note#Intro", false),
+            NodeKey::Path { path, .. } if path == "folder/Note.md#intro"
+        ));
+        assert!(matches!(
+            to_nodekey("diagram.png", true),
+            NodeKey::Path { path, .. } if path == "diagram.png"
+        ));
+        assert!(matches!(
+            to_nodekey("#Intro", false),
+            NodeKey::Path { path, .. } if path == "#intro"
+        ));
+    }
+
+    #[test]
+    fn test_collapse_to_source() {
+        let source = "See [[Note|the alias]], [[Note#Intro]] and [x](y.md).\n\n![[Note]]\n";
+        let events = MdParser::new_ext(source, buildonomy_md_options())
+            .into_offset_iter()
+            .map(|(event, range)| (event, Some(range)))
+            .collect::<Vec<_>>();
+        let sources = collapse_to_source(events)
+            .into_iter()
+            .filter_map(|(event, _)| match event {
+                MdEvent::InlineHtml(html) => Some(html.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec!["[[Note|the alias]]", "[[Note#Intro]]", "![[Note]]"]
+        );
+    }
+
+    #[test]
+    fn test_section_events() {
+        let content =
+            "# Note\n\nIntro.\n\n## Details {#details}\n\nInside.\n\n### Deeper\n\nStill \
+                       inside.\n\n## Other\n\nOutside.\n";
+        let events = MdParser::new_ext(content, buildonomy_md_options())
+            .map(|event| event.into_static())
+            .collect::<Vec<_>>();
+        let mut html_out = String::new();
+        pulldown_cmark::html::push_html(
+            &mut html_out,
+            section_events(events.clone(), "details")
+                .unwrap()
+                .into_iter(),
+        );
+        assert!(html_out.contains("Inside."), "{html_out}");
+        assert!(html_out.contains("Still inside."), "{html_out}");
+        assert!(!html_out.contains("Outside."), "{html_out}");
+        assert!(!html_out.contains("Intro."), "{html_out}");
+
+        assert!(section_events(events.clone(), "deeper").is_some());
+        assert!(section_events(events, "missing").is_none());
+    }
+}
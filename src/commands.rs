@@ -1,11 +1,27 @@
 use serde::{Deserialize, Serialize};
-use std::fmt::{Display, Formatter};
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use toml::Table;
 
 use crate::{
-    beliefbase::BeliefGraph,
+    beliefbase::{BeliefBase, BeliefGraph},
+    codec::{
+        compiler::ParseResult,
+        edit::{MovePlan, SourceEdit, SourceEditor},
+        network::detect_network_file,
+        DocumentCompiler,
+    },
     config::NetworkRecord,
-    properties::BeliefNode,
-    query::{PaginatedQuery, ResultsPage},
+    error::BuildonomyError,
+    event::{BeliefEvent, EventOrigin},
+    nodekey::NodeKey,
+    paths::{os_path_to_string, string_to_os_path},
+    properties::{BeliefNode, Bid, WeightKind},
+    query::{BeliefSource, PaginatedQuery, ResultsPage},
 };
 
 /// Command interface for noet-core library operations
@@ -19,6 +35,43 @@ pub enum Op {
     UpdateContent(String, String),
     /// Return a BeliefBase corresponding to a paginated query
     GetStates(PaginatedQuery),
+    /// Create a node under `parent`: a document at `path` (relative to the parent network's
+    /// directory) under a network, or a section under a document or section
+    CreateNode {
+        parent: NodeKey,
+        title: String,
+        path: Option<String>,
+        #[serde(default)]
+        payload: Table,
+    },
+    /// Set the `set` payload fields of a node and remove the `remove` fields
+    SetPayload {
+        node: NodeKey,
+        #[serde(default)]
+        set: Table,
+        #[serde(default)]
+        remove: Vec<String>,
+    },
+    /// Relate `source` to `sink` by linking to it from the text of `sink`
+    AddRelation {
+        source: NodeKey,
+        sink: NodeKey,
+        kind: WeightKind,
+        #[serde(default)]
+        payload: Table,
+    },
+    /// Remove the links relating `source` to `sink` with `kind`
+    RemoveRelation {
+        source: NodeKey,
+        sink: NodeKey,
+        kind: WeightKind,
+    },
+    /// Retitle a node
+    RenameNode(NodeKey, String),
+    /// Delete a document, or a section and its subsections
+    DeleteNode(NodeKey),
+    /// Move a file or directory, rewriting the relative links that the move would break
+    MovePath(String, String),
 }
 
 impl Display for Op {
@@ -35,6 +88,17 @@ impl Display for Op {
             ),
             Op::UpdateContent(p, _) => write!(f, "UpdateContent({p})"),
             Op::GetStates(pq) => write!(f, "GetStates({pq:?})"),
+            Op::CreateNode { parent, title, .. } => write!(f, "CreateNode({parent}, {title})"),
+            Op::SetPayload { node, .. } => write!(f, "SetPayload({node})"),
+            Op::AddRelation {
+                source, sink, kind, ..
+            } => write!(f, "AddRelation({source} -[{}]-> {sink})", kind.name()),
+            Op::RemoveRelation { source, sink, kind } => {
+                write!(f, "RemoveRelation({source} -[{}]-> {sink})", kind.name())
+            }
+            Op::RenameNode(node, title) => write!(f, "RenameNode({node}, {title})"),
+            Op::DeleteNode(node) => write!(f, "DeleteNode({node})"),
+            Op::MovePath(from, to) => write!(f, "MovePath({from} -> {to})"),
         }
     }
 }
@@ -53,6 +117,8 @@ pub enum OpResult {
     Networks(Vec<NetworkRecord>),
     State(BeliefGraph),
     NetworkState(String, BeliefNode),
+    /// Source files written or removed, relative to the repository root
+    Edited(Vec<String>),
 }
 
 impl Display for OpResult {
@@ -76,6 +142,321 @@ impl Display for OpResult {
             ),
             OpResult::State(_) => write!(f, "State"),
             OpResult::NetworkState(_, _) => write!(f, "NetworkState"),
+            OpResult::Edited(paths) => write!(f, "Edited({})", paths.join(", ")),
+        }
+    }
+}
+
+/// Executes [`Op`]s against the document tree rooted at a directory, keeping a [`BeliefBase`] in
+/// step with the source files.
+///
+/// Graph edits are written to the files that own the edited nodes (see
+/// [`codec::edit`](crate::codec::edit)), then the changed files are reparsed with write-back
+/// enabled, so the graph follows the documents and new nodes get their BIDs.
+///
+/// ```rust,no_run
+/// use noet_core::{commands::{Op, OpExecutor}, nodekey::NodeKey};
+/// use std::str::FromStr;
+///
+/// # async fn run() -> Result<(), noet_core::BuildonomyError> {
+/// let mut executor = OpExecutor::new("./docs")?;
+/// executor.load().await?;
+/// executor
+///     .execute(Op::RenameNode(NodeKey::from_str("id://intro")?, "Introduction".to_string()))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct OpExecutor {
+    compiler: DocumentCompiler,
+    events: UnboundedReceiver<BeliefEvent>,
+    cache: BeliefBase,
+    path_events: Vec<BeliefEvent>,
+}
+
+impl OpExecutor {
+    pub fn new(root: impl AsRef<Path>) -> Result<Self, BuildonomyError> {
+        let (tx, events) = unbounded_channel();
+        Ok(Self {
+            compiler: DocumentCompiler::new(root, Some(tx), None, true)?,
+            events,
+            cache: BeliefBase::empty(),
+            path_events: Vec::new(),
+        })
+    }
+
+    /// An executor starting from a known graph of the document tree, such as a
+    /// [`WatchService`](crate::watch::WatchService)'s database, so that parsed nodes keep the BIDs
+    /// they have there even where those aren't written to the sources.
+    pub fn with_cache(root: impl AsRef<Path>, cache: BeliefBase) -> Result<Self, BuildonomyError> {
+        let mut executor = Self::new(root)?;
+        executor.cache = cache;
+        Ok(executor)
+    }
+
+    pub fn compiler(&self) -> &DocumentCompiler {
+        &self.compiler
+    }
+
+    /// The graph of the document tree as of the last executed op.
+    pub fn cache(&self) -> &BeliefBase {
+        &self.cache
+    }
+
+    /// The path events of the moves applied since last taken, for keeping a database's `paths`
+    /// table in step with the cache.
+    pub fn take_path_events(&mut self) -> Vec<BeliefEvent> {
+        std::mem::take(&mut self.path_events)
+    }
+
+    /// Parse the document tree. [`execute`](Self::execute) does so on first use.
+    pub async fn load(&mut self) -> Result<Vec<ParseResult>, BuildonomyError> {
+        let results = self.compiler.parse_all(self.cache.clone(), false).await?;
+        while let Ok(event) = self.events.try_recv() {
+            self.cache.process_event(&event)?;
+            // The reparsed payload is authoritative, even where it only adds fields.
+            if let BeliefEvent::NodeUpdate(_, toml, _) = &event {
+                let node = BeliefNode::try_from(&toml[..])?;
+                self.cache.replace_payload(&node.bid, node.payload);
+            }
+        }
+        Ok(results)
+    }
+
+    pub async fn execute(&mut self, op: Op) -> Result<OpResult, BuildonomyError> {
+        if self.cache.is_empty() {
+            self.load().await?;
+        }
+        let repo_root = self.compiler.builder().repo_root().to_path_buf();
+        let editor = SourceEditor::new(&self.cache, self.compiler.builder().repo(), &repo_root);
+        let mut removed = Vec::new();
+        let edit = match op {
+            Op::LoadNetworks | Op::SetNetworks(_) => {
+                return Err(BuildonomyError::Command(format!(
+                    "{op} configures the networks of a WatchService"
+                )))
+            }
+            Op::GetStates(pq) => {
+                return Ok(OpResult::Page(self.cache.eval_page(&pq).await?));
+            }
+            Op::MovePath(from, to) => {
+                let plan = editor.plan_move(&from, &to)?;
+                return self.apply_move(&plan).await;
+            }
+            Op::UpdateContent(path, content) => SourceEdit {
+                path: repo_root.join(string_to_os_path(&path)),
+                content: Some(content),
+            },
+            Op::CreateNode {
+                parent,
+                title,
+                path,
+                payload,
+            } => editor.create_node(&parent, &title, path.as_deref(), &payload)?,
+            Op::SetPayload { node, set, remove } => editor.set_payload(&node, &set, &remove)?,
+            Op::AddRelation {
+                source,
+                sink,
+                kind,
+                payload,
+            } => editor.add_relation(&source, &sink, kind, &payload)?,
+            Op::RemoveRelation { source, sink, kind } => {
+                editor.remove_relation(&source, &sink, kind)?
+            }
+            Op::RenameNode(node, title) => editor.rename_node(&node, &title)?,
+            Op::DeleteNode(node) => {
+                let edit = editor.delete_node(&node)?;
+                removed = self.subtree(&node);
+                edit
+            }
+        };
+        self.write(vec![edit], removed, &repo_root).await
+    }
+
+    /// Plan moving the file or directory at `from` to `to` (relative to the repository root)
+    /// without touching the sources. See [`SourceEditor::plan_move`].
+    pub async fn plan_move(&mut self, from: &str, to: &str) -> Result<MovePlan, BuildonomyError> {
+        if self.cache.is_empty() {
+            self.load().await?;
+        }
+        let repo_root = self.compiler.builder().repo_root().to_path_buf();
+        SourceEditor::new(&self.cache, self.compiler.builder().repo(), &repo_root)
+            .plan_move(from, to)
+    }
+
+    /// Carry out a move planned by [`plan_move`](Self::plan_move) and reload the document tree.
+    /// Applying [`MovePlan::reverse`] afterwards undoes it.
+    ///
+    /// The moved documents' paths are rebased in the cache first, so their nodes keep their BIDs
+    /// at the new paths; the resulting path events are kept for
+    /// [`take_path_events`](Self::take_path_events). The compiler's session remembers where
+    /// every node was parsed, so rather than patching the moved subtree out of it, the tree is
+    /// parsed afresh in a new session.
+    pub async fn apply_move(&mut self, plan: &MovePlan) -> Result<OpResult, BuildonomyError> {
+        plan.apply()?;
+        let repo_root = self.compiler.builder().repo_root().to_path_buf();
+        let mut written = plan
+            .files
+            .iter()
+            .map(|(_, to)| to.clone())
+            .collect::<BTreeSet<_>>();
+        for edit in plan.edits() {
+            if let Ok(path) = edit.path.strip_prefix(&repo_root) {
+                written.insert(os_path_to_string(path));
+            }
         }
+        let mut path_events = self.rebase_paths(plan)?;
+        self.path_events.append(&mut path_events);
+        let (tx, events) = unbounded_channel();
+        self.compiler = DocumentCompiler::new(&repo_root, Some(tx), None, true)?;
+        self.events = events;
+        self.load().await?;
+        Ok(OpResult::Edited(written.into_iter().collect()))
     }
+
+    /// Point the Section relations of the documents `plan` moves at their new paths, returning
+    /// the path events that follow. Sections derive their paths from their documents', so their
+    /// relations are replayed after their documents' to rebase them too. Documents moved out of
+    /// the network directory their relation is relative to are left to the reparse.
+    fn rebase_paths(&mut self, plan: &MovePlan) -> Result<Vec<BeliefEvent>, BuildonomyError> {
+        let repo = self.compiler.builder().repo().bref();
+        let mut updates = Vec::new();
+        let mut replays = Vec::new();
+        {
+            let paths = self.cache.paths();
+            let relations = self.cache.relations();
+            let graph = relations.as_graph();
+            let sections = |bid: Bid| {
+                graph.raw_edges().iter().filter_map(move |edge| {
+                    let section = edge.weight.get(&WeightKind::Section)?;
+                    (graph[edge.source()] == bid).then_some((edge, section))
+                })
+            };
+            for (from, to) in plan.files.iter() {
+                let Some((_, bid)) = paths.net_get_from_path(&repo, from) else {
+                    continue;
+                };
+                for (edge, section) in sections(bid) {
+                    let doc_paths = section.get_doc_paths();
+                    let rebased = doc_paths
+                        .iter()
+                        .map(|path| {
+                            let net_dir = from.strip_suffix(path.as_str())?;
+                            if !(net_dir.is_empty() || net_dir.ends_with('/')) {
+                                return None;
+                            }
+                            to.strip_prefix(net_dir).map(str::to_string)
+                        })
+                        .collect::<Option<Vec<_>>>();
+                    let Some(rebased) = rebased.filter(|rebased| *rebased != doc_paths) else {
+                        continue;
+                    };
+                    let mut section = section.clone();
+                    section.set_doc_paths(rebased)?;
+                    let mut weights = edge.weight.clone();
+                    weights.set(WeightKind::Section, section);
+                    updates.push(BeliefEvent::RelationUpdate(
+                        bid,
+                        graph[edge.target()],
+                        weights,
+                        EventOrigin::Remote,
+                    ));
+                }
+                for nested in self.subtree(&NodeKey::Bid { bid }).into_iter() {
+                    if nested == bid {
+                        continue;
+                    }
+                    replays.extend(sections(nested).map(|(edge, _)| {
+                        BeliefEvent::RelationUpdate(
+                            nested,
+                            graph[edge.target()],
+                            edge.weight.clone(),
+                            EventOrigin::Remote,
+                        )
+                    }));
+                }
+            }
+        }
+        let mut path_events = Vec::new();
+        for update in updates.into_iter().chain(replays) {
+            path_events.extend(
+                self.cache
+                    .process_event(&update)?
+                    .into_iter()
+                    .filter(|event| {
+                        matches!(
+                            event,
+                            BeliefEvent::PathAdded(..)
+                                | BeliefEvent::PathUpdate(..)
+                                | BeliefEvent::PathsRemoved(..)
+                        )
+                    }),
+            );
+        }
+        Ok(path_events)
+    }
+
+    /// Apply `edits`, drop the `removed` nodes from the cache and reparse the touched files.
+    ///
+    /// Reparsing a document doesn't report the sections it no longer contains, so deleted nodes
+    /// are removed explicitly.
+    async fn write(
+        &mut self,
+        edits: Vec<SourceEdit>,
+        removed: Vec<Bid>,
+        repo_root: &Path,
+    ) -> Result<OpResult, BuildonomyError> {
+        let mut written = Vec::new();
+        for edit in edits.iter() {
+            edit.apply()?;
+            let path = edit
+                .path
+                .strip_prefix(repo_root)
+                .map(os_path_to_string)
+                .unwrap_or_else(|_| os_path_to_string(&edit.path));
+            if edit.content.is_some() {
+                self.compiler.on_file_modified(&edit.path);
+            } else {
+                self.compiler.on_file_deleted(&edit.path);
+                // The network lists its documents, so it drops the removed one when reparsed.
+                if let Some(network) = owning_network(&edit.path, repo_root) {
+                    self.compiler.on_file_modified(network);
+                }
+            }
+            written.push(path);
+        }
+        if !removed.is_empty() {
+            self.cache
+                .process_event(&BeliefEvent::NodesRemoved(removed, EventOrigin::Remote))?;
+        }
+        self.load().await?;
+        Ok(OpResult::Edited(written))
+    }
+
+    /// The bids of `node` and of the sections nested under it.
+    fn subtree(&self, node: &NodeKey) -> Vec<Bid> {
+        let Some(node) = self.cache.get(node) else {
+            return Vec::new();
+        };
+        let paths = self.cache.paths();
+        let Some(map) = paths.get_map(&self.compiler.builder().repo().bref()) else {
+            return vec![node.bid];
+        };
+        let Some((order, _)) = map.order_for_bid(&node.bid) else {
+            return vec![node.bid];
+        };
+        map.map()
+            .iter()
+            .filter(|(_, _, entry_order)| entry_order.starts_with(order))
+            .map(|(_, bid, _)| *bid)
+            .collect()
+    }
+}
+
+/// The network file of the nearest network directory containing `path`.
+fn owning_network(path: &Path, repo_root: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(repo_root))
+        .find_map(detect_network_file)
 }
@@ -7,9 +7,14 @@ use crate::{
         AnchorPath,
     },
     properties::{BeliefKind, BeliefNode, BeliefRelation, Bid, Bref, WeightKind, WeightSet},
-    query::{push_string_expr, AsSql, BeliefSource, Expression, StatePred},
+    query::{
+        path_sort_key, payload_json, push_order_by, push_string_expr, reading_positions, AsSql,
+        BeliefSource, Expression, OrderBy, OrderKey, Query, StatePred, BALANCE_CUTOFF,
+        MAX_TRAVERSAL,
+    },
 };
 use futures_core::future::BoxFuture;
+use petgraph::Direction;
 use sqlx::Execute;
 use sqlx::{
     error::BoxDynError,
@@ -18,7 +23,7 @@ use sqlx::{
     ConnectOptions, Row,
 };
 use sqlx::{migrate::MigrationType, Pool, QueryBuilder};
-use std::{collections::BTreeMap, fmt::Debug, result::Result};
+use std::{cmp, collections::BTreeMap, fmt::Debug, result::Result};
 use std::{
     collections::BTreeSet,
     fs,
@@ -188,7 +193,7 @@ impl<'a> Transaction<'a> {
 
     fn update_node(&mut self, belief: &BeliefNode) {
         self.qb
-            .push("INSERT OR REPLACE INTO beliefs(bid, bref, kind, title, schema, payload, id) ");
+            .push("INSERT OR REPLACE INTO beliefs(bid, bref, kind, title, schema, payload, id, payload_json) ");
         self.qb.push_values(vec![belief], |mut b, belief| {
             b.push_bind::<String>(belief.bid.into())
                 .push_bind::<String>(belief.bid.bref().to_string())
@@ -196,7 +201,8 @@ impl<'a> Transaction<'a> {
                 .push_bind::<String>(belief.title.clone())
                 .push_bind::<Option<String>>(belief.schema.clone())
                 .push_bind::<String>(belief.payload.to_string())
-                .push_bind::<Option<String>>(belief.id.clone());
+                .push_bind::<Option<String>>(belief.id.clone())
+                .push_bind::<String>(payload_json(&belief.payload).to_string());
         });
         self.qb.push("; ");
         self.staged += 1;
@@ -246,7 +252,7 @@ impl<'a> Transaction<'a> {
             return;
         }
         self.qb
-            .push("INSERT OR REPLACE INTO paths(net, path, target, ordering)");
+            .push("INSERT OR REPLACE INTO paths(net, path, target, ordering, sort_key)");
         self.qb
             .push_values(paths, |mut b, (path, target, order_vec)| {
                 let order_str = order_vec
@@ -257,7 +263,8 @@ impl<'a> Transaction<'a> {
                 b.push_bind::<String>(net.to_string())
                     .push_bind::<String>(path.clone())
                     .push_bind::<String>(target.into())
-                    .push_bind::<String>(order_str);
+                    .push_bind::<String>(order_str)
+                    .push_bind::<String>(path_sort_key(order_vec));
             });
         self.qb.push(";");
         self.staged += 1;
@@ -281,7 +288,7 @@ impl<'a> Transaction<'a> {
         } else {
             self.qb.push(
                 "INSERT OR REPLACE INTO relations \
-                 (sink, source, epistemic, section, pragmatic) ",
+                 (sink, source, epistemic, section, pragmatic, custom) ",
             );
             self.qb.push_values(
                 vec![(source, sink, weight_set)],
@@ -291,14 +298,34 @@ impl<'a> Transaction<'a> {
                         toml::to_string(w).unwrap_or_default()
                     };
 
+                    // Registered kinds share one JSON column, keyed by kind name
+                    let custom = weight
+                        .weights
+                        .iter()
+                        .filter(|(kind, _)| kind.is_custom())
+                        .map(|(kind, w)| (kind.name(), serialize_weight(w)))
+                        .collect::<BTreeMap<String, String>>();
+
                     b.push_bind::<String>(sink.to_string())
                         .push_bind::<String>(source.to_string())
                         .push_bind(weight.get(&WeightKind::Epistemic).map(serialize_weight))
                         .push_bind(weight.get(&WeightKind::Section).map(serialize_weight))
-                        .push_bind(weight.get(&WeightKind::Pragmatic).map(serialize_weight));
+                        .push_bind(weight.get(&WeightKind::Pragmatic).map(serialize_weight))
+                        .push_bind(
+                            (!custom.is_empty())
+                                .then(|| serde_json::to_string(&custom).unwrap_or_default()),
+                        );
                 },
             );
             self.qb.push("; ");
+            // Record the registered kinds, so a process opening the cache can read them back
+            // without loading the schemas that declared them.
+            for kind in weight_set.weights.keys().filter(|kind| kind.is_custom()) {
+                self.qb
+                    .push("INSERT OR IGNORE INTO relation_kinds (name) VALUES (");
+                self.qb.push_bind(kind.name());
+                self.qb.push("); ");
+            }
             self.staged += 1;
         }
     }
@@ -414,6 +441,138 @@ impl DbConnection {
         Ok(results)
     }
 
+    /// The BIDs within `depth` hops of `frontier`, or reachable from it at all when `depth` is
+    /// `None`, following relations of any of the `weights` kinds against (`Incoming`) or along
+    /// (`Outgoing`) their direction. The whole walk is one recursive query, rather than a round
+    /// trip per hop.
+    #[tracing::instrument(skip(self))]
+    async fn walk_relations(
+        &self,
+        frontier: &[Bid],
+        weights: Option<&WeightSet>,
+        direction: Direction,
+        depth: Option<usize>,
+    ) -> Result<Vec<Bid>, BuildonomyError> {
+        let (from, to) = match direction {
+            Direction::Incoming => ("sink", "source"),
+            Direction::Outgoing => ("source", "sink"),
+        };
+        let mut qb = QueryBuilder::<Sqlite>::new(match depth {
+            Some(_) => "WITH RECURSIVE walk(bid, depth) AS (SELECT value, 0 FROM json_each(",
+            // Without a depth, UNION drops the BIDs already walked, so cycles end the walk.
+            None => "WITH RECURSIVE walk(bid) AS (SELECT value FROM json_each(",
+        });
+        qb.push_bind(bids_json(frontier));
+        match depth {
+            Some(depth) => {
+                qb.push(format!(
+                    ") UNION SELECT relations.{to}, walk.depth + 1 FROM walk \
+                     JOIN relations ON relations.{from} = walk.bid AND walk.depth < "
+                ));
+                qb.push_bind(depth as i64);
+            }
+            None => {
+                qb.push(format!(
+                    ") UNION SELECT relations.{to} FROM walk \
+                     JOIN relations ON relations.{from} = walk.bid"
+                ));
+            }
+        }
+        if let Some(weights) = weights {
+            qb.push(format!(" AND ({})", kinds_sql(weights)));
+        }
+        qb.push(") SELECT DISTINCT bid FROM walk");
+        let walk_query = qb.build_query_scalar::<String>();
+        let walk_sql = walk_query.sql();
+        walk_query
+            .fetch_all(&self.0)
+            .await
+            .map_err(|e| {
+                tracing::error!(
+                    "[DbConnection.walk_relations] SQL error processing \
+                    walk_query '{}'\n\terror: {}",
+                    walk_sql,
+                    e
+                );
+                e
+            })?
+            .iter()
+            .map(|bid| Bid::try_from(bid.as_str()))
+            .collect()
+    }
+
+    /// The relations of any of the `weights` kinds into (`Incoming`) or out of (`Outgoing`) the
+    /// `bids`. For the BIDs [`walk_relations`](Self::walk_relations) reaches in that direction,
+    /// these are all the relations among them.
+    async fn walked_relations(
+        &self,
+        bids: &[Bid],
+        weights: &WeightSet,
+        direction: Direction,
+    ) -> Result<BidGraph, BuildonomyError> {
+        let end = match direction {
+            Direction::Incoming => "sink",
+            Direction::Outgoing => "source",
+        };
+        let mut qb = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT * FROM relations WHERE {end} IN (SELECT value FROM json_each("
+        ));
+        qb.push_bind(bids_json(bids));
+        qb.push(format!(")) AND ({})", kinds_sql(weights)));
+        let relations = qb
+            .build_query_as::<BeliefRelation>()
+            .fetch_all(&self.0)
+            .await?;
+        Ok(BidGraph::from_edges(relations))
+    }
+
+    /// The relations of the `weights` kinds among everything downstream of `from`, however far,
+    /// which is where the path searches look for their destination.
+    async fn downstream_relations(
+        &self,
+        from: Bid,
+        weights: &WeightSet,
+    ) -> Result<BidGraph, BuildonomyError> {
+        let downstream = self
+            .walk_relations(&[from], Some(weights), Direction::Outgoing, None)
+            .await?;
+        self.walked_relations(&downstream, weights, Direction::Outgoing)
+            .await
+    }
+
+    /// `paths` through `relations` as a graph of just those paths, loading only the states on
+    /// them.
+    async fn path_graph(
+        &self,
+        paths: &[Vec<Bid>],
+        relations: &BidGraph,
+        weights: &WeightSet,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        if paths.is_empty() {
+            return Ok(BeliefGraph::default());
+        }
+        let path_bids = paths
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let mut qb = QueryBuilder::<Sqlite>::new(
+            "SELECT * FROM beliefs WHERE bid IN (SELECT value FROM json_each(",
+        );
+        qb.push_bind(bids_json(&path_bids));
+        qb.push("))");
+        let states = qb
+            .build_query_as::<BeliefNode>()
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(|s| (s.bid, s))
+            .collect::<BTreeMap<Bid, BeliefNode>>();
+        Ok(BeliefGraph::from_paths(paths, &states, relations, weights))
+    }
+
     pub async fn is_db_balanced(&self) -> Result<(), BuildonomyError> {
         let mut qb = QueryBuilder::<Sqlite>::new("SELECT * FROM beliefs;");
         let state_query = qb.build_query_as::<BeliefNode>();
@@ -466,6 +625,30 @@ impl DbConnection {
     }
 }
 
+/// `bids` as one JSON array, to bind as a single variable and read back with `json_each`
+/// however many there are.
+fn bids_json(bids: &[Bid]) -> String {
+    serde_json::Value::from(
+        bids.iter()
+            .map(|bid| bid.to_string())
+            .collect::<Vec<String>>(),
+    )
+    .to_string()
+}
+
+/// A condition holding for `relations` rows with a weight of any of the `weights` kinds, which no
+/// row meets when `weights` is empty.
+fn kinds_sql(weights: &WeightSet) -> String {
+    if weights.is_empty() {
+        return "0".to_string();
+    }
+    weights
+        .into_iter()
+        .map(|(kind, _)| format!("{} IS NOT NULL", kind.sql_column()))
+        .collect::<Vec<String>>()
+        .join(" OR ")
+}
+
 fn get_all_paths(
     pool: Pool<Sqlite>,
     network_bid: Bid,
@@ -708,8 +891,7 @@ impl BeliefSource for DbConnection {
                     .join(", ");
                 let mut kind_q = Vec::<String>::new();
                 for (kind, _) in weight_filter {
-                    let column_name = format!("{kind:?}").to_lowercase();
-                    kind_q.push(format!("{column_name} IS NOT NULL",));
+                    kind_q.push(format!("{} IS NOT NULL", kind.sql_column()));
                 }
                 let mut qb = QueryBuilder::new(&format!(
                     "SELECT * FROM relations WHERE source IN ({}) AND {};",
@@ -763,6 +945,88 @@ impl BeliefSource for DbConnection {
         Ok(BeliefGraph { states, relations })
     }
 
+    /// Walks the same neighbourhood as the default implementation, but finds each direction's
+    /// nodes with a single [`DbConnection::walk_relations`] query and loads them all at once.
+    async fn eval_query(
+        &self,
+        query: &Query,
+        all_or_none: bool,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        let mut bg = self.eval_unbalanced(&query.seed).await?;
+        if all_or_none && bg.states.is_empty() {
+            return Ok(BeliefGraph::default());
+        }
+
+        if let Some(ref neighbor_walk) = query.traverse {
+            let mut walked_set: Option<BeliefGraph> = None;
+            for (walk_expr, direction, hops) in [
+                (
+                    bg.build_upstream_expr(neighbor_walk.filter.clone()),
+                    Direction::Incoming,
+                    cmp::min(MAX_TRAVERSAL, neighbor_walk.upstream),
+                ),
+                (
+                    bg.build_downstream_expr(neighbor_walk.filter.clone()),
+                    Direction::Outgoing,
+                    cmp::min(MAX_TRAVERSAL, neighbor_walk.downstream),
+                ),
+            ] {
+                // Each hop loads the current frontier, so the frontier itself is the first hop.
+                let Some(Expression::StateIn(StatePred::Bid(frontier))) = walk_expr else {
+                    continue;
+                };
+                if hops == 0 {
+                    continue;
+                }
+                let reached = self
+                    .walk_relations(
+                        &frontier,
+                        neighbor_walk.filter.as_ref(),
+                        direction,
+                        Some(hops as usize - 1),
+                    )
+                    .await?;
+                let walk_set = self
+                    .eval_unbalanced(&Expression::StateIn(StatePred::Bid(reached)))
+                    .await?;
+                walked_set
+                    .get_or_insert_with(|| bg.clone())
+                    .union_mut_with_trace(&walk_set);
+            }
+            if let Some(walked_set) = walked_set {
+                bg = walked_set;
+            }
+        }
+
+        if !bg.states.is_empty() {
+            self.balance(&mut bg).await?;
+        }
+        Ok(bg)
+    }
+
+    /// Loads the Section subtree below the set's downstream frontier in one walk instead of a
+    /// query per level, to the same `BALANCE_CUTOFF` depth.
+    async fn balance<'a>(&'a self, set: &'a mut BeliefGraph) -> Result<(), BuildonomyError> {
+        let Some(Expression::StateIn(StatePred::Bid(frontier))) = set.build_downstream_expr(None)
+        else {
+            return Ok(());
+        };
+        let section = WeightSet::from(WeightKind::Section);
+        let reached = self
+            .walk_relations(
+                &frontier,
+                Some(&section),
+                Direction::Outgoing,
+                Some(BALANCE_CUTOFF),
+            )
+            .await?;
+        let balance_set = self
+            .eval_trace(&Expression::StateIn(StatePred::Bid(reached)), section)
+            .await?;
+        set.union_mut(&balance_set);
+        Ok(())
+    }
+
     async fn get_all_paths(
         &self,
         network_bid: Bid,
@@ -777,6 +1041,88 @@ impl BeliefSource for DbConnection {
         .await
     }
 
+    async fn order_bids(
+        &self,
+        bids: &[Bid],
+        order_by: &[OrderBy],
+    ) -> Result<Vec<Bid>, BuildonomyError> {
+        if bids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut reading = serde_json::Map::new();
+        if order_by
+            .iter()
+            .any(|order| order.key == OrderKey::Prerequisites)
+        {
+            // Everything upstream of the bids, however far, as BeliefBase::ordered_bids reads it.
+            let epistemic = WeightSet::from(WeightKind::Epistemic);
+            let upstream = self
+                .walk_relations(bids, Some(&epistemic), Direction::Incoming, None)
+                .await?;
+            let prerequisites = self
+                .walked_relations(&upstream, &epistemic, Direction::Incoming)
+                .await?;
+            for (bid, position) in reading_positions(&prerequisites, bids) {
+                reading.insert(bid.to_string(), position.into());
+            }
+        }
+        let mut qb = QueryBuilder::new(
+            "SELECT bid FROM beliefs LEFT JOIN \
+             (SELECT target, MIN(sort_key) AS sort_key FROM paths GROUP BY target) \
+             ON target = bid LEFT JOIN \
+             (SELECT key AS reading_bid, value AS reading_position FROM json_each(",
+        );
+        qb.push_bind(serde_json::Value::Object(reading).to_string());
+        qb.push(")) ON reading_bid = bid WHERE bid IN (SELECT value FROM json_each(");
+        qb.push_bind(bids_json(bids));
+        qb.push("))");
+        push_order_by(&mut qb, order_by);
+        let mut order = qb
+            .build_query_scalar::<String>()
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|bid| Bid::try_from(bid.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Nodes the query found but the cache doesn't hold still belong on some page.
+        let found = order.iter().copied().collect::<BTreeSet<_>>();
+        order.extend(bids.iter().filter(|bid| !found.contains(bid)));
+        Ok(order)
+    }
+
+    async fn eval_shortest_path(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        let relations = self.downstream_relations(from, &weights).await?;
+        let paths = Vec::from_iter(relations.shortest_path(from, to, &weights));
+        self.path_graph(&paths, &relations, &weights).await
+    }
+
+    async fn eval_simple_paths(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+        max_hops: u8,
+    ) -> Result<BeliefGraph, BuildonomyError> {
+        let relations = self.downstream_relations(from, &weights).await?;
+        let paths = relations.simple_paths(from, to, &weights, max_hops as usize);
+        self.path_graph(&paths, &relations, &weights).await
+    }
+
+    async fn eval_reachable(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> Result<bool, BuildonomyError> {
+        let relations = self.downstream_relations(from, &weights).await?;
+        Ok(relations.is_reachable(from, to, &weights))
+    }
+
     async fn export_beliefgraph(&self) -> Result<BeliefGraph, BuildonomyError> {
         // Get all states from database
         let state_query = sqlx::query_as::<_, BeliefNode>("SELECT * FROM beliefs");
@@ -855,25 +1201,15 @@ pub async fn db_init(db_path: PathBuf) -> Result<Pool<Sqlite>, sqlx::Error> {
     if !Sqlite::database_exists(&fqdb).await.unwrap_or(false) {
         Sqlite::create_database(&fqdb).await?;
     }
+    // REGEXP backs the payload regex predicate
     let options = SqliteConnectOptions::from_str(&fqdb)?
         .read_only(false)
         .disable_statement_logging()
-        .create_if_missing(true);
+        .create_if_missing(true)
+        .with_regexp();
 
-    // Use PoolOptions with after_connect to register regexp on each connection
     use sqlx::pool::PoolOptions;
-    let pool = PoolOptions::<Sqlite>::new()
-        .after_connect(|conn, _meta| {
-            Box::pin(async move {
-                // Register the regexp function for this connection
-                sqlx::query("SELECT sqlite_compileoption_used('ENABLE_DBSTAT_VTAB')")
-                    .execute(&mut *conn)
-                    .await?;
-                Ok(())
-            })
-        })
-        .connect_with(options)
-        .await?;
+    let pool = PoolOptions::<Sqlite>::new().connect_with(options).await?;
 
     let migrations = MigrationList(vec![
         // Define your migrations here
@@ -886,11 +1222,41 @@ pub async fn db_init(db_path: PathBuf) -> Result<Pool<Sqlite>, sqlx::Error> {
             CREATE TABLE paths (net TEXT, path TEXT, target TEXT, ordering TEXT, UNIQUE(net, path)); \
             CREATE TABLE file_mtimes (path TEXT PRIMARY KEY, mtime INTEGER NOT NULL);",
             kind: MigrationType::ReversibleUp,
-        }
+        },
+        Migration {
+            version: 2,
+            description: "add_custom_relation_kinds",
+            sql: "ALTER TABLE relations ADD COLUMN custom TEXT; \
+            CREATE TABLE relation_kinds (name TEXT PRIMARY KEY);",
+            kind: MigrationType::ReversibleUp,
+        },
+        Migration {
+            version: 3,
+            description: "add_payload_json",
+            // Forgetting the file mtimes reparses every file, filling in the new column.
+            sql: "ALTER TABLE beliefs ADD COLUMN payload_json TEXT; DELETE FROM file_mtimes;",
+            kind: MigrationType::ReversibleUp,
+        },
+        Migration {
+            version: 4,
+            description: "add_path_sort_key",
+            sql: "ALTER TABLE paths ADD COLUMN sort_key TEXT; DELETE FROM file_mtimes;",
+            kind: MigrationType::ReversibleUp,
+        },
     ]);
     let migrator = Migrator::new(migrations.clone()).await?;
     migrator.run(&pool).await?;
 
+    // Relations of registered kinds can only be read once their kinds are registered.
+    let kinds = sqlx::query_scalar::<_, String>("SELECT name FROM relation_kinds;")
+        .fetch_all(&pool)
+        .await?;
+    for name in kinds {
+        if let Err(e) = WeightKind::register(&name) {
+            tracing::warn!("[db_init] Cannot register cached relation kind '{name}': {e}");
+        }
+    }
+
     let count_res = sqlx::query("SELECT COUNT(*) as bcount FROM beliefs;")
         .fetch_one(&pool)
         .await?;
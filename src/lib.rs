@@ -16,7 +16,7 @@
 //! - **Multi-pass compilation**: Diagnostic-driven resolution of forward references and circular dependencies
 //! - **Stable identifiers**: Automatically injects unique BIDs (Belief IDs) into source documents
 //! - **Bidirectional sync**: Changes flow from documents to graph *and* from graph back to documents
-//! - **Multi-format support**: Extensible codec system (Markdown, TOML, custom formats)
+//! - **Multi-format support**: Extensible codec system (Markdown, TOML, JSON, YAML, Org-mode, Jupyter, Rust doc comments, custom formats)
 //! - **Error tolerance**: Graceful handling of parse errors via diagnostic system
 //! - **Hypergraph relationships**: Rich semantic relationships with typed edges and custom payloads
 //! - **Nested networks**: Hierarchical network dependencies similar to git submodules
@@ -180,7 +180,8 @@
 //!
 //! The BeliefBase is a typed, weighted, directed hypergraph where:
 //! - **Nodes** are `BeliefNode` instances (documents, sections, custom entities)
-//! - **Edges** are typed relationships (`WeightKind`: Subsection, Epistemic, Pragmatic)
+//! - **Edges** are typed relationships (`WeightKind`: Subsection, Epistemic, Pragmatic, or a kind
+//!   the application registers with `WeightKind::register`)
 //! - Each edge can carry custom metadata in its `payload`
 //!
 //! ### Diagnostic-Driven Resolution
@@ -249,6 +250,8 @@ pub mod nodekey;
 pub mod paths;
 pub mod properties;
 pub mod query;
+#[cfg(all(feature = "service", not(target_arch = "wasm32")))]
+pub mod rpc;
 pub mod shard;
 #[cfg(test)]
 mod tests;
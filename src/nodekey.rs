@@ -412,7 +412,10 @@ impl NodeKey {
     }
 
     /// Resolve a network reference string to a BID using a [crate::beliefbase::BeliefBase] (sync).
-    fn resolve_network_sync(network_ref: &str, cache: &BeliefBase) -> Result<Bid, BuildonomyError> {
+    pub(crate) fn resolve_network_sync(
+        network_ref: &str,
+        cache: &BeliefBase,
+    ) -> Result<Bid, BuildonomyError> {
         // Try parsing as Bref first
         if let Ok(bref) = Bref::try_from(network_ref) {
             // Search states for a node with this namespace
@@ -612,7 +615,7 @@ impl FromStr for NodeKey {
 
                 let norm_input = value_str.strip_prefix('/').unwrap_or(value_str);
                 let norm_ap = AnchorPath::new(norm_input);
-                let norm_path: String = norm_ap.normalize().into();
+                let norm_path = strip_block_marker(norm_ap.normalize().into());
                 if norm_path.is_empty() {
                     return Err(BuildonomyError::Serialization(format!(
                         "[Nodekey] cannot generate a nodekey from an empty string. \
@@ -647,7 +650,9 @@ impl FromStr for NodeKey {
             }
             NodeKeyScheme::Id => {
                 let (net, value_str) = Self::parse_network_and_value(s, &ap, &scheme)?;
-                let id = to_anchor(&value_str);
+                // Rust item paths (`id:codec::compiler::DocumentCompiler`) use `.` separators,
+                // matching the ids RustDocCodec assigns to items.
+                let id = to_anchor(&value_str.replace("::", "."));
                 Ok(NodeKey::Id { net, id })
             }
             NodeKeyScheme::Path => {
@@ -655,7 +660,7 @@ impl FromStr for NodeKey {
                 let mut path_net = net;
                 let norm_input = value_str.strip_prefix('/').unwrap_or(&value_str);
                 let norm_ap = AnchorPath::new(norm_input);
-                let norm_path: String = norm_ap.normalize().into();
+                let norm_path = strip_block_marker(norm_ap.normalize().into());
                 // Only reclassify as asset when the path has a file extension that
                 // no codec recognises. Anchor-only paths (#section) and extensionless
                 // paths (directories, Gemfile-style names) stay in the document net.
@@ -671,6 +676,14 @@ impl FromStr for NodeKey {
     }
 }
 
+/// Obsidian links a block as `doc.md#^block-id`; the block's anchor is `block-id`.
+fn strip_block_marker(path: String) -> String {
+    match path.split_once("#^") {
+        Some((doc, anchor)) => format!("{doc}#{anchor}"),
+        None => path,
+    }
+}
+
 /// When parsing links, the net should be chosen based on how well the link str lines up with the
 /// stack. If it starts_with, it should chose the most specific of the start_with set. If it
 /// contains relative back-links, back link through the stack to find the proper path network, and
@@ -727,6 +740,17 @@ mod tests {
         assert!(matches!(key, NodeKey::Id { net, id }
         if net == network_bid && id == "supremum"));
 
+        // Rust item paths normalize `::` to `.`
+        let key: NodeKey = "id:codec::compiler::DocumentCompiler".parse().unwrap();
+        assert!(matches!(key, NodeKey::Id { net, id }
+        if net == Bref::default() && id == "codec.compiler.documentcompiler"));
+
+        // Obsidian block references address the block's anchor
+        let key: NodeKey = "notes/doc.md#^claim-1".parse().unwrap();
+        assert!(matches!(key, NodeKey::Path { path, .. } if path == "notes/doc.md#claim-1"));
+        let key: NodeKey = "#^claim-1".parse().unwrap();
+        assert!(matches!(key, NodeKey::Path { path, .. } if path == "#claim-1"));
+
         // Non-hierarchical path: path:docs/README.md (implicit network)
         let result: Result<NodeKey, _> = "path:docs/README.md".parse();
         assert_eq!(
@@ -1042,19 +1066,27 @@ mod tests {
                 path: "net/.hidden#achor".to_string()
             })
         );
-        // .toml files are now treated as assets (not BeliefNetwork documents)
+        // Files without a registered codec are treated as assets (not BeliefNetwork documents)
         assert_eq!(
-            "net/dir/file.toml".parse::<NodeKey>(),
+            "net/dir/file.csv".parse::<NodeKey>(),
             Ok(NodeKey::Path {
                 net: asset_namespace().bref(),
-                path: "net/dir/file.toml".to_string()
+                path: "net/dir/file.csv".to_string()
             })
         );
         assert_eq!(
-            "file.toml".parse::<NodeKey>(),
+            "file.csv".parse::<NodeKey>(),
             Ok(NodeKey::Path {
                 net: asset_namespace().bref(),
-                path: "file.toml".to_string()
+                path: "file.csv".to_string()
+            })
+        );
+        // .toml files have a codec (TomlCodec), so they are BeliefNetwork documents
+        assert_eq!(
+            "net/dir/file.toml".parse::<NodeKey>(),
+            Ok(NodeKey::Path {
+                net: default_bref,
+                path: "net/dir/file.toml".to_string()
             })
         );
         // index.md files are BeliefNetwork documents (not assets)
@@ -1109,20 +1141,20 @@ mod tests {
         );
 
         // Test path strings with explicit network
-        // .toml files are assets, so they use asset_namespace regardless of specified network
+        // .csv files are assets, so they use asset_namespace regardless of specified network
         let asset_bref = asset_namespace().bref();
         assert_eq!(
-            format!("{net_bref}/file.toml").parse::<NodeKey>(),
+            format!("{net_bref}/file.csv").parse::<NodeKey>(),
             Ok(NodeKey::Path {
                 net: asset_bref,
-                path: "file.toml".to_string()
+                path: "file.csv".to_string()
             })
         );
         assert_eq!(
-            format!("{net_bid}/file.toml").parse::<NodeKey>(),
+            format!("{net_bid}/file.csv").parse::<NodeKey>(),
             Ok(NodeKey::Path {
                 net: asset_bref,
-                path: "file.toml".to_string()
+                path: "file.csv".to_string()
             })
         );
 
@@ -247,6 +247,7 @@ pub struct PathMapMap {
     apis: BTreeSet<Bid>,
     titles: BTreeMap<Bid, String>,
     ids: BTreeMap<Bid, String>,
+    aliases: BTreeMap<Bid, Vec<String>>,
     relations: Arc<RwLock<BidGraph>>,
 }
 
@@ -266,6 +267,7 @@ impl Default for PathMapMap {
             apis: BTreeSet::default(),
             titles: BTreeMap::default(),
             ids: BTreeMap::default(),
+            aliases: BTreeMap::default(),
             relations: relations.clone(),
         };
         let api_pm = PathMap::new(WeightKind::Section, root, &pmm, relations);
@@ -326,6 +328,7 @@ impl PathMapMap {
         for node in states.values() {
             pmm.titles.insert(node.bid, node.title.clone());
             pmm.ids.insert(node.bid, node.id());
+            pmm.set_aliases(node);
             if node.kind.contains(BeliefKind::API) {
                 pmm.apis.insert(node.bid);
             }
@@ -346,6 +349,9 @@ impl PathMapMap {
         let href_node = BeliefNode::href_network();
         pmm.nets.insert(href_node.bid);
         pmm.titles.insert(href_node.bid, href_node.title.clone());
+        let tag_node = BeliefNode::tag_network();
+        pmm.nets.insert(tag_node.bid);
+        pmm.titles.insert(tag_node.bid, tag_node.title.clone());
 
         // Check for states vs relations mismatch
         let states_bids: std::collections::BTreeSet<_> = states.keys().copied().collect();
@@ -418,6 +424,15 @@ impl PathMapMap {
         &self.titles
     }
 
+    fn set_aliases(&mut self, node: &BeliefNode) {
+        let aliases = node.aliases();
+        if aliases.is_empty() {
+            self.aliases.remove(&node.bid);
+        } else {
+            self.aliases.insert(node.bid, aliases);
+        }
+    }
+
     pub fn is_anchor(&self, bid: &Bid) -> bool {
         !self.docs.contains(bid)
     }
@@ -461,6 +476,16 @@ impl PathMapMap {
             .and_then(|pm| pm.get_from_title(path.as_ref(), self))
     }
 
+    pub fn net_get_from_alias(&self, net: &Bref, alias: &str) -> Option<(Bid, Bid)> {
+        let normalized_net = if *net == Bref::default() {
+            &self.root.bref()
+        } else {
+            net
+        };
+        self.get_map(normalized_net)
+            .and_then(|pm| pm.get_from_alias(alias, self))
+    }
+
     pub fn net_get_from_id(&self, net: &Bref, path: &str) -> Option<(Bid, Bid)> {
         let normalized_net = if *net == Bref::default() {
             &self.root.bref()
@@ -603,6 +628,7 @@ impl PathMapMap {
                     if let Ok(node) = BeliefNode::try_from(&toml_str[..]) {
                         self.titles.insert(node.bid, node.title.clone());
                         self.ids.insert(node.bid, node.id());
+                        self.set_aliases(&node);
                         if node.kind.contains(BeliefKind::API) {
                             self.apis.insert(node.bid);
                         }
@@ -707,6 +733,7 @@ impl PathMapMap {
         for bid in bids {
             self.nets.remove(bid);
             self.ids.remove(bid);
+            self.aliases.remove(bid);
             self.docs.remove(bid);
             self.titles.remove(bid);
             self.map.remove(&bid.bref());
@@ -720,6 +747,9 @@ impl PathMapMap {
         if let Some(key) = self.ids.remove(from) {
             self.ids.insert(*to, key);
         }
+        if let Some(aliases) = self.aliases.remove(from) {
+            self.aliases.insert(*to, aliases);
+        }
         if self.docs.remove(from) {
             self.docs.insert(*to);
         }
@@ -775,6 +805,9 @@ pub struct PathMap {
     order_map: BTreeMap<String, usize>,
     id_map: IdMap,
     title_map: IdMap,
+    /// Anchored document aliases (see [BeliefNode::aliases]). Unlike ids, several aliases may map
+    /// to one bid.
+    alias_map: BTreeMap<String, Bid>,
     kind: WeightKind,
     net: Bid,
     subnets: BTreeSet<Bid>,
@@ -1022,15 +1055,19 @@ impl PathMap {
 
         let mut id_map = IdMap::default();
         let mut title_map = IdMap::default();
+        let mut alias_map = BTreeMap::new();
         for (_, bid, _) in map.iter() {
             if let Some(title) = nets.titles().get(bid) {
                 if !nets.is_anchor(bid) && !to_anchor(title).is_empty() {
-                    title_map.insert(title.clone(), *bid);
+                    title_map.insert(to_anchor(title), *bid);
                 }
             }
             if let Some(id) = nets.ids.get(bid) {
                 id_map.insert(id.clone(), *bid);
             }
+            for alias in nets.aliases.get(bid).into_iter().flatten() {
+                alias_map.insert(alias.clone(), *bid);
+            }
         }
         // tracing::debug!(
         //     "Initialized pathmap for {}, contains {} paths and subnets: {:?}",
@@ -1045,6 +1082,7 @@ impl PathMap {
             order_map,
             id_map,
             title_map,
+            alias_map,
             kind,
             net,
             subnets,
@@ -1150,6 +1188,19 @@ impl PathMap {
             })
     }
 
+    /// Returns the net and doc bid that lists the input (anchored) alias in its `aliases`
+    pub fn get_from_alias(&self, alias: &str, nets: &PathMapMap) -> Option<(Bid, Bid)> {
+        self.alias_map
+            .get(alias)
+            .map(|bid| (self.net, *bid))
+            .or_else(|| {
+                self.subnets.iter().find_map(|net_bid| {
+                    nets.get_map(&net_bid.bref())
+                        .and_then(|subnet_path_map| subnet_path_map.get_from_alias(alias, nets))
+                })
+            })
+    }
+
     /// Returns the net and bid that matches the input node id
     pub fn get_from_id(&self, id: &str, nets: &PathMapMap) -> Option<(Bid, Bid)> {
         self.id_map
@@ -1470,6 +1521,9 @@ impl PathMap {
                 if let Some(title) = self.title_map.remove(from) {
                     self.title_map.insert(title, *to);
                 }
+                for bid in self.alias_map.values_mut().filter(|bid| **bid == *from) {
+                    *bid = *to;
+                }
                 if self.subnets.remove(from) {
                     self.subnets.insert(*to);
                 }
@@ -1841,12 +1895,15 @@ impl PathMap {
                 if let Some(source_title) = nets.titles.get(source) {
                     if !nets.is_anchor(source) && !to_anchor(source_title).is_empty() {
                         // We only get title anchors if the title anchor is non-empty
-                        self.title_map.insert(source_title.clone(), *source);
+                        self.title_map.insert(to_anchor(source_title), *source);
                     }
                 }
                 if let Some(id_str) = nets.ids.get(source) {
                     self.id_map.insert(id_str.clone(), *source);
                 }
+                for alias in nets.aliases.get(source).into_iter().flatten() {
+                    self.alias_map.insert(alias.clone(), *source);
+                }
             }
         }
         derivatives
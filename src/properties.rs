@@ -14,6 +14,9 @@ use std::{
 };
 use toml::{from_str, to_string, value::Table, Value};
 
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
 pub use uuid::Uuid;
 // Use `Uuid` as a custom type, with `String` as the Builtin
 uniffi::custom_type!(Uuid, String, {
@@ -106,6 +109,12 @@ pub const UUID_NAMESPACE_ASSET: Uuid = Uuid::from_bytes([
     0x4b, 0x3d, 0x21, 0x54, 0xc0, 0xa9, 0x43, 0x7b, 0x93, 0x24, 0x5f, 0x62, 0xad, 0xeb, 0x9a, 0x44,
 ]);
 
+/// The 'tag' namespace UUID. This is used to create a universal network location for tracking
+/// the `#tags` used within source documents.
+pub const UUID_NAMESPACE_TAG: Uuid = Uuid::from_bytes([
+    0x3b, 0x3d, 0x21, 0x54, 0xc0, 0xa9, 0x43, 0x7b, 0x93, 0x24, 0x5f, 0x62, 0xad, 0xeb, 0x9a, 0x44,
+]);
+
 #[uniffi::export]
 pub fn buildonomy_namespace() -> Bid {
     Bid::from(UUID_NAMESPACE_BUILDONOMY)
@@ -121,10 +130,20 @@ pub fn asset_namespace() -> Bid {
     Bid::from(UUID_NAMESPACE_ASSET)
 }
 
+#[uniffi::export]
+pub fn tag_namespace() -> Bid {
+    Bid::from(UUID_NAMESPACE_TAG)
+}
+
 /// All reserved/const namespaces. Used by `is_reserved()` and anywhere
 /// the full set of system namespaces is needed.
-pub fn const_namespaces() -> [Bid; 3] {
-    [buildonomy_namespace(), href_namespace(), asset_namespace()]
+pub fn const_namespaces() -> [Bid; 4] {
+    [
+        buildonomy_namespace(),
+        href_namespace(),
+        asset_namespace(),
+        tag_namespace(),
+    ]
 }
 
 /// Namespaces that track external content anchored to the parsed repo (hrefs, assets).
@@ -596,6 +615,18 @@ pub const WEIGHT_DOC_PATHS: &str = "doc_paths";
 /// Only present when the link text differs from the target node's title.
 pub const WEIGHT_LINK_TITLE: &str = "title";
 
+/// Key for storing the target of a wikilink (`[[Note#Heading]]`) as written, in Weight payload.
+/// Marks relations parsed from wikilinks so codecs can resolve them without rewriting the source.
+pub const WEIGHT_WIKILINK: &str = "wikilink";
+
+/// Key marking a wikilink embed (`![[Note]]`) in Weight payload. Embeds transclude the target's
+/// content when rendered, rather than linking to it.
+pub const WEIGHT_EMBED: &str = "embed";
+
+/// Key storing the `#tag` (normalized, without the leading `#`) in Weight payload. Marks the
+/// relation from a tag node to the document or section it tags.
+pub const WEIGHT_TAG: &str = "tag";
+
 impl Weight {
     pub fn full() -> Weight {
         let mut weight = Weight {
@@ -702,15 +733,43 @@ impl Ord for Weight {
 /// - For Subsection edges: section numbering, heading text
 ///
 /// This separation enables clean separation of graph algorithms from domain semantics.
-#[derive(
-    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, uniffi::Enum,
-)]
+///
+/// Beyond the three built-in kinds, applications can register their own relation kinds (e.g.
+/// `supersedes`, `implements`) at runtime with [WeightKind::register]. A registered kind is
+/// identified by its name wherever it is serialized; the [WeightKind::Custom] index is only
+/// meaningful within the running process. Kinds are registered by the schemas that declare them
+/// or explicitly by the application, never by the data that uses them: deserializing an unknown
+/// name is an error, so a process reading relations written by another (a shard, a query) must
+/// register the same kinds first. The [WeightKind::LINK_KINDS] are registered in every process,
+/// and opening the database cache registers the kinds its relations use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum WeightKind {
-    Epistemic, // Knowledge dependencies
-    Section,   // Document structure
-    Pragmatic, // Action/being relationships
+    Epistemic,   // Knowledge dependencies
+    Section,     // Document structure
+    Pragmatic,   // Action/being relationships
+    Custom(u16), // Application-defined, indexes the relation kind registry
 }
 
+/// Names of the registered application-defined relation kinds, indexed by [WeightKind::Custom].
+/// Starts out holding the [WeightKind::LINK_KINDS].
+static RELATION_KINDS: Lazy<RwLock<Vec<String>>> = Lazy::new(|| {
+    RwLock::new(
+        WeightKind::LINK_KINDS
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    )
+});
+
+uniffi::custom_type!(WeightKind, String, {
+    try_lift: |val: String| -> Result<WeightKind, BuildonomyError> {
+        Ok(WeightKind::try_from(val.as_str())?)
+    },
+    lower: |obj: WeightKind| -> String {
+        obj.name()
+    },
+});
+
 /// [PragmaticKind] defines semantic kinds for Pragmatic edges in the intention lattice.
 /// Multiple kinds can be true simultaneously (stored as EnumSet in Weight.payload).
 ///
@@ -741,28 +800,121 @@ pub enum MotivationDimension {
 }
 
 impl WeightKind {
-    pub fn all() -> &'static [WeightKind] {
-        &[
-            WeightKind::Epistemic,
-            WeightKind::Section,
-            WeightKind::Pragmatic,
-        ]
+    const BUILTIN: [WeightKind; 3] = [
+        WeightKind::Epistemic,
+        WeightKind::Section,
+        WeightKind::Pragmatic,
+    ];
+
+    /// Relation kinds every process registers up front, so documents can declare them by link
+    /// title alone (e.g. `[claim](other.md "supports")`) without depending on a schema.
+    pub const LINK_KINDS: [&'static str; 4] = ["supports", "contradicts", "refutes", "elaborates"];
+
+    /// The built-in kinds followed by every registered application-defined kind.
+    pub fn all() -> Vec<WeightKind> {
+        let count = RELATION_KINDS.read().len() as u16;
+        Self::BUILTIN
+            .into_iter()
+            .chain((0..count).map(WeightKind::Custom))
+            .collect()
+    }
+
+    /// Register an application-defined relation kind, returning the existing kind when `name`
+    /// is already registered. Names must start with a letter and contain only ASCII letters,
+    /// digits, `_` and `-`, and may not shadow a built-in kind.
+    pub fn register(name: &str) -> Result<WeightKind, BuildonomyError> {
+        if Self::builtin(name).is_some() {
+            return Err(BuildonomyError::Custom(format!(
+                "Relation kind '{name}' is built in and cannot be registered"
+            )));
+        }
+        let valid_start = name.starts_with(|c: char| c.is_ascii_alphabetic());
+        let valid_chars = name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_start || !valid_chars {
+            return Err(BuildonomyError::Custom(format!(
+                "Invalid relation kind name '{name}'. Names start with a letter and contain only \
+                 ASCII letters, digits, '_' and '-'"
+            )));
+        }
+        let mut kinds = RELATION_KINDS.write();
+        if let Some(idx) = kinds.iter().position(|kind| kind == name) {
+            return Ok(WeightKind::Custom(idx as u16));
+        }
+        let idx = u16::try_from(kinds.len()).map_err(|_| {
+            BuildonomyError::Custom(format!(
+                "Cannot register relation kind '{name}': registry is full"
+            ))
+        })?;
+        kinds.push(name.to_string());
+        Ok(WeightKind::Custom(idx))
+    }
+
+    /// The kind's name as it is serialized: the variant name for built-in kinds, the registered
+    /// name for application-defined kinds.
+    pub fn name(&self) -> String {
+        match self {
+            WeightKind::Epistemic => "Epistemic".to_string(),
+            WeightKind::Section => "Section".to_string(),
+            WeightKind::Pragmatic => "Pragmatic".to_string(),
+            WeightKind::Custom(idx) => RELATION_KINDS
+                .read()
+                .get(*idx as usize)
+                .cloned()
+                .unwrap_or_else(|| format!("custom-{idx}")),
+        }
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, WeightKind::Custom(_))
+    }
+
+    fn builtin(name: &str) -> Option<WeightKind> {
+        match &name.to_lowercase()[..] {
+            "epistemic" => Some(WeightKind::Epistemic),
+            "section" | "subsection" => Some(WeightKind::Section),
+            "pragmatic" => Some(WeightKind::Pragmatic),
+            _ => None,
+        }
+    }
+
+    /// The SQL expression reading this kind's serialized weight from a `relations` row. Built-in
+    /// kinds have their own column; registered kinds are keyed by name in the `custom` JSON
+    /// column.
+    #[cfg(feature = "service")]
+    pub(crate) fn sql_column(&self) -> String {
+        match self {
+            WeightKind::Custom(_) => format!("json_extract(custom, '$.\"{}\"')", self.name()),
+            builtin => builtin.name().to_lowercase(),
+        }
     }
 }
 
 impl Display for WeightKind {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Serialize for WeightKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
     }
 }
 
+impl<'de> Deserialize<'de> for WeightKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        WeightKind::try_from(name.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Kinds are numbered densely: the built-in kinds take 0 to 2 and registered kinds follow in
+/// registration order.
 impl From<WeightKind> for u32 {
     fn from(src: WeightKind) -> u32 {
-        match src {
-            WeightKind::Epistemic => 0u32,
-            WeightKind::Section => u32::from(u16::MAX),
-            WeightKind::Pragmatic => 2 * u32::from(u16::MAX),
-        }
+        u32::from(&src)
     }
 }
 
@@ -770,8 +922,9 @@ impl From<&WeightKind> for u32 {
     fn from(src: &WeightKind) -> u32 {
         match src {
             WeightKind::Epistemic => 0u32,
-            WeightKind::Section => u32::from(u16::MAX),
-            WeightKind::Pragmatic => 2 * u32::from(u16::MAX),
+            WeightKind::Section => 1u32,
+            WeightKind::Pragmatic => 2u32,
+            WeightKind::Custom(idx) => 3 + u32::from(*idx),
         }
     }
 }
@@ -780,12 +933,19 @@ impl TryFrom<&str> for WeightKind {
     type Error = BuildonomyError;
 
     fn try_from(src: &str) -> Result<WeightKind, BuildonomyError> {
-        match &src.to_lowercase()[..] {
-            "epistemic" => Ok(WeightKind::Epistemic),
-            "subsection" => Ok(WeightKind::Section),
-            "pragmatic" => Ok(WeightKind::Pragmatic),
-            _ => Err(BuildonomyError::Custom(format!(
-                "Invalid str for WeightKind. Received {src}. Valid options: epistemic, subsection, pragmatic"
+        if let Some(kind) = WeightKind::builtin(src) {
+            return Ok(kind);
+        }
+        let kinds = RELATION_KINDS.read();
+        match kinds.iter().position(|kind| kind == src) {
+            Some(idx) => Ok(WeightKind::Custom(idx as u16)),
+            None => Err(BuildonomyError::Custom(format!(
+                "Invalid str for WeightKind. Received {src}. Valid options: epistemic, section, \
+                 pragmatic{}",
+                kinds
+                    .iter()
+                    .map(|kind| format!(", {kind}"))
+                    .collect::<String>()
             ))),
         }
     }
@@ -795,12 +955,15 @@ impl TryFrom<u32> for WeightKind {
     type Error = BuildonomyError;
 
     fn try_from(src: u32) -> Result<WeightKind, BuildonomyError> {
+        let count = RELATION_KINDS.read().len() as u32;
         match src {
-            0..=255 => Ok(WeightKind::Epistemic),
-            256..=511 => Ok(WeightKind::Section),
-            512..=767 => Ok(WeightKind::Pragmatic),
+            0 => Ok(WeightKind::Epistemic),
+            1 => Ok(WeightKind::Section),
+            2 => Ok(WeightKind::Pragmatic),
+            idx if idx < 3 + count => Ok(WeightKind::Custom((idx - 3) as u16)),
             _ => Err(BuildonomyError::Custom(format!(
-                "Invalid u32 for WeightKind. Max allowed value is 767. Received {src}"
+                "Invalid u32 for WeightKind. Max allowed value is {}. Received {src}",
+                2 + count
             ))),
         }
     }
@@ -886,11 +1049,12 @@ impl WeightSet {
         Self::default()
     }
 
+    /// A weight for every built-in and registered kind.
     pub fn full() -> Self {
-        let mut weights = BTreeMap::new();
-        weights.insert(WeightKind::Epistemic, Weight::full());
-        weights.insert(WeightKind::Section, Weight::full());
-        weights.insert(WeightKind::Pragmatic, Weight::full());
+        let weights = WeightKind::all()
+            .into_iter()
+            .map(|kind| (kind, Weight::full()))
+            .collect();
         Self { weights }
     }
 }
@@ -1048,6 +1212,42 @@ impl BeliefNode {
         }
     }
 
+    /// Creates a BeliefNode for the tag tracking network
+    pub fn tag_network() -> BeliefNode {
+        let mut table = Table::new();
+        table.insert(
+            "api".to_string(),
+            Value::String(buildonomy_namespace().to_string()),
+        );
+        BeliefNode {
+            bid: tag_namespace(),
+            title: format!(
+                "Buildonomy tag tracking network v{}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            schema: Some("api".to_string()),
+            payload: table,
+            // Tag network is always a Trace, no single document holds all of a tag's relations
+            kind: BeliefKindSet(BeliefKind::Network | BeliefKind::Trace),
+            id: Some("buildonomy_tag_network".to_string()),
+        }
+    }
+
+    /// A new node for the `#tag` (normalized, without the leading `#`) within the tag network.
+    ///
+    /// Tags are [BeliefKind::Symbol]s. They are also colored External so that, like href nodes,
+    /// they are addressed by their tag path rather than as anchors of a parent document.
+    pub fn tag(tag: &str) -> BeliefNode {
+        BeliefNode {
+            bid: Bid::new(tag_namespace()),
+            kind: BeliefKindSet(BeliefKind::Symbol | BeliefKind::External | BeliefKind::Trace),
+            title: tag.to_string(),
+            schema: None,
+            payload: Table::new(),
+            id: Some(tag.to_string()),
+        }
+    }
+
     pub fn unknown(bid: Bid) -> BeliefNode {
         BeliefNode {
             bid,
@@ -1082,6 +1282,21 @@ impl BeliefNode {
             })
     }
 
+    /// Anchored forms of the node's `aliases` payload entry (a string or a list of strings), as
+    /// written in Obsidian-style frontmatter. Aliases are alternate titles: they resolve
+    /// `NodeKey::Id` references that match no id, but are never used for node identity.
+    pub fn aliases(&self) -> Vec<String> {
+        let anchored = |alias: &str| Some(to_anchor(alias)).filter(|a| !a.is_empty());
+        match self.payload.get("aliases") {
+            Some(Value::String(alias)) => anchored(alias).into_iter().collect(),
+            Some(Value::Array(aliases)) => aliases
+                .iter()
+                .filter_map(|alias| alias.as_str().and_then(anchored))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Generate all valid hrefs per NodeKey::from_str parsing definition with optional namespace
     pub fn keys(
         &self,
@@ -1389,12 +1604,29 @@ impl FromRow<'_, SqliteRow> for BeliefRelation {
         let sink_str: &str = row.try_get("sink")?;
         let mut weights = BTreeMap::new();
 
-        for kind in WeightKind::all() {
-            let column_name = format!("{kind:?}").to_lowercase();
+        for kind in WeightKind::BUILTIN {
+            let column_name = kind.name().to_lowercase();
             // Try to get JSON string from column and deserialize as Weight
             if let Ok(Some(json_str)) = row.try_get::<Option<String>, &str>(&column_name) {
                 if let Ok(weight) = toml::from_str::<Weight>(&json_str) {
-                    weights.insert(*kind, weight);
+                    weights.insert(kind, weight);
+                }
+            }
+        }
+        // Registered kinds are stored together as a JSON object of name -> serialized weight.
+        if let Ok(Some(custom)) = row.try_get::<Option<String>, &str>("custom") {
+            let custom: BTreeMap<String, String> =
+                serde_json::from_str(&custom).unwrap_or_default();
+            for (name, weight_str) in custom {
+                let Ok(kind) = WeightKind::try_from(name.as_str()) else {
+                    tracing::warn!(
+                        "[BeliefRelation::from_row] Skipping a relation of kind '{name}', which \
+                         is not registered in this process"
+                    );
+                    continue;
+                };
+                if let Ok(weight) = toml::from_str::<Weight>(&weight_str) {
+                    weights.insert(kind, weight);
                 }
             }
         }
@@ -1556,6 +1788,41 @@ mod tests {
         assert!(parent_bid.is_parent_filter()(&child_bid));
     }
 
+    #[test]
+    fn test_registered_weight_kinds() {
+        let implements = WeightKind::register("implements").unwrap();
+        assert!(implements.is_custom());
+        assert_eq!(implements.to_string(), "implements");
+        assert_eq!(WeightKind::try_from("implements").unwrap(), implements);
+        assert_eq!(
+            WeightKind::try_from("subsection").unwrap(),
+            WeightKind::Section
+        );
+        assert!(WeightKind::all().contains(&implements));
+        assert!(WeightSet::full().get(&implements).is_some());
+
+        let idx = u32::from(implements);
+        assert!(idx >= 3);
+        assert_eq!(WeightKind::try_from(idx).unwrap(), implements);
+        assert_eq!(WeightKind::try_from(1u32).unwrap(), WeightKind::Section);
+
+        // Registered and built-in names deserialize to their kinds; unknown names are rejected
+        // rather than registered.
+        let ws: WeightSet = toml::from_str("[weights.implements]\n[weights.Epistemic]\n").unwrap();
+        assert!(ws.get(&implements).is_some());
+        assert!(ws.get(&WeightKind::Epistemic).is_some());
+        assert!(toml::to_string(&ws)
+            .unwrap()
+            .contains("[weights.implements]"));
+        assert!(toml::from_str::<WeightSet>("[weights.obsoletes]\n").is_err());
+        assert!(WeightKind::try_from("obsoletes").is_err());
+
+        // The link kinds are registered before anything asks for them.
+        for name in WeightKind::LINK_KINDS {
+            assert!(WeightKind::try_from(name).unwrap().is_custom());
+        }
+    }
+
     #[test]
     fn test_weight_set_operations() {
         let mut ws1 = WeightSet::empty();
@@ -5,10 +5,11 @@ use std::{
     hash::{Hash, Hasher},
     ops::Deref,
     path::PathBuf,
+    str::FromStr,
 };
 
 use enumset::EnumSet;
-use regex::{escape as re_escape, Regex, RegexBuilder};
+use regex::{escape as re_escape, Regex};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 #[cfg(feature = "service")]
@@ -24,13 +25,21 @@ use crate::{
     BuildonomyError,
 };
 
+mod order;
+mod parse;
+
+pub use order::{order_nodes, reading_positions, OrderBy, OrderKey};
+#[cfg(feature = "service")]
+pub(crate) use order::{path_sort_key, push_order_by};
+pub use parse::glob_regex;
+
 pub const DEFAULT_QUERY_DISTANCE: u8 = 5;
 
 /// Recursion Cutoff for query traversal
 pub const MAX_TRAVERSAL: u8 = 10;
 
 #[cfg(feature = "service")]
-fn push_id_expr<I: ToString>(
+pub(crate) fn push_id_expr<I: ToString>(
     qb: &mut QueryBuilder<Sqlite>,
     bids: &[I],
     column: &str,
@@ -52,6 +61,67 @@ fn push_id_expr<I: ToString>(
     qb.push(") ");
 }
 
+/// Wraps a payload condition so that nodes without the field fail it rather than evaluating to
+/// NULL, and so match the inverted predicate.
+#[cfg(feature = "service")]
+fn push_payload_expr(
+    qb: &mut QueryBuilder<Sqlite>,
+    match_pred: bool,
+    condition: impl FnOnce(&mut QueryBuilder<Sqlite>),
+) {
+    if !match_pred {
+        qb.push("NOT ");
+    }
+    qb.push("coalesce((");
+    condition(qb);
+    qb.push("), 0)");
+}
+
+/// The JSON path of a top-level payload field.
+#[cfg(feature = "service")]
+fn json_path(key: &str) -> String {
+    format!("$.\"{key}\"")
+}
+
+/// Compares the payload field at `path`, or the current `json_each` row without one, to `value`
+/// following the coercion rules of [`PayloadValue::cmp_field`]. Payload datetimes are stored as
+/// JSON strings.
+#[cfg(feature = "service")]
+fn push_typed_cmp(
+    qb: &mut QueryBuilder<Sqlite>,
+    path: Option<&str>,
+    comparison: Comparison,
+    value: &PayloadValue,
+) {
+    let types = match value {
+        PayloadValue::Number(_) => "('integer', 'real')",
+        PayloadValue::Boolean(_) => "('true', 'false')",
+        PayloadValue::Datetime(_) | PayloadValue::String(_) => "('text')",
+    };
+    let op = match comparison {
+        Comparison::Ne => "<>",
+        comparison => comparison.as_str(),
+    };
+    match path {
+        Some(path) => {
+            qb.push("json_type(payload_json, ");
+            qb.push_bind(path.to_string());
+            qb.push(format!(") IN {types} AND json_extract(payload_json, "));
+            qb.push_bind(path.to_string());
+            qb.push(format!(") {op} "));
+        }
+        None => {
+            qb.push(format!("type IN {types} AND value {op} "));
+        }
+    }
+    match value {
+        PayloadValue::Number(number) => qb.push_bind(*number),
+        PayloadValue::Boolean(boolean) => qb.push_bind(*boolean as i64),
+        PayloadValue::Datetime(datetime) => qb.push_bind(datetime.to_string()),
+        PayloadValue::String(string) => qb.push_bind(string.clone()),
+    };
+}
+
 #[cfg(feature = "service")]
 pub fn push_string_expr(
     qb: &mut QueryBuilder<Sqlite>,
@@ -252,19 +322,14 @@ impl PartialEq for WrappedRegex {
 }
 
 impl From<&str> for WrappedRegex {
+    // The case-insensitive flag is written into the pattern so that it survives serialization and
+    // reaches SQLite's REGEXP
     fn from(other: &str) -> WrappedRegex {
         WrappedRegex(
-            RegexBuilder::new(other)
-                .unicode(true)
-                .case_insensitive(true)
-                .build()
-                .unwrap_or(
-                    RegexBuilder::new(&re_escape(other))
-                        .unicode(true)
-                        .case_insensitive(true)
-                        .build()
-                        .expect("An escaped string to always suceed as a regex"),
-                ),
+            Regex::new(&format!("(?i){other}")).unwrap_or(
+                Regex::new(&format!("(?i){}", re_escape(other)))
+                    .expect("An escaped string to always suceed as a regex"),
+            ),
         )
     }
 }
@@ -284,6 +349,159 @@ impl From<Regex> for WrappedRegex {
 
 impl Eq for WrappedRegex {}
 
+/// How a payload field compares to a [`PayloadValue`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    pub fn holds(&self, ordering: cmp::Ordering) -> bool {
+        match self {
+            Comparison::Eq => ordering.is_eq(),
+            Comparison::Ne => ordering.is_ne(),
+            Comparison::Lt => ordering.is_lt(),
+            Comparison::Le => ordering.is_le(),
+            Comparison::Gt => ordering.is_gt(),
+            Comparison::Ge => ordering.is_ge(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Comparison::Eq => "=",
+            Comparison::Ne => "!=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+}
+
+/// A typed value to compare payload fields with. Fields are coerced to the value's type, and
+/// fields that can't be are never matched:
+/// - numbers compare with integer and float fields
+/// - booleans compare with boolean fields
+/// - strings and datetimes compare with string and datetime fields as text, which orders ISO
+///   8601 dates chronologically
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PayloadValue {
+    Number(f64),
+    Boolean(bool),
+    Datetime(toml::value::Datetime),
+    String(String),
+}
+
+impl PayloadValue {
+    /// Order a payload field relative to this value, if the field coerces to its type.
+    pub fn cmp_field(&self, field: &toml::Value) -> Option<cmp::Ordering> {
+        match (field, self) {
+            (toml::Value::Integer(field), PayloadValue::Number(value)) => {
+                (*field as f64).partial_cmp(value)
+            }
+            (toml::Value::Float(field), PayloadValue::Number(value)) => field.partial_cmp(value),
+            (toml::Value::Boolean(field), PayloadValue::Boolean(value)) => Some(field.cmp(value)),
+            (toml::Value::String(field), _) => Some(field.as_str().cmp(&self.text()?)),
+            (toml::Value::Datetime(field), _) => Some(field.to_string().cmp(&self.text()?)),
+            _ => None,
+        }
+    }
+
+    /// The text that string and datetime values compare as.
+    fn text(&self) -> Option<String> {
+        match self {
+            PayloadValue::String(value) => Some(value.clone()),
+            PayloadValue::Datetime(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for PayloadValue {
+    type Err = std::convert::Infallible;
+
+    /// Infer the type of a value written as text: booleans, then numbers, then TOML datetimes
+    /// (`2026-12-01`, `2026-12-01T09:30:00Z`, ...), and anything else is a string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if let Ok(value) = s.parse::<bool>() {
+            PayloadValue::Boolean(value)
+        } else if let Some(value) = s.parse::<f64>().ok().filter(|value| value.is_finite()) {
+            PayloadValue::Number(value)
+        } else if let Ok(value) = s.parse::<toml::value::Datetime>() {
+            PayloadValue::Datetime(value)
+        } else {
+            PayloadValue::String(s.to_string())
+        })
+    }
+}
+
+impl fmt::Display for PayloadValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PayloadValue::Number(value) => write!(f, "{value}"),
+            PayloadValue::Boolean(value) => write!(f, "{value}"),
+            PayloadValue::Datetime(value) => write!(f, "{value}"),
+            PayloadValue::String(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl PartialEq for PayloadValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PayloadValue::Number(lhs), PayloadValue::Number(rhs)) => {
+                lhs.to_bits() == rhs.to_bits()
+            }
+            (PayloadValue::Boolean(lhs), PayloadValue::Boolean(rhs)) => lhs == rhs,
+            (PayloadValue::Datetime(lhs), PayloadValue::Datetime(rhs)) => lhs == rhs,
+            (PayloadValue::String(lhs), PayloadValue::String(rhs)) => lhs == rhs,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for PayloadValue {}
+
+impl Hash for PayloadValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            PayloadValue::Number(value) => value.to_bits().hash(state),
+            PayloadValue::Boolean(value) => value.hash(state),
+            PayloadValue::Datetime(value) => value.to_string().hash(state),
+            PayloadValue::String(value) => value.hash(state),
+        }
+    }
+}
+
+/// A payload as JSON, the form payload predicates and orderings query in SQLite. TOML datetimes
+/// become strings, and non-finite floats, which JSON can't represent, null.
+pub(crate) fn payload_json(payload: &toml::Table) -> serde_json::Value {
+    payload
+        .iter()
+        .map(|(key, value)| (key.clone(), payload_json_value(value)))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+pub(crate) fn payload_json_value(value: &toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(string) => string.clone().into(),
+        toml::Value::Integer(integer) => (*integer).into(),
+        toml::Value::Float(float) => (*float).into(),
+        toml::Value::Boolean(boolean) => (*boolean).into(),
+        toml::Value::Datetime(datetime) => datetime.to_string().into(),
+        toml::Value::Array(items) => items.iter().map(payload_json_value).collect(),
+        toml::Value::Table(table) => payload_json(table),
+    }
+}
+
 /// Filter based on BeliefState properties
 ///
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -304,12 +522,23 @@ pub enum StatePred {
     Kind(EnumSet<BeliefKind>),
     // Return node of the specified network path
     NetPath(Bref, String),
+    // Return nodes whose network path matches a glob pattern (`*`, `?`, `[...]`)
+    NetPathGlob(Bref, String),
     // Return all paths within a network
     NetPathIn(Bid),
     // Return nodes containing a path that equals one of the predicate values
     Path(Vec<String>),
-    // Return nodes who's payload matches the key and regex value
+    // Return nodes who's payload matches the key and regex value. Strings match by their content,
+    // numbers, booleans and datetimes by their literal, and arrays and tables never match.
     Payload(String, WrappedRegex),
+    // Return nodes who's payload field compares to the value as specified
+    PayloadCompare(String, Comparison, PayloadValue),
+    // Return nodes who's payload field lies within the inclusive range of values
+    PayloadRange(String, PayloadValue, PayloadValue),
+    // Return nodes who's payload has the field
+    PayloadExists(String),
+    // Return nodes who's payload field is an array containing the value, or equals it
+    PayloadContains(String, PayloadValue),
 }
 
 impl StatePred {
@@ -323,20 +552,48 @@ impl StatePred {
             StatePred::Schema(schema) => node.schema.as_ref().filter(|s| *s == schema).is_some(),
             // Network splitting needs to be handled separately
             StatePred::NetId(..) => false,
-            StatePred::Kind(kind_set) => kind_set.is_superset(node.kind.0),
+            StatePred::Kind(kind_set) => node.kind.0.is_superset(*kind_set),
             // Path search needs to be handled separately
             StatePred::Path(..) => false,
             // Path search needs to be handled separately
             StatePred::NetPath(..) => false,
             // Path search needs to be handled separately
+            StatePred::NetPathGlob(..) => false,
+            // Path search needs to be handled separately
             StatePred::NetPathIn(..) => false,
-            StatePred::Payload(key, re) => {
-                if let Some(value) = node.payload.get(key) {
-                    re.0.is_match(&value.to_string())
-                } else {
-                    false
-                }
-            }
+            StatePred::Payload(key, re) => match node.payload.get(key) {
+                Some(toml::Value::String(value)) => re.0.is_match(value),
+                Some(toml::Value::Integer(value)) => re.0.is_match(&value.to_string()),
+                Some(toml::Value::Float(value)) => re.0.is_match(&format!("{value:?}")),
+                Some(toml::Value::Boolean(value)) => re.0.is_match(&value.to_string()),
+                Some(toml::Value::Datetime(value)) => re.0.is_match(&value.to_string()),
+                Some(toml::Value::Array(_) | toml::Value::Table(_)) | None => false,
+            },
+            StatePred::PayloadCompare(key, comparison, value) => node
+                .payload
+                .get(key)
+                .and_then(|field| value.cmp_field(field))
+                .is_some_and(|ordering| comparison.holds(ordering)),
+            StatePred::PayloadRange(key, low, high) => node.payload.get(key).is_some_and(|field| {
+                low.cmp_field(field)
+                    .is_some_and(|ordering| ordering.is_ge())
+                    && high
+                        .cmp_field(field)
+                        .is_some_and(|ordering| ordering.is_le())
+            }),
+            StatePred::PayloadExists(key) => node.payload.contains_key(key),
+            // A single value counts as an array of one, as it does for SQLite's json_each
+            StatePred::PayloadContains(key, value) => match node.payload.get(key) {
+                Some(toml::Value::Array(items)) => items.iter().any(|item| {
+                    value
+                        .cmp_field(item)
+                        .is_some_and(|ordering| ordering.is_eq())
+                }),
+                Some(field) => value
+                    .cmp_field(field)
+                    .is_some_and(|ordering| ordering.is_eq()),
+                None => false,
+            },
         }
     }
 }
@@ -345,7 +602,10 @@ impl StatePred {
 impl AsSql for StatePred {
     fn build_query(&self, match_pred: bool, qb: &mut QueryBuilder<Sqlite>) {
         match self {
-            StatePred::Path(..) | StatePred::NetPath(..) | StatePred::NetPathIn(..) => {
+            StatePred::Path(..)
+            | StatePred::NetPath(..)
+            | StatePred::NetPathGlob(..)
+            | StatePred::NetPathIn(..) => {
                 qb.push(
                     "SELECT DISTINCT target as bid \
                      FROM paths \
@@ -401,6 +661,12 @@ impl AsSql for StatePred {
                 qb.push(" AND net = ");
                 qb.push_bind(net.to_string());
             }
+            StatePred::NetPathGlob(net, pattern) => {
+                qb.push("path GLOB ");
+                qb.push_bind(parse::glob_sql(pattern));
+                qb.push(" AND net = ");
+                qb.push_bind(net.to_string());
+            }
             StatePred::NetPathIn(net) => {
                 qb.push("net = ");
                 qb.push_bind(net.to_string());
@@ -408,12 +674,48 @@ impl AsSql for StatePred {
             StatePred::Path(path_vec) => {
                 push_string_expr(qb, path_vec, "path", match_pred, false);
             }
-            StatePred::Payload(_key, _re_val) => {
-                tracing::warn!(
-                    "Cannot construct a payload query using the database! Instead, perform a \
-                    general query to the database then query the resulting BeliefBase in order \
-                    to filter by payload value."
-                );
+            StatePred::PayloadCompare(key, comparison, value) => {
+                push_payload_expr(qb, match_pred, |qb| {
+                    push_typed_cmp(qb, Some(&json_path(key)), *comparison, value);
+                });
+            }
+            StatePred::PayloadRange(key, low, high) => {
+                push_payload_expr(qb, match_pred, |qb| {
+                    push_typed_cmp(qb, Some(&json_path(key)), Comparison::Ge, low);
+                    qb.push(" AND ");
+                    push_typed_cmp(qb, Some(&json_path(key)), Comparison::Le, high);
+                });
+            }
+            StatePred::PayloadExists(key) => {
+                push_payload_expr(qb, match_pred, |qb| {
+                    qb.push("json_type(payload_json, ");
+                    qb.push_bind(json_path(key));
+                    qb.push(") IS NOT NULL");
+                });
+            }
+            StatePred::PayloadContains(key, value) => {
+                push_payload_expr(qb, match_pred, |qb| {
+                    qb.push("EXISTS (SELECT 1 FROM json_each(payload_json, ");
+                    qb.push_bind(json_path(key));
+                    qb.push(") WHERE ");
+                    push_typed_cmp(qb, None, Comparison::Eq, value);
+                    qb.push(")");
+                });
+            }
+            StatePred::Payload(key, re) => {
+                // JSON stores booleans as 1 and 0, and REGEXP only reads text
+                push_payload_expr(qb, match_pred, |qb| {
+                    qb.push("(CASE json_type(payload_json, ");
+                    qb.push_bind(json_path(key));
+                    qb.push(
+                        ") WHEN 'true' THEN 'true' WHEN 'false' THEN 'false' \
+                        WHEN 'array' THEN NULL WHEN 'object' THEN NULL \
+                        ELSE CAST(json_extract(payload_json, ",
+                    );
+                    qb.push_bind(json_path(key));
+                    qb.push(") AS TEXT) END) REGEXP ");
+                    qb.push_bind(re.as_str().to_string());
+                });
             }
         };
     }
@@ -475,11 +777,10 @@ impl AsSql for RelationPred {
                 }
                 RelationPred::Kind(kinds) => {
                     let mut kind_q = Vec::<String>::new();
-                    for kind in kinds {
-                        let column_name = format!("{kind:?}").to_lowercase();
+                    for (kind, _) in kinds {
                         kind_q.push(format!(
                             "{} IS{} NULL",
-                            column_name,
+                            kind.sql_column(),
                             if !match_pred { "" } else { " NOT" }
                         ));
                     }
@@ -495,7 +796,7 @@ impl AsSql for RelationPred {
         );
         build_where_clause(self, match_pred, qb);
         qb.push(
-            "UNION SELECT DISTINCT sink as bid \
+            " UNION SELECT DISTINCT sink as bid \
              FROM relations \
              WHERE ",
         );
@@ -516,6 +817,31 @@ pub trait AsSql {
     fn build_query(&self, match_pred: bool, qb: &mut QueryBuilder<Sqlite>);
 }
 
+/// The neighbourhood path searches from `from` look for their destination in
+fn path_search(from: Bid, weights: &WeightSet) -> Query {
+    Query {
+        seed: Expression::StateIn(StatePred::Bid(vec![from])),
+        traverse: Some(NeighborsExpression {
+            filter: Some(weights.clone()),
+            upstream: 0,
+            downstream: MAX_TRAVERSAL,
+        }),
+    }
+}
+
+/// The nodes `bids` depend on through Epistemic relations, which their
+/// [`OrderKey::Prerequisites`] order is drawn from
+pub(crate) fn prerequisites_search(bids: &[Bid]) -> Query {
+    Query {
+        seed: Expression::StateIn(StatePred::Bid(bids.to_vec())),
+        traverse: Some(NeighborsExpression {
+            filter: Some(WeightSet::from(WeightKind::Epistemic)),
+            upstream: MAX_TRAVERSAL,
+            downstream: 0,
+        }),
+    }
+}
+
 /// Cutoff limit for build balance expression recursion
 pub const BALANCE_CUTOFF: usize = 10;
 
@@ -572,6 +898,112 @@ pub trait BeliefSource: Sync {
         }
     }
 
+    /// Order nodes for pagination, as described by [`PaginatedQuery::order_by`]. The default
+    /// implementation can't order by path, leaving that key to the BID tiebreaker.
+    fn order_bids(
+        &self,
+        bids: &[Bid],
+        order_by: &[OrderBy],
+    ) -> impl std::future::Future<Output = Result<Vec<Bid>, BuildonomyError>> + Send {
+        async move {
+            let graph = self
+                .eval_unbalanced(&Expression::StateIn(StatePred::Bid(bids.to_vec())))
+                .await?;
+            let reading = match order_by
+                .iter()
+                .any(|order| order.key == OrderKey::Prerequisites)
+            {
+                true => {
+                    let prerequisites = self.eval_query(&prerequisites_search(bids), false).await?;
+                    reading_positions(&prerequisites.relations, bids)
+                }
+                false => BTreeMap::new(),
+            };
+            Ok(order_nodes(
+                bids.iter().filter_map(|bid| graph.states.get(bid)),
+                order_by,
+                &BTreeMap::new(),
+                &reading,
+            ))
+        }
+    }
+
+    /// Evaluate a query and return the requested page of its results.
+    fn eval_page(
+        &self,
+        pq: &PaginatedQuery,
+    ) -> impl std::future::Future<Output = Result<ResultsPage<BeliefGraph>, BuildonomyError>> + Send
+    {
+        async move {
+            let graph = self.eval_query(&pq.query, false).await?;
+            let bids = graph.states.keys().copied().collect::<Vec<_>>();
+            let order = self.order_bids(&bids, &pq.order_by).await?;
+            Ok(graph.paginate(pq, &order))
+        }
+    }
+
+    /// The shortest chain of relations of the `weights` kinds leading from `from` to `to`, as a
+    /// graph of just that path, which is empty when there is none. The default implementation
+    /// only searches [`MAX_TRAVERSAL`] hops downstream of `from`; the [`BeliefBase`] and database
+    /// sources search everything downstream, however far.
+    fn eval_shortest_path(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> impl std::future::Future<Output = Result<BeliefGraph, BuildonomyError>> + Send {
+        async move {
+            let graph = self.eval_query(&path_search(from, &weights), true).await?;
+            let paths = Vec::from_iter(graph.relations.shortest_path(from, to, &weights));
+            Ok(BeliefGraph::from_paths(
+                &paths,
+                &graph.states,
+                &graph.relations,
+                &weights,
+            ))
+        }
+    }
+
+    /// Every chain of at most `max_hops` relations of the `weights` kinds leading from `from` to
+    /// `to` without visiting a node twice, as a graph of just those paths. The default
+    /// implementation only searches [`MAX_TRAVERSAL`] hops downstream of `from`, as
+    /// [`eval_shortest_path`](Self::eval_shortest_path) does.
+    fn eval_simple_paths(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+        max_hops: u8,
+    ) -> impl std::future::Future<Output = Result<BeliefGraph, BuildonomyError>> + Send {
+        async move {
+            let graph = self.eval_query(&path_search(from, &weights), true).await?;
+            let paths = graph
+                .relations
+                .simple_paths(from, to, &weights, max_hops as usize);
+            Ok(BeliefGraph::from_paths(
+                &paths,
+                &graph.states,
+                &graph.relations,
+                &weights,
+            ))
+        }
+    }
+
+    /// Whether a chain of relations of the `weights` kinds leads from `from` to `to`. The default
+    /// implementation only searches [`MAX_TRAVERSAL`] hops downstream of `from`, as
+    /// [`eval_shortest_path`](Self::eval_shortest_path) does.
+    fn eval_reachable(
+        &self,
+        from: Bid,
+        to: Bid,
+        weights: WeightSet,
+    ) -> impl std::future::Future<Output = Result<bool, BuildonomyError>> + Send {
+        async move {
+            let graph = self.eval_query(&path_search(from, &weights), true).await?;
+            Ok(graph.relations.is_reachable(from, to, &weights))
+        }
+    }
+
     fn eval_balanced(
         &self,
         expr: &Expression,
@@ -789,6 +1221,9 @@ pub struct ResultsPage<B> {
     pub count: usize,
     pub start: usize,
     pub results: B,
+    /// The BIDs of the page's nodes, in the query's order
+    #[serde(default)]
+    pub order: Vec<Bid>,
 }
 
 /// Depth-limited graph traversal configuration for Query.
@@ -826,6 +1261,13 @@ pub struct PaginatedQuery {
     pub query: Query,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    /// The order to page through the results in. Nodes equal on every key, or all nodes when
+    /// empty, are ordered by BID.
+    #[serde(default)]
+    pub order_by: Vec<OrderBy>,
+    /// The payload fields to return, instead of the whole payload
+    #[serde(default)]
+    pub projection: Option<Vec<String>>,
 }
 
 impl Eq for PaginatedQuery {}
@@ -0,0 +1,200 @@
+//! Ordering of paginated query results.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "service")]
+use sqlx::{QueryBuilder, Sqlite};
+
+use super::payload_json_value;
+use crate::{
+    beliefbase::BidGraph,
+    properties::{BeliefNode, Bid, WeightKind, WeightSet},
+};
+
+/// What [`OrderBy`] sorts nodes by.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum OrderKey {
+    Title,
+    /// The node's position in its network's document order: the smallest `PathMap` sort key among
+    /// its paths. Nodes without a path come first.
+    Path,
+    /// Nodes without a schema come first.
+    Schema,
+    /// A top-level payload field. Nodes without the field come first, then numbers (and booleans,
+    /// as 0 and 1), then text: strings, datetimes, and arrays and tables as JSON.
+    Payload(String),
+    /// The node's position in the Epistemic reading order of the ordered nodes (see
+    /// [`BidGraph::reading_order`]): every node comes after the nodes it depends on, so a
+    /// document's prerequisites lead its learning path.
+    Prerequisites,
+}
+
+/// One key of a [`PaginatedQuery`](super::PaginatedQuery)'s order. Nodes equal on every key are
+/// ordered by BID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OrderBy {
+    pub key: OrderKey,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+impl OrderBy {
+    pub fn asc(key: OrderKey) -> Self {
+        OrderBy {
+            key,
+            descending: false,
+        }
+    }
+
+    pub fn desc(key: OrderKey) -> Self {
+        OrderBy {
+            key,
+            descending: true,
+        }
+    }
+}
+
+/// The value a node sorts by for one [`OrderKey`], compared the way SQLite compares the
+/// corresponding column: NULL, then numbers, then text.
+#[derive(Debug, PartialEq, PartialOrd)]
+enum SortValue {
+    Null,
+    Number(f64),
+    Text(String),
+    Path(Vec<u16>),
+}
+
+fn sort_value(
+    node: &BeliefNode,
+    key: &OrderKey,
+    path_order: Option<&Vec<u16>>,
+    reading_position: Option<&usize>,
+) -> SortValue {
+    match key {
+        OrderKey::Title => SortValue::Text(node.title.clone()),
+        OrderKey::Schema => node
+            .schema
+            .clone()
+            .map(SortValue::Text)
+            .unwrap_or(SortValue::Null),
+        OrderKey::Path => path_order
+            .cloned()
+            .map(SortValue::Path)
+            .unwrap_or(SortValue::Null),
+        OrderKey::Payload(field) => match node.payload.get(field).map(payload_json_value) {
+            None | Some(serde_json::Value::Null) => SortValue::Null,
+            Some(serde_json::Value::Bool(value)) => SortValue::Number(value as u8 as f64),
+            Some(serde_json::Value::Number(value)) => value
+                .as_f64()
+                .map(SortValue::Number)
+                .unwrap_or(SortValue::Null),
+            Some(serde_json::Value::String(value)) => SortValue::Text(value),
+            Some(value) => SortValue::Text(value.to_string()),
+        },
+        OrderKey::Prerequisites => reading_position
+            .map(|position| SortValue::Number(*position as f64))
+            .unwrap_or(SortValue::Null),
+    }
+}
+
+/// The BIDs of `nodes` sorted by `order_by`, then by BID. `path_orders` holds the document order
+/// of the nodes that have a path, and `reading_positions` their [`reading_positions`].
+pub fn order_nodes<'a>(
+    nodes: impl IntoIterator<Item = &'a BeliefNode>,
+    order_by: &[OrderBy],
+    path_orders: &BTreeMap<Bid, Vec<u16>>,
+    reading_positions: &BTreeMap<Bid, usize>,
+) -> Vec<Bid> {
+    let mut keyed = nodes
+        .into_iter()
+        .map(|node| {
+            let values = order_by
+                .iter()
+                .map(|order| {
+                    sort_value(
+                        node,
+                        &order.key,
+                        path_orders.get(&node.bid),
+                        reading_positions.get(&node.bid),
+                    )
+                })
+                .collect::<Vec<_>>();
+            (values, node.bid)
+        })
+        .collect::<Vec<_>>();
+    keyed.sort_by(|(lhs, lhs_bid), (rhs, rhs_bid)| {
+        lhs.iter()
+            .zip(rhs.iter())
+            .zip(order_by.iter())
+            .map(|((lhs, rhs), order)| {
+                let ordering = lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal);
+                if order.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| lhs_bid.cmp(rhs_bid))
+    });
+    keyed.into_iter().map(|(_, bid)| bid).collect()
+}
+
+/// Each of `bids`' position in their Epistemic reading order over `relations`, which should hold
+/// the relations upstream of them, for [`OrderKey::Prerequisites`].
+pub fn reading_positions(relations: &BidGraph, bids: &[Bid]) -> BTreeMap<Bid, usize> {
+    let ordered = bids.iter().collect::<BTreeSet<_>>();
+    relations
+        .reading_order(bids, &WeightSet::from(WeightKind::Epistemic))
+        .order
+        .into_iter()
+        .filter(|bid| ordered.contains(bid))
+        .enumerate()
+        .map(|(position, bid)| (bid, position))
+        .collect()
+}
+
+/// The sort key a path's document order is stored as in the `paths` table: four hex digits per
+/// level, so that the text orders as [`order_nodes`] orders the levels.
+#[cfg(feature = "service")]
+pub(crate) fn path_sort_key(order: &[u16]) -> String {
+    order.iter().map(|idx| format!("{idx:04x}")).collect()
+}
+
+/// `ORDER BY` terms matching [`order_nodes`], for a query over `beliefs` joined with each node's
+/// smallest path `sort_key` and its `reading_position`.
+#[cfg(feature = "service")]
+pub(crate) fn push_order_by(qb: &mut QueryBuilder<Sqlite>, order_by: &[OrderBy]) {
+    qb.push(" ORDER BY ");
+    for order in order_by {
+        match &order.key {
+            OrderKey::Title => {
+                qb.push("title");
+            }
+            OrderKey::Path => {
+                qb.push("sort_key");
+            }
+            OrderKey::Schema => {
+                qb.push("schema");
+            }
+            OrderKey::Payload(field) => {
+                qb.push("json_extract(payload_json, ");
+                qb.push_bind(super::json_path(field));
+                qb.push(")");
+            }
+            OrderKey::Prerequisites => {
+                qb.push("reading_position");
+            }
+        }
+        qb.push(if order.descending {
+            " DESC, "
+        } else {
+            " ASC, "
+        });
+    }
+    qb.push("bid ASC");
+}
@@ -0,0 +1,928 @@
+//! Textual syntax for [`Expression`]s, parsed by [`Expression::from_str`] and written by its
+//! `Display` implementation.
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use enumset::EnumSet;
+use regex::{escape as re_escape, Regex};
+
+use super::{Comparison, Expression, PayloadValue, RelationPred, SetOp, StatePred, WrappedRegex};
+use crate::{
+    beliefbase::BeliefBase,
+    nodekey::NodeKey,
+    properties::{BeliefKind, Bid, Bref, Weight, WeightKind, WeightSet},
+    BuildonomyError,
+};
+
+/// Characters that end an unquoted value.
+const DELIMITERS: [char; 7] = ['&', '|', '^', '(', ')', ',', '"'];
+
+impl FromStr for Expression {
+    type Err = BuildonomyError;
+
+    /// Parse an expression combining predicates with set operators. `&` (intersection) binds
+    /// tighter than `|` (union), `-` (difference) and `^` (symmetric difference), which associate
+    /// left to right. Parentheses group, and `!` negates a single predicate:
+    ///
+    /// ```text
+    /// kind:Document & schema:task & payload.status~/open/ | net:docs/path:guides/*
+    /// ```
+    ///
+    /// | Predicate | Matches |
+    /// |---|---|
+    /// | `*` | every node |
+    /// | `bid:<bid>,...` | nodes with one of the BIDs |
+    /// | `bref:<bref>,...` | nodes with one of the BREFs |
+    /// | `ns:<bref>,...` | nodes in one of the namespaces |
+    /// | `kind:<kind>,...` | nodes of all of the kinds (`Document`, `Network`, ...) |
+    /// | `schema:<schema>` | nodes of the schema |
+    /// | `path:<path>,...` | nodes at one of the paths |
+    /// | `net:<net>` | nodes with a path in the network |
+    /// | `net:<net>/path:<path>` | the node at the path in the network, or with `*`, `?` or `[...]` in the path, the nodes whose path matches it |
+    /// | `net:<net>/id:<id>` | the node with the id in the network |
+    /// | `payload.<key>~/<regex>/` | nodes with a scalar payload field matching the regex, strings by their content |
+    /// | `payload.<key>=<value>`, `!=`, `<`, `<=`, `>`, `>=` | nodes with a payload field comparing so to the value |
+    /// | `payload.<key>:<low>..<high>` | nodes with a payload field within the inclusive range |
+    /// | `payload.<key>?` | nodes with the payload field |
+    /// | `payload.<key>[]=<value>` | nodes with a payload array containing the value |
+    /// | `rel:*`, `rel:<kind>,...` | relations of any kind, or of one of the kinds |
+    /// | `source:<bid>,...`, `sink:<bid>,...`, `node:<bid>,...` | relations from, to, or either way with one of the nodes |
+    ///
+    /// Payload values are typed as described by [`PayloadValue`]: `true` and `false` are booleans,
+    /// `3` and `-0.5` numbers, `2026-12-01` a datetime, and quoted values always strings.
+    ///
+    /// Values with whitespace or any of `&|^(),"` are written in double quotes. Networks are
+    /// referred to by BID or BREF; network ids fail to parse with
+    /// [`BuildonomyError::UnresolvedNetwork`] unless resolved by
+    /// [`Expression::from_str_with_cache`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser::new(s, &BTreeMap::new()).parse()
+    }
+}
+
+impl Expression {
+    /// Parse an expression like [`Expression::from_str`], resolving the network ids it refers to
+    /// against `cache`.
+    pub fn from_str_with_cache(s: &str, cache: &BeliefBase) -> Result<Self, BuildonomyError> {
+        let mut networks = BTreeMap::new();
+        loop {
+            match Parser::new(s, &networks).parse() {
+                Err(BuildonomyError::UnresolvedNetwork { network_ref, .. })
+                    if !networks.contains_key(&network_ref) =>
+                {
+                    let net = NodeKey::resolve_network_sync(&network_ref, cache)?;
+                    networks.insert(network_ref, net);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A regex matching a whole path against a glob `pattern`: `*` matches any run of characters
+/// (`/` included, as in SQLite's `GLOB`), `?` any one character, and `[...]` one character of
+/// a set (`[!...]` or `[^...]` for one that is not).
+pub fn glob_regex(pattern: &str) -> Result<Regex, BuildonomyError> {
+    let mut re = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            '[' => {
+                re.push('[');
+                if chars.next_if(|c| matches!(c, '!' | '^')).is_some() {
+                    re.push('^');
+                }
+                // A ']' right after the opening bracket (or its negation) is a member of the set.
+                let mut first = true;
+                loop {
+                    match chars.next() {
+                        Some(']') if !first => break,
+                        Some(c @ ('\\' | '[' | ']')) => {
+                            re.push('\\');
+                            re.push(c);
+                        }
+                        Some(c) => re.push(c),
+                        None => {
+                            return Err(BuildonomyError::Serialization(format!(
+                                "Unclosed '[' in path pattern '{pattern}'"
+                            )))
+                        }
+                    }
+                    first = false;
+                }
+                re.push(']');
+            }
+            c => re.push_str(&re_escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    re.push('$');
+    Ok(Regex::new(&re)?)
+}
+
+/// `pattern` as SQLite's `GLOB` reads it. `GLOB` only negates a set with `[^...]`, so `[!...]` is
+/// rewritten to match what [`glob_regex`] does with it.
+#[cfg(feature = "service")]
+pub(crate) fn glob_sql(pattern: &str) -> String {
+    let mut sql = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        sql.push(c);
+        if c != '[' {
+            continue;
+        }
+        if chars.next_if_eq(&'!').is_some() {
+            sql.push('^');
+        }
+        // A ']' right after the opening bracket (or its negation) is a member of the set.
+        if let Some(first) = chars.next() {
+            sql.push(first);
+        }
+        for c in chars.by_ref() {
+            sql.push(c);
+            if c == ']' {
+                break;
+            }
+        }
+    }
+    sql
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+enum Predicate {
+    State(StatePred),
+    Relation(RelationPred),
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    networks: &'a BTreeMap<String, Bid>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str, networks: &'a BTreeMap<String, Bid>) -> Self {
+        Parser {
+            src,
+            pos: 0,
+            networks,
+        }
+    }
+
+    fn parse(mut self) -> Result<Expression, BuildonomyError> {
+        let expr = self.union()?;
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) => Err(self.error(format!("unexpected '{c}'"))),
+            None => Ok(expr),
+        }
+    }
+
+    fn union(&mut self) -> Result<Expression, BuildonomyError> {
+        let mut lhs = self.intersection()?;
+        loop {
+            self.skip_whitespace();
+            let op = match self.peek() {
+                Some('|') => SetOp::Union,
+                Some('-') => SetOp::Difference,
+                Some('^') => SetOp::SymmetricDifference,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            let rhs = self.intersection()?;
+            lhs = Expression::Dyad(Box::new(lhs), op, Box::new(rhs));
+        }
+    }
+
+    fn intersection(&mut self) -> Result<Expression, BuildonomyError> {
+        let mut lhs = self.unary()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat('&') {
+                return Ok(lhs);
+            }
+            let rhs = self.unary()?;
+            lhs = Expression::Dyad(Box::new(lhs), SetOp::Intersection, Box::new(rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, BuildonomyError> {
+        self.skip_whitespace();
+        if self.eat('(') {
+            let expr = self.union()?;
+            self.skip_whitespace();
+            if !self.eat(')') {
+                return Err(self.error("expected ')'"));
+            }
+            return Ok(expr);
+        }
+        if self.eat('!') {
+            if self.peek() == Some('(') {
+                return Err(self.error("'!' negates a single predicate"));
+            }
+            return Ok(match self.predicate()? {
+                Predicate::State(pred) => Expression::StateNotIn(pred),
+                Predicate::Relation(pred) => Expression::RelationNotIn(pred),
+            });
+        }
+        Ok(match self.predicate()? {
+            Predicate::State(pred) => Expression::StateIn(pred),
+            Predicate::Relation(pred) => Expression::RelationIn(pred),
+        })
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, BuildonomyError> {
+        if self.eat('*') {
+            return Ok(Predicate::State(StatePred::Any));
+        }
+        let key_pos = self.pos;
+        let key = self.take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if key.is_empty() {
+            return Err(self.error("expected a predicate"));
+        }
+        if let Some(field) = key.strip_prefix("payload.") {
+            return Ok(Predicate::State(self.payload(field.to_string())?));
+        }
+        if !self.eat(':') {
+            return Err(self.error(format!("expected ':' after '{key}'")));
+        }
+        Ok(match key {
+            "bid" => Predicate::State(StatePred::Bid(self.list(parse_bid)?)),
+            "bref" => Predicate::State(StatePred::Bref(self.list(parse_bref)?)),
+            "ns" => Predicate::State(StatePred::InNamespace(self.list(parse_bref)?)),
+            "kind" => Predicate::State(StatePred::Kind(
+                self.list(parse_kind)?.into_iter().collect(),
+            )),
+            "schema" => Predicate::State(StatePred::Schema(self.value()?)),
+            "path" => Predicate::State(StatePred::Path(self.list(Ok)?)),
+            "net" => Predicate::State(self.network()?),
+            "rel" if self.eat('*') => Predicate::Relation(RelationPred::Any),
+            "rel" => {
+                let mut kinds = WeightSet::empty();
+                for kind in self.list(|name| WeightKind::try_from(&name[..]))? {
+                    kinds.set(kind, Weight::default());
+                }
+                Predicate::Relation(RelationPred::Kind(kinds))
+            }
+            "source" => Predicate::Relation(RelationPred::SourceIn(self.list(parse_bid)?)),
+            "sink" => Predicate::Relation(RelationPred::SinkIn(self.list(parse_bid)?)),
+            "node" => Predicate::Relation(RelationPred::NodeIn(self.list(parse_bid)?)),
+            _ => {
+                self.pos = key_pos;
+                return Err(self.error(format!("unknown predicate '{key}'")));
+            }
+        })
+    }
+
+    /// The rest of a `payload.<key>` predicate.
+    fn payload(&mut self, key: String) -> Result<StatePred, BuildonomyError> {
+        if self.eat('?') {
+            return Ok(StatePred::PayloadExists(key));
+        }
+        if self.eat('~') {
+            return Ok(StatePred::Payload(key, WrappedRegex::from(self.regex()?)));
+        }
+        if self.eat_str("[]=") {
+            return Ok(StatePred::PayloadContains(key, self.payload_value(false)?));
+        }
+        if self.eat(':') {
+            let low = self.payload_value(true)?;
+            if !self.eat_str("..") {
+                return Err(self.error("expected '..' between the bounds of a range"));
+            }
+            return Ok(StatePred::PayloadRange(
+                key,
+                low,
+                self.payload_value(false)?,
+            ));
+        }
+        let comparisons = [
+            Comparison::Le,
+            Comparison::Ge,
+            Comparison::Ne,
+            Comparison::Lt,
+            Comparison::Gt,
+            Comparison::Eq,
+        ];
+        match comparisons.into_iter().find(|op| self.eat_str(op.as_str())) {
+            Some(comparison) => Ok(StatePred::PayloadCompare(
+                key,
+                comparison,
+                self.payload_value(false)?,
+            )),
+            None => Err(self.error(format!(
+                "expected '?', '~/<regex>/', '[]=<value>', ':<low>..<high>' or a comparison \
+                 after 'payload.{key}'"
+            ))),
+        }
+    }
+
+    /// A value to compare payload fields with. Quoted values are strings and the type of bare
+    /// ones is inferred; the bare lower bound of a range ends at `..`.
+    fn payload_value(&mut self, lower_bound: bool) -> Result<PayloadValue, BuildonomyError> {
+        if self.peek() == Some('"') {
+            return Ok(PayloadValue::String(self.value()?));
+        }
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_whitespace()
+                || DELIMITERS.contains(&c)
+                || (lower_bound && self.src[self.pos..].starts_with(".."))
+            {
+                break;
+            }
+            self.next();
+        }
+        if self.pos == start {
+            return Err(self.error("expected a value"));
+        }
+        let Ok(value) = self.src[start..self.pos].parse();
+        Ok(value)
+    }
+
+    /// The rest of a `net:` predicate.
+    fn network(&mut self) -> Result<StatePred, BuildonomyError> {
+        let net_ref = self
+            .take_while(|c| c != '/' && !c.is_whitespace() && !DELIMITERS.contains(&c))
+            .to_string();
+        if net_ref.is_empty() {
+            return Err(self.error("expected a network after 'net:'"));
+        }
+        if !self.eat('/') {
+            return Ok(StatePred::NetPathIn(self.network_bid(&net_ref)?));
+        }
+        let key = self.take_while(|c| c.is_alphanumeric()).to_string();
+        if !self.eat(':') || !matches!(&key[..], "path" | "id") {
+            return Err(self.error(format!("expected 'path:' or 'id:' after 'net:{net_ref}/'")));
+        }
+        let value = self.value()?;
+        let net = self.network_bref(&net_ref, &key, &value)?;
+        Ok(match &key[..] {
+            "id" => StatePred::NetId(net, value),
+            _ if is_glob(&value) => {
+                glob_regex(&value)?;
+                StatePred::NetPathGlob(net, value)
+            }
+            _ => StatePred::NetPath(net, value),
+        })
+    }
+
+    fn network_bid(&self, net_ref: &str) -> Result<Bid, BuildonomyError> {
+        if let Some(net) = self.networks.get(net_ref) {
+            return Ok(*net);
+        }
+        Bid::try_from(net_ref).map_err(|_| BuildonomyError::UnresolvedNetwork {
+            network_ref: net_ref.to_string(),
+            key_type: "net".to_string(),
+            value: String::new(),
+        })
+    }
+
+    fn network_bref(&self, net_ref: &str, key: &str, value: &str) -> Result<Bref, BuildonomyError> {
+        if let Some(net) = self.networks.get(net_ref) {
+            return Ok(net.bref());
+        }
+        if let Ok(net) = Bid::try_from(net_ref) {
+            return Ok(net.bref());
+        }
+        Bref::try_from(net_ref).map_err(|_| BuildonomyError::UnresolvedNetwork {
+            network_ref: net_ref.to_string(),
+            key_type: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Comma separated values.
+    fn list<T>(
+        &mut self,
+        parse: impl Fn(String) -> Result<T, BuildonomyError>,
+    ) -> Result<Vec<T>, BuildonomyError> {
+        let mut values = Vec::new();
+        loop {
+            let value_pos = self.pos;
+            let value = self.value()?;
+            values.push(parse(value).map_err(|err| {
+                let mut parser = Parser::new(self.src, self.networks);
+                parser.pos = value_pos;
+                parser.error(err.to_string())
+            })?);
+            if !self.eat(',') {
+                return Ok(values);
+            }
+        }
+    }
+
+    /// A quoted or bare value.
+    fn value(&mut self) -> Result<String, BuildonomyError> {
+        if !self.eat('"') {
+            let value = self.take_while(|c| !c.is_whitespace() && !DELIMITERS.contains(&c));
+            if value.is_empty() {
+                return Err(self.error("expected a value"));
+            }
+            return Ok(value.to_string());
+        }
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.next() {
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                Some(c) => value.push(c),
+                None => break,
+            }
+        }
+        Err(self.error("unclosed '\"'"))
+    }
+
+    /// A `/`-delimited regex, in which `\/` stands for `/`.
+    fn regex(&mut self) -> Result<Regex, BuildonomyError> {
+        if !self.eat('/') {
+            return Err(self.error("expected '/<regex>/'"));
+        }
+        let start = self.pos;
+        let mut re = String::new();
+        loop {
+            match self.next() {
+                Some('/') => break,
+                Some('\\') if self.peek() == Some('/') => {
+                    self.pos += 1;
+                    re.push('/');
+                }
+                Some(c) => re.push(c),
+                None => {
+                    self.pos = start;
+                    return Err(self.error("unclosed regex"));
+                }
+            }
+        }
+        Regex::new(&re).map_err(|err| {
+            self.pos = start;
+            self.error(err.to_string())
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let eaten = self.peek() == Some(c);
+        if eaten {
+            self.pos += c.len_utf8();
+        }
+        eaten
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let eaten = self.src[self.pos..].starts_with(s);
+        if eaten {
+            self.pos += s.len();
+        }
+        eaten
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.next();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn error(&self, msg: impl fmt::Display) -> BuildonomyError {
+        BuildonomyError::Serialization(format!(
+            "Invalid query '{}' at character {}: {msg}",
+            self.src,
+            self.src[..self.pos].chars().count()
+        ))
+    }
+}
+
+fn parse_bid(value: String) -> Result<Bid, BuildonomyError> {
+    Bid::try_from(&value[..])
+}
+
+fn parse_bref(value: String) -> Result<Bref, BuildonomyError> {
+    Bref::try_from(&value[..])
+}
+
+fn parse_kind(value: String) -> Result<BeliefKind, BuildonomyError> {
+    EnumSet::<BeliefKind>::all()
+        .iter()
+        .find(|kind| kind.to_string().eq_ignore_ascii_case(&value))
+        .ok_or_else(|| {
+            BuildonomyError::Serialization(format!(
+                "unknown kind '{value}'. Valid options: {}",
+                EnumSet::<BeliefKind>::all()
+                    .iter()
+                    .map(|kind| kind.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+}
+
+/// `value`, quoted if it would not read back as a single value.
+fn quoted(value: &str) -> String {
+    let bare = !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || DELIMITERS.contains(&c) || c == '\\');
+    if bare {
+        value.to_string()
+    } else {
+        quoted_always(value)
+    }
+}
+
+fn quoted_always(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A payload value as it parses back: strings that would be read as another type are quoted.
+fn payload_text(value: &PayloadValue) -> String {
+    match value {
+        PayloadValue::String(string) if !matches!(string.parse(), Ok(PayloadValue::String(_))) => {
+            quoted_always(string)
+        }
+        value => quoted(&value.to_string()),
+    }
+}
+
+fn joined<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|value| quoted(&value.to_string()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn operand(f: &mut fmt::Formatter, expr: &Expression) -> fmt::Result {
+            match expr {
+                Expression::Dyad(..) => write!(f, "({expr})"),
+                _ => write!(f, "{expr}"),
+            }
+        }
+        match self {
+            Expression::StateIn(pred) => write!(f, "{pred}"),
+            Expression::StateNotIn(pred) => write!(f, "!{pred}"),
+            Expression::RelationIn(pred) => write!(f, "{pred}"),
+            Expression::RelationNotIn(pred) => write!(f, "!{pred}"),
+            Expression::Dyad(lhs, op, rhs) => {
+                operand(f, lhs)?;
+                write!(f, " {op} ")?;
+                operand(f, rhs)
+            }
+        }
+    }
+}
+
+impl fmt::Display for SetOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self {
+            SetOp::Union => "|",
+            SetOp::Intersection => "&",
+            SetOp::Difference => "-",
+            SetOp::SymmetricDifference => "^",
+        };
+        write!(f, "{op}")
+    }
+}
+
+impl fmt::Display for StatePred {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatePred::Any => write!(f, "*"),
+            StatePred::InNamespace(brefs) => write!(f, "ns:{}", joined(brefs)),
+            StatePred::Bid(bids) => write!(f, "bid:{}", joined(bids)),
+            StatePred::Bref(brefs) => write!(f, "bref:{}", joined(brefs)),
+            StatePred::NetId(net, id) => write!(f, "net:{net}/id:{}", quoted(id)),
+            StatePred::Schema(schema) => write!(f, "schema:{}", quoted(schema)),
+            StatePred::Kind(kinds) => {
+                write!(f, "kind:{}", joined(&kinds.iter().collect::<Vec<_>>()))
+            }
+            StatePred::NetPath(net, path) | StatePred::NetPathGlob(net, path) => {
+                write!(f, "net:{net}/path:{}", quoted(path))
+            }
+            StatePred::NetPathIn(net) => write!(f, "net:{net}"),
+            StatePred::Path(paths) => write!(f, "path:{}", joined(paths)),
+            StatePred::Payload(key, re) => {
+                write!(f, "payload.{key}~/{}/", re.as_str().replace('/', "\\/"))
+            }
+            StatePred::PayloadCompare(key, comparison, value) => {
+                write!(
+                    f,
+                    "payload.{key}{}{}",
+                    comparison.as_str(),
+                    payload_text(value)
+                )
+            }
+            StatePred::PayloadRange(key, low, high) => {
+                let low = match low {
+                    PayloadValue::String(low) if low.contains("..") => quoted_always(low),
+                    low => payload_text(low),
+                };
+                write!(f, "payload.{key}:{low}..{}", payload_text(high))
+            }
+            StatePred::PayloadExists(key) => write!(f, "payload.{key}?"),
+            StatePred::PayloadContains(key, value) => {
+                write!(f, "payload.{key}[]={}", payload_text(value))
+            }
+        }
+    }
+}
+
+impl fmt::Display for RelationPred {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RelationPred::Any => write!(f, "rel:*"),
+            RelationPred::Kind(kinds) => write!(
+                f,
+                "rel:{}",
+                joined(&kinds.weights.keys().collect::<Vec<_>>())
+            ),
+            RelationPred::SinkIn(bids) => write!(f, "sink:{}", joined(bids)),
+            RelationPred::SourceIn(bids) => write!(f, "source:{}", joined(bids)),
+            RelationPred::NodeIn(bids) => write!(f, "node:{}", joined(bids)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::create_test_beliefbase;
+
+    fn parse(s: &str) -> Expression {
+        Expression::from_str(s).unwrap_or_else(|err| panic!("{s}: {err}"))
+    }
+
+    #[test]
+    fn test_parse_precedence_and_negation() {
+        let doc = Expression::StateIn(StatePred::Kind(BeliefKind::Document.into()));
+        let task = Expression::StateIn(StatePred::Schema("task".to_string()));
+        let open = Expression::StateIn(StatePred::Payload(
+            "status".to_string(),
+            WrappedRegex::from(Regex::new("open").unwrap()),
+        ));
+        let not_api = Expression::StateNotIn(StatePred::Kind(BeliefKind::API.into()));
+        let dyad = |lhs: Expression, op, rhs: Expression| {
+            Expression::Dyad(Box::new(lhs), op, Box::new(rhs))
+        };
+
+        assert_eq!(
+            parse("kind:Document & schema:task & payload.status~/open/ | !kind:api"),
+            dyad(
+                dyad(
+                    dyad(doc.clone(), SetOp::Intersection, task.clone()),
+                    SetOp::Intersection,
+                    open.clone()
+                ),
+                SetOp::Union,
+                not_api.clone()
+            )
+        );
+        assert_eq!(
+            parse("kind:document & (schema:task - payload.status~/open/)"),
+            dyad(
+                doc.clone(),
+                SetOp::Intersection,
+                dyad(task, SetOp::Difference, open)
+            )
+        );
+        assert_eq!(
+            parse("  !kind:API^*"),
+            dyad(
+                not_api,
+                SetOp::SymmetricDifference,
+                Expression::StateIn(StatePred::Any)
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let bid = Bid::new(Bid::nil());
+        let net = Bid::new(Bid::nil());
+        assert_eq!(
+            parse(&format!("bid:{bid},{net}")),
+            Expression::StateIn(StatePred::Bid(vec![bid, net]))
+        );
+        assert_eq!(
+            parse(&format!("net:{}/path:\"guides/a b.md\"", net.bref())),
+            Expression::StateIn(StatePred::NetPath(net.bref(), "guides/a b.md".to_string()))
+        );
+        assert_eq!(
+            parse(&format!("net:{net}/path:guides/*")),
+            Expression::StateIn(StatePred::NetPathGlob(net.bref(), "guides/*".to_string()))
+        );
+        assert_eq!(
+            parse(&format!("net:{net}")),
+            Expression::StateIn(StatePred::NetPathIn(net))
+        );
+        assert_eq!(
+            parse("rel:epistemic,Section"),
+            Expression::RelationIn(RelationPred::Kind(
+                WeightSet::from(WeightKind::Epistemic).union(&WeightSet::from(WeightKind::Section))
+            ))
+        );
+        let payload =
+            |key: &str, pred: fn(String) -> StatePred| Expression::StateIn(pred(key.to_string()));
+        assert_eq!(
+            parse(r#"payload.title="a (b)""#),
+            payload("title", |key| StatePred::PayloadCompare(
+                key,
+                Comparison::Eq,
+                PayloadValue::String("a (b)".to_string())
+            ))
+        );
+        assert_eq!(
+            parse("payload.priority>=3"),
+            payload("priority", |key| StatePred::PayloadCompare(
+                key,
+                Comparison::Ge,
+                PayloadValue::Number(3.0)
+            ))
+        );
+        assert_eq!(
+            parse("payload.due<2026-12-01"),
+            payload("due", |key| StatePred::PayloadCompare(
+                key,
+                Comparison::Lt,
+                PayloadValue::Datetime("2026-12-01".parse().unwrap())
+            ))
+        );
+        assert_eq!(
+            parse("payload.done!=true"),
+            payload("done", |key| StatePred::PayloadCompare(
+                key,
+                Comparison::Ne,
+                PayloadValue::Boolean(true)
+            ))
+        );
+        assert_eq!(
+            parse("payload.version=\"3\""),
+            payload("version", |key| StatePred::PayloadCompare(
+                key,
+                Comparison::Eq,
+                PayloadValue::String("3".to_string())
+            ))
+        );
+        assert_eq!(
+            parse("payload.score:-0.5..1.5"),
+            payload("score", |key| StatePred::PayloadRange(
+                key,
+                PayloadValue::Number(-0.5),
+                PayloadValue::Number(1.5)
+            ))
+        );
+        assert_eq!(
+            parse("payload.owner?"),
+            payload("owner", StatePred::PayloadExists)
+        );
+        assert_eq!(
+            parse("payload.tags[]=rfc"),
+            payload("tags", |key| StatePred::PayloadContains(
+                key,
+                PayloadValue::String("rfc".to_string())
+            ))
+        );
+        let Expression::StateIn(StatePred::Payload(_, re)) = parse(r"payload.url~/^https:\/\//")
+        else {
+            panic!("expected a payload predicate");
+        };
+        assert!(re.is_match("https://example.com"));
+
+        for invalid in [
+            "",
+            "kind:Document &",
+            "(kind:Document",
+            "kind:Nope",
+            "colour:red",
+            "!(kind:Document | kind:API)",
+            "payload.status~/(/",
+            "payload.status",
+            "payload.priority:3",
+            "bid:not-a-bid",
+            "schema:task schema:other",
+        ] {
+            assert!(Expression::from_str(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_parse_network_ids() {
+        assert!(matches!(
+            Expression::from_str("net:node-1/id:intro"),
+            Err(BuildonomyError::UnresolvedNetwork { network_ref, .. }) if network_ref == "node-1"
+        ));
+
+        let bb = create_test_beliefbase();
+        let net = bb
+            .states()
+            .values()
+            .find(|node| node.title == "Node 1")
+            .unwrap()
+            .bid;
+        assert_eq!(
+            Expression::from_str_with_cache("net:node-1/id:intro | net:node-1", &bb).unwrap(),
+            Expression::Dyad(
+                Box::new(Expression::StateIn(StatePred::NetId(
+                    net.bref(),
+                    "intro".to_string()
+                ))),
+                SetOp::Union,
+                Box::new(Expression::StateIn(StatePred::NetPathIn(net)))
+            )
+        );
+        assert!(Expression::from_str_with_cache("net:nowhere/id:intro", &bb).is_err());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        let net = Bid::new(Bid::nil());
+        for expr in [
+            "*".to_string(),
+            format!("ns:{} & !bref:{}", net.bref(), net.bref()),
+            "kind:Network,Document | schema:\"two words\"".to_string(),
+            format!("net:{}/id:intro - (net:{net} ^ path:a.md,b.md)", net.bref()),
+            format!(
+                "net:{}/path:docs/*.md & payload.status~/^open|closed$/",
+                net.bref()
+            ),
+            format!("payload.url~/https:\\/\\// | !rel:Pragmatic & (sink:{net} | rel:*)"),
+            "payload.priority>=3 & payload.due:2026-01-01..\"2026-12-31\"".to_string(),
+            "payload.version!=\"3\" | payload.tags[]=\"a..b\" | payload.range:\"a..b\"..z"
+                .to_string(),
+            "payload.owner? - (payload.done=false ^ payload.at<=2026-12-01T09:30:00Z)".to_string(),
+        ] {
+            let parsed = parse(&expr);
+            assert_eq!(parse(&parsed.to_string()), parsed, "{expr} -> {parsed}");
+        }
+    }
+
+    #[test]
+    fn test_glob_regex() {
+        let re = glob_regex("guides/*.md").unwrap();
+        assert!(re.is_match("guides/a.md") && re.is_match("guides/sub/b.md"));
+        assert!(!re.is_match("guides/a.mdx") && !re.is_match("other/guides/a.md"));
+        let re = glob_regex("v?/[!a-c]x.md").unwrap();
+        assert!(re.is_match("v1/dx.md") && !re.is_match("v1/ax.md"));
+        assert!(glob_regex("[ab").is_err());
+    }
+
+    /// SQLite's GLOB matches the same paths as `glob_regex` once the pattern is passed through
+    /// `glob_sql`.
+    #[cfg(feature = "service")]
+    #[tokio::test]
+    async fn test_glob_sql_matches_glob_regex() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        for pattern in [
+            "v?/[!a-c]x.md",
+            "v?/[^a-c]x.md",
+            "[]!]*",
+            "[!]a]?",
+            "guides/*.md",
+        ] {
+            for path in [
+                "v1/ax.md",
+                "v1/dx.md",
+                "!x",
+                "]",
+                "ab",
+                "bb",
+                "guides/sub/a.md",
+            ] {
+                let sql_match = sqlx::query_scalar::<_, bool>("SELECT ? GLOB ?")
+                    .bind(path)
+                    .bind(glob_sql(pattern))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    sql_match,
+                    glob_regex(pattern).unwrap().is_match(path),
+                    "{path} GLOB {pattern}"
+                );
+            }
+        }
+    }
+}
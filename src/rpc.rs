@@ -0,0 +1,393 @@
+//! # JSON-RPC access to a running [`WatchService`]
+//!
+//! A service started with [`WatchService::serve_rpc`] listens on a Unix domain socket or a local
+//! TCP port and answers [JSON-RPC 2.0](https://www.jsonrpc.org/specification) requests, one JSON
+//! object per line. Its one method, `execute`, takes an [`Op`] as params and returns the
+//! [`OpResult`] of [`WatchService::execute`]:
+//!
+//! ```text
+//! --> {"jsonrpc": "2.0", "id": 1, "method": "execute", "params": {"GetStates": {"query": ..., "limit": 20, "offset": null}}}
+//! <-- {"jsonrpc": "2.0", "id": 1, "result": {"Page": {"count": 42, "start": 0, "results": ...}}}
+//! --> {"jsonrpc": "2.0", "id": 2, "method": "execute", "params": "LoadNetworks"}
+//! <-- {"jsonrpc": "2.0", "id": 2, "result": {"Networks": [...]}}
+//! ```
+//!
+//! Queries are answered from the service's database and graph edits from its parse of the
+//! network, so CLI invocations and editor plugins don't reparse the repository per request.
+//! [`RpcClient`] is the Rust side of the protocol:
+//!
+//! ```rust,no_run
+//! use noet_core::{
+//!     commands::{Op, OpResult},
+//!     rpc::{RpcAddr, RpcClient},
+//! };
+//!
+//! # async fn run() -> Result<(), noet_core::BuildonomyError> {
+//! let mut client = RpcClient::connect(&"/tmp/noet.sock".parse::<RpcAddr>()?).await?;
+//! if let OpResult::Networks(networks) = client.execute(Op::LoadNetworks).await? {
+//!     println!("{} networks", networks.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use futures::{stream::FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fmt::{Display, Formatter},
+    future::Future,
+    net::SocketAddr,
+    path::PathBuf,
+    pin::Pin,
+    str::FromStr,
+};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::{
+    io::{
+        AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines, ReadHalf,
+        WriteHalf,
+    },
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{
+    commands::{Op, OpResult},
+    error::BuildonomyError,
+    watch::WatchService,
+};
+
+/// The JSON-RPC protocol version spoken by the service.
+pub const JSONRPC_VERSION: &str = "2.0";
+/// The method executing an [`Op`].
+pub const EXECUTE_METHOD: &str = "execute";
+
+/// The request line isn't JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// The request isn't a JSON-RPC request.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method isn't [`EXECUTE_METHOD`].
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The params aren't an [`Op`].
+pub const INVALID_PARAMS: i64 = -32602;
+/// Executing the op failed.
+pub const EXECUTION_ERROR: i64 = -32000;
+
+/// Where a service listens: a Unix domain socket path, or a TCP socket address.
+///
+/// Parsed from a string, anything that reads as a socket address (`127.0.0.1:9038`) is TCP and
+/// anything else a socket path. The service edits sources for whoever reaches it, so TCP
+/// addresses must be loopback ones.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RpcAddr {
+    #[cfg(unix)]
+    Unix(PathBuf),
+    Tcp(SocketAddr),
+}
+
+impl FromStr for RpcAddr {
+    type Err = BuildonomyError;
+
+    fn from_str(addr: &str) -> Result<Self, Self::Err> {
+        if let Ok(socket_addr) = addr.parse::<SocketAddr>() {
+            let addr = RpcAddr::Tcp(socket_addr);
+            addr.check_loopback()?;
+            return Ok(addr);
+        }
+        #[cfg(unix)]
+        if !addr.is_empty() {
+            return Ok(RpcAddr::Unix(PathBuf::from(addr)));
+        }
+        Err(BuildonomyError::Command(format!(
+            "'{addr}' is not an address to listen on"
+        )))
+    }
+}
+
+impl RpcAddr {
+    fn check_loopback(&self) -> Result<(), BuildonomyError> {
+        match self {
+            RpcAddr::Tcp(addr) if !addr.ip().is_loopback() => Err(BuildonomyError::Command(
+                format!("Refusing to listen on {addr}: TCP addresses must be loopback addresses"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Display for RpcAddr {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            #[cfg(unix)]
+            RpcAddr::Unix(path) => write!(f, "{}", path.display()),
+            RpcAddr::Tcp(addr) => write!(f, "{addr}"),
+        }
+    }
+}
+
+/// A JSON-RPC request. Requests without an `id` are notifications and get no response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// A JSON-RPC response, carrying either a `result` or an `error`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<OpResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcResponse {
+    fn result(id: Value, result: OpResult) -> Self {
+        RpcResponse {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        RpcResponse {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: None,
+            error: Some(RpcError {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+/// A bidirectional byte stream of either socket kind.
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+enum Listener {
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+    Tcp(TcpListener),
+}
+
+impl Listener {
+    async fn bind(addr: &RpcAddr) -> Result<Self, BuildonomyError> {
+        addr.check_loopback()?;
+        match addr {
+            #[cfg(unix)]
+            RpcAddr::Unix(path) => {
+                // A socket file nobody answers on is left over from a service that didn't shut
+                // down cleanly.
+                if path.exists() {
+                    if UnixStream::connect(path).await.is_ok() {
+                        return Err(BuildonomyError::Service(format!(
+                            "A service is already listening on {}",
+                            path.display()
+                        )));
+                    }
+                    std::fs::remove_file(path)?;
+                }
+                Ok(Listener::Unix(UnixListener::bind(path)?, path.clone()))
+            }
+            RpcAddr::Tcp(addr) => Ok(Listener::Tcp(TcpListener::bind(addr).await?)),
+        }
+    }
+
+    async fn accept(&self) -> std::io::Result<Box<dyn Connection>> {
+        match self {
+            #[cfg(unix)]
+            Listener::Unix(listener, _) => Ok(Box::new(listener.accept().await?.0)),
+            Listener::Tcp(listener) => Ok(Box::new(listener.accept().await?.0)),
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Answer requests on `addr` until `shutdown` completes, serving connections concurrently.
+pub(crate) async fn serve(
+    service: &WatchService,
+    addr: &RpcAddr,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), BuildonomyError> {
+    let listener = Listener::bind(addr).await?;
+    tracing::info!("[rpc] Listening on {addr}");
+    let mut connections: FuturesUnordered<Pin<Box<dyn Future<Output = ()> + '_>>> =
+        FuturesUnordered::new();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok(stream) => connections.push(Box::pin(async move {
+                    if let Err(err) = handle_connection(service, stream).await {
+                        tracing::warn!("[rpc] Connection closed: {err}");
+                    }
+                })),
+                Err(err) => tracing::warn!("[rpc] Failed to accept a connection: {err}"),
+            },
+            Some(()) = connections.next(), if !connections.is_empty() => {}
+        }
+    }
+    tracing::info!("[rpc] Stopped listening on {addr}");
+    Ok(())
+}
+
+async fn handle_connection(
+    service: &WatchService,
+    stream: Box<dyn Connection>,
+) -> Result<(), BuildonomyError> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = respond(service, &line).await {
+            let mut response = serde_json::to_string(&response)?;
+            response.push('\n');
+            writer.write_all(response.as_bytes()).await?;
+            writer.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+async fn respond(service: &WatchService, line: &str) -> Option<RpcResponse> {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(value) => value,
+        Err(err) => {
+            return Some(RpcResponse::error(
+                Value::Null,
+                PARSE_ERROR,
+                err.to_string(),
+            ))
+        }
+    };
+    let request = match serde_json::from_value::<RpcRequest>(request) {
+        Ok(request) if request.jsonrpc == JSONRPC_VERSION => request,
+        Ok(request) => {
+            return Some(RpcResponse::error(
+                request.id.unwrap_or_default(),
+                INVALID_REQUEST,
+                format!("Unsupported JSON-RPC version '{}'", request.jsonrpc),
+            ))
+        }
+        Err(err) => {
+            return Some(RpcResponse::error(
+                Value::Null,
+                INVALID_REQUEST,
+                err.to_string(),
+            ))
+        }
+    };
+    let response = execute(service, &request).await;
+    request.id.map(|id| match response {
+        Ok(result) => RpcResponse::result(id, result),
+        Err((code, message)) => RpcResponse::error(id, code, message),
+    })
+}
+
+async fn execute(service: &WatchService, request: &RpcRequest) -> Result<OpResult, (i64, String)> {
+    if request.method != EXECUTE_METHOD {
+        return Err((
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{}'", request.method),
+        ));
+    }
+    let op = serde_json::from_value::<Op>(request.params.clone())
+        .map_err(|err| (INVALID_PARAMS, err.to_string()))?;
+    tracing::debug!("[rpc] Executing {op}");
+    service
+        .execute(op)
+        .await
+        .map_err(|err| (EXECUTION_ERROR, err.to_string()))
+}
+
+/// A connection to a service listening with [`WatchService::serve_rpc`].
+pub struct RpcClient {
+    lines: Lines<BufReader<ReadHalf<Box<dyn Connection>>>>,
+    writer: WriteHalf<Box<dyn Connection>>,
+    next_id: u64,
+}
+
+impl RpcClient {
+    pub async fn connect(addr: &RpcAddr) -> Result<Self, BuildonomyError> {
+        let stream: Box<dyn Connection> = match addr {
+            #[cfg(unix)]
+            RpcAddr::Unix(path) => Box::new(UnixStream::connect(path).await?),
+            RpcAddr::Tcp(addr) => Box::new(TcpStream::connect(addr).await?),
+        };
+        let (reader, writer) = tokio::io::split(stream);
+        Ok(RpcClient {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            next_id: 1,
+        })
+    }
+
+    /// Execute `op` on the service. Errors the service reports are returned as
+    /// [`BuildonomyError::Service`].
+    pub async fn execute(&mut self, op: Op) -> Result<OpResult, BuildonomyError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = RpcRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Some(Value::from(id)),
+            method: EXECUTE_METHOD.to_string(),
+            params: serde_json::to_value(&op)?,
+        };
+        let mut request = serde_json::to_string(&request)?;
+        request.push('\n');
+        self.writer.write_all(request.as_bytes()).await?;
+        self.writer.flush().await?;
+
+        let Some(line) = self.lines.next_line().await? else {
+            return Err(BuildonomyError::Service(
+                "The service closed the connection".to_string(),
+            ));
+        };
+        let response = serde_json::from_str::<RpcResponse>(&line)?;
+        if response.id != id {
+            return Err(BuildonomyError::Service(format!(
+                "Expected the response to request {id}, got {}",
+                response.id
+            )));
+        }
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(BuildonomyError::Service(format!(
+                "{} ({})",
+                error.message, error.code
+            ))),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(BuildonomyError::Service(format!(
+                "Response to request {id} has neither a result nor an error"
+            ))),
+        }
+    }
+}
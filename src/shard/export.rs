@@ -47,7 +47,7 @@ use crate::{
             estimate_size_mb, network_shard_meta, GlobalShardMeta, SearchManifest, ShardConfig,
             ShardManifest,
         },
-        wire::{GlobalShard, NetworkShard, SerializableBidGraph},
+        wire::{GlobalShard, GraphExport, NetworkShard, SerializableBidGraph},
     },
 };
 use std::{
@@ -96,7 +96,7 @@ pub async fn export_beliefbase(
     search_manifest: &SearchManifest,
 ) -> Result<ExportMode, BuildonomyError> {
     // Serialize the full graph to measure its size.
-    let json_string = serde_json::to_string_pretty(&graph)
+    let json_string = serde_json::to_string_pretty(&GraphExport::new(&graph))
         .map_err(|e| BuildonomyError::Serialization(e.to_string()))?;
     let total_bytes = json_string.len();
 
@@ -168,13 +168,15 @@ async fn export_sharded(
     let partition = partition_graph(&graph, pathmap);
 
     // ── Write global shard ────────────────────────────────────────────────
+    let global_edges = SerializableBidGraph::from_bid_graph(&partition.global_relations);
     let global_shard = GlobalShard {
+        relation_kinds: global_edges.relation_kinds(),
         states: partition
             .global_states
             .iter()
             .filter_map(|bid| graph.states.get(bid).map(|n| (bid.to_string(), n.clone())))
             .collect(),
-        relations: SerializableBidGraph::from_bid_graph(&partition.global_relations),
+        relations: global_edges,
     };
 
     let global_json = serde_json::to_string_pretty(&global_shard)
@@ -210,14 +212,16 @@ async fn export_sharded(
             false,
         );
 
+        let net_edges = SerializableBidGraph::from_bid_graph(&BidGraph::from(net_relations));
         let net_shard = NetworkShard {
+            relation_kinds: net_edges.relation_kinds(),
             network_bref: net_bref.to_string(),
             network_bid: net_bid.to_string(),
             states: net_states
                 .iter()
                 .filter_map(|bid| graph.states.get(bid).map(|n| (bid.to_string(), n.clone())))
                 .collect(),
-            relations: SerializableBidGraph::from_bid_graph(&BidGraph::from(net_relations)),
+            relations: net_edges,
         };
 
         let shard_json = serde_json::to_string_pretty(&net_shard)
@@ -421,6 +425,7 @@ mod tests {
         let node = make_node("Test Doc", BeliefKind::Document);
         let bid = node.bid;
         let shard = NetworkShard {
+            relation_kinds: vec![],
             network_bref: "01abc".to_string(),
             network_bid: bid.to_string(),
             states: [(bid.to_string(), node)].into_iter().collect(),
@@ -497,6 +502,7 @@ mod tests {
         let bid_b = node_b.bid;
 
         let shard = NetworkShard {
+            relation_kinds: vec![],
             network_bref: "01abc".to_string(),
             network_bid: bid_a.to_string(),
             states: [
@@ -561,6 +567,7 @@ mod tests {
         let bid = node.bid;
 
         let shard = GlobalShard {
+            relation_kinds: vec![],
             states: [(bid.to_string(), node.clone())].into_iter().collect(),
             relations: SerializableBidGraph::default(),
         };
@@ -600,6 +607,80 @@ mod tests {
         );
     }
 
+    /// Exports list the registered relation kinds their relations use, and `from_json` registers
+    /// them before deserializing, as `BeliefBaseWasm::from_json` and `load_shard` do. The kind is
+    /// renamed after export to stand in for a reader that never registered it.
+    #[tokio::test]
+    async fn test_relation_kinds_round_trip() {
+        use crate::{properties::WeightKind, shard::wire::from_json};
+
+        let supersedes = WeightKind::register("wire-supersedes").unwrap();
+        let node_a = make_node("Node A", BeliefKind::Document);
+        let node_b = make_node("Node B", BeliefKind::Document);
+        let (bid_a, bid_b) = (node_a.bid, node_b.bid);
+        let graph = BeliefGraph {
+            states: [(bid_a, node_a), (bid_b, node_b)].into_iter().collect(),
+            relations: BidGraph::from_edges(vec![(
+                bid_a,
+                bid_b,
+                crate::properties::WeightSet::from(supersedes),
+            )]),
+        };
+        let edge_kinds = |relations: &BidGraph| {
+            relations
+                .as_graph()
+                .raw_edges()
+                .iter()
+                .flat_map(|edge| edge.weight.weights.keys().map(|kind| kind.name()))
+                .collect::<Vec<_>>()
+        };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = ShardConfig::default();
+        for (threshold, renamed) in [(usize::MAX, "wire-replaces"), (1, "wire-obsoletes")] {
+            config.shard_threshold = threshold;
+            export_beliefbase(
+                graph.clone(),
+                &PathMapMap::default(),
+                temp_dir.path(),
+                &config,
+                &SearchManifest::new(),
+            )
+            .await
+            .unwrap();
+            assert!(WeightKind::try_from(renamed).is_err());
+
+            let relations = if threshold == usize::MAX {
+                let json = std::fs::read_to_string(temp_dir.path().join("beliefbase.json"))
+                    .unwrap()
+                    .replace("wire-supersedes", renamed);
+                assert!(serde_json::from_str::<BeliefGraph>(&json).is_err());
+                from_json::<BeliefGraph>(&json).unwrap().relations
+            } else {
+                let json = std::fs::read_to_string(temp_dir.path().join("beliefbase/global.json"))
+                    .unwrap()
+                    .replace("wire-supersedes", renamed);
+                assert!(serde_json::from_str::<GlobalShard>(&json).is_err());
+                let shard = from_json::<GlobalShard>(&json).unwrap();
+                assert_eq!(shard.relation_kinds, vec![renamed.to_string()]);
+                BidGraph::from_edges(
+                    shard
+                        .relations
+                        .edges
+                        .into_iter()
+                        .map(|edge| {
+                            let source = Bid::try_from(edge.source.as_str()).unwrap();
+                            let sink = Bid::try_from(edge.sink.as_str()).unwrap();
+                            (source, sink, edge.weights)
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            };
+            assert!(WeightKind::try_from(renamed).is_ok());
+            assert_eq!(edge_kinds(&relations), vec![renamed.to_string()]);
+        }
+    }
+
     /// Verify that nodes shared between two loaded shards are not removed when
     /// only one shard is unloaded. This mirrors the `still_needed` filtering
     /// in `BeliefBaseWasm::unload_shard`.
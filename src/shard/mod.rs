@@ -58,4 +58,4 @@ pub use manifest::{SearchManifest, ShardConfig, ShardManifest, SHARD_THRESHOLD};
 #[cfg(not(target_arch = "wasm32"))]
 pub use search::{build_search_indices, SearchIndex};
 
-pub use wire::{GlobalShard, NetworkShard, SerializableBidGraph, SerializableEdge};
+pub use wire::{GlobalShard, GraphExport, NetworkShard, SerializableBidGraph, SerializableEdge};
@@ -14,15 +14,27 @@
 //! - [`GlobalShard`] — contents of `beliefbase/global.json`
 //! - [`SerializableBidGraph`] — portable edge list (BID strings, not petgraph indices)
 //! - [`SerializableEdge`] — one edge in a [`SerializableBidGraph`]
+//! - [`GraphExport`] — contents of the monolithic `beliefbase.json`
+//!
+//! ## Relation kinds
+//!
+//! Relations of application-registered kinds (e.g. `supersedes`) serialize by kind name, and a
+//! name only deserializes once it is registered in the reading process. Each document therefore
+//! lists the registered kinds its relations use in a `relation_kinds` field ahead of them, and
+//! [`from_json`] registers those before deserializing the rest.
 //!
 //! ## References
 //!
 //! - `docs/design/search_and_sharding.md` §5 — Per-network shard format
 //! - Issue 50: BeliefBase Sharding
 
-use crate::properties::{BeliefNode, WeightSet};
-use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use crate::{
+    beliefbase::BeliefGraph,
+    error::BuildonomyError,
+    properties::{BeliefNode, WeightKind, WeightSet},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
 // ── Per-network shard ─────────────────────────────────────────────────────────
 
@@ -36,6 +48,9 @@ use std::collections::BTreeMap;
 /// See `docs/design/search_and_sharding.md` §5 for the schema.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkShard {
+    /// Registered relation kinds used by `relations`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relation_kinds: Vec<String>,
     /// Short reference (5 hex chars) of the network.
     pub network_bref: String,
     /// Full BID of the network node.
@@ -55,6 +70,9 @@ pub struct NetworkShard {
 /// link resolution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlobalShard {
+    /// Registered relation kinds used by `relations`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relation_kinds: Vec<String>,
     /// Nodes that belong to no specific network: API node, namespace roots,
     /// and any node not found in any network's PathMap.
     pub states: BTreeMap<String, BeliefNode>,
@@ -62,6 +80,57 @@ pub struct GlobalShard {
     pub relations: SerializableBidGraph,
 }
 
+// ── Monolithic export ────────────────────────────────────────────────────────
+
+/// The JSON representation of the monolithic `beliefbase.json`: the exported
+/// `BeliefGraph`, preceded by the registered relation kinds its relations use.
+/// Reads back as a plain `BeliefGraph` through [`from_json`].
+#[derive(Debug, Serialize)]
+pub struct GraphExport<'a> {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub relation_kinds: Vec<String>,
+    #[serde(flatten)]
+    pub graph: &'a BeliefGraph,
+}
+
+impl<'a> GraphExport<'a> {
+    pub fn new(graph: &'a BeliefGraph) -> Self {
+        let g = graph.relations.as_graph();
+        Self {
+            relation_kinds: relation_kinds(g.raw_edges().iter().map(|e| &e.weight)),
+            graph,
+        }
+    }
+}
+
+// ── Relation kinds ────────────────────────────────────────────────────────────
+
+/// Names of the registered relation kinds among `weights`, sorted.
+pub fn relation_kinds<'a>(weights: impl IntoIterator<Item = &'a WeightSet>) -> Vec<String> {
+    weights
+        .into_iter()
+        .flat_map(|weights| weights.weights.keys())
+        .filter(|kind| kind.is_custom())
+        .map(|kind| kind.name())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Deserialize a shard or `beliefbase.json`, first registering the relation kinds it lists so
+/// its relations of those kinds can be read.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, BuildonomyError> {
+    #[derive(Deserialize)]
+    struct Listed {
+        #[serde(default)]
+        relation_kinds: Vec<String>,
+    }
+    for name in serde_json::from_str::<Listed>(json)?.relation_kinds {
+        WeightKind::register(&name)?;
+    }
+    Ok(serde_json::from_str(json)?)
+}
+
 // ── Portable BidGraph serialization ──────────────────────────────────────────
 
 /// A portable serialization of a `BidGraph` as a list of `(source, sink, weights)` triples.
@@ -74,6 +143,13 @@ pub struct SerializableBidGraph {
     pub edges: Vec<SerializableEdge>,
 }
 
+impl SerializableBidGraph {
+    /// Names of the registered relation kinds among the edges, for a shard's `relation_kinds`.
+    pub fn relation_kinds(&self) -> Vec<String> {
+        relation_kinds(self.edges.iter().map(|edge| &edge.weights))
+    }
+}
+
 /// One edge in a [`SerializableBidGraph`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SerializableEdge {
@@ -166,8 +166,8 @@ use crate::{
     nodekey::NodeKey,
     paths::AnchorPath,
     properties::{
-        asset_namespace, buildonomy_namespace, content_namespaces, href_namespace, BeliefKind,
-        BeliefNode, Bid, Bref, WeightKind, WEIGHT_SORT_KEY,
+        asset_namespace, buildonomy_namespace, content_namespaces, href_namespace, tag_namespace,
+        BeliefKind, BeliefNode, Bid, Bref, WeightKind, WEIGHT_SORT_KEY,
     },
     query::{Expression, StatePred},
 };
@@ -406,6 +406,29 @@ impl BeliefBaseWasm {
         }
     }
 
+    /// Register an application-defined relation kind so edges of that kind can be looked up by
+    /// name in `NodeContext.graph`. Returns the kind's name; registering a name twice is a no-op.
+    /// The kinds an exported `beliefbase.json` or shard uses are listed in it and registered as
+    /// it loads, so this is only needed for kinds the data doesn't carry yet.
+    ///
+    /// # JavaScript Example
+    /// ```javascript,ignore
+    /// BeliefBaseWasm.registerRelationKind("supersedes");
+    /// const superseded = ctx.graph.get("supersedes");
+    /// ```
+    #[wasm_bindgen(js_name = registerRelationKind)]
+    pub fn register_relation_kind(name: &str) -> Result<String, JsValue> {
+        WeightKind::register(name)
+            .map(|kind| kind.name())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// The names of every relation kind: the built-in kinds followed by the registered ones.
+    #[wasm_bindgen(js_name = relationKinds)]
+    pub fn relation_kinds() -> Vec<String> {
+        WeightKind::all().iter().map(|kind| kind.name()).collect()
+    }
+
     /// Get the entry point as a plain JS object `{ bid, bref }`.
     ///
     /// # JavaScript Example
@@ -535,8 +558,8 @@ impl BeliefBaseWasm {
     /// ```
     #[wasm_bindgen(constructor)]
     pub fn from_json(data: String, entry_bid_str: String) -> Result<BeliefBaseWasm, JsValue> {
-        // Parse JSON into BeliefGraph
-        let graph: BeliefGraph = serde_json::from_str(&data).map_err(|e| {
+        // Parse JSON into BeliefGraph, registering the relation kinds it lists first
+        let graph: BeliefGraph = crate::shard::wire::from_json(&data).map_err(|e| {
             let msg = format!("❌ Failed to parse BeliefGraph JSON: {}", e);
             console::error_1(&msg.clone().into());
             JsValue::from_str(&msg)
@@ -674,8 +697,8 @@ impl BeliefBaseWasm {
             BTreeMap<String, BeliefNode>,
             Vec<(Bid, Bid, crate::properties::WeightSet)>,
         ) = if bref_key == "global" {
-            let shard: crate::shard::GlobalShard =
-                serde_json::from_str(&shard_json).map_err(|e| {
+            let shard: crate::shard::GlobalShard = crate::shard::wire::from_json(&shard_json)
+                .map_err(|e| {
                     let msg = format!("❌ Failed to parse global shard: {}", e);
                     console::error_1(&msg.clone().into());
                     JsValue::from_str(&msg)
@@ -692,8 +715,8 @@ impl BeliefBaseWasm {
                 .collect();
             (shard.states, edges)
         } else {
-            let shard: crate::shard::NetworkShard =
-                serde_json::from_str(&shard_json).map_err(|e| {
+            let shard: crate::shard::NetworkShard = crate::shard::wire::from_json(&shard_json)
+                .map_err(|e| {
                     let msg = format!("❌ Failed to parse network shard '{}': {}", bref_key, e);
                     console::error_1(&msg.clone().into());
                     JsValue::from_str(&msg)
@@ -1341,6 +1364,19 @@ impl BeliefBaseWasm {
         BidBrefResult::from_bid(asset_namespace()).to_js()
     }
 
+    /// Get tag namespace BID (`#tag` tracking network)
+    ///
+    /// See `docs/design/architecture.md` § 10 for network namespace details.
+    ///
+    /// # JavaScript Example
+    /// ```javascript,ignore
+    /// const tag_bid = BeliefBaseWasm.tag_namespace();
+    /// ```
+    #[wasm_bindgen]
+    pub fn tag_namespace() -> JsValue {
+        BidBrefResult::from_bid(tag_namespace()).to_js()
+    }
+
     /// Get buildonomy namespace BID (API node for version management)
     ///
     /// See `docs/design/architecture.md` § 10 for network namespace details.
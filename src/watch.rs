@@ -239,20 +239,26 @@
 //! - [`DocumentCompiler`] - The underlying compiler
 //! - [`Event`] - Events emitted by the service
 //! - [`DbConnection`] - Database connection wrapper
+//! - [`rpc`] - JSON-RPC access to a running service ([`WatchService::serve_rpc`])
 //! - [`LatticeConfigProvider`] - Configuration interface
 
 use crate::{
-    beliefbase::BeliefGraph,
+    beliefbase::{BeliefBase, BeliefGraph},
     codec::{
         compiler::{CompilerStats, DocumentCompiler},
-        network::detect_network_file,
-        CodecMap,
+        network::{detect_network_file, NETWORK_NAME},
+        CodecMap, SCHEMAS,
     },
+    commands::{Op, OpExecutor, OpResult},
     config::{LatticeConfigProvider, NetworkRecord, TomlConfigProvider},
     db::{db_init, DbConnection, Transaction},
     error::BuildonomyError,
     event::{BeliefEvent, Event},
-    query::{BeliefSource, PaginatedQuery, Query, ResultsPage},
+    nodekey::NodeKey,
+    paths::{os_path_to_string, string_to_os_path},
+    properties::Bid,
+    query::{BeliefSource, Expression, OrderBy, PaginatedQuery, Query, ResultsPage, StatePred},
+    rpc::{self, RpcAddr},
 };
 
 use notify_debouncer_full::{
@@ -263,8 +269,9 @@ use notify_debouncer_full::{
 use parking_lot::{Mutex, RwLock};
 use serde::Deserialize;
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     fs::{read_to_string, write},
+    future::Future,
     path::{Path, PathBuf},
     result::Result,
     sync::{
@@ -296,12 +303,21 @@ type NetworkWatcherMap = HashMap<PathBuf, WatcherWithSyncer>;
 #[derive(Default)]
 struct BnWatchers(pub Arc<Mutex<NetworkWatcherMap>>);
 
+/// Map of evaluated queries and their result orders to when they were evaluated, their results and
+/// the order of those results
+type PaginatedQueryMap = HashMap<(Query, Vec<OrderBy>), (SystemTime, BeliefGraph, Vec<Bid>)>;
+
 #[derive(Default)]
-struct PaginationCache(pub Arc<RwLock<HashMap<Query, (SystemTime, BeliefGraph)>>>);
+struct PaginationCache(pub Arc<RwLock<PaginatedQueryMap>>);
+
+/// Graph editors of the watched networks, each with the commit generation of the network's
+/// syncer it was loaded at
+type NetworkEditorMap = HashMap<PathBuf, (u64, OpExecutor)>;
 
 pub struct WatchService {
     watchers: Arc<Mutex<BnWatchers>>,
     pagination_cache: Arc<Mutex<PaginationCache>>,
+    editors: tokio::sync::Mutex<NetworkEditorMap>,
     db: DbConnection,
     codecs: CodecMap,
     event_tx: Sender<Event>,
@@ -354,6 +370,7 @@ impl WatchService {
         Ok(WatchService {
             watchers: Arc::new(Mutex::new(BnWatchers::default())),
             pagination_cache: Arc::new(Mutex::new(PaginationCache::default())),
+            editors: tokio::sync::Mutex::new(HashMap::new()),
             db,
             codecs,
             event_tx,
@@ -505,14 +522,15 @@ impl WatchService {
         &self,
         pq: PaginatedQuery,
     ) -> Result<ResultsPage<BeliefGraph>, BuildonomyError> {
+        let cache_key = (pq.query.clone(), pq.order_by.clone());
         let (mut maybe_page, pagination_complete) = {
             while self.pagination_cache.lock().0.is_locked() {
                 tracing::info!("[client operation] Waiting for read access to query cache");
                 sleep(Duration::from_millis(100)).await;
             }
             let cache = self.pagination_cache.lock().0.read_arc();
-            if let Some((_, res)) = cache.get(&pq.query) {
-                let page = res.paginate(pq.limit, pq.offset);
+            if let Some((_, res, order)) = cache.get(&cache_key) {
+                let page = res.paginate(&pq, order);
                 tracing::debug!("Returning page from cache. Page len: {}", page.count);
                 let completely_paged =
                     page.count <= pq.offset.unwrap_or(0) + page.results.states.len();
@@ -528,7 +546,7 @@ impl WatchService {
                 sleep(Duration::from_millis(100)).await;
             }
             let mut cache = self.pagination_cache.lock().0.write_arc();
-            cache.remove(&pq.query);
+            cache.remove(&cache_key);
         }
 
         let page = match maybe_page.take() {
@@ -537,7 +555,9 @@ impl WatchService {
                 tracing::debug!("No cached query. Freshly evaluating ...");
                 let connection = self.db_connection();
                 let fresh_res = connection.eval_query(&pq.query, false).await?;
-                let page = fresh_res.paginate(pq.limit, pq.offset);
+                let bids = fresh_res.states.keys().copied().collect::<Vec<_>>();
+                let order = connection.order_bids(&bids, &pq.order_by).await?;
+                let page = fresh_res.paginate(&pq, &order);
                 tracing::debug!("Returning fresh page. Page len: {}", page.count);
                 {
                     tracing::debug!("Caching query");
@@ -546,7 +566,7 @@ impl WatchService {
                         sleep(Duration::from_millis(100)).await;
                     }
                     let mut cache = self.pagination_cache.lock().0.write_arc();
-                    cache.insert(pq.query.clone(), (SystemTime::now(), fresh_res));
+                    cache.insert(cache_key, (SystemTime::now(), fresh_res, order));
                 }
                 page
             }
@@ -555,6 +575,155 @@ impl WatchService {
         Ok(page)
     }
 
+    /// Execute an [`Op`] against the watched networks.
+    ///
+    /// Queries are answered from the database. Graph edits are made by an [`OpExecutor`] for the
+    /// network holding the edited node (for [`Op::MovePath`], the moved path), and the watcher
+    /// then syncs the written files like any other change. A move also rebases the moved
+    /// subtree's rows in the database's `paths` table. A network's editor is reloaded once
+    /// its syncer has committed changes since the editor was loaded.
+    ///
+    /// Paths in [`Op::UpdateContent`] and [`Op::MovePath`] are absolute, or relative to the root
+    /// of a watched network.
+    pub async fn execute(&self, op: Op) -> Result<OpResult, BuildonomyError> {
+        match op {
+            Op::LoadNetworks => Ok(OpResult::Networks(self.get_networks()?)),
+            Op::SetNetworks(networks) => Ok(OpResult::Networks(self.set_networks(Some(networks))?)),
+            Op::GetStates(pq) => Ok(OpResult::Page(self.get_states(pq).await?)),
+            Op::UpdateContent(path, content) => {
+                let (root, path) = self.network_path(&path)?;
+                self.set_content(root.join(string_to_os_path(&path)), content)
+                    .await?;
+                Ok(OpResult::Ok)
+            }
+            Op::MovePath(from, to) => {
+                let (root, from) = self.network_path(&from)?;
+                let to = relative_to(&root, &string_to_os_path(&to)).ok_or_else(|| {
+                    BuildonomyError::Command(format!(
+                        "Can't move {from} out of its network at {root:?}"
+                    ))
+                })?;
+                let mut editors = self.editors.lock().await;
+                self.refresh_editor(&mut editors, &root).await?;
+                let (_, executor) = editors.get_mut(&root).expect("refreshed editor");
+                let result = executor.execute(Op::MovePath(from, to)).await?;
+                // The syncer reparses the moved files, but only the editor knows which paths
+                // they moved from.
+                let mut transaction = Transaction::new();
+                for event in executor.take_path_events() {
+                    transaction.add_event(&event)?;
+                }
+                if transaction.has_pending() {
+                    transaction.execute(&self.db.0).await?;
+                }
+                Ok(result)
+            }
+            op => self.edit(op).await,
+        }
+    }
+
+    /// Answer JSON-RPC requests to [`execute`](Self::execute) ops on `addr` until `shutdown`
+    /// completes, blocking the calling thread. See [`rpc`] for the protocol.
+    pub fn serve_rpc(
+        &self,
+        addr: &RpcAddr,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), BuildonomyError> {
+        self.runtime.block_on(rpc::serve(self, addr, shutdown))
+    }
+
+    async fn edit(&self, op: Op) -> Result<OpResult, BuildonomyError> {
+        let Some(node) = edited_node(&op).cloned() else {
+            return Err(BuildonomyError::Command(format!(
+                "{op} doesn't edit a node"
+            )));
+        };
+        let roots = self.watched_networks();
+        let mut editors = self.editors.lock().await;
+        editors.retain(|root, _| roots.contains(root));
+        for root in roots.iter() {
+            self.refresh_editor(&mut editors, root).await?;
+            let (_, executor) = editors.get_mut(root).expect("refreshed editor");
+            if executor.cache().get(&node).is_some() {
+                return executor.execute(op).await;
+            }
+        }
+        Err(BuildonomyError::NotFound(format!(
+            "No watched network contains {node}"
+        )))
+    }
+
+    /// (Re)load the editor of the network at `root` if its syncer has committed since.
+    async fn refresh_editor(
+        &self,
+        editors: &mut NetworkEditorMap,
+        root: &Path,
+    ) -> Result<(), BuildonomyError> {
+        let generation = {
+            let binding = self.watchers.lock();
+            let watchers = binding.0.lock();
+            watchers
+                .get(root)
+                .map(|(_, syncer)| syncer.commit_generation.load(Ordering::SeqCst))
+                .unwrap_or_default()
+        };
+        if editors
+            .get(root)
+            .is_some_and(|(loaded, _)| *loaded == generation)
+        {
+            return Ok(());
+        }
+        tracing::debug!("[WatchService] Loading graph editor for {root:?}");
+        let known = self
+            .db
+            .eval_query(
+                &Query {
+                    seed: Expression::StateIn(StatePred::Any),
+                    traverse: None,
+                },
+                false,
+            )
+            .await?;
+        let mut executor = OpExecutor::with_cache(root, BeliefBase::from(known))?;
+        executor.load().await?;
+        editors.insert(root.to_path_buf(), (generation, executor));
+        Ok(())
+    }
+
+    fn watched_networks(&self) -> Vec<PathBuf> {
+        let binding = self.watchers.lock();
+        let mut roots = binding.0.lock().keys().cloned().collect::<Vec<_>>();
+        roots.sort();
+        roots
+    }
+
+    /// The root of the watched network holding `path`, and `path` relative to it. A relative
+    /// path belongs to the only watched network, or else to the first one it exists in. Paths
+    /// that resolve outside of the network, through `..` or a symlinked directory, are rejected.
+    fn network_path(&self, path: &str) -> Result<(PathBuf, String), BuildonomyError> {
+        let os_path = string_to_os_path(path);
+        let roots = self.watched_networks();
+        let found = if os_path.is_absolute() {
+            roots.into_iter().find_map(|root| {
+                let relative = relative_to(&root, &os_path)?;
+                Some((root, relative))
+            })
+        } else if roots.len() == 1 {
+            roots
+                .into_iter()
+                .next()
+                .map(|root| (root, path.to_string()))
+        } else {
+            roots
+                .into_iter()
+                .find(|root| root.join(&os_path).exists())
+                .map(|root| (root, path.to_string()))
+        };
+        found
+            .filter(|(root, relative)| is_within(root, &root.join(string_to_os_path(relative))))
+            .ok_or_else(|| BuildonomyError::NotFound(format!("{path} is not in a watched network")))
+    }
+
     pub fn enable_network_syncer(&self, repo_path: &PathBuf) -> Result<(), BuildonomyError> {
         let binding = self.watchers.lock();
         let mut watchers = binding.0.lock();
@@ -623,6 +792,21 @@ impl WatchService {
                                         return;
                                     }
 
+                                    // Schema files, and the network files that may declare
+                                    // them, reload the schemas of their network. This includes
+                                    // removed schema files, so it precedes the is_file filter.
+                                    let schema_networks: BTreeSet<PathBuf> = event
+                                        .paths
+                                        .iter()
+                                        .filter_map(|p| {
+                                            SCHEMAS.schema_network(p).or_else(|| {
+                                                p.ends_with(NETWORK_NAME)
+                                                    .then(|| p.parent().map(Path::to_path_buf))
+                                                    .flatten()
+                                            })
+                                        })
+                                        .collect();
+
                                     // Filter paths to only include files with registered codec extensions
                                     // Note: Assets are discovered and tracked during document parsing
                                     let sync_paths: Vec<&PathBuf> = event
@@ -655,11 +839,12 @@ impl WatchService {
                                                 }
                                             }
 
-                                            debouncer_codec.path_get(p).is_some()
+                                            debouncer_codec.document_get(p).is_some()
+                                                && SCHEMAS.schema_network(p).is_none()
                                         })
                                         .collect();
 
-                                    if !sync_paths.is_empty() {
+                                    if !sync_paths.is_empty() || !schema_networks.is_empty() {
                                         // Enqueue changed files for re-parsing
                                         tracing::info!(
                                             "[Debouncer] {} files to enqueue",
@@ -673,6 +858,13 @@ impl WatchService {
                                         }
                                         tracing::info!("[Debouncer] Acquired write lock");
                                         let mut compiler = compiler_ref.write();
+                                        for network_dir in schema_networks {
+                                            tracing::info!(
+                                                "[Debouncer] Schemas changed, reloading network: {:?}",
+                                                network_dir
+                                            );
+                                            compiler.reload_schemas(network_dir);
+                                        }
                                         for path in sync_paths {
                                             tracing::info!(
                                                 "[Debouncer] File changed, enqueuing for re-parse: {:?}",
@@ -721,6 +913,39 @@ impl WatchService {
     }
 }
 
+/// The node an [`OpExecutor`] edits the source of to carry out `op`.
+fn edited_node(op: &Op) -> Option<&NodeKey> {
+    match op {
+        Op::CreateNode { parent, .. } => Some(parent),
+        Op::SetPayload { node, .. } | Op::RenameNode(node, _) | Op::DeleteNode(node) => Some(node),
+        Op::AddRelation { sink, .. } | Op::RemoveRelation { sink, .. } => Some(sink),
+        _ => None,
+    }
+}
+
+/// `path` relative to `root`, for a relative path or an absolute one under `root`.
+fn relative_to(root: &Path, path: &Path) -> Option<String> {
+    if path.is_relative() {
+        return Some(os_path_to_string(path));
+    }
+    path.strip_prefix(root)
+        .ok()
+        .or_else(|| path.strip_prefix(root.canonicalize().ok()?).ok())
+        .map(os_path_to_string)
+}
+
+/// Whether `path` names an entry of a directory within `root`, once its parent directory is
+/// resolved.
+fn is_within(root: &Path, path: &Path) -> bool {
+    let (Some(parent), Some(_)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    match (parent.canonicalize(), root.canonicalize()) {
+        (Ok(parent), Ok(root)) => parent.starts_with(root),
+        _ => false,
+    }
+}
+
 pub(crate) struct FileUpdateSyncer {
     pub compiler: Arc<RwLock<DocumentCompiler>>,
     pub compiler_handle: JoinHandle<Result<(), BuildonomyError>>,
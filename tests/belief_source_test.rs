@@ -26,8 +26,15 @@ use test_log::test;
 use noet_core::{
     beliefbase::{BeliefBase, BeliefGraph, BidGraph},
     db::{db_init, DbConnection, Transaction},
-    properties::{buildonomy_namespace, BeliefNode, BeliefRelation, Bid, WeightKind, WeightSet},
-    query::{BeliefSource, Expression, RelationPred, StatePred},
+    event::{BeliefEvent, EventOrigin},
+    properties::{
+        buildonomy_namespace, BeliefKind, BeliefKindSet, BeliefNode, BeliefRelation, Bid, Weight,
+        WeightKind, WeightSet, WEIGHT_SORT_KEY,
+    },
+    query::{
+        BeliefSource, Comparison, Expression, NeighborsExpression, OrderBy, OrderKey,
+        PaginatedQuery, PayloadValue, Query, RelationPred, StatePred, MAX_TRAVERSAL,
+    },
 };
 
 /// Test that DbConnection and BeliefBase return identical results for the same queries
@@ -264,6 +271,382 @@ async fn test_belief_source_equivalence() -> Result<(), Box<dyn std::error::Erro
     Ok(())
 }
 
+/// Relations of registered kinds are stored alongside the built-in kinds and can be queried and
+/// traced by kind. A database created before registered kinds existed is upgraded on init.
+#[test(tokio::test)]
+async fn test_custom_relation_kind_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let supersedes = WeightKind::register("supersedes")?;
+    assert_eq!(WeightKind::register("supersedes")?, supersedes);
+    assert!(WeightKind::register("Section").is_err());
+    assert!(WeightKind::register("not a kind").is_err());
+
+    let test_tempdir = tempdir()?;
+    let db_path = test_tempdir.path().join("test_belief_cache.db");
+    let db_pool = db_init(db_path.clone()).await?;
+    sqlx::query("ALTER TABLE relations DROP COLUMN custom; DROP TABLE relation_kinds;")
+        .execute(&db_pool)
+        .await?;
+    sqlx::query("DELETE FROM _sqlx_migrations WHERE version = 2")
+        .execute(&db_pool)
+        .await?;
+    db_pool.close().await;
+    let db = DbConnection(db_init(db_path).await?);
+
+    let net_bid = Bid::new(buildonomy_namespace());
+    let old_bid = Bid::new(net_bid);
+    let new_bid = Bid::new(net_bid);
+    let mut states = BTreeMap::new();
+    for (bid, title) in [(net_bid, "Net"), (old_bid, "Old"), (new_bid, "New")] {
+        states.insert(
+            bid,
+            BeliefNode {
+                bid,
+                kind: Default::default(),
+                title: title.to_string(),
+                schema: None,
+                payload: Default::default(),
+                id: None,
+            },
+        );
+    }
+    let mut superseded = WeightSet::from(supersedes);
+    superseded.set(WeightKind::Epistemic, Default::default());
+    let edges = vec![
+        BeliefRelation {
+            source: net_bid,
+            sink: old_bid,
+            weights: WeightSet::from(WeightKind::Section),
+        },
+        BeliefRelation {
+            source: net_bid,
+            sink: new_bid,
+            weights: WeightSet::from(WeightKind::Section),
+        },
+        BeliefRelation {
+            source: new_bid,
+            sink: old_bid,
+            weights: superseded.clone(),
+        },
+    ];
+    let test_bb = BeliefBase::new(states, BidGraph::from_edges(edges))?;
+
+    let parsed_nodes: BTreeSet<Bid> = test_bb.states().keys().copied().collect();
+    let mut transaction = Transaction::default();
+    for event in BeliefBase::compute_diff(&BeliefBase::empty(), &test_bb, &parsed_nodes)? {
+        transaction.add_event(&event).ok();
+    }
+    transaction.execute(&db.0).await?;
+
+    let exported = db.export_beliefgraph().await?;
+    let weights = exported
+        .relations
+        .as_graph()
+        .raw_edges()
+        .iter()
+        .find(|edge| {
+            exported.relations.as_graph()[edge.source()] == new_bid
+                && exported.relations.as_graph()[edge.target()] == old_bid
+        })
+        .map(|edge| edge.weight.clone())
+        .expect("supersedes edge");
+    assert_eq!(weights, superseded);
+
+    let expr_kind = Expression::RelationIn(RelationPred::Kind(WeightSet::from(supersedes)));
+    for result in [
+        test_bb.eval_unbalanced(&expr_kind).await?,
+        db.eval_unbalanced(&expr_kind).await?,
+    ] {
+        assert!(result.states.contains_key(&new_bid));
+        assert!(result.states.contains_key(&old_bid));
+    }
+
+    let expr_all = Expression::StateIn(StatePred::Any);
+    let session_trace = test_bb
+        .eval_trace(&expr_all, WeightSet::from(supersedes))
+        .await?;
+    let db_trace = db
+        .eval_trace(&expr_all, WeightSet::from(supersedes))
+        .await?;
+    assert_belief_graphs_equivalent(
+        &session_trace,
+        &db_trace,
+        "eval_trace(supersedes) should return identical results",
+    );
+    assert_eq!(db_trace.relations.as_graph().edge_count(), 1);
+
+    // Registered kinds serialize by name.
+    let json = serde_json::to_string(&superseded)?;
+    assert!(json.contains("\"supersedes\""), "{json}");
+    assert_eq!(serde_json::from_str::<WeightSet>(&json)?, superseded);
+
+    // The cache records the kinds it stores, and registers them when it is opened again, so a
+    // process that never loaded the declaring schema still reads those relations.
+    let cached_kinds = sqlx::query_scalar::<_, String>("SELECT name FROM relation_kinds")
+        .fetch_all(&db.0)
+        .await?;
+    assert_eq!(cached_kinds, vec!["supersedes".to_string()]);
+    assert!(WeightKind::try_from("replaces").is_err());
+    sqlx::query(
+        "INSERT INTO relation_kinds (name) VALUES ('replaces'); \
+         UPDATE relations SET custom = json_set(custom, '$.replaces', '') \
+         WHERE custom IS NOT NULL;",
+    )
+    .execute(&db.0)
+    .await?;
+    db.0.close().await;
+    let db = DbConnection(db_init(test_tempdir.path().join("test_belief_cache.db")).await?);
+    let replaces = WeightKind::try_from("replaces")?;
+    let db_trace = db.eval_trace(&expr_all, WeightSet::from(replaces)).await?;
+    assert_eq!(db_trace.relations.as_graph().edge_count(), 1);
+    Ok(())
+}
+
+/// A network node with the given `(title, kind, payload TOML)` children, cached in a database
+/// under `dir`. Returns the graph, the database and the BIDs by title.
+async fn flat_network_fixture<const N: usize>(
+    dir: &std::path::Path,
+    nodes: [(&'static str, BeliefKindSet, &str); N],
+) -> Result<(BeliefBase, DbConnection, BTreeMap<&'static str, Bid>), Box<dyn std::error::Error>> {
+    let db = DbConnection(db_init(dir.join("test_belief_cache.db")).await?);
+
+    let net_bid = Bid::new(buildonomy_namespace());
+    let mut states = BTreeMap::new();
+    let mut bids = BTreeMap::from([("Net", net_bid)]);
+    states.insert(
+        net_bid,
+        BeliefNode {
+            bid: net_bid,
+            kind: Default::default(),
+            title: "Net".to_string(),
+            schema: None,
+            payload: Default::default(),
+            id: None,
+        },
+    );
+    for (title, kind, payload) in nodes {
+        let bid = Bid::new(net_bid);
+        bids.insert(title, bid);
+        states.insert(
+            bid,
+            BeliefNode {
+                bid,
+                kind,
+                title: title.to_string(),
+                schema: None,
+                payload: toml::from_str(payload)?,
+                id: None,
+            },
+        );
+    }
+    let edges: Vec<BeliefRelation> = bids
+        .iter()
+        .filter(|(title, _)| **title != "Net")
+        .map(|(_, bid)| BeliefRelation {
+            source: net_bid,
+            sink: *bid,
+            weights: WeightSet::from(WeightKind::Section),
+        })
+        .collect();
+    let test_bb = BeliefBase::new(states, BidGraph::from_edges(edges))?;
+
+    let parsed_nodes: BTreeSet<Bid> = test_bb.states().keys().copied().collect();
+    let mut transaction = Transaction::default();
+    for event in BeliefBase::compute_diff(&BeliefBase::empty(), &test_bb, &parsed_nodes)? {
+        transaction.add_event(&event).ok();
+    }
+    transaction.execute(&db.0).await?;
+    Ok((test_bb, db, bids))
+}
+
+/// Asserts each expression selects the nodes with the expected titles, both in memory and through
+/// the database.
+async fn assert_state_predicates<const N: usize>(
+    test_bb: &BeliefBase,
+    db: &DbConnection,
+    bids: &BTreeMap<&'static str, Bid>,
+    cases: [(Expression, Vec<&str>); N],
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (expr, expected) in cases {
+        let expected = BTreeSet::from_iter(expected.into_iter().map(|title| bids[title]));
+        for (source, result) in [
+            ("BeliefBase", test_bb.eval_unbalanced(&expr).await?),
+            ("DbConnection", db.eval_unbalanced(&expr).await?),
+        ] {
+            let matched = BTreeSet::from_iter(
+                result
+                    .states
+                    .values()
+                    .filter(|node| node.kind.is_complete())
+                    .map(|node| node.bid),
+            );
+            assert_eq!(matched, expected, "{source}: {expr:?}");
+        }
+    }
+    Ok(())
+}
+
+/// Typed payload predicates and payload regexes select the same nodes in memory and through
+/// SQLite's JSON functions, including when inverted.
+#[test(tokio::test)]
+async fn test_payload_predicates_equivalence() -> Result<(), Box<dyn std::error::Error>> {
+    let test_tempdir = tempdir()?;
+    let (test_bb, db, bids) = flat_network_fixture(
+        test_tempdir.path(),
+        [
+            (
+                "A",
+                Default::default(),
+                "priority = 5\ndue = 2026-11-01\ntags = [\"rfc\", \"api\"]\nowner = \"ann\"\ndone = false",
+            ),
+            (
+                "B",
+                Default::default(),
+                "priority = 2.5\ndue = \"2026-12-15\"\ntags = \"rfc\"\ndone = true",
+            ),
+            ("C", Default::default(), "priority = \"7\"\ntags = [\"draft\"]"),
+        ],
+    )
+    .await?;
+
+    let key = |key: &str| key.to_string();
+    let number = PayloadValue::Number;
+    assert_state_predicates(
+        &test_bb,
+        &db,
+        &bids,
+        [
+            (
+                Expression::StateIn(StatePred::PayloadCompare(
+                    key("priority"),
+                    Comparison::Ge,
+                    number(3.0),
+                )),
+                vec!["A"],
+            ),
+            (
+                Expression::StateIn(StatePred::PayloadCompare(
+                    key("priority"),
+                    Comparison::Ne,
+                    number(5.0),
+                )),
+                vec!["B"],
+            ),
+            (
+                Expression::StateIn(StatePred::PayloadCompare(
+                    key("due"),
+                    Comparison::Lt,
+                    PayloadValue::Datetime("2026-12-01".parse()?),
+                )),
+                vec!["A"],
+            ),
+            (
+                Expression::StateIn(StatePred::PayloadCompare(
+                    key("done"),
+                    Comparison::Eq,
+                    PayloadValue::Boolean(false),
+                )),
+                vec!["A"],
+            ),
+            (
+                Expression::StateIn(StatePred::PayloadRange(
+                    key("priority"),
+                    number(2.0),
+                    number(5.0),
+                )),
+                vec!["A", "B"],
+            ),
+            (
+                Expression::StateIn(StatePred::PayloadExists(key("owner"))),
+                vec!["A"],
+            ),
+            (
+                Expression::StateNotIn(StatePred::PayloadExists(key("owner"))),
+                vec!["B", "C", "Net"],
+            ),
+            (
+                Expression::StateIn(StatePred::PayloadContains(
+                    key("tags"),
+                    PayloadValue::String("rfc".to_string()),
+                )),
+                vec!["A", "B"],
+            ),
+            (
+                Expression::StateNotIn(StatePred::PayloadContains(
+                    key("tags"),
+                    PayloadValue::String("rfc".to_string()),
+                )),
+                vec!["C", "Net"],
+            ),
+            (
+                Expression::StateIn(StatePred::Payload(key("owner"), "^ann$".into())),
+                vec!["A"],
+            ),
+            (
+                Expression::StateIn(StatePred::Payload(key("due"), "^2026-1[12]".into())),
+                vec!["A", "B"],
+            ),
+            (
+                Expression::StateIn(StatePred::Payload(key("priority"), r"^(5|2\.5)$".into())),
+                vec!["A", "B"],
+            ),
+            (
+                Expression::StateIn(StatePred::Payload(key("done"), "^TRUE$".into())),
+                vec!["B"],
+            ),
+            (
+                Expression::StateIn(StatePred::Payload(key("tags"), "rfc".into())),
+                vec!["B"],
+            ),
+            (
+                Expression::StateNotIn(StatePred::Payload(key("priority"), "^7$".into())),
+                vec!["A", "B", "Net"],
+            ),
+        ],
+    )
+    .await
+}
+
+/// A kind predicate selects the nodes having every kind in the set, in memory as in SQL's
+/// `kind & mask = mask`.
+#[test(tokio::test)]
+async fn test_kind_predicate_equivalence() -> Result<(), Box<dyn std::error::Error>> {
+    let test_tempdir = tempdir()?;
+    let (test_bb, db, bids) = flat_network_fixture(
+        test_tempdir.path(),
+        [
+            ("Doc", BeliefKind::Document.into(), ""),
+            (
+                "DocNet",
+                (BeliefKind::Document | BeliefKind::Network).into(),
+                "",
+            ),
+            ("Sub", BeliefKind::Network.into(), ""),
+        ],
+    )
+    .await?;
+
+    let kind = |kinds| Expression::StateIn(StatePred::Kind(kinds));
+    assert_state_predicates(
+        &test_bb,
+        &db,
+        &bids,
+        [
+            (kind(BeliefKind::Document.into()), vec!["Doc", "DocNet"]),
+            (kind(BeliefKind::Network.into()), vec!["DocNet", "Sub"]),
+            (
+                kind(BeliefKind::Document | BeliefKind::Network),
+                vec!["DocNet"],
+            ),
+            (kind(BeliefKind::Document | BeliefKind::Symbol), vec![]),
+            (
+                Expression::StateNotIn(StatePred::Kind(BeliefKind::Document | BeliefKind::Network)),
+                vec!["Doc", "Net", "Sub"],
+            ),
+        ],
+    )
+    .await
+}
+
 /// Helper function to assert two BeliefGraphs are equivalent
 fn assert_belief_graphs_equivalent(
     session_graph: &BeliefGraph,
@@ -381,3 +764,588 @@ fn assert_belief_graphs_equivalent(
 
     tracing::info!("✅ {} - Graphs are equivalent", message);
 }
+
+#[test(tokio::test)]
+async fn test_paginated_order_and_projection_equivalence() -> Result<(), Box<dyn std::error::Error>>
+{
+    let test_tempdir = tempdir()?;
+    let db = DbConnection(db_init(test_tempdir.path().join("test_belief_cache.db")).await?);
+
+    let net_bid = Bid::new(buildonomy_namespace());
+    let mut states = BTreeMap::new();
+    let mut bids = BTreeMap::new();
+    for (title, schema, payload) in [
+        ("Net", Some("buildonomy.Network"), ""),
+        (
+            "Charlie",
+            Some("buildonomy.Document"),
+            "rank = 2\nowner = \"bo\"",
+        ),
+        (
+            "Alpha",
+            Some("buildonomy.Document"),
+            "rank = 10\nowner = \"ann\"",
+        ),
+        ("Bravo", None, "rank = 2.5"),
+        ("Delta", Some("buildonomy.Section"), "rank = \"high\""),
+    ] {
+        let bid = if title == "Net" {
+            net_bid
+        } else {
+            Bid::new(net_bid)
+        };
+        bids.insert(title, bid);
+        states.insert(
+            bid,
+            BeliefNode {
+                bid,
+                kind: if title == "Net" {
+                    BeliefKind::Network.into()
+                } else {
+                    BeliefKind::Document.into()
+                },
+                title: title.to_string(),
+                schema: schema.map(str::to_string),
+                payload: toml::from_str(payload)?,
+                id: None,
+            },
+        );
+    }
+    // Document order differs from both title and BID order.
+    let edges: Vec<BeliefRelation> = ["Charlie", "Alpha", "Delta", "Bravo"]
+        .iter()
+        .enumerate()
+        .map(|(index, title)| {
+            let mut weight = Weight::default();
+            weight.set(WEIGHT_SORT_KEY, index as u16).unwrap();
+            let mut weights = WeightSet::empty();
+            weights.set(WeightKind::Section, weight);
+            BeliefRelation {
+                source: bids[title],
+                sink: net_bid,
+                weights,
+            }
+        })
+        .collect();
+    let test_bb = BeliefBase::new(states, BidGraph::from_edges(edges))?;
+
+    let parsed_nodes: BTreeSet<Bid> = test_bb.states().keys().copied().collect();
+    let mut transaction = Transaction::default();
+    for event in BeliefBase::compute_diff(&BeliefBase::empty(), &test_bb, &parsed_nodes)? {
+        transaction.add_event(&event).ok();
+    }
+    for (net, paths) in test_bb.paths().all_paths() {
+        for (path, bid, order) in paths {
+            transaction
+                .add_event(&BeliefEvent::PathAdded(
+                    net,
+                    path,
+                    bid,
+                    order,
+                    EventOrigin::Remote,
+                ))
+                .ok();
+        }
+    }
+    transaction.execute(&db.0).await?;
+
+    let all = bids.values().copied().collect::<Vec<_>>();
+    let rank = || OrderKey::Payload("rank".to_string());
+    for (order_by, expected) in [
+        (
+            vec![OrderBy::asc(OrderKey::Title)],
+            vec!["Alpha", "Bravo", "Charlie", "Delta", "Net"],
+        ),
+        (
+            vec![OrderBy::desc(OrderKey::Title)],
+            vec!["Net", "Delta", "Charlie", "Bravo", "Alpha"],
+        ),
+        (
+            vec![
+                OrderBy::asc(OrderKey::Schema),
+                OrderBy::desc(OrderKey::Title),
+            ],
+            vec!["Bravo", "Charlie", "Alpha", "Net", "Delta"],
+        ),
+        // Missing fields first, then numbers, then text
+        (
+            vec![OrderBy::asc(rank())],
+            vec!["Net", "Charlie", "Bravo", "Alpha", "Delta"],
+        ),
+        (
+            vec![OrderBy::desc(rank())],
+            vec!["Delta", "Alpha", "Bravo", "Charlie", "Net"],
+        ),
+        // The network's own path has an empty order, so it leads
+        (
+            vec![OrderBy::asc(OrderKey::Path)],
+            vec!["Net", "Charlie", "Alpha", "Delta", "Bravo"],
+        ),
+        (
+            vec![OrderBy::desc(OrderKey::Path)],
+            vec!["Bravo", "Delta", "Alpha", "Charlie", "Net"],
+        ),
+    ] {
+        let expected = expected
+            .into_iter()
+            .map(|title| bids[title])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            test_bb.order_bids(&all, &order_by).await?,
+            expected,
+            "BeliefBase: {order_by:?}"
+        );
+        assert_eq!(
+            db.order_bids(&all, &order_by).await?,
+            expected,
+            "DbConnection: {order_by:?}"
+        );
+    }
+
+    // Nodes equal on every key fall back to BID order
+    let mut by_bid = all.clone();
+    by_bid.sort();
+    let owner_desc = vec![OrderBy::desc(OrderKey::Payload("owner".to_string()))];
+    let unowned = by_bid
+        .iter()
+        .filter(|bid| !test_bb.states()[bid].payload.contains_key("owner"))
+        .copied()
+        .collect::<Vec<_>>();
+    for order in [
+        test_bb.order_bids(&all, &owner_desc).await?,
+        db.order_bids(&all, &owner_desc).await?,
+    ] {
+        assert_eq!(order[..2], [bids["Charlie"], bids["Alpha"]]);
+        assert_eq!(order[2..], unowned[..]);
+    }
+
+    // More BIDs than SQLite binds variables are ordered all the same
+    let mut many = all.clone();
+    many.extend((0..40_000).map(|_| Bid::new(net_bid)));
+    let by_title = vec![OrderBy::asc(OrderKey::Title)];
+    let order = db.order_bids(&many, &by_title).await?;
+    assert_eq!(order.len(), many.len());
+    assert_eq!(
+        order[..5],
+        ["Alpha", "Bravo", "Charlie", "Delta", "Net"].map(|title| bids[title])
+    );
+    let by_prerequisites = vec![OrderBy::asc(OrderKey::Prerequisites)];
+    assert_eq!(
+        db.order_bids(&many, &by_prerequisites).await?.len(),
+        many.len()
+    );
+
+    let pq = PaginatedQuery {
+        query: Query {
+            seed: Expression::StateIn(StatePred::Bid(all.clone())),
+            traverse: None,
+        },
+        limit: Some(2),
+        offset: Some(1),
+        order_by: vec![OrderBy::asc(OrderKey::Title)],
+        projection: Some(vec!["owner".to_string()]),
+    };
+    for (source, page) in [
+        ("BeliefBase", test_bb.eval_page(&pq).await?),
+        ("DbConnection", db.eval_page(&pq).await?),
+    ] {
+        assert_eq!(page.count, 5, "{source}");
+        assert_eq!(page.order, vec![bids["Bravo"], bids["Charlie"]], "{source}");
+        assert_eq!(
+            page.results.states.keys().copied().collect::<BTreeSet<_>>(),
+            BTreeSet::from([bids["Bravo"], bids["Charlie"]]),
+            "{source}"
+        );
+        assert!(page.results.states[&bids["Bravo"]].payload.is_empty());
+        assert_eq!(
+            page.results.states[&bids["Charlie"]].payload,
+            toml::from_str::<toml::Table>("owner = \"bo\"")?,
+            "{source}"
+        );
+    }
+    Ok(())
+}
+
+/// Two documents of a network with sections, and an Epistemic relation across them:
+///
+/// ```text
+/// S1a1 -> S1a -> D1 -> Net      (Section)
+/// S1b  -> D1
+/// S2a1 -> S2a -> D2 -> Net      (Section)
+/// S1a1 -> S2a1                  (Epistemic)
+/// ```
+///
+/// Returns the graph, cached in a database under `dir`, and its BIDs by title.
+async fn network_fixture(
+    dir: &std::path::Path,
+) -> Result<(BeliefBase, DbConnection, BTreeMap<&'static str, Bid>), Box<dyn std::error::Error>> {
+    let db = DbConnection(db_init(dir.join("test_belief_cache.db")).await?);
+
+    let net_bid = Bid::new(buildonomy_namespace());
+    let mut states = BTreeMap::new();
+    let mut bids = BTreeMap::new();
+    for title in ["Net", "D1", "D2", "S1a", "S1b", "S2a", "S1a1", "S2a1"] {
+        let bid = if title == "Net" {
+            net_bid
+        } else {
+            Bid::new(net_bid)
+        };
+        bids.insert(title, bid);
+        states.insert(
+            bid,
+            BeliefNode {
+                bid,
+                kind: if title == "Net" {
+                    BeliefKind::Network.into()
+                } else {
+                    BeliefKind::Document.into()
+                },
+                title: title.to_string(),
+                schema: None,
+                payload: Default::default(),
+                id: None,
+            },
+        );
+    }
+    let mut edges: Vec<BeliefRelation> = [
+        ("D1", "Net"),
+        ("D2", "Net"),
+        ("S1a", "D1"),
+        ("S1b", "D1"),
+        ("S2a", "D2"),
+        ("S1a1", "S1a"),
+        ("S2a1", "S2a"),
+    ]
+    .iter()
+    .map(|(source, sink)| BeliefRelation {
+        source: bids[source],
+        sink: bids[sink],
+        weights: WeightSet::from(WeightKind::Section),
+    })
+    .collect();
+    edges.push(BeliefRelation {
+        source: bids["S1a1"],
+        sink: bids["S2a1"],
+        weights: WeightSet::from(WeightKind::Epistemic),
+    });
+    let test_bb = BeliefBase::new(states, BidGraph::from_edges(edges))?;
+
+    let parsed_nodes: BTreeSet<Bid> = test_bb.states().keys().copied().collect();
+    let mut transaction = Transaction::default();
+    for event in BeliefBase::compute_diff(&BeliefBase::empty(), &test_bb, &parsed_nodes)? {
+        transaction.add_event(&event).ok();
+    }
+    transaction.execute(&db.0).await?;
+    Ok((test_bb, db, bids))
+}
+
+#[test(tokio::test)]
+async fn test_traversal_equivalence() -> Result<(), Box<dyn std::error::Error>> {
+    let test_tempdir = tempdir()?;
+    let (test_bb, db, bids) = network_fixture(test_tempdir.path()).await?;
+
+    for (seed, upstream, downstream, filter) in [
+        ("S1a1", 0, 0, None),
+        ("S1a1", 0, 2, None),
+        ("D1", 2, 0, None),
+        ("Net", 1, 0, None),
+        ("Net", 3, 0, None),
+        ("S2a1", 3, 0, Some(WeightSet::from(WeightKind::Epistemic))),
+        ("S1a", 1, 1, Some(WeightSet::from(WeightKind::Section))),
+    ] {
+        let query = Query {
+            seed: Expression::StateIn(StatePred::Bid(vec![bids[seed]])),
+            traverse: Some(NeighborsExpression {
+                filter,
+                upstream,
+                downstream,
+            }),
+        };
+        let session_graph = test_bb.eval_query(&query, false).await?;
+        let db_graph = db.eval_query(&query, false).await?;
+        assert_belief_graphs_equivalent(&session_graph, &db_graph, &format!("{query:?}"));
+    }
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_path_queries_equivalence() -> Result<(), Box<dyn std::error::Error>> {
+    let test_tempdir = tempdir()?;
+    let (test_bb, db, bids) = network_fixture(test_tempdir.path()).await?;
+    let titles = |graph: &BeliefGraph| {
+        graph
+            .states
+            .values()
+            .map(|node| node.title.clone())
+            .collect::<BTreeSet<_>>()
+    };
+    let expected = |expected: &[&str]| {
+        expected
+            .iter()
+            .map(|title| title.to_string())
+            .collect::<BTreeSet<_>>()
+    };
+    let section = WeightSet::from(WeightKind::Section);
+
+    for (source, shortest, all, reachable) in [
+        (
+            "BeliefBase",
+            test_bb
+                .eval_shortest_path(bids["S1a1"], bids["Net"], WeightSet::full())
+                .await?,
+            test_bb
+                .eval_simple_paths(bids["S1a1"], bids["Net"], WeightSet::full(), 4)
+                .await?,
+            [
+                test_bb
+                    .eval_reachable(bids["S1a1"], bids["D2"], WeightSet::full())
+                    .await?,
+                test_bb
+                    .eval_reachable(bids["S1a1"], bids["D2"], section.clone())
+                    .await?,
+                test_bb
+                    .eval_reachable(bids["Net"], bids["S1a1"], WeightSet::full())
+                    .await?,
+            ],
+        ),
+        (
+            "DbConnection",
+            db.eval_shortest_path(bids["S1a1"], bids["Net"], WeightSet::full())
+                .await?,
+            db.eval_simple_paths(bids["S1a1"], bids["Net"], WeightSet::full(), 4)
+                .await?,
+            [
+                db.eval_reachable(bids["S1a1"], bids["D2"], WeightSet::full())
+                    .await?,
+                db.eval_reachable(bids["S1a1"], bids["D2"], section.clone())
+                    .await?,
+                db.eval_reachable(bids["Net"], bids["S1a1"], WeightSet::full())
+                    .await?,
+            ],
+        ),
+    ] {
+        assert_eq!(
+            titles(&shortest),
+            expected(&["S1a1", "S1a", "D1", "Net"]),
+            "{source}"
+        );
+        assert_eq!(shortest.relations.as_graph().edge_count(), 3, "{source}");
+        assert!(
+            shortest
+                .states
+                .values()
+                .all(|node| !node.kind.is_complete()),
+            "{source}: path nodes only carry the path's relations"
+        );
+
+        assert_eq!(
+            titles(&all),
+            expected(&["S1a1", "S1a", "D1", "S2a1", "S2a", "D2", "Net"]),
+            "{source}"
+        );
+        assert_eq!(all.relations.as_graph().edge_count(), 7, "{source}");
+        assert_eq!(reachable, [true, false, false], "{source}");
+    }
+
+    // Bounding the hops drops the longer route through the Epistemic relation
+    let bounded = test_bb
+        .eval_simple_paths(bids["S1a1"], bids["Net"], WeightSet::full(), 3)
+        .await?;
+    assert_eq!(titles(&bounded), expected(&["S1a1", "S1a", "D1", "Net"]));
+    let none = db
+        .eval_shortest_path(bids["S1a1"], bids["D2"], section)
+        .await?;
+    assert!(none.states.is_empty());
+
+    // No relation carries a weight of no kind
+    let (from, to, none) = (bids["S1a1"], bids["Net"], WeightSet::empty());
+    for (source, shortest, all, reachable) in [
+        (
+            "BeliefBase",
+            test_bb.eval_shortest_path(from, to, none.clone()).await?,
+            test_bb.eval_simple_paths(from, to, none.clone(), 4).await?,
+            test_bb.eval_reachable(from, to, none.clone()).await?,
+        ),
+        (
+            "DbConnection",
+            db.eval_shortest_path(from, to, none.clone()).await?,
+            db.eval_simple_paths(from, to, none.clone(), 4).await?,
+            db.eval_reachable(from, to, none.clone()).await?,
+        ),
+    ] {
+        assert!(shortest.states.is_empty(), "{source}");
+        assert!(all.states.is_empty(), "{source}");
+        assert!(!reachable, "{source}");
+    }
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_path_queries_past_max_traversal() -> Result<(), Box<dyn std::error::Error>> {
+    let test_tempdir = tempdir()?;
+    let db = DbConnection(db_init(test_tempdir.path().join("test_belief_cache.db")).await?);
+
+    // A chain of Epistemic relations longer than MAX_TRAVERSAL hops
+    let net_bid = Bid::new(buildonomy_namespace());
+    let chain = (0..=MAX_TRAVERSAL as usize + 2)
+        .map(|_| Bid::new(net_bid))
+        .collect::<Vec<_>>();
+    let states = std::iter::once(net_bid)
+        .chain(chain.iter().copied())
+        .map(|bid| {
+            let node = BeliefNode {
+                bid,
+                kind: if bid == net_bid {
+                    BeliefKind::Network.into()
+                } else {
+                    BeliefKind::Document.into()
+                },
+                title: bid.to_string(),
+                schema: None,
+                payload: Default::default(),
+                id: None,
+            };
+            (bid, node)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let mut edges = chain
+        .iter()
+        .map(|bid| BeliefRelation {
+            source: *bid,
+            sink: net_bid,
+            weights: WeightSet::from(WeightKind::Section),
+        })
+        .collect::<Vec<_>>();
+    edges.extend(chain.windows(2).map(|step| BeliefRelation {
+        source: step[0],
+        sink: step[1],
+        weights: WeightSet::from(WeightKind::Epistemic),
+    }));
+    let test_bb = BeliefBase::new(states, BidGraph::from_edges(edges))?;
+    let parsed_nodes: BTreeSet<Bid> = test_bb.states().keys().copied().collect();
+    let mut transaction = Transaction::default();
+    for event in BeliefBase::compute_diff(&BeliefBase::empty(), &test_bb, &parsed_nodes)? {
+        transaction.add_event(&event).ok();
+    }
+    transaction.execute(&db.0).await?;
+
+    let (from, to) = (chain[0], chain[chain.len() - 1]);
+    let epistemic = WeightSet::from(WeightKind::Epistemic);
+    let hops = u8::try_from(chain.len())?;
+    for (source, shortest, all, reachable) in [
+        (
+            "BeliefBase",
+            test_bb
+                .eval_shortest_path(from, to, epistemic.clone())
+                .await?,
+            test_bb
+                .eval_simple_paths(from, to, epistemic.clone(), hops)
+                .await?,
+            test_bb.eval_reachable(from, to, epistemic.clone()).await?,
+        ),
+        (
+            "DbConnection",
+            db.eval_shortest_path(from, to, epistemic.clone()).await?,
+            db.eval_simple_paths(from, to, epistemic.clone(), hops)
+                .await?,
+            db.eval_reachable(from, to, epistemic.clone()).await?,
+        ),
+    ] {
+        let expected = chain.iter().copied().collect::<BTreeSet<_>>();
+        assert_eq!(
+            shortest.states.keys().copied().collect::<BTreeSet<_>>(),
+            expected,
+            "{source}"
+        );
+        assert_eq!(
+            shortest.relations.as_graph().edge_count(),
+            chain.len() - 1,
+            "{source}"
+        );
+        assert_eq!(
+            all.states.keys().copied().collect::<BTreeSet<_>>(),
+            expected,
+            "{source}"
+        );
+        assert!(reachable, "{source}");
+    }
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_reading_order_equivalence() -> Result<(), Box<dyn std::error::Error>> {
+    let test_tempdir = tempdir()?;
+    let (mut test_bb, db, bids) = network_fixture(test_tempdir.path()).await?;
+
+    // S1a is a prerequisite of S2a, and S1b and S2a depend on each other
+    let mut transaction = Transaction::default();
+    for (source, sink) in [("S1a", "S2a"), ("S1b", "S2a"), ("S2a", "S1b")] {
+        let event = BeliefEvent::RelationUpdate(
+            bids[source],
+            bids[sink],
+            WeightSet::from(WeightKind::Epistemic),
+            EventOrigin::Remote,
+        );
+        test_bb.process_event(&event)?;
+        transaction.add_event(&event).ok();
+    }
+    transaction.execute(&db.0).await?;
+
+    let mut cycle = vec![bids["S1b"], bids["S2a"]];
+    cycle.sort();
+    assert_eq!(test_bb.epistemic_cycles(), vec![cycle.clone()]);
+
+    let reading = test_bb.reading_order(&[bids["S2a"], bids["S2a1"]]);
+    assert_eq!(reading.cycles, vec![cycle.clone()]);
+    let position = |order: &[Bid], title: &str| {
+        order
+            .iter()
+            .position(|bid| *bid == bids[title])
+            .unwrap_or_else(|| panic!("{title} is missing from the order"))
+    };
+    assert_eq!(reading.order.len(), 5);
+    assert!(position(&reading.order, "S1a") < position(&reading.order, "S2a"));
+    assert!(position(&reading.order, "S1a1") < position(&reading.order, "S2a1"));
+    assert_eq!(
+        position(&reading.order, "S1b").abs_diff(position(&reading.order, "S2a")),
+        1,
+        "a cycle's members are kept together"
+    );
+
+    // Ordering the same nodes by prerequisites reproduces the reading order
+    let pq = PaginatedQuery {
+        query: Query {
+            seed: Expression::StateIn(StatePred::Bid(vec![bids["S2a"], bids["S2a1"]])),
+            traverse: Some(NeighborsExpression {
+                filter: Some(WeightSet::from(WeightKind::Epistemic)),
+                upstream: 3,
+                downstream: 0,
+            }),
+        },
+        limit: None,
+        offset: None,
+        order_by: vec![OrderBy::asc(OrderKey::Prerequisites)],
+        projection: None,
+    };
+    let mut reversed = reading.order.clone();
+    reversed.reverse();
+    let descending = PaginatedQuery {
+        order_by: vec![OrderBy::desc(OrderKey::Prerequisites)],
+        ..pq.clone()
+    };
+    for (source, page, expected) in [
+        ("BeliefBase", test_bb.eval_page(&pq).await?, &reading.order),
+        ("DbConnection", db.eval_page(&pq).await?, &reading.order),
+        ("DbConnection", db.eval_page(&descending).await?, &reversed),
+    ] {
+        // The Section parents the query also returns have no reading position
+        let ordered = page
+            .order
+            .iter()
+            .filter(|bid| reading.order.contains(bid))
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(&ordered, expected, "{source}");
+    }
+    Ok(())
+}
@@ -6,10 +6,14 @@
 //! organized by feature area:
 //!
 //! - `bid_tests`: BID generation and caching
+//! - `block_tests`: Block references below heading granularity
 //! - `section_tests`: Section metadata and handling
 //! - `anchor_tests`: Anchor generation and collision detection
 //! - `link_tests`: Link resolution and formatting
 //! - `asset_tests`: Asset tracking and content addressing
+//! - `schema_tests`: Typed frontmatter validation, network schema files, versioned migrations and
+//!   relation constraints
+//! - `tag_tests`: Tag extraction and tag hierarchy
 
 #[path = "codec_test/common.rs"]
 mod common;
@@ -20,7 +24,13 @@ mod anchor_tests;
 mod asset_tests;
 #[path = "codec_test/bid_tests.rs"]
 mod bid_tests;
+#[path = "codec_test/block_tests.rs"]
+mod block_tests;
 #[path = "codec_test/link_tests.rs"]
 mod link_tests;
+#[path = "codec_test/schema_tests.rs"]
+mod schema_tests;
 #[path = "codec_test/section_tests.rs"]
 mod section_tests;
+#[path = "codec_test/tag_tests.rs"]
+mod tag_tests;
@@ -0,0 +1,184 @@
+//! Block reference tests
+
+use noet_core::{
+    beliefbase::BeliefBase,
+    codec::{DocumentCompiler, ParseDiagnostic},
+    event::BeliefEvent,
+    properties::{BeliefNode, Bid, WeightKind},
+};
+use std::fs;
+use test_log::test;
+use tokio::sync::mpsc::unbounded_channel;
+
+use super::common::find_file;
+
+fn has_edge(bb: &BeliefBase, source: &Bid, sink: &Bid, kind: WeightKind) -> bool {
+    match (bb.bid_to_index(source), bb.bid_to_index(sink)) {
+        (Some(source_idx), Some(sink_idx)) => bb
+            .relations()
+            .as_graph()
+            .edges_connecting(source_idx, sink_idx)
+            .any(|edge| edge.weight().weights.contains_key(&kind)),
+        _ => false,
+    }
+}
+
+fn node_with_id(bb: &BeliefBase, id: &str) -> BeliefNode {
+    bb.states()
+        .values()
+        .find(|node| node.id.as_deref() == Some(id))
+        .cloned()
+        .unwrap_or_else(|| panic!("node with id {id}"))
+}
+
+/// `^block-id` markers make paragraphs, list items and tables addressable: they become nodes
+/// below their section with the block text as payload, links can target them, and the HTML output
+/// anchors them.
+#[test(tokio::test)]
+async fn test_block_references() -> Result<(), Box<dyn std::error::Error>> {
+    let src_dir = tempfile::tempdir()?;
+    let html_dir = tempfile::tempdir()?;
+    let root = src_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"blocks\"\ntitle: \"Blocks\"\n---\n\n# Blocks\n",
+    )?;
+    let claims_body = "## Evidence\n\nThe build is deterministic. ^deterministic\n\n\
+                       - first\n- second ^second\n\n\
+                       | run | result |\n|---|---|\n| 1 | pass |\n\n^results\n\n\
+                       A duplicate anchor. ^evidence\n";
+    fs::write(
+        root.join("claims.md"),
+        format!("---\ntitle: Claims\n---\n\n# Claims\n\nIntro.\n\n{claims_body}"),
+    )?;
+    let reader_body = "See [the claim](claims.md#^deterministic) and [[Claims#^second]].\n\n\
+                       ![[Claims#^results]]\n";
+    fs::write(root.join("reader.md"), format!("# Reader\n\n{reader_body}"))?;
+
+    let mut compiler = DocumentCompiler::with_html_output(
+        root,
+        None,
+        Some(5),
+        true,
+        Some(html_dir.path().to_path_buf()),
+        None,
+        false,
+        None,
+        None,
+    )?;
+    let results = compiler.parse_all(BeliefBase::default(), false).await?;
+    let mut global_bb = compiler.cache().clone();
+
+    let warnings = results
+        .iter()
+        .filter(|result| result.path.ends_with("claims.md"))
+        .flat_map(|result| result.diagnostics.iter())
+        .filter_map(|diagnostic| match diagnostic {
+            ParseDiagnostic::Warning { message, .. } => Some(message.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        warnings
+            .iter()
+            .any(|message| message.contains("'^evidence' is already used")),
+        "{warnings:#?}"
+    );
+
+    // Blocks are nodes below their section, carrying their text.
+    let evidence = node_with_id(&global_bb, "evidence");
+    let deterministic = node_with_id(&global_bb, "deterministic");
+    let second = node_with_id(&global_bb, "second");
+    let results_block = node_with_id(&global_bb, "results");
+    assert_eq!(
+        deterministic
+            .payload
+            .get("text")
+            .and_then(|text| text.as_str()),
+        Some("The build is deterministic.")
+    );
+    assert_eq!(
+        second.payload.get("text").and_then(|text| text.as_str()),
+        Some("second")
+    );
+    assert_eq!(
+        results_block
+            .payload
+            .get("text")
+            .and_then(|text| text.as_str()),
+        Some("run result 1 pass")
+    );
+    for block in [&deterministic, &second, &results_block] {
+        assert!(
+            has_edge(&global_bb, &block.bid, &evidence.bid, WeightKind::Section),
+            "{} is below its section",
+            block.title
+        );
+    }
+
+    // Obsidian's `#^block-id` form resolves to the block.
+    let reader = global_bb
+        .states()
+        .values()
+        .find(|node| node.title == "Reader")
+        .cloned()
+        .expect("reader document");
+    assert!(has_edge(
+        &global_bb,
+        &deterministic.bid,
+        &reader.bid,
+        WeightKind::Epistemic
+    ));
+
+    // Markers are left as written and block BIDs are recorded with the section metadata.
+    let claims_source = fs::read_to_string(root.join("claims.md"))?;
+    for marker in [
+        "The build is deterministic. ^deterministic\n",
+        "second ^second\n",
+        "\n^results\n",
+    ] {
+        assert!(claims_source.contains(marker), "{claims_source}");
+    }
+    assert!(
+        claims_source.contains("id://deterministic"),
+        "{claims_source}"
+    );
+    let reader_source = fs::read_to_string(root.join("reader.md"))?;
+    assert!(
+        reader_source.contains("[the claim](claims.md#^deterministic"),
+        "{reader_source}"
+    );
+
+    let claims_html = fs::read_to_string(
+        find_file(html_dir.path(), "claims.html").expect("claims.html to be generated"),
+    )?;
+    assert!(
+        claims_html.contains("<p id=\"deterministic\">The build is deterministic.</p>"),
+        "{claims_html}"
+    );
+    assert!(
+        claims_html.contains("<li id=\"second\">second</li>"),
+        "{claims_html}"
+    );
+    assert!(
+        claims_html.contains("<div id=\"results\">\n<table>"),
+        "{claims_html}"
+    );
+    assert!(!claims_html.contains("^results"), "{claims_html}");
+    // The colliding marker is not a block and stays as written.
+    assert!(claims_html.contains("^evidence"), "{claims_html}");
+
+    // Re-parsing against the populated cache keeps the block BIDs.
+    let (accum_tx, mut accum_rx) = unbounded_channel::<BeliefEvent>();
+    let mut compiler = DocumentCompiler::new(root, Some(accum_tx), None, false)?;
+    compiler.parse_all(global_bb.clone(), true).await?;
+    while let Ok(event) = accum_rx.try_recv() {
+        global_bb.process_event(&event)?;
+    }
+    assert_eq!(
+        node_with_id(&global_bb, "deterministic").bid,
+        deterministic.bid
+    );
+
+    Ok(())
+}
@@ -49,6 +49,18 @@ pub fn generate_test_root(test_net: &str) -> Result<(TempDir, PathBuf), Buildono
     Ok((temp_dir, test_root))
 }
 
+/// Find a file by name anywhere below `dir`, e.g. a page in an HTML output tree.
+pub fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    fs::read_dir(dir).ok()?.flatten().find_map(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            find_file(&path, name)
+        } else {
+            (path.file_name()? == name).then_some(path)
+        }
+    })
+}
+
 #[derive(Debug, Default, Deserialize)]
 struct ABid {
     bid: Bid,
@@ -1,2 +1,210 @@
 //! Link resolution and formatting tests
-// Link tests placeholder - to be populated
+
+use noet_core::{
+    beliefbase::BeliefBase,
+    codec::{DocumentCompiler, ParseDiagnostic},
+    event::BeliefEvent,
+    properties::WeightKind,
+};
+use std::fs;
+use test_log::test;
+use tokio::sync::mpsc::unbounded_channel;
+
+use super::common::find_file;
+
+/// An Obsidian-style vault: wikilinks resolve by title and alias, headings become anchors,
+/// embeds transclude the target section, ambiguous titles warn, and the wikilink syntax survives
+/// write-back.
+#[test(tokio::test)]
+async fn test_obsidian_vault_wikilinks() -> Result<(), Box<dyn std::error::Error>> {
+    let src_dir = tempfile::tempdir()?;
+    let html_dir = tempfile::tempdir()?;
+    let root = src_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"vault\"\ntitle: \"Vault\"\n---\n\n# Vault\n",
+    )?;
+    fs::write(
+        root.join("note.md"),
+        "---\ntitle: Note\naliases: [Nickname, The Note]\n---\n\n# Note\n\nNote intro.\n\n\
+         ## Details\n\nThe embedded details.\n\n## Other\n\nNot embedded.\n",
+    )?;
+    fs::write(
+        root.join("twin_a.md"),
+        "---\ntitle: Twin\n---\n\nFirst twin.\n",
+    )?;
+    fs::write(
+        root.join("twin_b.md"),
+        "---\ntitle: Twin\n---\n\nSecond twin.\n",
+    )?;
+    let reader_body = "See [[Note]], [[Nickname|my nickname]], [[The Note]] and \
+                       [[Note#Details]].\n\n![[Note#Details]]\n\nAlso [[Twin]] and [[Missing]].\n";
+    fs::write(root.join("reader.md"), format!("# Reader\n\n{reader_body}"))?;
+
+    let mut compiler = DocumentCompiler::with_html_output(
+        root,
+        None,
+        Some(5),
+        true,
+        Some(html_dir.path().to_path_buf()),
+        None,
+        false,
+        None,
+        None,
+    )?;
+    let results = compiler.parse_all(BeliefBase::default(), false).await?;
+
+    let warnings = results
+        .iter()
+        .filter(|result| result.path.ends_with("reader.md"))
+        .flat_map(|result| result.diagnostics.iter())
+        .filter_map(|diagnostic| match diagnostic {
+            ParseDiagnostic::Warning { message, .. } => Some(message.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let unresolved = warnings
+        .iter()
+        .filter(|message| message.contains("unresolved link"))
+        .collect::<Vec<_>>();
+    assert_eq!(unresolved.len(), 1, "{warnings:#?}");
+    assert!(unresolved[0].contains("missing"), "{warnings:#?}");
+    let ambiguous = warnings
+        .iter()
+        .filter(|message| message.contains("ambiguous link 'twin'"))
+        .collect::<Vec<_>>();
+    assert_eq!(ambiguous.len(), 1, "{warnings:#?}");
+    assert!(
+        ambiguous[0].contains("2 documents"),
+        "The warning lists both candidate BIDs: {}",
+        ambiguous[0]
+    );
+
+    // BIDs were written into the frontmatter, but the wikilinks are untouched.
+    let reader_source = fs::read_to_string(root.join("reader.md"))?;
+    assert!(reader_source.contains("bid"), "{reader_source}");
+    assert!(
+        reader_source.contains(reader_body.trim_end()),
+        "{reader_source}"
+    );
+
+    let reader_html = fs::read_to_string(
+        find_file(html_dir.path(), "reader.html").expect("reader.html to be generated"),
+    )?;
+    assert!(reader_html.contains("href=\"note.html\""), "{reader_html}");
+    assert!(reader_html.contains(">my nickname</a>"), "{reader_html}");
+    assert!(
+        reader_html.contains("href=\"note.html#details\""),
+        "{reader_html}"
+    );
+    assert!(
+        reader_html.contains("class=\"wikilink-embed\""),
+        "{reader_html}"
+    );
+    assert!(
+        reader_html.contains("The embedded details."),
+        "{reader_html}"
+    );
+    assert!(!reader_html.contains("Not embedded."), "{reader_html}");
+    Ok(())
+}
+
+/// Links declare their relation kind through the title attribute or an attribute block written
+/// right after them. The kind and payload survive a second compile pass, and the attribute
+/// block is dropped from the rendered HTML.
+#[test(tokio::test)]
+async fn test_typed_links() -> Result<(), Box<dyn std::error::Error>> {
+    // Neither kind needs registering: both are link kinds.
+    let supports = WeightKind::try_from("supports")?;
+    let contradicts = WeightKind::try_from("contradicts")?;
+    let src_dir = tempfile::tempdir()?;
+    let html_dir = tempfile::tempdir()?;
+    let root = src_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"typed\"\ntitle: \"Typed\"\n---\n\n# Typed\n",
+    )?;
+    fs::write(root.join("evidence.md"), "# Evidence\n\nSome evidence.\n")?;
+    fs::write(root.join("rebuttal.md"), "# Rebuttal\n\nA rebuttal.\n")?;
+    fs::write(
+        root.join("claim.md"),
+        "# Claim\n\nBacked by [the evidence](evidence.md \"supports\") but see [the rebuttal](rebuttal.md){rel=contradicts confidence=0.7}.\n",
+    )?;
+
+    let typed_edges = |global_bb: &BeliefBase| {
+        let title_of = |bid| {
+            global_bb
+                .states()
+                .get(&bid)
+                .map(|node| node.title.clone())
+                .unwrap_or_default()
+        };
+        let relations = global_bb.relations();
+        let graph = relations.as_graph();
+        graph
+            .raw_edges()
+            .iter()
+            .filter(|edge| title_of(graph[edge.target()]) == "Claim")
+            .flat_map(|edge| {
+                let source = title_of(graph[edge.source()]);
+                edge.weight
+                    .weights
+                    .iter()
+                    .map(move |(kind, weight)| (source.clone(), *kind, weight.clone()))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for pass in 0..2 {
+        let mut global_bb = BeliefBase::empty();
+        let (accum_tx, mut accum_rx) = unbounded_channel::<BeliefEvent>();
+        let mut compiler = DocumentCompiler::with_html_output(
+            root,
+            Some(accum_tx),
+            None,
+            true,
+            Some(html_dir.path().to_path_buf()),
+            None,
+            false,
+            None,
+            None,
+        )?;
+        compiler.parse_all(global_bb.clone(), false).await?;
+        while let Ok(event) = accum_rx.try_recv() {
+            global_bb.process_event(&event)?;
+        }
+
+        let edges = typed_edges(&global_bb);
+        assert!(
+            edges
+                .iter()
+                .any(|(source, kind, _)| source == "Evidence" && *kind == supports),
+            "pass {pass}: {edges:#?}"
+        );
+        let rebuttal = edges
+            .iter()
+            .find(|(source, kind, _)| source == "Rebuttal" && *kind == contradicts)
+            .unwrap_or_else(|| panic!("pass {pass}: {edges:#?}"));
+        assert_eq!(rebuttal.2.get::<f64>("confidence"), Some(0.7));
+        assert!(
+            !edges
+                .iter()
+                .any(|(_, kind, _)| *kind == WeightKind::Epistemic),
+            "pass {pass}: {edges:#?}"
+        );
+    }
+
+    // The attribute block is the source of truth and is left untouched.
+    let claim_source = fs::read_to_string(root.join("claim.md"))?;
+    assert!(
+        claim_source.contains("){rel=contradicts confidence=0.7}"),
+        "{claim_source}"
+    );
+
+    let claim_html = fs::read_to_string(
+        find_file(html_dir.path(), "claim.html").expect("claim.html to be generated"),
+    )?;
+    assert!(!claim_html.contains("rel=contradicts"), "{claim_html}");
+    assert!(claim_html.contains("</a>."), "{claim_html}");
+    Ok(())
+}
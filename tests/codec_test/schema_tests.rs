@@ -0,0 +1,488 @@
+//! Typed frontmatter validation tests
+
+use noet_core::{
+    beliefbase::BeliefBase,
+    codec::{
+        compiler::ParseResult,
+        migration::{apply_migrations, plan_migrations},
+        schema_registry::{FieldDefinition, FieldType, SchemaDefinition},
+        DocumentCompiler, ParseDiagnostic, SCHEMAS,
+    },
+    event::BeliefEvent,
+};
+use std::fs;
+use test_log::test;
+use tokio::sync::mpsc::unbounded_channel;
+
+fn diagnostics_for<'a>(results: &'a [ParseResult], file: &str) -> Vec<&'a ParseDiagnostic> {
+    results
+        .iter()
+        .filter(|result| result.path.ends_with(file))
+        .flat_map(|result| result.diagnostics.iter())
+        .collect()
+}
+
+fn line_of(content: &str, prefix: &str) -> usize {
+    content
+        .lines()
+        .position(|line| line.starts_with(prefix))
+        .map(|idx| idx + 1)
+        .unwrap_or_else(|| panic!("{prefix} in {content}"))
+}
+
+/// Documents declaring a registered schema are checked against its typed fields: violations are
+/// reported at the offending frontmatter line, and defaults are materialised into the payload
+/// without touching the source.
+#[test(tokio::test)]
+async fn test_typed_frontmatter_validation() -> Result<(), Box<dyn std::error::Error>> {
+    SCHEMAS.register(
+        "schema_tests.task".to_string(),
+        SchemaDefinition {
+            graph_fields: vec![],
+            fields: vec![
+                FieldDefinition {
+                    required: true,
+                    enum_values: vec!["open".to_string(), "done".to_string()],
+                    ..FieldDefinition::new("status", FieldType::String)
+                },
+                FieldDefinition {
+                    default: Some(toml::Value::Integer(3)),
+                    ..FieldDefinition::new("priority", FieldType::Integer)
+                },
+                FieldDefinition {
+                    required: true,
+                    ..FieldDefinition::new("owner", FieldType::String)
+                },
+            ],
+            ..SchemaDefinition::default()
+        },
+    );
+
+    let temp_dir = tempfile::tempdir()?;
+    let root = temp_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"tasks\"\ntitle: \"Tasks\"\n---\n\n# Tasks\n",
+    )?;
+    fs::write(
+        root.join("valid.md"),
+        "---\ntitle: Valid\nschema: schema_tests.task\nstatus: open\nowner: ana\n---\n\n# Valid\n",
+    )?;
+    let invalid_source = "---\ntitle: Invalid\nschema: schema_tests.task\nstatus: blocked\n\
+                          priority: high\n---\n\n# Invalid\n";
+    fs::write(root.join("invalid.md"), invalid_source)?;
+
+    let mut global_bb = BeliefBase::empty();
+    let (accum_tx, mut accum_rx) = unbounded_channel::<BeliefEvent>();
+    let mut compiler = DocumentCompiler::new(root, Some(accum_tx), None, true)?;
+    let results = compiler.parse_all(global_bb.clone(), false).await?;
+    while let Ok(event) = accum_rx.try_recv() {
+        global_bb.process_event(&event)?;
+    }
+
+    assert!(
+        diagnostics_for(&results, "valid.md")
+            .iter()
+            .all(|diagnostic| !diagnostic.to_string().contains("Schema")),
+        "{:#?}",
+        diagnostics_for(&results, "valid.md")
+    );
+
+    let invalid = diagnostics_for(&results, "invalid.md");
+    let find = |needle: &str| {
+        invalid
+            .iter()
+            .find(|diagnostic| diagnostic.to_string().contains(needle))
+            .copied()
+            .unwrap_or_else(|| panic!("diagnostic containing {needle:?}: {invalid:#?}"))
+    };
+    let status = find("field 'status' has value 'blocked'");
+    assert!(matches!(status, ParseDiagnostic::Warning { .. }));
+    assert_eq!(
+        status.location().map(|(line, _)| line),
+        Some(line_of(invalid_source, "status:"))
+    );
+    let priority = find("field 'priority' has type string, expected integer");
+    assert!(priority.is_parse_error());
+    assert_eq!(
+        priority.location().map(|(line, _)| line),
+        Some(line_of(invalid_source, "priority:"))
+    );
+    let owner = find("required field 'owner' is missing");
+    assert!(owner.is_parse_error());
+    assert_eq!(
+        owner.location().map(|(line, _)| line),
+        Some(line_of(invalid_source, "schema:"))
+    );
+
+    // The default is part of the payload, but not of the source.
+    let valid = global_bb
+        .states()
+        .values()
+        .find(|node| node.title == "Valid")
+        .cloned()
+        .expect("valid document");
+    assert_eq!(
+        valid.payload.get("priority"),
+        Some(&toml::Value::Integer(3))
+    );
+    let valid_source = fs::read_to_string(root.join("valid.md"))?;
+    assert!(valid_source.contains("bid"), "{valid_source}");
+    assert!(!valid_source.contains("priority"), "{valid_source}");
+    Ok(())
+}
+
+/// Networks load schemas from their `schemas/` directory and from the files their `index.md`
+/// declares. Schemas apply to the network and its subnets, a subnet's schema shadows its parent's,
+/// and schema files are not parsed as documents.
+#[test(tokio::test)]
+async fn test_network_schema_files() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let root = temp_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"projects\"\ntitle: \"Projects\"\nschemas: [review.json]\n---\n\n# Projects\n",
+    )?;
+    fs::create_dir_all(root.join("schemas"))?;
+    fs::write(
+        root.join("schemas/task.toml"),
+        "[fields.status]\ntype = \"string\"\nrequired = true\n",
+    )?;
+    fs::write(
+        root.join("review.json"),
+        r#"{"$id": "schema_files.review", "properties": {"score": {"type": "bogus"}}}"#,
+    )?;
+    let subnet = root.join("sub");
+    fs::create_dir_all(subnet.join("schemas"))?;
+    fs::write(
+        subnet.join("index.md"),
+        "---\nid: \"sub\"\ntitle: \"Sub\"\n---\n\n# Sub\n",
+    )?;
+    fs::write(
+        subnet.join("schemas/task.toml"),
+        "[fields.owner]\ntype = \"string\"\nrequired = true\n",
+    )?;
+    fs::write(
+        root.join("top.md"),
+        "---\ntitle: Top\nschema: task\n---\n\n# Top\n",
+    )?;
+    fs::write(
+        subnet.join("nested.md"),
+        "---\ntitle: Nested\nschema: task\n---\n\n# Nested\n",
+    )?;
+
+    let mut global_bb = BeliefBase::empty();
+    let (accum_tx, mut accum_rx) = unbounded_channel::<BeliefEvent>();
+    let mut compiler = DocumentCompiler::new(root, Some(accum_tx), None, false)?;
+    let results = compiler.parse_all(global_bb.clone(), false).await?;
+    while let Ok(event) = accum_rx.try_recv() {
+        global_bb.process_event(&event)?;
+    }
+
+    let messages = |results: &[ParseResult], file: &str| {
+        diagnostics_for(results, file)
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect::<Vec<_>>()
+    };
+    let top = messages(&results, "top.md");
+    assert!(
+        top.iter()
+            .any(|message| message.contains("required field 'status' is missing")),
+        "{top:#?}"
+    );
+    assert!(
+        !top.iter().any(|message| message.contains("owner")),
+        "{top:#?}"
+    );
+    let nested = messages(&results, "nested.md");
+    assert!(
+        nested
+            .iter()
+            .any(|message| message.contains("required field 'owner' is missing")),
+        "{nested:#?}"
+    );
+    assert!(
+        !nested.iter().any(|message| message.contains("status")),
+        "{nested:#?}"
+    );
+
+    // The broken declared schema is reported against its file; no schema file is a document.
+    let review = diagnostics_for(&results, "review.json");
+    assert!(
+        review.iter().any(|diagnostic| diagnostic.is_parse_error()
+            && diagnostic.to_string().contains("unknown type 'bogus'")),
+        "{review:#?}"
+    );
+    assert!(results
+        .iter()
+        .all(|result| !result.path.ends_with("task.toml")));
+    assert!(global_bb.states().values().all(|node| {
+        let title = node.title.to_lowercase();
+        title != "task" && title != "review"
+    }));
+
+    // Fixing the schema file and reloading the network's schemas clears the error.
+    fs::write(
+        root.join("review.json"),
+        r#"{"$id": "schema_files.review", "properties": {"score": {"type": "number"}}}"#,
+    )?;
+    compiler.reload_schemas(root);
+    let results = compiler.parse_all(global_bb.clone(), false).await?;
+    assert!(
+        diagnostics_for(&results, "review.json").is_empty(),
+        "{:#?}",
+        diagnostics_for(&results, "review.json")
+    );
+    assert!(messages(&results, "top.md")
+        .iter()
+        .any(|message| message.contains("required field 'status' is missing")));
+    Ok(())
+}
+
+/// Documents behind their schema's version are upgraded step by step as they are parsed, and
+/// write-back persists the upgraded frontmatter. `plan_migrations` reports them beforehand.
+#[test(tokio::test)]
+async fn test_versioned_schema_migration() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let root = temp_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"migrations\"\ntitle: \"Migrations\"\n---\n\n# Migrations\n",
+    )?;
+    fs::create_dir_all(root.join("schemas"))?;
+    fs::write(
+        root.join("schemas/ticket.toml"),
+        "version = 3\n\n[fields.assignee]\ntype = \"string\"\nrequired = true\n\n\
+         [[migrations]]\nfrom = 1\nrename = { owner = \"assignee\" }\n\n\
+         [[migrations]]\nfrom = 2\nmove = { due = \"dates.due\" }\ndefault = { priority = 3 }\n",
+    )?;
+    fs::write(
+        root.join("old.md"),
+        "---\ntitle: Old\nschema: ticket\nowner: ana\ndue: 2024-05-01\n---\n\n# Old\n",
+    )?;
+    fs::write(
+        root.join("middle.md"),
+        "---\ntitle: Middle\nschema: ticket\nschema_version: 2\nassignee: bo\n---\n\n# Middle\n",
+    )?;
+    fs::write(
+        root.join("current.md"),
+        "---\ntitle: Current\nschema: ticket\nschema_version: 3\nassignee: cy\n---\n\n# Current\n",
+    )?;
+
+    let mut pending = plan_migrations(root)?;
+    pending.sort_by(|a, b| a.path.cmp(&b.path));
+    let summary = pending
+        .iter()
+        .map(|migration| {
+            (
+                migration
+                    .path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string(),
+                migration.from_version,
+                migration.to_version,
+                migration.changed_fields.join(","),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "middle.md".to_string(),
+                2,
+                3,
+                "priority,schema_version".to_string()
+            ),
+            (
+                "old.md".to_string(),
+                1,
+                3,
+                "assignee,dates,due,owner,priority,schema_version".to_string()
+            ),
+        ]
+    );
+
+    let mut global_bb = BeliefBase::empty();
+    let (accum_tx, mut accum_rx) = unbounded_channel::<BeliefEvent>();
+    let mut compiler = DocumentCompiler::new(root, Some(accum_tx), None, true)?;
+    let results = compiler.parse_all(global_bb.clone(), false).await?;
+    while let Ok(event) = accum_rx.try_recv() {
+        global_bb.process_event(&event)?;
+    }
+    // The upgraded document satisfies the current schema.
+    assert!(
+        !diagnostics_for(&results, "old.md")
+            .iter()
+            .any(|diagnostic| diagnostic.to_string().contains("Schema")),
+        "{:#?}",
+        diagnostics_for(&results, "old.md")
+    );
+
+    let old = global_bb
+        .states()
+        .values()
+        .find(|node| node.title == "Old")
+        .cloned()
+        .expect("old document");
+    assert_eq!(
+        old.payload.get("assignee").and_then(|value| value.as_str()),
+        Some("ana")
+    );
+    assert!(old.payload.get("owner").is_none());
+    assert_eq!(
+        old.payload.get("schema_version"),
+        Some(&toml::Value::Integer(3))
+    );
+
+    let old_source = fs::read_to_string(root.join("old.md"))?;
+    assert!(!old_source.contains("owner"), "{old_source}");
+    for expected in [
+        "assignee",
+        "ana",
+        "priority",
+        "dates",
+        "due",
+        "schema_version",
+    ] {
+        assert!(old_source.contains(expected), "{expected} in {old_source}");
+    }
+    let middle_source = fs::read_to_string(root.join("middle.md"))?;
+    assert!(middle_source.contains("priority"), "{middle_source}");
+    assert!(!middle_source.contains("dates"), "{middle_source}");
+    let current_source = fs::read_to_string(root.join("current.md"))?;
+    assert!(!current_source.contains("priority"), "{current_source}");
+
+    // Nothing is left to migrate.
+    assert!(plan_migrations(root)?.is_empty());
+    Ok(())
+}
+
+/// `apply_migrations` writes back only the documents it migrates, leaving files that parsing
+/// would otherwise rewrite (here, to inject BIDs) byte for byte as they were.
+#[test(tokio::test)]
+async fn test_apply_migrations_only_writes_migrated_documents(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let root = temp_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"apply-migrations\"\ntitle: \"Apply Migrations\"\n---\n\n# Apply Migrations\n",
+    )?;
+    fs::create_dir_all(root.join("schemas"))?;
+    fs::write(
+        root.join("schemas/chore.toml"),
+        "version = 2\n\n[[migrations]]\nfrom = 1\nrename = { owner = \"assignee\" }\n",
+    )?;
+    let old = root.join("old.md");
+    fs::write(
+        &old,
+        "---\ntitle: Old\nschema: chore\nowner: ana\n---\n\n# Old\n",
+    )?;
+    let notes = "# Notes\n\nNo frontmatter, so a parse would inject a BID.\n\n## Details\n";
+    fs::write(root.join("notes.md"), notes)?;
+    let index = fs::read(root.join("index.md"))?;
+
+    let pending = plan_migrations(root)?;
+    assert_eq!(pending.len(), 1);
+    apply_migrations(root, &pending).await?;
+
+    let old_source = fs::read_to_string(&old)?;
+    assert!(old_source.contains("assignee"), "{old_source}");
+    assert!(!old_source.contains("owner"), "{old_source}");
+    assert_eq!(fs::read_to_string(root.join("notes.md"))?, notes);
+    assert_eq!(fs::read(root.join("index.md"))?, index);
+    assert!(plan_migrations(root)?.is_empty());
+    Ok(())
+}
+
+/// Relations read from schema graph fields are checked once resolved: targets must declare an
+/// allowed schema, a field may hold at most `max_count` relations, and acyclic fields may not
+/// close a loop across documents.
+#[test(tokio::test)]
+async fn test_relation_constraints() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let root = temp_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"constraints\"\ntitle: \"Constraints\"\n---\n\n# Constraints\n",
+    )?;
+    fs::create_dir_all(root.join("schemas"))?;
+    fs::write(
+        root.join("schemas/goal.toml"),
+        "[graph_fields.parents]\ndirection = \"downstream\"\nweight_kind = \"Pragmatic\"\n\
+         target_schemas = [\"goal\"]\nacyclic = true\n\n\
+         [graph_fields.owner]\ndirection = \"downstream\"\nweight_kind = \"Epistemic\"\n\
+         max_count = 1\n",
+    )?;
+    let alpha = "---\nid: alpha\ntitle: Alpha\nschema: goal\nparents: [\"beta\", \"note\"]\n\
+                 owner: [\"beta\", \"note\"]\n---\n\n# Alpha\n";
+    fs::write(root.join("alpha.md"), alpha)?;
+    fs::write(
+        root.join("beta.md"),
+        "---\nid: beta\ntitle: Beta\nschema: goal\nparents: [\"alpha\"]\nowner: \"note\"\n---\n\n\
+         # Beta\n",
+    )?;
+    fs::write(
+        root.join("note.md"),
+        "---\nid: note\ntitle: Note\n---\n\n# Note\n",
+    )?;
+
+    let global_bb = BeliefBase::empty();
+    let (accum_tx, _accum_rx) = unbounded_channel::<BeliefEvent>();
+    let mut compiler = DocumentCompiler::new(root, Some(accum_tx), None, false)?;
+    let results = compiler.parse_all(global_bb.clone(), false).await?;
+
+    let alpha_diagnostics = diagnostics_for(&results, "alpha.md");
+    let wrong_target = alpha_diagnostics
+        .iter()
+        .find(|diagnostic| {
+            diagnostic.to_string().contains(
+                "Schema 'goal': field 'parents' relates to 'Note', which has no schema, \
+                 expected one of: goal",
+            )
+        })
+        .unwrap_or_else(|| panic!("{alpha_diagnostics:#?}"));
+    assert!(wrong_target.is_parse_error());
+    assert_eq!(
+        wrong_target.location().map(|(line, _)| line),
+        Some(line_of(alpha, "parents:"))
+    );
+    let too_many = alpha_diagnostics
+        .iter()
+        .find(|diagnostic| {
+            diagnostic
+                .to_string()
+                .contains("field 'owner' has 2 relations, at most 1 allowed")
+        })
+        .unwrap_or_else(|| panic!("{alpha_diagnostics:#?}"));
+    assert_eq!(
+        too_many.location().map(|(line, _)| line),
+        Some(line_of(alpha, "owner:"))
+    );
+
+    // Whichever of the pair is parsed second closes the loop.
+    let cycles = results
+        .iter()
+        .flat_map(|result| result.diagnostics.iter())
+        .filter(|diagnostic| {
+            let message = diagnostic.to_string();
+            message.contains("field 'parents' relation to") && message.contains("forms a cycle")
+        })
+        .count();
+    assert!(cycles > 0, "{results:#?}");
+
+    // A single owner and schema-conforming parents are fine.
+    let beta = diagnostics_for(&results, "beta.md");
+    assert!(
+        !beta.iter().any(|diagnostic| {
+            let message = diagnostic.to_string();
+            message.contains("at most") || message.contains("expected one of")
+        }),
+        "{beta:#?}"
+    );
+    Ok(())
+}
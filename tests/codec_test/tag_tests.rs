@@ -0,0 +1,158 @@
+//! Tag extraction and tag hierarchy tests
+
+use noet_core::{
+    beliefbase::BeliefBase,
+    codec::{tag, DocumentCompiler},
+    event::BeliefEvent,
+    properties::{tag_namespace, BeliefKind, Bid, WeightKind},
+    query::BeliefSource,
+};
+use std::fs;
+use test_log::test;
+use tokio::sync::mpsc::unbounded_channel;
+
+fn has_edge(bb: &BeliefBase, source: &Bid, sink: &Bid, kind: WeightKind) -> bool {
+    match (bb.bid_to_index(source), bb.bid_to_index(sink)) {
+        (Some(source_idx), Some(sink_idx)) => bb
+            .relations()
+            .as_graph()
+            .edges_connecting(source_idx, sink_idx)
+            .any(|edge| edge.weight().weights.contains_key(&kind)),
+        _ => false,
+    }
+}
+
+fn tag_bids(bb: &BeliefBase) -> Vec<Bid> {
+    bb.states()
+        .values()
+        .filter(|node| node.bid.parent_bref() == tag_namespace().bref())
+        .map(|node| node.bid)
+        .collect()
+}
+
+/// Inline and frontmatter tags become Symbol nodes in the tag namespace, related to the document
+/// or section that uses them, and nested tags are related to their parents.
+#[test(tokio::test)]
+async fn test_tags_and_tag_hierarchy() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let root = temp_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"tags\"\ntitle: \"Tags\"\n---\n\n# Tags\n",
+    )?;
+    fs::write(
+        root.join("proposal.md"),
+        "---\ntitle: Proposal\ntags: [RFC, status/draft]\n---\n\n# Proposal\n\nIntro.\n\n\
+         ## Design\n\nThe design is an #rfc too, see issue #12.\n\n\
+         ```\n#not-a-tag\n```\n",
+    )?;
+    fs::write(
+        root.join("notes.md"),
+        "# Notes\n\nNothing tagged here.\n\n## Decision\n\nAccepted. #status/final\n",
+    )?;
+
+    let mut global_bb = BeliefBase::empty();
+    let (accum_tx, mut accum_rx) = unbounded_channel::<BeliefEvent>();
+    let mut compiler = DocumentCompiler::new(root, Some(accum_tx), None, false)?;
+    compiler.parse_all(global_bb.clone(), false).await?;
+    while let Ok(event) = accum_rx.try_recv() {
+        global_bb.process_event(&event)?;
+    }
+
+    let get_tag = |tag: &str| {
+        global_bb
+            .get(&tag::to_nodekey(tag))
+            .unwrap_or_else(|| panic!("tag node for #{tag}"))
+    };
+    let rfc = get_tag("rfc");
+    let status = get_tag("status");
+    let draft = get_tag("status/draft");
+    let final_tag = get_tag("status/final");
+    assert!(rfc.kind.contains(BeliefKind::Symbol));
+    assert_eq!(draft.title, "status/draft");
+    assert!(global_bb.get(&tag::to_nodekey("not-a-tag")).is_none());
+    assert!(global_bb.get(&tag::to_nodekey("12")).is_none());
+    assert_eq!(tag_bids(&global_bb).len(), 4, "{:?}", tag_bids(&global_bb));
+
+    // Both status tags share a single parent.
+    assert!(has_edge(
+        &global_bb,
+        &status.bid,
+        &draft.bid,
+        WeightKind::Epistemic
+    ));
+    assert!(has_edge(
+        &global_bb,
+        &status.bid,
+        &final_tag.bid,
+        WeightKind::Epistemic
+    ));
+
+    let node_titled = |title: &str| {
+        global_bb
+            .states()
+            .values()
+            .find(|node| node.title == title)
+            .cloned()
+            .unwrap_or_else(|| panic!("node titled {title}"))
+    };
+    let proposal = node_titled("Proposal");
+    let design = node_titled("Design");
+    let decision = node_titled("Decision");
+    assert!(has_edge(
+        &global_bb,
+        &rfc.bid,
+        &proposal.bid,
+        WeightKind::Epistemic
+    ));
+    assert!(has_edge(
+        &global_bb,
+        &draft.bid,
+        &proposal.bid,
+        WeightKind::Epistemic
+    ));
+    assert!(has_edge(
+        &global_bb,
+        &rfc.bid,
+        &design.bid,
+        WeightKind::Epistemic
+    ));
+    assert!(has_edge(
+        &global_bb,
+        &final_tag.bid,
+        &decision.bid,
+        WeightKind::Epistemic
+    ));
+    assert!(!has_edge(
+        &global_bb,
+        &final_tag.bid,
+        &proposal.bid,
+        WeightKind::Epistemic
+    ));
+
+    // Everything tagged #status, directly or through a child tag.
+    let tagged = global_bb
+        .eval_query(&tag::tagged_query("#status").unwrap(), false)
+        .await?;
+    let tagged_nodes = tagged
+        .states
+        .values()
+        .filter(|node| node.kind.is_complete())
+        .map(|node| node.bid)
+        .collect::<Vec<_>>();
+    assert!(tagged_nodes.contains(&proposal.bid), "{tagged_nodes:?}");
+    assert!(tagged_nodes.contains(&decision.bid), "{tagged_nodes:?}");
+    assert!(!tagged_nodes.contains(&design.bid), "{tagged_nodes:?}");
+
+    // Re-parsing against the populated cache reuses the existing tag nodes.
+    let (accum_tx, mut accum_rx) = unbounded_channel::<BeliefEvent>();
+    let mut compiler = DocumentCompiler::new(root, Some(accum_tx), None, false)?;
+    compiler.parse_all(global_bb.clone(), true).await?;
+    while let Ok(event) = accum_rx.try_recv() {
+        global_bb.process_event(&event)?;
+    }
+    assert_eq!(tag_bids(&global_bb).len(), 4, "{:?}", tag_bids(&global_bb));
+    let draft_again = global_bb.get(&tag::to_nodekey("status/draft"));
+    assert_eq!(draft_again.map(|node| node.bid), Some(draft.bid));
+    Ok(())
+}
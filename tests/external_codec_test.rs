@@ -0,0 +1,317 @@
+//! External codec protocol tests.
+//!
+//! This binary doubles as the reference stub codec: run with `--stub-codec` it serves the
+//! [`noet_core::codec::external`] protocol on stdio for a toy line-based format (`.stub`):
+//!
+//! ```text
+//! title: Document title
+//! bid: <uuid>          (optional, for the current node)
+//! link: other.md       (a relation from the current node)
+//! == Section title     (starts a section node)
+//! ```
+//!
+//! Every other line is body text. Without the flag it runs the tests, which register the binary
+//! itself as the codec for `.stub` files. It uses a custom harness (`harness = false`) so the stub
+//! can own stdout.
+
+use noet_core::{
+    beliefbase::BeliefBase,
+    codec::{
+        compiler::DocumentCompiler,
+        external::{
+            register_external_codecs, ExternalCodecConfig, Request, Response, WireDiagnostic,
+            WireNode, WireRelation,
+        },
+        ParseDiagnostic, CODECS,
+    },
+    paths::AnchorPath,
+    properties::WeightKind,
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufRead, Write},
+};
+use tempfile::TempDir;
+
+const STUB_FLAG: &str = "--stub-codec";
+
+/// A node of a parsed `.stub` file.
+#[derive(Default)]
+struct StubNode {
+    /// Line the node starts on (its `==` heading, or 0 for the document).
+    start: usize,
+    /// Line and value of the node's `bid:` line.
+    bid: Option<(usize, String)>,
+}
+
+#[derive(Default)]
+struct StubCodec {
+    lines: Vec<String>,
+    nodes: Vec<StubNode>,
+    /// BIDs resolved by noet, by node index.
+    injected: BTreeMap<usize, String>,
+}
+
+impl StubCodec {
+    fn handle(&mut self, request: Request) -> Response {
+        match request {
+            Request::Proto { path } => match fs::read_to_string(&path) {
+                Ok(content) => {
+                    let mut document = serde_json::Map::new();
+                    if let Some(title) = content.lines().find_map(|l| l.strip_prefix("title: ")) {
+                        document.insert("title".to_string(), title.into());
+                    }
+                    Response {
+                        node: Some(WireNode {
+                            document,
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }
+                }
+                Err(e) => Response {
+                    error: Some(e.to_string()),
+                    ..Default::default()
+                },
+            },
+            Request::Parse { content, .. } => self.parse(&content),
+            Request::InjectContext { index, node } => {
+                self.injected.insert(index, node.bid.to_string());
+                Response::default()
+            }
+            Request::Finalize => Response::default(),
+            Request::GenerateSource => Response {
+                source: self.source(),
+                ..Default::default()
+            },
+            Request::GenerateHtml => {
+                let body = self
+                    .lines
+                    .iter()
+                    .filter_map(|line| line.strip_prefix("== "))
+                    .map(|title| format!("<h2>{title}</h2>"))
+                    .collect::<String>();
+                Response {
+                    fragments: vec![("doc.html".to_string(), body)],
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    fn parse(&mut self, content: &str) -> Response {
+        self.lines = content.lines().map(str::to_string).collect();
+        self.nodes = vec![StubNode::default()];
+        let mut nodes = vec![WireNode::default()];
+        let mut diagnostics = Vec::new();
+        for (idx, line) in self.lines.iter().enumerate() {
+            let node = nodes.last_mut().expect("document node");
+            let stub = self.nodes.last_mut().expect("document node");
+            if let Some(title) = line.strip_prefix("== ") {
+                let mut document = serde_json::Map::new();
+                document.insert("title".to_string(), title.into());
+                nodes.push(WireNode {
+                    heading: 3,
+                    document,
+                    ..Default::default()
+                });
+                self.nodes.push(StubNode {
+                    start: idx,
+                    bid: None,
+                });
+            } else if let Some(title) = line.strip_prefix("title: ") {
+                node.document.insert("title".to_string(), title.into());
+            } else if let Some(bid) = line.strip_prefix("bid: ") {
+                node.document.insert("bid".to_string(), bid.into());
+                stub.bid = Some((idx, bid.to_string()));
+            } else if let Some(target) = line.strip_prefix("link: ") {
+                node.upstream.push(WireRelation {
+                    key: target.to_string(),
+                    kind: WeightKind::Epistemic,
+                    weight: None,
+                    location: None,
+                });
+            } else if line.starts_with("==") {
+                diagnostics.push(WireDiagnostic {
+                    level: "warning".to_string(),
+                    message: "section headings need a space after '=='".to_string(),
+                    line: Some(idx + 1),
+                    column: Some(1),
+                });
+            }
+        }
+        Response {
+            nodes,
+            diagnostics,
+            ..Default::default()
+        }
+    }
+
+    /// The source with injected BIDs written as `bid:` lines, or `None` if nothing changed.
+    fn source(&self) -> Option<String> {
+        let mut lines = self.lines.clone();
+        let mut changed = false;
+        // Edit bottom-up so earlier line numbers stay valid.
+        for (idx, bid) in self.injected.iter().rev() {
+            let node = &self.nodes[*idx];
+            match node.bid.as_ref() {
+                Some((_, existing)) if existing == bid => {}
+                Some((line, _)) => {
+                    lines[*line] = format!("bid: {bid}");
+                    changed = true;
+                }
+                None => {
+                    let at = if *idx == 0 { 0 } else { node.start + 1 };
+                    lines.insert(at, format!("bid: {bid}"));
+                    changed = true;
+                }
+            }
+        }
+        changed.then(|| lines.join("\n") + "\n")
+    }
+}
+
+fn serve_stub() {
+    let mut codec = StubCodec::default();
+    let stdout = std::io::stdout();
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => codec.handle(request),
+            Err(e) => Response {
+                error: Some(format!("invalid request: {e}")),
+                ..Default::default()
+            },
+        };
+        let mut out = stdout.lock();
+        serde_json::to_writer(&mut out, &response).unwrap();
+        out.write_all(b"\n").unwrap();
+        out.flush().unwrap();
+    }
+}
+
+fn write_stub_config(dir: &std::path::Path) -> std::path::PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    let config_path = dir.join("codecs.toml");
+    fs::write(
+        &config_path,
+        format!(
+            "[[codec]]\nextension = \"stub\"\ncommand = {:?}\nargs = [{STUB_FLAG:?}]\n",
+            exe.display().to_string()
+        ),
+    )
+    .unwrap();
+    config_path
+}
+
+fn test_config_file() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("codecs.toml"),
+        "[[codec]]\nextension = \"adoc\"\ncommand = \"bin/noet-asciidoc\"\n\n\
+        [[codec]]\nstem = \"Makefile\"\ncommand = \"noet-make\"\nargs = [\"--stdio\"]\n",
+    )
+    .unwrap();
+    let configs = ExternalCodecConfig::from_file(&temp_dir.path().join("codecs.toml")).unwrap();
+    assert_eq!(configs.len(), 2);
+    assert_eq!(configs[0].extension.as_deref(), Some("adoc"));
+    assert_eq!(
+        configs[0].command,
+        temp_dir.path().join("bin/noet-asciidoc")
+    );
+    assert!(configs[0].args.is_empty());
+    assert_eq!(configs[1].stem.as_deref(), Some("Makefile"));
+    assert_eq!(configs[1].command.to_str(), Some("noet-make"));
+
+    fs::write(
+        temp_dir.path().join("bad.toml"),
+        "[[codec]]\ncommand = \"noet-anything\"\n",
+    )
+    .unwrap();
+    assert!(ExternalCodecConfig::from_file(&temp_dir.path().join("bad.toml")).is_err());
+}
+
+async fn test_stub_codec_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid: \"test-network\"\ntitle: \"Test Network\"\n---\n\n# Test Network\n",
+    )
+    .unwrap();
+    fs::write(root.join("other.md"), "# Other\n\nThe other document.\n").unwrap();
+    let stub_path = root.join("doc.stub");
+    fs::write(
+        &stub_path,
+        "title: Stub Document\nlink: other.md\nIntro text.\n== Details\nlink: missing.md\n==Bad\n",
+    )
+    .unwrap();
+
+    let registered = register_external_codecs(&write_stub_config(root)).unwrap();
+    assert_eq!(registered.len(), 1);
+    assert!(CODECS.get(&AnchorPath::new("doc.stub")).is_some());
+
+    let mut compiler = DocumentCompiler::new(root, None, Some(2), true).unwrap();
+    let results = compiler
+        .parse_all(BeliefBase::default(), false)
+        .await
+        .unwrap();
+
+    let stub_diagnostics = results
+        .iter()
+        .filter(|result| result.path.ends_with("doc.stub"))
+        .flat_map(|result| result.diagnostics.iter())
+        .collect::<Vec<_>>();
+    assert!(
+        stub_diagnostics.iter().any(|diagnostic| matches!(
+            diagnostic,
+            ParseDiagnostic::Warning { message, location: Some(_) }
+                if message.contains("space after")
+        )),
+        "{stub_diagnostics:?}"
+    );
+    let unresolved_links = results
+        .iter()
+        .flat_map(|result| result.diagnostics.iter())
+        .filter_map(|diagnostic| match diagnostic {
+            ParseDiagnostic::Warning { message, .. } if message.contains("unresolved link") => {
+                Some(message.clone())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(unresolved_links.len(), 1, "{unresolved_links:?}");
+    assert!(
+        unresolved_links[0].contains("missing.md"),
+        "{unresolved_links:?}"
+    );
+
+    // The stub wrote BIDs for the document and its section back through generate_source.
+    let written = fs::read_to_string(&stub_path).unwrap();
+    let lines = written.lines().collect::<Vec<_>>();
+    assert!(lines[0].starts_with("bid: "), "{written}");
+    assert_eq!(lines[1], "title: Stub Document");
+    assert_eq!(lines[4], "== Details");
+    assert!(lines[5].starts_with("bid: "), "{written}");
+    assert_ne!(lines[0], lines[5]);
+}
+
+fn main() {
+    if std::env::args().any(|arg| arg == STUB_FLAG) {
+        serve_stub();
+        return;
+    }
+
+    test_config_file();
+    println!("test test_config_file ... ok");
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(test_stub_codec_round_trip());
+    println!("test test_stub_codec_round_trip ... ok");
+}
@@ -0,0 +1,319 @@
+//! Structured graph edits executed through [`OpExecutor`], checking both the rewritten source and
+//! the reparsed graph.
+
+#[cfg(feature = "service")]
+use noet_core::{
+    beliefbase::BeliefBase,
+    commands::{Op, OpExecutor, OpResult},
+    event::BeliefEvent,
+    nodekey::NodeKey,
+    properties::{BeliefNode, Bid, WeightKind},
+};
+#[cfg(feature = "service")]
+use std::fs;
+#[cfg(feature = "service")]
+use test_log::test;
+
+#[cfg(feature = "service")]
+fn node_titled(bb: &BeliefBase, title: &str) -> Option<BeliefNode> {
+    bb.states()
+        .values()
+        .find(|node| node.title == title)
+        .cloned()
+}
+
+#[cfg(feature = "service")]
+fn key(bb: &BeliefBase, title: &str) -> NodeKey {
+    let node = node_titled(bb, title).unwrap_or_else(|| panic!("no node titled {title}"));
+    NodeKey::Bid { bid: node.bid }
+}
+
+#[cfg(feature = "service")]
+fn kinds_between(bb: &BeliefBase, source: Bid, sink: Bid) -> Vec<WeightKind> {
+    let relations = bb.relations();
+    let graph = relations.as_graph();
+    graph
+        .raw_edges()
+        .iter()
+        .filter(|edge| graph[edge.source()] == source && graph[edge.target()] == sink)
+        .flat_map(|edge| edge.weight.weights.keys().copied().collect::<Vec<_>>())
+        .collect()
+}
+
+#[test(tokio::test)]
+#[cfg(feature = "service")]
+async fn test_graph_edits_write_back_to_sources() -> Result<(), Box<dyn std::error::Error>> {
+    let supports = WeightKind::register("supports")?;
+    let src_dir = tempfile::tempdir()?;
+    let root = src_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid = \"edits\"\ntitle = \"Edits\"\n---\n\n# Edits\n",
+    )?;
+    fs::write(
+        root.join("claim.md"),
+        "# Claim\n\nThe claim.\n\n## Background\n\nSome context.\n",
+    )?;
+    fs::write(root.join("evidence.md"), "# Evidence\n\nSome evidence.\n")?;
+
+    let mut executor = OpExecutor::new(root)?;
+    executor.load().await?;
+    let claim = key(executor.cache(), "Claim");
+    let claim_bid = node_titled(executor.cache(), "Claim").unwrap().bid;
+    let evidence_bid = node_titled(executor.cache(), "Evidence").unwrap().bid;
+
+    // Payload fields land in the frontmatter.
+    let mut set = toml::Table::new();
+    set.insert(
+        "status".to_string(),
+        toml::Value::String("draft".to_string()),
+    );
+    let result = executor
+        .execute(Op::SetPayload {
+            node: claim.clone(),
+            set,
+            remove: Vec::new(),
+        })
+        .await?;
+    assert_eq!(result, OpResult::Edited(vec!["claim.md".to_string()]));
+    assert!(fs::read_to_string(root.join("claim.md"))?.contains("status = \"draft\""));
+    let node = node_titled(executor.cache(), "Claim").unwrap();
+    assert_eq!(node.bid, claim_bid);
+    assert_eq!(
+        node.payload.get("status").and_then(|v| v.as_str()),
+        Some("draft")
+    );
+
+    // Relations are typed links in the sink's text.
+    let mut payload = toml::Table::new();
+    payload.insert("confidence".to_string(), toml::Value::Float(0.7));
+    executor
+        .execute(Op::AddRelation {
+            source: key(executor.cache(), "Evidence"),
+            sink: claim.clone(),
+            kind: supports,
+            payload,
+        })
+        .await?;
+    let source = fs::read_to_string(root.join("claim.md"))?;
+    assert!(
+        source.contains(
+            "The claim.\n\n[Evidence](evidence.md){rel=supports confidence=0.7}\n\n## Background"
+        ),
+        "{source}"
+    );
+    assert_eq!(
+        kinds_between(executor.cache(), evidence_bid, claim_bid),
+        vec![supports]
+    );
+
+    executor
+        .execute(Op::RemoveRelation {
+            source: key(executor.cache(), "Evidence"),
+            sink: claim.clone(),
+            kind: supports,
+        })
+        .await?;
+    let source = fs::read_to_string(root.join("claim.md"))?;
+    assert!(source.contains("The claim.\n\nEvidence\n"), "{source}");
+    assert!(kinds_between(executor.cache(), evidence_bid, claim_bid).is_empty());
+
+    // Sections are headings; renaming keeps their identity.
+    let mut payload = toml::Table::new();
+    payload.insert("priority".to_string(), toml::Value::Integer(1));
+    executor
+        .execute(Op::CreateNode {
+            parent: claim.clone(),
+            title: "Open Questions".to_string(),
+            path: None,
+            payload,
+        })
+        .await?;
+    let questions = node_titled(executor.cache(), "Open Questions").expect("new section");
+    assert_eq!(
+        questions
+            .payload
+            .get("priority")
+            .and_then(|v| v.as_integer()),
+        Some(1)
+    );
+    assert!(fs::read_to_string(root.join("claim.md"))?.contains("\n## Open Questions"));
+
+    let background = node_titled(executor.cache(), "Background").unwrap().bid;
+    executor
+        .execute(Op::RenameNode(
+            NodeKey::Bid { bid: background },
+            "Context".to_string(),
+        ))
+        .await?;
+    assert_eq!(
+        executor
+            .cache()
+            .states()
+            .get(&background)
+            .map(|n| n.title.as_str()),
+        Some("Context")
+    );
+    assert!(fs::read_to_string(root.join("claim.md"))?.contains("## Context { #background }"));
+
+    executor
+        .execute(Op::DeleteNode(NodeKey::Bid { bid: background }))
+        .await?;
+    let source = fs::read_to_string(root.join("claim.md"))?;
+    assert!(!source.contains("Some context."), "{source}");
+    assert!(!executor.cache().states().contains_key(&background));
+
+    // Documents are files under their network.
+    let network = key(executor.cache(), "Edits");
+    executor
+        .execute(Op::CreateNode {
+            parent: network,
+            title: "Rebuttal".to_string(),
+            path: Some("rebuttal.md".to_string()),
+            payload: toml::Table::new(),
+        })
+        .await?;
+    assert!(root.join("rebuttal.md").exists());
+    let rebuttal = node_titled(executor.cache(), "Rebuttal").expect("new document");
+
+    let result = executor
+        .execute(Op::DeleteNode(NodeKey::Bid { bid: rebuttal.bid }))
+        .await?;
+    assert_eq!(result, OpResult::Edited(vec!["rebuttal.md".to_string()]));
+    assert!(!root.join("rebuttal.md").exists());
+    assert!(!executor.cache().states().contains_key(&rebuttal.bid));
+
+    // Titles aren't payload.
+    let mut set = toml::Table::new();
+    set.insert("title".to_string(), toml::Value::String("Nope".to_string()));
+    assert!(executor
+        .execute(Op::SetPayload {
+            node: claim,
+            set,
+            remove: Vec::new(),
+        })
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[test(tokio::test)]
+#[cfg(feature = "service")]
+async fn test_move_rewrites_links_and_reverses() -> Result<(), Box<dyn std::error::Error>> {
+    let src_dir = tempfile::tempdir()?;
+    let root = src_dir.path();
+    fs::write(
+        root.join("index.md"),
+        "---\nid = \"moves\"\ntitle = \"Moves\"\n---\n\n# Moves\n",
+    )?;
+    fs::create_dir(root.join("docs"))?;
+    fs::write(
+        root.join("docs/a.md"),
+        "# A\n\nSee [B](b.md) and [the notes](../notes.md).\n\n## Details\n\nMore.\n",
+    )?;
+    fs::write(
+        root.join("docs/b.md"),
+        "# B\n\nBuilds on [the details](a.md#details).\n",
+    )?;
+    fs::write(
+        root.join("notes.md"),
+        "# Notes\n\nStart at [A](docs/a.md).\n",
+    )?;
+
+    let mut executor = OpExecutor::new(root)?;
+    executor.load().await?;
+    let a = node_titled(executor.cache(), "A").unwrap().bid;
+    let notes = node_titled(executor.cache(), "Notes").unwrap().bid;
+    let read = |path: &str| fs::read_to_string(root.join(path)).unwrap();
+    let before = ["docs/a.md", "docs/b.md", "notes.md"].map(read);
+
+    // Planning is a dry run.
+    let plan = executor.plan_move("docs/a.md", "guides/a.md").await?;
+    assert_eq!(
+        plan.files,
+        vec![("docs/a.md".to_string(), "guides/a.md".to_string())]
+    );
+    let mut rewrites = plan
+        .links
+        .iter()
+        .map(|link| (link.path.as_str(), link.from.as_str(), link.to.as_str()))
+        .collect::<Vec<_>>();
+    rewrites.sort();
+    assert_eq!(
+        rewrites,
+        vec![
+            ("docs/b.md", "a.md#details", "../guides/a.md#details"),
+            ("guides/a.md", "b.md", "../docs/b.md"),
+            ("notes.md", "docs/a.md", "guides/a.md"),
+        ]
+    );
+    assert_eq!(read("docs/a.md"), before[0]);
+
+    let result = executor.apply_move(&plan).await?;
+    assert_eq!(
+        result,
+        OpResult::Edited(vec![
+            "docs/b.md".to_string(),
+            "guides/a.md".to_string(),
+            "notes.md".to_string()
+        ])
+    );
+    assert!(!root.join("docs/a.md").exists());
+    let moved = read("guides/a.md");
+    assert!(
+        moved.contains("See [B](../docs/b.md ") && moved.contains("[the notes](../notes.md "),
+        "{moved}"
+    );
+    assert!(read("docs/b.md").contains("[the details](../guides/a.md#details)"));
+    assert!(read("notes.md").contains("[A](guides/a.md)"));
+
+    // The node keeps its identity and its relations under the new path.
+    let cache = executor.cache();
+    let repo = node_titled(cache, "Moves").unwrap().bid;
+    assert_eq!(
+        cache
+            .paths()
+            .net_path(&repo.bref(), &a)
+            .map(|(_, path)| path),
+        Some("guides/a.md".to_string())
+    );
+    assert!(!cache
+        .paths()
+        .all_paths()
+        .values()
+        .flatten()
+        .any(|(path, ..)| path.starts_with("docs/a.md")));
+    assert!(!kinds_between(cache, a, notes).is_empty());
+
+    // The moved subtree's paths are rebased as path events, for a database to follow.
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for event in executor.take_path_events() {
+        match event {
+            BeliefEvent::PathsRemoved(_, paths, _) => removed.extend(paths),
+            BeliefEvent::PathAdded(_, path, ..) | BeliefEvent::PathUpdate(_, path, ..) => {
+                added.push(path)
+            }
+            event => panic!("unexpected {event:?}"),
+        }
+    }
+    removed.sort();
+    added.sort();
+    assert_eq!(removed, vec!["docs/a.md", "docs/a.md#details"]);
+    assert_eq!(added, vec!["guides/a.md", "guides/a.md#details"]);
+    assert!(executor.take_path_events().is_empty());
+
+    executor.apply_move(&plan.reverse()).await?;
+    assert!(!root.join("guides").exists());
+    assert_eq!(["docs/a.md", "docs/b.md", "notes.md"].map(read), before);
+    let cache = executor.cache();
+    let repo = node_titled(cache, "Moves").unwrap().bid;
+    assert_eq!(
+        cache
+            .paths()
+            .net_path(&repo.bref(), &a)
+            .map(|(_, path)| path),
+        Some("docs/a.md".to_string())
+    );
+    Ok(())
+}
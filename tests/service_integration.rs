@@ -12,9 +12,14 @@ mod common;
 
 #[cfg(feature = "service")]
 use noet_core::{
+    commands::{Op, OpResult},
     config::NetworkRecord,
+    db::db_init,
     event::Event,
+    nodekey::NodeKey,
     properties::{BeliefNode, Bid},
+    query::{Expression, PaginatedQuery, Query, StatePred},
+    rpc::{RpcAddr, RpcClient},
     watch::WatchService,
 };
 #[cfg(feature = "service")]
@@ -282,3 +287,132 @@ fn test_database_connection_is_public() {
     // This should compile - DbConnection constructor is public
     let _db_conn = DbConnection(pool);
 }
+
+#[test]
+#[cfg(feature = "service")]
+fn test_rpc_executes_ops() {
+    // Test that a listening service answers queries from its database and makes graph edits
+    let temp_dir = TempDir::new().unwrap();
+    let root_dir = temp_dir.path().to_path_buf();
+    let network_path = common::create_test_network(&temp_dir);
+    let addr = RpcAddr::Unix(root_dir.join("noet.sock"));
+
+    let (tx, _rx) = channel::<Event>();
+    let service = WatchService::new(root_dir, tx, false).unwrap();
+    service.enable_network_syncer(&network_path).unwrap();
+    service
+        .wait_for_idle(Duration::from_secs(30))
+        .expect("initial parse should complete");
+
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    std::thread::scope(|scope| {
+        let server = scope.spawn(|| {
+            service.serve_rpc(&addr, async {
+                stop_rx.await.ok();
+            })
+        });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let mut client = None;
+            for _ in 0..50 {
+                match RpcClient::connect(&addr).await {
+                    Ok(connected) => {
+                        client = Some(connected);
+                        break;
+                    }
+                    Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+                }
+            }
+            let mut client = client.expect("service should listen");
+
+            let query = PaginatedQuery {
+                query: Query {
+                    seed: Expression::StateIn(StatePred::Any),
+                    traverse: None,
+                },
+                limit: Some(100),
+                offset: None,
+                order_by: vec![],
+                projection: None,
+            };
+            let OpResult::Page(page) = client.execute(Op::GetStates(query)).await.unwrap() else {
+                panic!("GetStates should return a page");
+            };
+            let doc = page
+                .results
+                .states
+                .values()
+                .find(|node| node.kind.is_document() && !node.kind.is_network())
+                .expect("the document should be in the database")
+                .clone();
+
+            let result = client
+                .execute(Op::RenameNode(
+                    NodeKey::Bid { bid: doc.bid },
+                    "Renamed Document".to_string(),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(result, OpResult::Edited(vec!["doc1.md".to_string()]));
+            let content = std::fs::read_to_string(network_path.join("doc1.md")).unwrap();
+            assert!(content.contains("# Renamed Document"), "{content}");
+
+            // Errors are reported without closing the connection
+            let missing = NodeKey::Bid {
+                bid: Bid::new(Bid::nil()),
+            };
+            assert!(client.execute(Op::DeleteNode(missing)).await.is_err());
+
+            // Content can't be written outside of the watched network
+            let escaping = network_path.join("../escaped.md");
+            for path in ["../escaped.md".to_string(), escaping.display().to_string()] {
+                let update = Op::UpdateContent(path, "# Escaped\n".to_string());
+                assert!(client.execute(update).await.is_err());
+            }
+            assert!(!escaping.exists());
+            assert!(matches!(
+                client.execute(Op::LoadNetworks).await,
+                Ok(OpResult::Networks(_))
+            ));
+
+            // A move rebases the moved document's rows in the paths table
+            client
+                .execute(Op::MovePath(
+                    "doc1.md".to_string(),
+                    "moved/doc1.md".to_string(),
+                ))
+                .await
+                .unwrap();
+            let pool = db_init(temp_dir.path().join("belief_cache.db"))
+                .await
+                .unwrap();
+            let paths: Vec<String> = sqlx::query_scalar("SELECT path FROM paths")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            assert!(
+                paths.iter().any(|path| path == "moved/doc1.md"),
+                "{paths:?}"
+            );
+            assert!(
+                !paths.iter().any(|path| path.starts_with("doc1.md")),
+                "{paths:?}"
+            );
+        });
+
+        stop_tx.send(()).unwrap();
+        server.join().unwrap().expect("service should stop cleanly");
+    });
+    assert!(!temp_dir.path().join("noet.sock").exists());
+
+    // TCP services only listen on loopback addresses
+    assert!("127.0.0.1:9038".parse::<RpcAddr>().is_ok());
+    assert!("[::1]:9038".parse::<RpcAddr>().is_ok());
+    assert!("0.0.0.0:9038".parse::<RpcAddr>().is_err());
+
+    service.disable_network_syncer(&network_path).ok();
+}